@@ -0,0 +1,150 @@
+// what the OS calls its network adapters. On Windows the friendly name from the Network
+// Connections window and the driver's description, by either the adapter's GUID name or its
+// friendly name, whichever the counters came under; on macOS the service name from Network
+// settings ("Wi-Fi", "USB LAN") by BSD name. Nothing to look up elsewhere
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Adapter {
+    // "Ethernet 2"
+    pub friendly: String,
+    // "Intel(R) Ethernet Connection I219-V"
+    pub description: String,
+}
+
+impl Adapter {
+    // "Ethernet 2 — Intel I219-V"; just the friendly name when the driver gives no description
+    pub fn label(&self, dash: &str) -> String {
+        if self.description.is_empty() || self.description == self.friendly {
+            self.friendly.clone()
+        } else {
+            format!("{} {dash} {}", self.friendly, self.description)
+        }
+    }
+}
+
+// interface name as the counters have it -> adapter
+pub type Adapters = HashMap<String, Adapter>;
+
+// (GUID name, friendly name, description) per adapter, each findable by either name
+pub fn index(found: Vec<(String, String, String)>) -> Adapters {
+    let mut adapters = Adapters::new();
+    for (guid, friendly, description) in found {
+        let adapter = Adapter { friendly: friendly.clone(), description };
+        if !friendly.is_empty() {
+            adapters.insert(friendly, adapter.clone());
+        }
+        adapters.insert(guid, adapter);
+    }
+    adapters
+}
+
+#[cfg(windows)]
+pub fn read() -> Adapters {
+    use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR};
+    use windows::Win32::NetworkManagement::IpHelper::{
+        GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST, IP_ADAPTER_ADDRESSES_LH,
+    };
+    use windows::Win32::Networking::WinSock::AF_UNSPEC;
+
+    // the documented starting size; the call says how much it needs when that's short
+    let mut size: u32 = 15 * 1024;
+    for _ in 0..3 {
+        // u64s keep the buffer aligned for the structs written into it
+        let mut buf = vec![0u64; (size as usize).div_ceil(8)];
+        let first = buf.as_mut_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>();
+        let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+        // SAFETY: `first` points at `size` writable bytes, which the call won't go past
+        let ret = unsafe { GetAdaptersAddresses(AF_UNSPEC.0 as u32, flags, None, Some(first), &mut size) };
+        if ret == ERROR_BUFFER_OVERFLOW.0 {
+            continue;
+        }
+        if ret != NO_ERROR.0 {
+            return Adapters::new();
+        }
+        let mut found = Vec::new();
+        let mut next = first.cast_const();
+        while !next.is_null() {
+            // SAFETY: a node of the list the call just wrote into `buf`, which is still alive
+            let a = unsafe { &*next };
+            // SAFETY: null-terminated strings inside the same buffer
+            let (guid, friendly, description) =
+                unsafe { (a.AdapterName.to_string(), a.FriendlyName.to_string(), a.Description.to_string()) };
+            if let Ok(guid) = guid {
+                found.push((guid, friendly.unwrap_or_default(), description.unwrap_or_default()));
+            }
+            next = a.Next;
+        }
+        return index(found);
+    }
+    Adapters::new()
+}
+
+// `networksetup -listnetworkserviceorder`: a "(1) USB LAN" line, "(*) ..." when disabled,
+// then "(Hardware Port: USB 10/100/1000 LAN, Device: en5)". Services without a device (VPNs
+// configured but not up) are skipped
+pub fn parse_service_order(text: &str) -> Adapters {
+    let mut found = Vec::new();
+    let mut service = None;
+    for line in text.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("(Hardware Port: ") {
+            let device = rest.rsplit_once("Device: ").map_or("", |(_, d)| d.trim_end_matches(')').trim());
+            if let Some(service) = service.take()
+                && !device.is_empty()
+            {
+                found.push((device.to_string(), service, String::new()));
+            }
+        } else if line.starts_with('(')
+            && let Some((_, name)) = line.split_once(") ")
+        {
+            service = Some(name.trim().to_string());
+        }
+    }
+    index(found)
+}
+
+#[cfg(target_os = "macos")]
+pub fn read() -> Adapters {
+    std::process::Command::new("networksetup")
+        .arg("-listnetworkserviceorder")
+        .output()
+        .map(|out| parse_service_order(&String::from_utf8_lossy(&out.stdout)))
+        .unwrap_or_default()
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+pub fn read() -> Adapters {
+    Adapters::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_and_labels() {
+        let adapters = index(vec![
+            ("{4D36E972-E325-11CE-BFC1-08002BE10318}".to_string(), "Ethernet 2".to_string(), "Intel I219-V".to_string()),
+            ("{7A1C2E90-0000-4F2A-9C1D-7E3B00000001}".to_string(), "Wi-Fi".to_string(), "Wi-Fi".to_string()),
+        ]);
+        let label = |name: &str| adapters.get(name).map(|a| a.label("—"));
+        assert_eq!(label("{4D36E972-E325-11CE-BFC1-08002BE10318}").as_deref(), Some("Ethernet 2 — Intel I219-V"));
+        assert_eq!(label("Ethernet 2").as_deref(), Some("Ethernet 2 — Intel I219-V"));
+        assert_eq!(label("Wi-Fi").as_deref(), Some("Wi-Fi"), "nothing to add");
+        assert_eq!(label("Ethernet 3"), None);
+        assert_eq!(adapters.len(), 4);
+    }
+
+    #[test]
+    fn macos_services() {
+        let text = std::fs::read_to_string(format!("{}/tests/fixtures/networksetup_service_order", env!("CARGO_MANIFEST_DIR")));
+        let adapters = parse_service_order(&text.unwrap());
+        let label = |name: &str| adapters.get(name).map(|a| a.label("—"));
+        assert_eq!(label("en0").as_deref(), Some("Wi-Fi"));
+        assert_eq!(label("en5").as_deref(), Some("USB LAN"));
+        assert_eq!(label("bridge0").as_deref(), Some("Thunderbolt Bridge"));
+        assert_eq!(label("en7").as_deref(), Some("iPhone USB"), "disabled, but still named");
+        assert!(!adapters.values().any(|a| a.friendly == "Work VPN"), "no device");
+    }
+}
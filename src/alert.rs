@@ -0,0 +1,350 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::format::parse_rate;
+use crate::net::{glob_match, RowData};
+
+// one ring per this long at most, however many alerts fire in between
+const BELL_EVERY: Duration = Duration::from_secs(10);
+
+// the oldest events go first once the log is this long
+pub const LOG_CAP: usize = 500;
+
+// what a rule looks at, per second like the table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Rx,
+    Tx,
+    // rx + tx
+    Total,
+    // in + out for the rest
+    Packets,
+    Errors,
+    Drops,
+}
+
+impl Metric {
+    const ALL: [Metric; 6] = [Metric::Rx, Metric::Tx, Metric::Total, Metric::Packets, Metric::Errors, Metric::Drops];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::Rx => "rx",
+            Metric::Tx => "tx",
+            Metric::Total => "total",
+            Metric::Packets => "packets",
+            Metric::Errors => "errors",
+            Metric::Drops => "drops",
+        }
+    }
+
+    // bytes/s, formatted like the RX/TX columns; the others are counts per second
+    pub fn is_bytes(self) -> bool {
+        matches!(self, Metric::Rx | Metric::Tx | Metric::Total)
+    }
+
+    // raw rates, smoothing only changes what the table shows
+    pub fn value(self, r: &RowData) -> f64 {
+        match self {
+            Metric::Rx => r.rx_bps,
+            Metric::Tx => r.tx_bps,
+            Metric::Total => r.rx_bps + r.tx_bps,
+            Metric::Packets => r.packets_in + r.packets_out,
+            Metric::Errors => r.errors_in + r.errors_out,
+            Metric::Drops => r.drops_in + r.drops_out,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+impl Op {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Op::Above => ">",
+            Op::AtLeast => ">=",
+            Op::Below => "<",
+            Op::AtMost => "<=",
+        }
+    }
+
+    // for the alert log: "eth0 rx 94.1 MB/s exceeded 80 MB/s"
+    pub fn verb(self) -> &'static str {
+        match self {
+            Op::Above => "exceeded",
+            Op::AtLeast => "reached",
+            Op::Below => "fell below",
+            Op::AtMost => "fell to",
+        }
+    }
+
+    pub fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Op::Above => value > threshold,
+            Op::AtLeast => value >= threshold,
+            Op::Below => value < threshold,
+            Op::AtMost => value <= threshold,
+        }
+    }
+}
+
+// "eth0:rx:>:80MB/s": which interfaces (a name, a glob, or * for all), what, and when
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    pub pattern: String,
+    pub metric: Metric,
+    pub op: Op,
+    pub threshold: f64,
+}
+
+impl Rule {
+    pub fn applies_to(&self, name: &str) -> bool {
+        self.pattern == "any" || glob_match(&self.pattern, name)
+    }
+}
+
+// the form parse_rule reads back, thresholds in plain bytes/s
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}:{}", self.pattern, self.metric.label(), self.op.symbol(), self.threshold)
+    }
+}
+
+// `iface:metric:op:value`, e.g. "eth0:rx:>:80MB/s" or "*:errors:>:0"; rates take the
+// units --warn does
+pub fn parse_rule(s: &str) -> Result<Rule, String> {
+    let invalid = |why: String| format!("invalid alert '{s}': {why}, expected iface:metric:op:value like \"eth0:rx:>:80MB/s\"");
+    let parts: Vec<&str> = s.split(':').map(str::trim).collect();
+    let [pattern, metric, op, value] = parts[..] else {
+        return Err(invalid("need four fields".to_string()));
+    };
+    if pattern.is_empty() {
+        return Err(invalid("no interface".to_string()));
+    }
+    let names: Vec<&str> = Metric::ALL.iter().map(|m| m.label()).collect();
+    let metric = Metric::ALL
+        .into_iter()
+        .find(|m| m.label() == metric.to_ascii_lowercase())
+        .ok_or_else(|| invalid(format!("unknown metric '{metric}', one of {}", names.join(", "))))?;
+    let op = [Op::Above, Op::AtLeast, Op::Below, Op::AtMost]
+        .into_iter()
+        .find(|o| o.symbol() == op)
+        .ok_or_else(|| invalid(format!("unknown comparison '{op}', one of >, >=, <, <=")))?;
+    let threshold = parse_rate(value).map_err(invalid)?;
+    Ok(Rule { pattern: pattern.to_string(), metric, op, threshold })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transition {
+    Fired,
+    Cleared,
+}
+
+// one line of the alert log
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlertEvent {
+    // seconds since the unix epoch
+    pub at: f64,
+    pub interface: String,
+    pub rule: Rule,
+    // when it fired, or at the last quiet sample when it cleared
+    pub value: f64,
+    pub transition: Transition,
+}
+
+// a rule holding for an interface; it clears after `clear_after` samples in a row where it doesn't
+struct Firing {
+    rule: usize,
+    interface: String,
+    value: f64,
+    quiet: u32,
+}
+
+#[derive(Default)]
+pub struct Alerts {
+    pub rules: Vec<Rule>,
+    pub clear_after: u32,
+    firing: Vec<Firing>,
+    // oldest first
+    pub log: Vec<AlertEvent>,
+    // events ever logged, including ones capped or cleared away since; the unread badge
+    // counts from here
+    pub logged: u64,
+    last_bell: Option<Instant>,
+}
+
+impl Alerts {
+    pub fn new(rules: Vec<Rule>, clear_after: u32) -> Self {
+        Alerts { rules, clear_after: clear_after.max(1), ..Default::default() }
+    }
+
+    // checks every rule against one sample's rows; true when something fired and the
+    // bell hasn't rung within BELL_EVERY
+    pub fn update(&mut self, rows: &[RowData], unix: f64, at: Instant) -> bool {
+        let mut events = Vec::new();
+        // an interface that went away counts as quiet
+        for f in &mut self.firing {
+            let rule = &self.rules[f.rule];
+            match rows.iter().find(|r| r.interface == f.interface) {
+                Some(r) => {
+                    f.value = rule.metric.value(r);
+                    f.quiet = if rule.op.holds(f.value, rule.threshold) { 0 } else { f.quiet + 1 };
+                }
+                None => f.quiet += 1,
+            }
+        }
+        let clear_after = self.clear_after;
+        self.firing.retain(|f| {
+            let cleared = f.quiet >= clear_after;
+            if cleared {
+                events.push((f.rule, f.interface.clone(), f.value, Transition::Cleared));
+            }
+            !cleared
+        });
+
+        for (i, rule) in self.rules.iter().enumerate() {
+            for r in rows.iter().filter(|r| rule.applies_to(&r.interface)) {
+                let value = rule.metric.value(r);
+                let already = self.firing.iter().any(|f| f.rule == i && f.interface == r.interface);
+                if !already && rule.op.holds(value, rule.threshold) {
+                    self.firing.push(Firing { rule: i, interface: r.interface.clone(), value, quiet: 0 });
+                    events.push((i, r.interface.clone(), value, Transition::Fired));
+                }
+            }
+        }
+
+        let fired = events.iter().any(|e| e.3 == Transition::Fired);
+        self.logged += events.len() as u64;
+        for (rule, interface, value, transition) in events {
+            self.log.push(AlertEvent { at: unix, interface, rule: self.rules[rule].clone(), value, transition });
+        }
+        let over = self.log.len().saturating_sub(LOG_CAP);
+        self.log.drain(..over);
+
+        let ring = fired && self.last_bell.is_none_or(|last| at.duration_since(last) >= BELL_EVERY);
+        if ring {
+            self.last_bell = Some(at);
+        }
+        ring
+    }
+
+    // a firing rule about `metric` on this interface, for highlighting its cells
+    pub fn is_firing(&self, interface: &str, metric: Metric) -> bool {
+        self.firing.iter().any(|f| f.interface == interface && self.rules[f.rule].metric == metric)
+    }
+
+    pub fn firing_metrics(&self, interface: &str) -> Vec<Metric> {
+        Metric::ALL.into_iter().filter(|&m| self.is_firing(interface, m)).collect()
+    }
+
+    pub fn firing_count(&self) -> usize {
+        self.firing.len()
+    }
+
+    // empties the log; what is firing stays firing and logs its clear as usual
+    pub fn clear_log(&mut self) {
+        self.log.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::{deltas, collect, Baseline, Counters, Filter, NetworkSource};
+    use std::collections::HashMap;
+
+    #[test]
+    fn rules() {
+        let rule = parse_rule("eth0:rx:>:80MB/s").unwrap();
+        assert_eq!(rule, Rule { pattern: "eth0".to_string(), metric: Metric::Rx, op: Op::Above, threshold: 80e6 });
+        assert_eq!(parse_rule(&rule.to_string()), Ok(rule));
+        assert_eq!(parse_rule("*:errors:>:0").unwrap().metric, Metric::Errors);
+        assert_eq!(parse_rule("wl*: TX : <= : 1k").unwrap().op, Op::AtMost);
+        assert!(parse_rule("any:drops:>=:5").unwrap().applies_to("veth1"));
+        assert!(!parse_rule("eth*:rx:>:1").unwrap().applies_to("wlan0"));
+        for bad in ["eth0:rx:>", "eth0:speed:>:1", "eth0:rx:=:1", "eth0:rx:>:fast", ":rx:>:1"] {
+            assert!(parse_rule(bad).is_err(), "{bad}");
+        }
+        assert!(parse_rule("eth0:rx:!:1").unwrap_err().contains("one of >, >=, <, <="));
+    }
+
+    // eth0 receives whatever the script says for each read
+    struct Script {
+        rx: Vec<u64>,
+        reads: usize,
+        total: u64,
+    }
+
+    impl NetworkSource for Script {
+        fn snapshot(&mut self) -> Vec<Counters> {
+            self.total += self.rx.get(self.reads).copied().unwrap_or(0);
+            self.reads += 1;
+            vec![Counters { name: "eth0".to_string(), rx_bytes: self.total, ..Default::default() }]
+        }
+    }
+
+    // (transitions, whether the bell rang) after each of the script's samples, one second apart
+    fn run(alerts: &mut Alerts, rx: &[u64]) -> Vec<(Vec<Transition>, bool)> {
+        let mut source = Script { rx: std::iter::once(0).chain(rx.iter().copied()).collect(), reads: 0, total: 0 };
+        let (mut baseline, mut totals) = (Baseline::new(), HashMap::new());
+        let filter = Filter { show_virtual: true, ..Default::default() };
+        deltas(&mut source, &mut baseline);
+        let start = Instant::now();
+        (0..rx.len())
+            .map(|i| {
+                let rows = collect(&mut source, &mut baseline, &mut totals, 1.0, &filter);
+                let before = alerts.log.len();
+                let bell = alerts.update(&rows, i as f64, start + Duration::from_secs(i as u64));
+                (alerts.log[before..].iter().map(|e| e.transition).collect(), bell)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fires_once_and_clears_after_quiet_samples() {
+        use Transition::{Cleared, Fired};
+        let mut alerts = Alerts::new(vec![parse_rule("eth0:rx:>:1000").unwrap()], 2);
+        let transitions: Vec<_> = run(&mut alerts, &[500, 2000, 3000, 500, 2000, 500, 400, 500]).into_iter().map(|t| t.0).collect();
+        assert_eq!(transitions, [vec![], vec![Fired], vec![], vec![], vec![], vec![], vec![Cleared], vec![]]);
+        assert_eq!((alerts.log[0].value, alerts.log[1].value), (2000.0, 400.0));
+        assert_eq!(alerts.log[0].interface, "eth0");
+        assert_eq!(alerts.firing_count(), 0);
+    }
+
+    #[test]
+    fn highlights_and_rate_limits_the_bell() {
+        let rules = vec![parse_rule("*:rx:>:1000").unwrap(), parse_rule("eth0:total:>=:1500").unwrap()];
+        let mut alerts = Alerts::new(rules, 1);
+        let mut rx = vec![2000, 0, 2000, 0];
+        rx.extend([0; 8]);
+        rx.push(1200);
+        let bells: Vec<bool> = run(&mut alerts, &rx).into_iter().map(|t| t.1).collect();
+        // the second firing is 2s after the first ring, the third 12s after it
+        let rang: Vec<usize> = (0..bells.len()).filter(|&i| bells[i]).collect();
+        assert_eq!(rang, [0, 12]);
+        assert!(alerts.is_firing("eth0", Metric::Rx) && !alerts.is_firing("eth0", Metric::Total));
+        assert_eq!(alerts.firing_metrics("eth0"), [Metric::Rx]);
+        assert!(!alerts.is_firing("wlan0", Metric::Rx));
+        assert_eq!(alerts.log.iter().filter(|e| e.transition == Transition::Fired).count(), 5);
+    }
+
+    #[test]
+    fn log_is_capped_and_clears() {
+        let mut alerts = Alerts::new(vec![parse_rule("eth0:rx:>:1000").unwrap()], 1);
+        // fires and clears on every other sample
+        let rx: Vec<u64> = (0..LOG_CAP as u64 + 100).map(|i| if i % 2 == 0 { 2000 } else { 0 }).collect();
+        run(&mut alerts, &rx);
+        assert_eq!(alerts.log.len(), LOG_CAP);
+        assert_eq!(alerts.logged, LOG_CAP as u64 + 100);
+        assert_eq!(alerts.log.last().unwrap().at, (LOG_CAP + 99) as f64);
+
+        alerts.clear_log();
+        assert!(alerts.log.is_empty());
+        assert_eq!(alerts.logged, LOG_CAP as u64 + 100);
+    }
+}
@@ -0,0 +1,2804 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use humansize::{format_size, BINARY, DECIMAL};
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+
+use crate::adapters::Adapters;
+use crate::alert::{AlertEvent, Alerts, Metric, Transition};
+use crate::capture::{self, Capture, Talker, Tally};
+use crate::cli::{Args, Backend};
+use crate::clipboard::{self, Copied};
+use crate::columns::{self, Column, Columns};
+use crate::conns::{sort_sockets, ConnSort, Listing, Socket};
+use crate::containers::Containers;
+use crate::csvlog::CsvLog;
+use crate::datacap::{self, Budget};
+use crate::export;
+use crate::format::{format_rate, group_digits, human_count_rate, human_pps, DigitSeparator, FormatOptions, UnitStandard};
+use crate::glyphs::Glyphs;
+use crate::history::{self, History, Sample};
+use crate::host::Host;
+use crate::iftype::{Group, IfaceKind};
+use crate::influx::Influx;
+use crate::keymap::{key_label, Key, Keymap};
+use crate::neigh::Neighbor;
+use crate::net::{collect, deltas, Baseline, Filter, NetworkSource, RowData, Totals};
+use crate::netns::Selection;
+use crate::notify::Notifier;
+use crate::ping::{self, Latency, Pinged, Pinger, Target};
+use crate::procs::{self, ProcRate, Reading};
+use crate::prometheus::Exporter;
+use crate::quantile::{Percentiles, Quantiles};
+use crate::remote::{Link, Remote, Server, Update};
+use crate::replay::{self, Replay};
+use crate::resolve::{self, Names, Resolver};
+use crate::route::{self, Route};
+use crate::snapshot;
+use crate::socket::QuerySocket;
+use crate::statsd::Statsd;
+use crate::theme::{self, Theme, ThemeName};
+use crate::ui::TableText;
+use crate::wg::Tracker;
+
+// refresh interval bounds for the +/- keys (ms)
+pub const MIN_REFRESH_MS: u64 = 100;
+pub const MAX_REFRESH_MS: u64 = 10_000;
+
+// two clicks on the same row within this long open it
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+// rows one notch of the mouse wheel moves
+const WHEEL_ROWS: isize = 3;
+
+// how long a status bar message like "copied eth0" stays up
+const MESSAGE_FOR: Duration = Duration::from_secs(3);
+
+// a row stays highlighted this long after its last error
+pub const ERROR_FLASH: Duration = Duration::from_secs(5);
+
+// reverse lookups sent per sample at most, so a tab full of new addresses trickles in
+// instead of flooding the resolver
+pub const LOOKUPS_PER_SAMPLE: usize = 16;
+
+// routes and neighbors change rarely, and reading them runs netstat or `ip`
+pub const TABLES_EVERY: Duration = Duration::from_secs(5);
+
+// matching veths to containers walks every process, and containers come and go slower still
+pub const CONTAINERS_EVERY: Duration = Duration::from_secs(10);
+
+// what the detail view lists, and how long one computation of them is shown
+pub const PERCENTILES: [f64; 3] = [0.5, 0.95, 0.99];
+const PERCENTILES_EVERY: Duration = Duration::from_secs(1);
+
+// rate coloring: green below warn, yellow below crit, red above (bytes/s)
+#[derive(Clone, Copy, Debug)]
+pub struct Thresholds {
+    pub warn: f64,
+    pub crit: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds { warn: 1e6, crit: 10e6 }
+    }
+}
+
+// exponential moving average with a time constant instead of a fixed weight, so the
+// weight follows the real interval: a sample after a long stall (suspend, a blocked
+// terminal) counts almost fully instead of being dragged towards stale history
+pub fn ema(prev: f64, value: f64, elapsed: Duration, tau: Duration) -> f64 {
+    let tau = tau.as_secs_f64();
+    if tau <= 0.0 {
+        return value;
+    }
+    let alpha = 1.0 - (-elapsed.as_secs_f64() / tau).exp();
+    prev + alpha * (value - prev)
+}
+
+// an interface is idle once its last IDLE_SAMPLES samples all stayed under IDLE_BPS
+// (rx+tx), so one quiet interval doesn't hide a busy interface
+pub const IDLE_SAMPLES: usize = 5;
+pub const IDLE_BPS: f64 = 128.0;
+
+// step by 100ms below 1s and by 1s above, so both ends of the range are reachable quickly
+pub fn faster(refresh_ms: u64) -> u64 {
+    let step = if refresh_ms > 1000 { 1000 } else { 100 };
+    refresh_ms.saturating_sub(step).max(MIN_REFRESH_MS)
+}
+
+pub fn slower(refresh_ms: u64) -> u64 {
+    let step = if refresh_ms >= 1000 { 1000 } else { 100 };
+    (refresh_ms + step).min(MAX_REFRESH_MS)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Total,
+    Rx,
+    Tx,
+    Name,
+    Packets,
+    Errors,
+}
+
+impl SortKey {
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Total => SortKey::Rx,
+            SortKey::Rx => SortKey::Tx,
+            SortKey::Tx => SortKey::Name,
+            SortKey::Name => SortKey::Packets,
+            SortKey::Packets => SortKey::Errors,
+            SortKey::Errors => SortKey::Total,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Total => "total",
+            SortKey::Rx => "rx",
+            SortKey::Tx => "tx",
+            SortKey::Name => "name",
+            SortKey::Packets => "packets",
+            SortKey::Errors => "errors",
+        }
+    }
+
+    // what clicking a column header sorts by; RX and TX alone rather than their total
+    pub fn for_column(column: Column) -> Option<Self> {
+        match column {
+            Column::Iface => Some(SortKey::Name),
+            Column::Rx => Some(SortKey::Rx),
+            Column::Tx => Some(SortKey::Tx),
+            Column::PktIn | Column::PktOut => Some(SortKey::Packets),
+            Column::ErrIn | Column::ErrOut => Some(SortKey::Errors),
+            _ => None,
+        }
+    }
+
+    // table columns that get the ▲/▼ indicator
+    pub fn columns(self) -> &'static [Column] {
+        match self {
+            SortKey::Total => &[Column::Rx, Column::Tx],
+            SortKey::Rx => &[Column::Rx],
+            SortKey::Tx => &[Column::Tx],
+            SortKey::Name => &[Column::Iface],
+            SortKey::Packets => &[Column::PktIn, Column::PktOut],
+            SortKey::Errors => &[Column::ErrIn, Column::ErrOut],
+        }
+    }
+
+    // the value being sorted on, rates or session totals depending on the view
+    pub fn value(self, r: &RowData, cumulative: bool) -> f64 {
+        let t = &r.totals;
+        match (self, cumulative) {
+            (SortKey::Name, _) => 0.0,
+            (SortKey::Total, false) => r.rx_bps + r.tx_bps,
+            (SortKey::Rx, false) => r.rx_bps,
+            (SortKey::Tx, false) => r.tx_bps,
+            (SortKey::Packets, false) => r.packets_in + r.packets_out,
+            (SortKey::Errors, false) => r.errors_in + r.errors_out,
+            (SortKey::Total, true) => (t.rx_bytes + t.tx_bytes) as f64,
+            (SortKey::Rx, true) => t.rx_bytes as f64,
+            (SortKey::Tx, true) => t.tx_bytes as f64,
+            (SortKey::Packets, true) => (t.packets_in + t.packets_out) as f64,
+            (SortKey::Errors, true) => (t.errors_in + t.errors_out) as f64,
+        }
+    }
+}
+
+// stable sort, so rows with equal keys keep their relative order
+pub fn sort_rows(rows: &mut [RowData], key: SortKey, desc: bool, cumulative: bool) {
+    rows.sort_by(|a, b| {
+        let ord = if key == SortKey::Name {
+            a.interface.to_lowercase().cmp(&b.interface.to_lowercase())
+        } else {
+            key.value(a, cumulative)
+                .partial_cmp(&key.value(b, cumulative))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        };
+        if desc { ord.reverse() } else { ord }
+    });
+}
+
+// `r`, then its shown ports in table order, then theirs. Every port has one master, so
+// starting from interfaces without one can't go round in circles
+fn nest<'a>(r: &'a RowData, shown: &[&'a RowData], masters: &HashMap<&str, &str>, out: &mut Vec<&'a RowData>) {
+    out.push(r);
+    for port in shown.iter().filter(|p| masters.get(p.interface.as_str()) == Some(&r.interface.as_str())) {
+        nest(port, shown, masters, out);
+    }
+}
+
+// the order `prev` had while it's frozen; interfaces that weren't there go last, in the
+// order sort_rows gave them
+pub fn keep_order(rows: &mut [RowData], prev: &[RowData]) {
+    let rank: HashMap<&str, usize> = prev.iter().enumerate().map(|(i, r)| (r.interface.as_str(), i)).collect();
+    rows.sort_by_key(|r| rank.get(r.interface.as_str()).copied().unwrap_or(usize::MAX));
+}
+
+// pinned interfaces first, in pin order, the rest keep the order sort_rows gave them
+pub fn pin_rows(rows: &mut [RowData], pins: &[String]) {
+    rows.sort_by_key(|r| pins.iter().position(|p| *p == r.interface).unwrap_or(usize::MAX));
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Help,
+    Open,
+    Back,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    Top,
+    Bottom,
+    Faster,
+    Slower,
+    ToggleVirtual,
+    ToggleTotals,
+    Pause,
+    ToggleBits,
+    ToggleStandard,
+    ToggleSparkScale,
+    ToggleChart,
+    CycleSort,
+    ReverseSort,
+    FreezeOrder,
+    ToggleStats,
+    ToggleAverages,
+    ToggleMulticast,
+    ChooseColumns,
+    ColumnLeft,
+    ColumnRight,
+    MoveColumnLeft,
+    MoveColumnRight,
+    ResetStats,
+    ToggleGauge,
+    ToggleSmooth,
+    ToggleIdle,
+    TogglePin,
+    Hide,
+    HiddenList,
+    Search,
+    ToggleGroups,
+    CycleTheme,
+    Copy,
+    CopyTable,
+    Export,
+    ExportAs,
+    AlertLog,
+    Connections,
+    ConnectionFilter,
+    Processes,
+    TopTalkers,
+    ToggleNames,
+    NestMembers,
+}
+
+impl Action {
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "quit (ctrl+c works too)",
+            Action::Help => "show this help",
+            Action::Open => "open/close interface detail",
+            Action::Back => "close detail view or quit",
+            Action::Up => "select previous interface",
+            Action::Down => "select next interface",
+            Action::PageUp => "scroll a page up",
+            Action::PageDown => "scroll a page down",
+            Action::HalfPageUp => "scroll half a page up",
+            Action::HalfPageDown => "scroll half a page down",
+            Action::Top => "jump to the first interface",
+            Action::Bottom => "jump to the last interface",
+            Action::Faster => "refresh faster",
+            Action::Slower => "refresh slower",
+            Action::ToggleVirtual => "show/hide virtual interfaces",
+            Action::ToggleTotals => "switch rates / session totals",
+            Action::Pause => "pause/resume the display",
+            Action::ToggleBits => "switch bytes/s / bits/s",
+            Action::ToggleStandard => "switch IEC / SI byte units",
+            Action::ToggleSparkScale => "scale sparklines per row / globally",
+            Action::ToggleChart => "show/hide history chart",
+            Action::CycleSort => "cycle sort column",
+            Action::ReverseSort => "reverse sort direction",
+            Action::FreezeOrder => "freeze the row order, the numbers keep updating",
+            Action::ToggleStats => "show/hide peak and average columns",
+            Action::ToggleAverages => "show/hide 1m/5m/15m rolling averages",
+            Action::ToggleMulticast => "show/hide multicast packets received",
+            Action::ChooseColumns => "choose the table's columns",
+            Action::ColumnLeft => "put the header cursor on the column to the left",
+            Action::ColumnRight => "put the header cursor on the column to the right",
+            Action::MoveColumnLeft => "move the column under the header cursor left",
+            Action::MoveColumnRight => "move the column under the header cursor right",
+            Action::ResetStats => "reset peaks and averages",
+            Action::ToggleGauge => "show utilization as bars / percent",
+            Action::ToggleSmooth => "smooth RX/TX rates on/off",
+            Action::ToggleIdle => "hide/show idle interfaces",
+            Action::TogglePin => "pin/unpin the selected interface to the top",
+            Action::Hide => "hide the selected interface",
+            Action::HiddenList => "list hidden interfaces to show them again",
+            Action::Search => "search interface names (enter keeps, esc clears)",
+            Action::ToggleGroups => "group interfaces by type (enter/space folds a group)",
+            Action::CycleTheme => "cycle color theme (dark, light, monochrome)",
+            Action::Copy => "copy the selected interface's stats",
+            Action::CopyTable => "copy every row of the table",
+            Action::Export => "write the table to nettui-<time>.txt",
+            Action::ExportAs => "write the table to a file, asking for the path and format",
+            Action::AlertLog => "list recent alert events (c clears them)",
+            Action::Connections => "switch between interfaces and active connections",
+            Action::ConnectionFilter => "connections: selected interface only / all",
+            Action::Processes => "estimated per-process TCP rates (procs feature, Linux)",
+            Action::TopTalkers => "top remote hosts by bytes (pcap feature, Linux, needs CAP_NET_RAW)",
+            Action::ToggleNames => "remote addresses as numbers / reverse DNS names",
+            Action::NestMembers => "nest bond and bridge ports and VLANs under the interface they belong to",
+        }
+    }
+
+    // a word or two for the status bar
+    pub fn hint(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Help => "help",
+            Action::Open => "detail",
+            Action::Back => "back",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::PageUp => "page up",
+            Action::PageDown => "page down",
+            Action::HalfPageUp => "half up",
+            Action::HalfPageDown => "half down",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::Faster => "faster",
+            Action::Slower => "slower",
+            Action::ToggleVirtual => "virtual",
+            Action::ToggleTotals => "totals",
+            Action::Pause => "pause",
+            Action::ToggleBits => "bits",
+            Action::ToggleStandard => "SI/IEC",
+            Action::ToggleSparkScale => "spark scale",
+            Action::ToggleChart => "chart",
+            Action::CycleSort => "sort",
+            Action::ReverseSort => "reverse",
+            Action::FreezeOrder => "freeze",
+            Action::ToggleStats => "stats",
+            Action::ToggleAverages => "averages",
+            Action::ToggleMulticast => "multicast",
+            Action::ChooseColumns => "columns",
+            Action::ColumnLeft => "column left",
+            Action::ColumnRight => "column right",
+            Action::MoveColumnLeft => "move left",
+            Action::MoveColumnRight => "move right",
+            Action::ResetStats => "reset stats",
+            Action::ToggleGauge => "bars",
+            Action::ToggleSmooth => "smooth",
+            Action::ToggleIdle => "idle",
+            Action::TogglePin => "pin",
+            Action::Hide => "hide",
+            Action::HiddenList => "hidden",
+            Action::Search => "search",
+            Action::ToggleGroups => "groups",
+            Action::CycleTheme => "theme",
+            Action::Copy => "copy",
+            Action::CopyTable => "copy all",
+            Action::Export => "export",
+            Action::ExportAs => "export as",
+            Action::AlertLog => "alerts",
+            Action::Connections => "connections",
+            Action::ConnectionFilter => "filter",
+            Action::Processes => "processes",
+            Action::TopTalkers => "talkers",
+            Action::ToggleNames => "names",
+            Action::NestMembers => "nest",
+        }
+    }
+
+    // what the config file's [keys] table calls it
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Help => "help",
+            Action::Open => "open",
+            Action::Back => "back",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::HalfPageUp => "half_page_up",
+            Action::HalfPageDown => "half_page_down",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::Faster => "faster",
+            Action::Slower => "slower",
+            Action::ToggleVirtual => "virtual",
+            Action::ToggleTotals => "totals",
+            Action::Pause => "pause",
+            Action::ToggleBits => "bits",
+            Action::ToggleStandard => "si",
+            Action::ToggleSparkScale => "spark_scale",
+            Action::ToggleChart => "chart",
+            Action::CycleSort => "sort",
+            Action::ReverseSort => "reverse_sort",
+            Action::FreezeOrder => "freeze_order",
+            Action::ToggleStats => "stats",
+            Action::ToggleAverages => "averages",
+            Action::ToggleMulticast => "multicast",
+            Action::ChooseColumns => "columns",
+            Action::ColumnLeft => "column_left",
+            Action::ColumnRight => "column_right",
+            Action::MoveColumnLeft => "move_column_left",
+            Action::MoveColumnRight => "move_column_right",
+            Action::ResetStats => "reset_stats",
+            Action::ToggleGauge => "gauge",
+            Action::ToggleSmooth => "smooth",
+            Action::ToggleIdle => "idle",
+            Action::TogglePin => "pin",
+            Action::Hide => "hide",
+            Action::HiddenList => "hidden",
+            Action::Search => "search",
+            Action::ToggleGroups => "groups",
+            Action::CycleTheme => "theme",
+            Action::Copy => "copy",
+            Action::CopyTable => "copy_table",
+            Action::Export => "export",
+            Action::ExportAs => "export_as",
+            Action::AlertLog => "alerts",
+            Action::Connections => "connections",
+            Action::ConnectionFilter => "connection_filter",
+            Action::Processes => "processes",
+            Action::TopTalkers => "talkers",
+            Action::ToggleNames => "resolve",
+            Action::NestMembers => "nest_members",
+        }
+    }
+}
+
+// what the status bar offers in each view, most useful first; the keys come from the keymap
+pub const TABLE_HINTS: &[Action] = &[
+    Action::Quit,
+    Action::Help,
+    Action::Search,
+    Action::Open,
+    Action::Pause,
+    Action::CycleSort,
+    Action::Faster,
+    Action::Slower,
+    Action::ToggleTotals,
+    Action::ToggleBits,
+    Action::ToggleVirtual,
+    Action::ToggleChart,
+    Action::Hide,
+    Action::TogglePin,
+];
+pub const DETAIL_HINTS: &[Action] = &[
+    Action::Back,
+    Action::Up,
+    Action::Down,
+    Action::Pause,
+    Action::Faster,
+    Action::Slower,
+    Action::ToggleBits,
+    Action::Help,
+];
+pub const CONNECTION_HINTS: &[Action] = &[
+    Action::Connections,
+    Action::Up,
+    Action::Down,
+    Action::CycleSort,
+    Action::ReverseSort,
+    Action::ConnectionFilter,
+    Action::ToggleNames,
+    Action::Help,
+    Action::Quit,
+];
+pub const PROCESS_HINTS: &[Action] = &[
+    Action::Back,
+    Action::Up,
+    Action::Down,
+    Action::Connections,
+    Action::Help,
+    Action::Quit,
+];
+pub const TALKER_HINTS: &[Action] = &[
+    Action::Back,
+    Action::Up,
+    Action::Down,
+    Action::TopTalkers,
+    Action::ToggleNames,
+    Action::Help,
+    Action::Quit,
+];
+
+// raw mode turns ctrl+c into a key press instead of SIGINT
+fn is_ctrl_c(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+// width of the sparkline column
+pub const SPARK_WIDTH: usize = 20;
+
+// one line of the table: an interface, or in grouped mode a section header with the
+// interfaces under it, whether or not the section is folded
+#[derive(Clone, Debug)]
+pub enum Entry<'a> {
+    Group(Group, Vec<&'a RowData>),
+    Row(&'a RowData),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    Table,
+    Detail,
+    // the second tab, sockets instead of interfaces
+    Connections,
+    // who is moving the bytes on the selected interface
+    Processes,
+    // which remote hosts, from a packet capture
+    Talkers,
+}
+
+// everything the loop and the draw code share
+pub struct AppState {
+    pub view: View,
+    pub show_help: bool,
+    pub refresh_ms: u64,
+    pub cumulative: bool,
+    pub format: FormatOptions,
+    pub thresholds: Thresholds,
+    // the overrides from the config file survive a theme change
+    pub theme_name: ThemeName,
+    pub theme: Theme,
+    pub colors: Vec<(String, Color)>,
+    // NO_COLOR, --no-color or a dumb terminal: monochrome whatever the theme or the config says
+    pub no_color: bool,
+    // box drawing, sparkline levels and arrows; plain ASCII under --ascii
+    pub glyphs: &'static Glyphs,
+    pub keymap: Keymap,
+    pub filter: Filter,
+    pub csv: Option<CsvLog>,
+    pub exporter: Option<Exporter>,
+    pub paused: bool,
+    pub sort_key: SortKey,
+    pub sort_desc: bool,
+    // false until the first sample lands, so an empty table isn't mistaken for "no match"
+    pub sampled: bool,
+    // tracked by name so re-sorting keeps the cursor on the same interface
+    pub selected: Option<String>,
+    pub rows: Vec<RowData>,
+    // snapshot shown while paused
+    pub frozen: Vec<RowData>,
+    // 'f': samples keep the rows where they are instead of sorting them again; picking a
+    // sort still sorts once
+    pub freeze_order: bool,
+    // counters from the last read, rates are the difference to the next one
+    pub baseline: Baseline,
+    pub totals: HashMap<String, Totals>,
+    pub history: History,
+    // scale every sparkline against the busiest interface instead of its own peak
+    pub spark_global: bool,
+    // history chart under the table; the detail view always has one
+    pub show_chart: bool,
+    // utilization drawn as a bar instead of a bare percentage
+    pub show_gauge: bool,
+    // show the moving averages instead of the raw rates; only the display changes,
+    // history, CSV and the exporter always get the raw numbers
+    pub smooth: bool,
+    pub smooth_tau: Duration,
+    // leave idle interfaces out of the table, see is_idle
+    pub hide_idle: bool,
+    // listed first in this order whatever the sort; saved to the config file on quit
+    pub pins: Vec<String>,
+    // left out of the table and its totals but still sampled, so showing one again
+    // continues its history instead of starting over
+    pub hidden: Vec<String>,
+    // the 'X' overlay and the entry under its cursor
+    pub show_hidden: bool,
+    pub hidden_cursor: usize,
+    // every column in drawing order, on or off
+    pub columns: Columns,
+    // the column '<' and '>' move, None until an arrow key picks one
+    pub header_cursor: Option<Column>,
+    // the 'C' chooser and the entry under its cursor
+    pub show_columns: bool,
+    pub columns_cursor: usize,
+    // where a change in the chooser is saved, None without a config file to write
+    pub config_path: Option<PathBuf>,
+    // hostname, OS and kernel for the header, empty until main reads them
+    pub host: Host,
+    // when nettui started, for the uptime next to the clock
+    pub started: Instant,
+    // case-insensitive substring the names must contain, narrowed live while typing
+    pub search: String,
+    // the '/' prompt is open and takes every key; the cursor counts chars, not bytes
+    pub searching: bool,
+    pub search_cursor: usize,
+    // the 'E' prompt, with the path as typed so far; export_overwrite while it asks whether to
+    // replace the file already there
+    pub exporting: bool,
+    pub export_overwrite: bool,
+    pub export_path: String,
+    pub export_cursor: usize,
+    pub export_format: export::Format,
+    // sections by interface type, each foldable; sorting applies within a section
+    pub grouped: bool,
+    pub collapsed: Vec<Group>,
+    // bond and bridge ports right under their master and VLANs under their parent, indented
+    pub nest_members: bool,
+    // the cursor sits on this section's header instead of on `selected`
+    pub cursor_group: Option<Group>,
+    // rx/tx percentile estimates over the last hour, for the detail view
+    pub quantiles: Quantiles,
+    // the detail view's last percentiles, when and for which interface; the draw code
+    // recomputes them at most every PERCENTILES_EVERY
+    pub percentile_cache: RefCell<Option<(Instant, String, Option<Percentiles>)>>,
+    // when each interface last had an rx and a tx error; like totals, kept by name so an
+    // interface that disappears for a while still has them when it comes back
+    pub last_errors: HashMap<String, (Option<Instant>, Option<Instant>)>,
+    // highest (rx, tx) rate seen since stats_since
+    pub peaks: HashMap<String, (f64, f64)>,
+    // same, but never reset; the exit summary reports these
+    pub session_peaks: HashMap<String, (f64, f64)>,
+    // totals at the last reset, averages are (totals - baseline) / time since reset
+    pub stats_baseline: HashMap<String, Totals>,
+    pub stats_since: Instant,
+    // formatted table cells, rebuilt by refresh_text
+    pub text: TableText,
+    // something on screen changed since the last draw
+    pub dirty: bool,
+    // first visible table row, kept across frames so re-sorting doesn't jump back to the top;
+    // the draw code updates it, hence the Cell
+    pub table_offset: Cell<usize>,
+    // table rows that fit on screen at the last draw, the PageUp/PageDown step
+    pub page_rows: Cell<usize>,
+    // clicks select, double-clicks open, the wheel scrolls; off with --no-mouse
+    pub mouse: bool,
+    // where the last draw put the table (inside the border, header row first) and each
+    // shown column as (column, x, width), empty when no table was drawn
+    pub table_area: Cell<Rect>,
+    pub table_columns: RefCell<Vec<(Column, u16, u16)>>,
+    // when and on which entry the last click landed, for double-clicks
+    pub last_click: Option<(Instant, usize)>,
+    // --alert rules, what is firing and the log of transitions
+    pub alerts: Alerts,
+    // the 'a' pane, how many of its newest entries are scrolled past, and alerts.logged
+    // when it was last open; the header counts the rest as unread
+    pub show_alerts: bool,
+    pub alerts_scroll: usize,
+    pub alerts_seen: u64,
+    // --data-cap's running total
+    pub budget: Option<Budget>,
+    // --notify's worker, told about every sample
+    pub notifier: Option<Notifier>,
+    // an alert fired; the event loop rings the terminal bell and clears it
+    pub bell: bool,
+    // shown in place of the key hints until MESSAGE_FOR has passed
+    pub message: Option<(String, Instant)>,
+    // when the last sample landed, for the status bar
+    pub last_sample: Option<Instant>,
+    // the connections tab: the last read and when it landed, sorted by conns_sort; read
+    // every conns_every by a conns::Poller, and only while the tab is shown
+    pub conns: Listing,
+    pub conns_at: Option<Instant>,
+    pub conns_every: Duration,
+    pub conns_sort: ConnSort,
+    pub conns_desc: bool,
+    // first listed socket, the tab scrolls instead of having a cursor
+    pub conns_scroll: usize,
+    // every socket instead of only the selected interface's
+    pub conns_all: bool,
+    // the processes tab's last reading, from a procs::Meter on a Poller at conns_every
+    pub procs: Reading,
+    pub procs_at: Option<Instant>,
+    // the top talkers tab: a capture on capture_on while the tab is up, or why it couldn't
+    // start; its packets are tallied between samples into talkers
+    pub capture: Option<Capture>,
+    pub capture_on: Option<String>,
+    pub capture_error: Option<String>,
+    pub tally: Tally,
+    pub talkers: Vec<Talker>,
+    pub talkers_at: Option<Instant>,
+    // remote addresses by name in both tabs; answers from the resolver's worker land in
+    // names, and at most LOOKUPS_PER_SAMPLE new questions go out per sample
+    pub resolve: bool,
+    pub names: Names,
+    pub resolver: Option<Resolver>,
+    pub lookups_left: usize,
+    // the routing and neighbor tables, re-read every TABLES_EVERY with the samples
+    pub routes: Vec<Route>,
+    pub neighbors: Vec<Neighbor>,
+    pub tables_at: Option<Instant>,
+    // gateway latency: whether to probe at all (--no-ping), the Pinger once started, what
+    // it was last told to probe, and the results by interface
+    pub ping: bool,
+    pub pinger: Option<Pinger>,
+    pub ping_targets: Vec<Target>,
+    pub latency: HashMap<String, Latency>,
+    // peers of the WireGuard interface whose detail view is open, read with every sample
+    pub wireguard: Option<Tracker>,
+    // RX/TX of each bond and bridge port, hidden ones included, by port name
+    pub member_rates: HashMap<String, (f64, f64)>,
+    // --containers: the container behind each veth, re-read every CONTAINERS_EVERY
+    pub containers: bool,
+    pub container_names: Containers,
+    pub containers_at: Option<Instant>,
+    // --backend and --netns/--all-netns, what main builds the source from
+    pub backend: Backend,
+    pub netns: Selection,
+    // namespaces that failed to read in the last sample, each announced once when it starts failing
+    pub netns_errors: Vec<(String, String)>,
+    // Windows adapter and macOS service names, looked up again only when the interface list changes
+    pub adapters: Adapters,
+    adapters_for: Vec<String>,
+    // --replay: the recording samples come from instead of the live counters
+    pub replay: Option<Replay>,
+    // `nettui serve`: where each sample goes to the clients
+    pub server: Option<Server>,
+    // `nettui connect`: the server samples come from, and whether it's reachable
+    pub remote: Option<Remote>,
+    // --socket: answers queries from the last sample
+    pub socket: Option<QuerySocket>,
+    // --influx: where each sample is written, and how that's going
+    pub influx: Option<Influx>,
+    // --statsd: gauges sent for each sample
+    pub statsd: Option<Statsd>,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        AppState::new()
+    }
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        let mut app = AppState {
+            view: View::Table,
+            show_help: false,
+            refresh_ms: 500,
+            cumulative: false,
+            format: FormatOptions::default(),
+            thresholds: Thresholds::default(),
+            theme_name: ThemeName::Dark,
+            theme: Theme::builtin(ThemeName::Dark),
+            colors: Vec::new(),
+            no_color: theme::no_color_env(),
+            glyphs: Glyphs::new(false),
+            keymap: Keymap::default(),
+            filter: Filter::default(),
+            csv: None,
+            exporter: None,
+            paused: false,
+            sort_key: SortKey::Total,
+            sort_desc: true,
+            sampled: false,
+            selected: None,
+            rows: Vec::new(),
+            frozen: Vec::new(),
+            freeze_order: false,
+            baseline: Baseline::new(),
+            totals: HashMap::new(),
+            history: History::new(history::DEFAULT_CAPACITY),
+            spark_global: false,
+            show_chart: false,
+            show_gauge: false,
+            smooth: false,
+            smooth_tau: Duration::from_secs(2),
+            hide_idle: false,
+            pins: Vec::new(),
+            hidden: Vec::new(),
+            show_hidden: false,
+            hidden_cursor: 0,
+            columns: Columns::default(),
+            header_cursor: None,
+            show_columns: false,
+            columns_cursor: 0,
+            config_path: None,
+            host: Host::default(),
+            started: Instant::now(),
+            search: String::new(),
+            searching: false,
+            search_cursor: 0,
+            exporting: false,
+            export_overwrite: false,
+            export_path: String::new(),
+            export_cursor: 0,
+            export_format: export::Format::Txt,
+            grouped: false,
+            nest_members: false,
+            collapsed: Vec::new(),
+            cursor_group: None,
+            quantiles: Quantiles::new(),
+            last_errors: HashMap::new(),
+            percentile_cache: RefCell::new(None),
+            peaks: HashMap::new(),
+            session_peaks: HashMap::new(),
+            stats_baseline: HashMap::new(),
+            stats_since: Instant::now(),
+            text: TableText::default(),
+            dirty: true,
+            table_offset: Cell::new(0),
+            page_rows: Cell::new(0),
+            mouse: true,
+            table_area: Cell::new(Rect::default()),
+            table_columns: RefCell::new(Vec::new()),
+            last_click: None,
+            message: None,
+            alerts: Alerts::default(),
+            show_alerts: false,
+            alerts_scroll: 0,
+            alerts_seen: 0,
+            notifier: None,
+            budget: None,
+            bell: false,
+            last_sample: None,
+            conns: Listing::default(),
+            conns_at: None,
+            conns_every: Duration::from_secs(2),
+            conns_sort: ConnSort::State,
+            conns_desc: false,
+            conns_scroll: 0,
+            conns_all: false,
+            procs: Reading::default(),
+            procs_at: None,
+            capture: None,
+            capture_on: None,
+            capture_error: None,
+            tally: Tally::default(),
+            talkers: Vec::new(),
+            talkers_at: None,
+            resolve: false,
+            names: Names::new(resolve::DEFAULT_CACHE),
+            resolver: None,
+            lookups_left: LOOKUPS_PER_SAMPLE,
+            routes: Vec::new(),
+            neighbors: Vec::new(),
+            tables_at: None,
+            ping: true,
+            pinger: None,
+            ping_targets: Vec::new(),
+            latency: HashMap::new(),
+            wireguard: None,
+            member_rates: HashMap::new(),
+            containers: false,
+            container_names: Containers::new(),
+            containers_at: None,
+            backend: Backend::Sysinfo,
+            netns: Selection::default(),
+            netns_errors: Vec::new(),
+            adapters: Adapters::new(),
+            adapters_for: Vec::new(),
+            replay: None,
+            server: None,
+            remote: None,
+            socket: None,
+            influx: None,
+            statsd: None,
+        };
+        app.set_theme(ThemeName::Dark);
+        app.refresh_text();
+        app
+    }
+
+    pub fn from_args(args: Args) -> Self {
+        let mut app = AppState::new();
+        app.refresh_ms = args.interval;
+        app.format.bits = args.bits;
+        app.format.precision = args.precision as usize;
+        app.format.fixed = !args.compact;
+        app.format.separator = args.digit_separator;
+        app.glyphs = Glyphs::new(args.ascii);
+        app.mouse = !args.no_mouse;
+        // Config::parse already turned conflicting bindings into an error
+        app.keymap = Keymap::new(&args.keys).unwrap_or_default();
+        // a thin space is no better on these terminals than box drawing
+        if args.ascii && app.format.separator == DigitSeparator::Thin {
+            app.format.separator = DigitSeparator::Comma;
+        }
+        app.colors = args.colors;
+        app.no_color |= args.no_color;
+        app.set_theme(args.theme);
+        // a column list turns off whatever it leaves out, the flags only add
+        if !args.columns.is_empty() {
+            app.columns = Columns::from_names(&args.columns);
+        }
+        for (on, group) in [(args.stats, &columns::STATS[..]), (args.averages, &columns::AVERAGES), (args.multicast, &columns::MULTICAST)] {
+            for &c in group.iter().filter(|_| on) {
+                app.columns.set(c, true);
+            }
+        }
+        app.reserve_history();
+        app.smooth = args.smooth;
+        app.hide_idle = args.hide_idle;
+        app.pins = args.pin;
+        app.hidden = args.hide;
+        app.smooth_tau = Duration::from_millis(args.smooth_tau);
+        app.conns_every = Duration::from_millis(args.connections_interval);
+        app.resolve = args.resolve;
+        app.names = Names::new(args.dns_cache);
+        app.ping = !args.no_ping;
+        app.containers = args.containers;
+        app.backend = args.backend;
+        app.netns = Selection { names: args.netns, all: args.all_netns };
+        app.thresholds = Thresholds { warn: args.warn, crit: args.crit.max(args.warn) };
+        app.alerts = Alerts::new(args.alert, args.alert_clear);
+        if let Some(cap) = args.data_cap {
+            app.budget = Some(Budget::load(cap, datacap::default_path(), snapshot::unix_now()));
+        }
+        if args.notify || args.notify_alerts {
+            app.notifier = Some(Notifier::start(Duration::from_secs(args.notify_every), args.notify_alerts));
+        }
+        if args.si {
+            app.format.standard = UnitStandard::Si;
+        }
+        app.filter.include = args.iface.into_iter().chain(args.interface).collect();
+        app.filter.exclude = args.exclude;
+        app.filter.show_virtual = args.show_virtual;
+        app.refresh_text();
+        app
+    }
+
+    // reads the counters once so the first sample has something to diff against
+    pub fn prime(&mut self, networks: &mut impl NetworkSource) {
+        deltas(networks, &mut self.baseline);
+    }
+
+    // one sample tick: `elapsed` is the time since the previous sample
+    pub fn sample(&mut self, networks: &mut impl NetworkSource, now: Instant, elapsed: Duration) {
+        let ports = self.port_totals();
+        let cursor = self.selected_index();
+        let prev = std::mem::replace(
+            &mut self.rows,
+            collect(networks, &mut self.baseline, &mut self.totals, elapsed.as_secs_f64(), &self.filter),
+        );
+        self.smooth_rates(&prev, elapsed);
+        self.update_member_rates(&ports, elapsed);
+        self.note_netns_errors(networks.netns_errors(), now);
+        self.update_adapters(networks);
+        sort_rows(&mut self.rows, self.sort_key, self.sort_desc, self.cumulative);
+        pin_rows(&mut self.rows, &self.pins);
+        if self.freeze_order {
+            keep_order(&mut self.rows, &prev);
+        }
+        self.record_history(now);
+        self.update_peaks();
+        self.note_errors(now);
+        if self.tables_at.is_none_or(|at| now.saturating_duration_since(at) >= TABLES_EVERY) {
+            self.routes = networks.routes();
+            self.neighbors = networks.neighbors();
+            self.tables_at = Some(now);
+            self.update_ping_targets();
+        }
+        self.update_talkers(now, elapsed);
+        self.update_wireguard(networks, elapsed);
+        if self.containers && self.containers_at.is_none_or(|at| now.saturating_duration_since(at) >= CONTAINERS_EVERY) {
+            self.container_names = networks.containers();
+            self.containers_at = Some(now);
+        }
+        self.lookups_left = LOOKUPS_PER_SAMPLE;
+        self.request_names(now);
+        let logged = self.alerts.logged;
+        self.bell |= self.alerts.update(&self.rows, snapshot::unix_now(), now);
+        self.notify(&prev, (self.alerts.logged - logged) as usize, now);
+        if let Some(budget) = &mut self.budget {
+            budget.update(self.totals.get(&budget.cap.interface), snapshot::unix_now(), now);
+        }
+        if self.show_alerts {
+            self.alerts_seen = self.alerts.logged;
+        }
+        self.follow_selection(cursor, now);
+        self.sampled = true;
+        self.last_sample = Some(now);
+        if let Some(csv) = &mut self.csv {
+            csv.write(&self.rows, snapshot::unix_now());
+        }
+        if let Some(exporter) = &self.exporter {
+            exporter.update(&self.rows);
+        }
+        if let Some(server) = &self.server {
+            server.update(&self.rows, snapshot::unix_now(), elapsed.as_secs_f64());
+        }
+        if let Some(socket) = &self.socket {
+            socket.update(&self.rows, snapshot::unix_now(), elapsed.as_secs_f64());
+        }
+        if let Some(influx) = &self.influx {
+            influx.update(&self.rows, snapshot::unix_now(), elapsed.as_secs_f64());
+        }
+        if let Some(statsd) = &mut self.statsd {
+            statsd.update(&self.rows, snapshot::unix_now(), elapsed.as_secs_f64());
+        }
+        self.refresh_text();
+        self.dirty = true;
+    }
+
+    // `nettui connect`: a server's sample goes through sample() as if read here
+    pub fn remote_update(&mut self, update: Update, now: Instant) {
+        let Some(mut remote) = self.remote.take() else {
+            return;
+        };
+        match update {
+            Update::Connected(hello) => {
+                self.host = hello.host.clone();
+                if hello.interval_ms > 0 {
+                    self.refresh_ms = hello.interval_ms;
+                    self.reserve_history();
+                }
+                remote.link = Link::Connected(hello);
+                remote.failures = 0;
+            }
+            Update::Sample(frame) => {
+                // an interval no recording would have is a broken server, its sample is dropped
+                let interval = Some(frame.interval).filter(|&i| i <= replay::MAX_INTERVAL);
+                if let Some(interval) = interval.and_then(|i| Duration::try_from_secs_f64(i).ok()) {
+                    // the first one needs a baseline, which is what came before it
+                    if !self.sampled {
+                        remote.before(&frame);
+                        self.prime(&mut remote);
+                    }
+                    remote.advance(&frame);
+                    self.sample(&mut remote, now, interval);
+                }
+            }
+            Update::Lost(reason, wait) => {
+                remote.link = Link::Lost(reason, now + wait);
+                remote.failures += 1;
+            }
+        }
+        self.remote = Some(remote);
+        self.refresh_text();
+        self.dirty = true;
+    }
+
+    // --replay: samples every frame up to `to` like a live tick. History, peaks and alerts
+    // can't be unwound, so stepping back starts over from the first frame
+    pub fn seek(&mut self, to: usize) {
+        let Some(mut replay) = self.replay.take().filter(|r| !r.is_empty()) else {
+            return;
+        };
+        let to = to.min(replay.len() - 1);
+        if replay.shown.is_none_or(|shown| to < shown) {
+            self.rewind();
+            replay.next = 0;
+            replay.shown = None;
+            self.prime(&mut replay);
+        }
+        while replay.shown.is_none_or(|shown| shown < to) {
+            let i = replay.shown.map_or(0, |shown| shown + 1);
+            replay.next = i + 1;
+            let (at, interval) = replay.timing(i);
+            self.sample(&mut replay, at, interval);
+            replay.shown = Some(i);
+        }
+        self.replay = Some(replay);
+        // sample() drew the title while the replay was out
+        self.refresh_text();
+    }
+
+    // a Tick while replaying: whatever frames are due at the playback speed
+    pub fn play(&mut self, now: Instant) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        let due = replay.due(now);
+        if due == 0 {
+            return;
+        }
+        let to = replay.shown.map_or(due - 1, |shown| shown + due);
+        self.seek(to);
+        if let Some(replay) = &mut self.replay
+            && replay.shown == Some(replay.len() - 1)
+        {
+            replay.playing = false;
+            self.message = Some(("end of the recording".to_string(), now));
+            self.refresh_text();
+        }
+    }
+
+    fn step(&mut self, delta: isize) {
+        if let Some(shown) = self.replay.as_ref().and_then(|r| r.shown) {
+            self.seek(shown.saturating_add_signed(delta));
+        }
+    }
+
+    // space at the last frame plays the recording again from the start
+    fn toggle_playing(&mut self) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        let now = Instant::now();
+        if !replay.playing && replay.shown == Some(replay.len() - 1) {
+            self.seek(0);
+        }
+        if let Some(replay) = &mut self.replay {
+            replay.toggle_playing(now);
+        }
+    }
+
+    // everything sample() builds up, for a replay starting over
+    fn rewind(&mut self) {
+        self.rows.clear();
+        self.frozen.clear();
+        self.baseline = Baseline::new();
+        self.totals.clear();
+        self.history = History::new(self.history.capacity());
+        self.quantiles = Quantiles::new();
+        self.last_errors.clear();
+        self.peaks.clear();
+        self.session_peaks.clear();
+        self.stats_baseline.clear();
+        self.member_rates.clear();
+        self.alerts = Alerts::new(std::mem::take(&mut self.alerts.rules), self.alerts.clear_after);
+        self.alerts_seen = 0;
+        self.last_sample = None;
+        self.sampled = false;
+    }
+
+    // hidden interfaces count too, the baseline has every one the source reported
+    fn update_adapters(&mut self, networks: &impl NetworkSource) {
+        let mut names: Vec<String> = self.baseline.keys().cloned().collect();
+        names.sort_unstable();
+        if names != self.adapters_for {
+            self.adapters = networks.adapters();
+            self.adapters_for = names;
+        }
+    }
+
+    // what the table and the detail view call an interface: on Windows the adapter's friendly
+    // name and description, on macOS the service name, rather than what the counters came under
+    pub fn display_name(&self, name: &str) -> String {
+        self.adapters.get(name).map_or_else(|| name.to_string(), |a| a.label(self.glyphs.dash))
+    }
+
+    // a namespace that can't be read is left out with a message, the rest carry on
+    fn note_netns_errors(&mut self, errors: Vec<(String, String)>, now: Instant) {
+        if let Some((ns, e)) = errors.iter().find(|e| !self.netns_errors.contains(e)) {
+            self.message = Some((format!("netns {ns}: {e}"), now));
+        }
+        self.netns_errors = errors;
+    }
+
+    // `new` is how many events the alerts just logged
+    fn notify(&mut self, prev: &[RowData], new: usize, now: Instant) {
+        if self.notifier.is_none() {
+            return;
+        }
+        let log = &self.alerts.log;
+        let fired: Vec<(String, String)> = log[log.len().saturating_sub(new)..]
+            .iter()
+            .filter(|e| e.transition == Transition::Fired)
+            .map(|e| (e.interface.clone(), self.alert_line(e)))
+            .collect();
+        let Some(notifier) = &mut self.notifier else {
+            return;
+        };
+        notifier.update(prev, &self.rows, &fired, now);
+        // the worker reports its first failure, after that they are ignored
+        if let Some(w) = notifier.warning() {
+            self.message = Some((format!("cannot send notifications: {w}"), now));
+        }
+    }
+
+    // averaged every sample whether or not smoothing is shown, so toggling it on is
+    // instantly steady; an interface without a previous row keeps its seed
+    fn smooth_rates(&mut self, prev: &[RowData], elapsed: Duration) {
+        let prev: HashMap<&str, &RowData> = prev.iter().map(|p| (p.interface.as_str(), p)).collect();
+        for r in &mut self.rows {
+            if let Some(p) = prev.get(r.interface.as_str()) {
+                r.rx_smooth = ema(p.rx_smooth, r.rx_bps, elapsed, self.smooth_tau);
+                r.tx_smooth = ema(p.tx_smooth, r.tx_bps, elapsed, self.smooth_tau);
+            }
+        }
+    }
+
+    // what the table shows for RX/TX
+    pub fn shown_rates(&self, r: &RowData) -> (f64, f64) {
+        if self.smooth { (r.rx_smooth, r.tx_smooth) } else { (r.rx_bps, r.tx_bps) }
+    }
+
+    // the rows the table lists, in order; hidden ones never, idle ones not while hide_idle is on,
+    // and only names matching the search
+    pub fn shown(&self) -> Vec<&RowData> {
+        let rows = if self.paused { &self.frozen } else { &self.rows };
+        rows.iter()
+            .filter(|r| !self.hidden.contains(&r.interface))
+            .filter(|r| self.matches_search(&r.interface))
+            .filter(|r| !(self.hide_idle && self.is_idle(&r.interface)))
+            .collect()
+    }
+
+    pub fn matches_search(&self, name: &str) -> bool {
+        self.search.is_empty() || name.to_lowercase().contains(&self.search.to_lowercase())
+    }
+
+    // needs IDLE_SAMPLES samples of history, a new interface is never idle; the newest
+    // sample counts too, so traffic brings a row back on the very next sample
+    pub fn is_idle(&self, name: &str) -> bool {
+        let Some(ring) = self.history.get(name) else {
+            return false;
+        };
+        ring.len() >= IDLE_SAMPLES && ring.iter().rev().take(IDLE_SAMPLES).all(|s| s.total() < IDLE_BPS)
+    }
+
+    // rows left out of the table by hide_idle, for the header
+    pub fn hidden_idle(&self) -> usize {
+        let rows = if self.paused { &self.frozen } else { &self.rows };
+        if !self.hide_idle {
+            return 0;
+        }
+        rows.iter().filter(|r| !self.hidden.contains(&r.interface) && self.is_idle(&r.interface)).count()
+    }
+
+    pub fn rate(&self, bps: f64) -> String {
+        format_rate(bps, &self.format)
+    }
+
+    // the built-in theme with the config file's colors on top, or plain monochrome under
+    // no_color; the cached cells carry their styles, so callers refresh_text afterwards
+    pub fn set_theme(&mut self, name: ThemeName) {
+        if self.no_color {
+            self.theme_name = ThemeName::Monochrome;
+            self.theme = Theme::builtin(ThemeName::Monochrome);
+            return;
+        }
+        self.theme_name = name;
+        self.theme = Theme::builtin(name).with_overrides(&self.colors);
+    }
+
+    // green/yellow/red by the --warn/--crit thresholds
+    pub fn rate_style(&self, bps: f64) -> Style {
+        if bps < 1.0 {
+            return self.theme.text;
+        }
+        self.theme.level(bps, self.thresholds.warn, self.thresholds.crit)
+    }
+
+    // a single new error is worth noticing
+    pub fn error_style(&self, per_sec: f64) -> Style {
+        if per_sec > 0.0 { self.theme.error } else { self.theme.text }
+    }
+
+    pub fn pps(&self, pps: f64) -> String {
+        human_pps(pps, self.format.fixed)
+    }
+
+    // a raw counter with its digits grouped
+    pub fn count(&self, n: u64) -> String {
+        group_digits(n, self.format.separator)
+    }
+
+    // packets get their own unit so a small-packet flood reads as one next to the byte rates
+    pub fn packet_rate(&self, pps: f64) -> String {
+        human_count_rate(pps, "pps", self.format.fixed)
+    }
+
+    pub fn size(&self, bytes: u64) -> String {
+        match self.format.standard {
+            UnitStandard::Iec => format_size(bytes, BINARY),
+            UnitStandard::Si => format_size(bytes, DECIMAL),
+        }
+    }
+
+    pub fn selected_name(&self) -> Option<&str> {
+        self.selected.as_deref()
+    }
+
+    // the table's lines: shown() as it is, or split into sections with folded ones
+    // reduced to their header; if asked, ports and VLANs follow what they belong to
+    pub fn entries(&self) -> Vec<Entry<'_>> {
+        let shown = self.shown();
+        let masters = self.masters(&shown);
+        if !self.grouped {
+            if masters.is_empty() {
+                return shown.into_iter().map(Entry::Row).collect();
+            }
+            let mut rows = Vec::new();
+            for r in shown.iter().filter(|r| !masters.contains_key(r.interface.as_str())) {
+                nest(r, &shown, &masters, &mut rows);
+            }
+            return rows.into_iter().map(Entry::Row).collect();
+        }
+        let mut entries = Vec::new();
+        for group in Group::ALL {
+            let mut members = Vec::new();
+            for r in shown.iter().filter(|r| r.info.kind.group() == group && !masters.contains_key(r.interface.as_str())) {
+                nest(r, &shown, &masters, &mut members);
+            }
+            if members.is_empty() {
+                continue;
+            }
+            entries.push(Entry::Group(group, members.clone()));
+            if !self.collapsed.contains(&group) {
+                entries.extend(members.into_iter().map(Entry::Row));
+            }
+        }
+        entries
+    }
+
+    // port or VLAN -> what it belongs to among the shown interfaces while they're nested;
+    // one whose master is filtered out stays where it was
+    fn masters<'a>(&self, shown: &[&'a RowData]) -> HashMap<&'a str, &'a str> {
+        let mut masters = HashMap::new();
+        if !self.nest_members {
+            return masters;
+        }
+        let is_shown = |name: &str| shown.iter().any(|s| s.interface == name);
+        for r in shown {
+            for port in r.info.members.iter().filter(|p| **p != r.interface && is_shown(p)) {
+                masters.entry(port.as_str()).or_insert(r.interface.as_str());
+            }
+        }
+        // a VLAN on a bond's port is unusual, the port keeps its bond
+        for r in shown {
+            if let Some(vlan) = r.info.vlan.as_ref().filter(|v| v.parent != r.interface && is_shown(&v.parent)) {
+                masters.entry(r.interface.as_str()).or_insert(vlan.parent.as_str());
+            }
+        }
+        masters
+    }
+
+    // how many masters up the shown interface `name` is nested, 0 when it isn't
+    pub fn nest_depth(&self, name: &str) -> usize {
+        if !self.nest_members {
+            return 0;
+        }
+        let masters = self.masters(&self.shown());
+        // a VLAN on a bridge over a bond over ports is as deep as it gets; the bound only guards
+        // against a source that reports a loop
+        std::iter::successors(masters.get(name), |m| masters.get(*m)).take(8).count()
+    }
+
+    // position of the cursor in entries()
+    pub fn selected_index(&self) -> Option<usize> {
+        self.entries().iter().position(|e| match (e, self.cursor_group) {
+            (Entry::Group(g, _), Some(cursor)) => *g == cursor,
+            (Entry::Row(r), None) => self.selected_name() == Some(r.interface.as_str()),
+            _ => false,
+        })
+    }
+
+    // puts the cursor on entry `idx`, or the last one past the end
+    fn select_at(&mut self, idx: usize) {
+        let entries = self.entries();
+        let (group, name) = match entries.get(idx.min(entries.len().saturating_sub(1))) {
+            Some(Entry::Group(g, _)) => (Some(*g), self.selected.clone()),
+            Some(Entry::Row(r)) => (None, Some(r.interface.clone())),
+            None => (None, None),
+        };
+        self.cursor_group = group;
+        self.selected = name;
+    }
+
+    // the selected interface went away (unplugged, container stopped, gone idle): the cursor
+    // takes whatever row is now where it was and says so; `cursor` is where it was before
+    fn follow_selection(&mut self, cursor: Option<usize>, now: Instant) {
+        let (Some(name), Some(at), None) = (self.selected.clone(), cursor, self.cursor_group) else {
+            return;
+        };
+        if self.selected_index().is_some() {
+            return;
+        }
+        self.select_at(at);
+        let message = match (&self.selected, self.cursor_group) {
+            (Some(next), None) if *next != name => format!("{name} went away, selected {next}"),
+            _ => format!("{name} went away"),
+        };
+        self.message = Some((message, now));
+    }
+
+    // clamped at both ends; starts from the top when nothing is selected yet
+    fn move_selection(&mut self, delta: isize) {
+        if self.shown().is_empty() {
+            return;
+        }
+        let idx = match self.selected_index() {
+            Some(i) => i.saturating_add_signed(delta),
+            None => 0,
+        };
+        self.select_at(idx);
+    }
+
+    // folds or unfolds the section under the cursor, nothing when the cursor is on a row
+    fn toggle_group(&mut self) {
+        let Some(group) = self.cursor_group else {
+            return;
+        };
+        match self.collapsed.iter().position(|g| *g == group) {
+            Some(i) => {
+                self.collapsed.remove(i);
+            }
+            None => self.collapsed.push(group),
+        }
+        self.refresh_text();
+    }
+
+    fn sort(&mut self) {
+        sort_rows(&mut self.rows, self.sort_key, self.sort_desc, self.cumulative);
+        sort_rows(&mut self.frozen, self.sort_key, self.sort_desc, self.cumulative);
+        pin_rows(&mut self.rows, &self.pins);
+        pin_rows(&mut self.frozen, &self.pins);
+    }
+
+    // names read best A-Z, everything else highest first
+    fn cycle_sort(&mut self) {
+        self.sort_key = self.sort_key.next();
+        self.sort_desc = self.sort_key != SortKey::Name;
+        self.sort();
+    }
+
+    fn reverse_sort(&mut self) {
+        self.sort_desc = !self.sort_desc;
+        self.sort();
+    }
+
+    fn record_history(&mut self, at: Instant) {
+        for r in &self.rows {
+            self.history.push(&r.interface, Sample { at, rx: r.rx_bps, tx: r.tx_bps });
+            self.quantiles.push(&r.interface, at, r.rx_bps, r.tx_bps);
+        }
+        self.history.prune_stale(at, history::STALE_AFTER);
+        self.quantiles.prune(at);
+    }
+
+    fn note_errors(&mut self, now: Instant) {
+        for r in &self.rows {
+            if r.errors_in > 0.0 || r.errors_out > 0.0 {
+                let last = self.last_errors.entry(r.interface.clone()).or_default();
+                if r.errors_in > 0.0 {
+                    last.0 = Some(now);
+                }
+                if r.errors_out > 0.0 {
+                    last.1 = Some(now);
+                }
+            }
+        }
+    }
+
+    // had an error within ERROR_FLASH of the latest sample; the highlight goes with the
+    // next sample after that
+    pub fn error_flash(&self, name: &str) -> bool {
+        let (Some(now), Some(&(rx, tx))) = (self.last_sample, self.last_errors.get(name)) else {
+            return false;
+        };
+        rx.max(tx).is_some_and(|at| now.saturating_duration_since(at) < ERROR_FLASH)
+    }
+
+    fn update_peaks(&mut self) {
+        for r in &self.rows {
+            for peaks in [&mut self.peaks, &mut self.session_peaks] {
+                let peak = peaks.entry(r.interface.clone()).or_default();
+                peak.0 = peak.0.max(r.rx_bps);
+                peak.1 = peak.1.max(r.tx_bps);
+            }
+        }
+    }
+
+    // session average from cumulative bytes, not a mean of instantaneous samples
+    pub fn average(&self, r: &RowData) -> (f64, f64) {
+        let secs = self.stats_since.elapsed().as_secs_f64().max(1e-3);
+        let base = self.stats_baseline.get(&r.interface).copied().unwrap_or_default();
+        (
+            r.totals.rx_bytes.saturating_sub(base.rx_bytes) as f64 / secs,
+            r.totals.tx_bytes.saturating_sub(base.tx_bytes) as f64 / secs,
+        )
+    }
+
+    // highest rx and tx rate since the stats were last reset
+    pub fn peak(&self, r: &RowData) -> (f64, f64) {
+        self.peaks.get(&r.interface).copied().unwrap_or_default()
+    }
+
+    // p50/p95/p99 of rx and tx, cached for PERCENTILES_EVERY since sorting the reservoirs
+    // on every frame adds up
+    pub fn percentiles(&self, name: &str) -> Option<Percentiles> {
+        let mut cache = self.percentile_cache.borrow_mut();
+        if let Some((at, cached, p)) = cache.as_ref()
+            && cached == name
+            && at.elapsed() < PERCENTILES_EVERY
+        {
+            return p.clone();
+        }
+        let now = self.last_sample.unwrap_or_else(Instant::now);
+        let p = self.quantiles.percentiles(name, now, &PERCENTILES);
+        *cache = Some((Instant::now(), name.to_string(), p.clone()));
+        p
+    }
+
+    // "~" marks an average over less than its window, "--" is no history at all
+    pub fn average_cell(&self, name: &str, window: Duration) -> String {
+        let now = self.last_sample.unwrap_or_else(Instant::now);
+        match self.history.average(name, now, window) {
+            Some(a) if a.partial => format!("~{}", self.rate(a.rate).trim()),
+            Some(a) => self.rate(a.rate),
+            None => "--".to_string(),
+        }
+    }
+
+    // enough samples for the longest average window at the current interval
+    fn reserve_history(&mut self) {
+        if columns::AVERAGES.iter().any(|&c| self.columns.shows(c)) {
+            let longest = history::AVERAGE_WINDOWS[2].1.as_millis() as u64;
+            self.history.reserve((longest / self.refresh_ms.max(1)) as usize + 2);
+        }
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.peaks.clear();
+        self.stats_baseline = self.totals.clone();
+        self.stats_since = Instant::now();
+    }
+
+    pub fn sparkline(&self, name: &str) -> String {
+        let Some(ring) = self.history.get(name) else {
+            return String::new();
+        };
+        let max = if self.spark_global { self.history.global_max() } else { self.history.max(name) };
+        history::sparkline(ring.iter(), max, SPARK_WIDTH, self.glyphs.bars)
+    }
+
+    // returns false when the app should quit
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if is_ctrl_c(&key) {
+            return false;
+        }
+        if self.show_help {
+            //any key dismisses the help overlay
+            self.show_help = false;
+            self.dirty = true;
+            return true;
+        }
+        if self.show_hidden {
+            self.hidden_list_key(key.code);
+            self.dirty = true;
+            return true;
+        }
+        if self.show_columns {
+            self.column_list_key(key.code);
+            self.dirty = true;
+            return true;
+        }
+        if self.show_alerts {
+            self.alert_log_key(key.code);
+            self.dirty = true;
+            return true;
+        }
+        if self.searching {
+            self.search_key(key);
+            self.dirty = true;
+            return true;
+        }
+        if self.exporting {
+            self.export_key(key);
+            self.dirty = true;
+            return true;
+        }
+        match self.keymap.action_for(Key::from_event(&key)) {
+            Some(action) => self.apply(action),
+            None => true,
+        }
+    }
+
+    // hit-tested against the table as it was last drawn; overlays and the search prompt
+    // ignore the mouse, except that a click closes the help like any key
+    pub fn handle_mouse(&mut self, event: MouseEvent, now: Instant) {
+        if self.show_help {
+            if matches!(event.kind, MouseEventKind::Down(_)) {
+                self.show_help = false;
+                self.dirty = true;
+            }
+            return;
+        }
+        if self.show_hidden || self.show_columns || self.show_alerts || self.searching || self.exporting || self.view != View::Table {
+            return;
+        }
+        let area = self.table_area.get();
+        let inside = area.contains((event.column, event.row).into());
+        match event.kind {
+            MouseEventKind::ScrollUp if inside => self.move_selection(-WHEEL_ROWS),
+            MouseEventKind::ScrollDown if inside => self.move_selection(WHEEL_ROWS),
+            MouseEventKind::Down(MouseButton::Left) if inside && event.row == area.y => {
+                let columns = self.table_columns.borrow();
+                let clicked = columns.iter().find(|(_, x, w)| (*x..x + w).contains(&event.column)).map(|c| c.0);
+                drop(columns);
+                let Some(key) = clicked.and_then(SortKey::for_column) else {
+                    return;
+                };
+                if key == self.sort_key {
+                    self.reverse_sort();
+                } else {
+                    self.sort_key = key;
+                    self.sort_desc = key != SortKey::Name;
+                    self.sort();
+                }
+                self.refresh_text();
+            }
+            MouseEventKind::Down(MouseButton::Left) if inside => {
+                // below the rows is the footer
+                let line = (event.row - area.y - 1) as usize;
+                let idx = self.table_offset.get() + line;
+                if line >= self.page_rows.get() || idx >= self.entries().len() {
+                    return;
+                }
+                self.select_at(idx);
+                let double = self.last_click.is_some_and(|(at, i)| i == idx && now.duration_since(at) <= DOUBLE_CLICK);
+                if double {
+                    self.last_click = None;
+                    self.apply(Action::Open);
+                } else {
+                    self.last_click = Some((now, idx));
+                }
+            }
+            _ => return,
+        }
+        self.dirty = true;
+    }
+
+    // returns false when the app should quit
+    pub fn apply(&mut self, action: Action) -> bool {
+        match action {
+            Action::Quit => return false,
+            Action::Help => self.show_help = true,
+            Action::Up
+            | Action::Down
+            | Action::PageUp
+            | Action::PageDown
+            | Action::HalfPageUp
+            | Action::HalfPageDown
+            | Action::Top
+            | Action::Bottom
+                if self.view == View::Connections =>
+            {
+                self.scroll_connections(action)
+            }
+            Action::CycleSort if self.view == View::Connections => {
+                self.conns_sort = self.conns_sort.next();
+                self.conns_desc = false;
+                sort_sockets(&mut self.conns.sockets, self.conns_sort, self.conns_desc);
+            }
+            Action::ReverseSort if self.view == View::Connections => {
+                self.conns_desc = !self.conns_desc;
+                sort_sockets(&mut self.conns.sockets, self.conns_sort, self.conns_desc);
+            }
+            Action::Open if self.view == View::Connections => {}
+            Action::Open | Action::Pause if self.view == View::Table && self.cursor_group.is_some() => self.toggle_group(),
+            Action::Pause if self.replay.is_some() => self.toggle_playing(),
+            Action::ColumnLeft if self.replay.is_some() => self.step(-1),
+            Action::ColumnRight if self.replay.is_some() => self.step(1),
+            Action::Faster | Action::Slower if self.remote.is_some() => {
+                self.message = Some(("the server sets the interval, see its -n".to_string(), Instant::now()));
+            }
+            Action::Faster | Action::Slower if self.replay.is_some() => {
+                if let Some(replay) = &mut self.replay {
+                    replay.speed = match action {
+                        Action::Faster => (replay.speed * 2).min(replay::MAX_SPEED),
+                        _ => (replay.speed / 2).max(1),
+                    }
+                }
+            }
+            Action::Open => {
+                self.view = match self.view {
+                    View::Detail => View::Table,
+                    View::Table if self.selected.is_some() => View::Detail,
+                    View::Table => View::Table,
+                    View::Connections => View::Connections,
+                    View::Processes => View::Processes,
+                    View::Talkers => View::Talkers,
+                }
+            }
+            Action::Back if self.view == View::Table => return false,
+            Action::Back => self.view = View::Table,
+            Action::Up => self.move_selection(-1),
+            Action::Down => self.move_selection(1),
+            Action::PageUp => self.move_selection(-(self.page_rows.get().max(1) as isize)),
+            Action::PageDown => self.move_selection(self.page_rows.get().max(1) as isize),
+            Action::HalfPageUp => self.move_selection(-((self.page_rows.get() / 2).max(1) as isize)),
+            Action::HalfPageDown => self.move_selection((self.page_rows.get() / 2).max(1) as isize),
+            Action::Top => self.select_at(0),
+            Action::Bottom => self.select_at(usize::MAX),
+            Action::Faster => self.refresh_ms = faster(self.refresh_ms),
+            Action::Slower => self.refresh_ms = slower(self.refresh_ms),
+            Action::ToggleVirtual => self.filter.show_virtual = !self.filter.show_virtual,
+            Action::ToggleTotals => {
+                self.cumulative = !self.cumulative;
+                self.sort();
+            }
+            Action::Pause => self.toggle_pause(),
+            Action::ToggleSparkScale => self.spark_global = !self.spark_global,
+            Action::ToggleChart => self.show_chart = !self.show_chart,
+            Action::ToggleBits => self.format.bits = !self.format.bits,
+            Action::ToggleStandard => {
+                self.format.standard = match self.format.standard {
+                    UnitStandard::Iec => UnitStandard::Si,
+                    UnitStandard::Si => UnitStandard::Iec,
+                }
+            }
+            Action::CycleSort => self.cycle_sort(),
+            Action::ReverseSort => self.reverse_sort(),
+            Action::FreezeOrder => self.freeze_order = !self.freeze_order,
+            Action::ToggleStats => self.columns.toggle_all(&columns::STATS),
+            Action::ToggleAverages => self.columns.toggle_all(&columns::AVERAGES),
+            Action::ToggleMulticast => self.columns.toggle_all(&columns::MULTICAST),
+            Action::ColumnLeft => self.move_header_cursor(-1),
+            Action::ColumnRight => self.move_header_cursor(1),
+            Action::MoveColumnLeft | Action::MoveColumnRight => {
+                let delta = if action == Action::MoveColumnLeft { -1 } else { 1 };
+                match self.header_cursor {
+                    Some(column) => {
+                        self.columns.shift(column, delta);
+                        self.save_columns();
+                    }
+                    None => self.message = Some((format!("{}/{} picks a column to move first", self.glyphs.left, self.glyphs.right), Instant::now())),
+                }
+            }
+            Action::ResetStats => self.reset_stats(),
+            Action::ToggleGauge => self.show_gauge = !self.show_gauge,
+            Action::ToggleSmooth => self.smooth = !self.smooth,
+            Action::ToggleIdle => self.hide_idle = !self.hide_idle,
+            Action::TogglePin => self.toggle_pin(),
+            Action::Hide => self.hide_selected(),
+            Action::HiddenList => {
+                self.show_hidden = true;
+                self.hidden_cursor = 0;
+            }
+            Action::ChooseColumns => {
+                self.show_columns = true;
+                self.columns_cursor = 0;
+            }
+            Action::ToggleGroups => {
+                self.grouped = !self.grouped;
+                self.cursor_group = None;
+            }
+            Action::NestMembers => self.nest_members = !self.nest_members,
+            Action::Search => {
+                self.searching = true;
+                self.search_cursor = self.search.chars().count();
+            }
+            Action::CycleTheme => self.set_theme(self.theme_name.next()),
+            Action::Copy => self.copy(false),
+            Action::CopyTable => self.copy(true),
+            Action::Export => {
+                let format = export::Format::Txt;
+                let path = export::default_path(snapshot::unix_now(), format);
+                let result = export::write_numbered(self, &path, format);
+                self.exported(&path, result);
+            }
+            Action::ExportAs => {
+                self.exporting = true;
+                self.export_overwrite = false;
+                let path = export::unused(&export::default_path(snapshot::unix_now(), self.export_format));
+                self.export_path = path.to_string_lossy().into_owned();
+                self.export_cursor = self.export_path.chars().count();
+            }
+            Action::AlertLog => {
+                self.show_alerts = true;
+                self.alerts_scroll = 0;
+                self.alerts_seen = self.alerts.logged;
+            }
+            Action::Connections => {
+                self.view = if self.view == View::Connections { View::Table } else { View::Connections };
+            }
+            Action::ConnectionFilter => {
+                self.conns_all = !self.conns_all;
+                self.conns_scroll = 0;
+            }
+            Action::Processes => match procs::unavailable() {
+                Some(why) => self.message = Some((format!("no processes tab: {why}"), Instant::now())),
+                None => self.view = if self.view == View::Processes { View::Table } else { View::Processes },
+            },
+            Action::ToggleNames => {
+                self.resolve = !self.resolve;
+                self.request_names(Instant::now());
+            }
+            Action::TopTalkers => match capture::unavailable() {
+                Some(why) => self.message = Some((format!("no top talkers: {why}"), Instant::now())),
+                None => self.view = if self.view == View::Talkers { View::Table } else { View::Talkers },
+            },
+        }
+        self.follow_capture();
+        if matches!(action, Action::Faster | Action::ToggleAverages) {
+            self.reserve_history();
+        }
+        self.dirty = true;
+        // moving the cursor or switching views leaves every cell as it is
+        if !matches!(
+            action,
+            Action::Up
+                | Action::Down
+                | Action::PageUp
+                | Action::PageDown
+                | Action::HalfPageUp
+                | Action::HalfPageDown
+                | Action::Top
+                | Action::Bottom
+                | Action::Help
+                | Action::Open
+                | Action::Back
+                | Action::Copy
+                | Action::CopyTable
+                | Action::Export
+                | Action::ExportAs
+                | Action::Connections
+                | Action::ConnectionFilter
+                | Action::Processes
+                | Action::TopTalkers
+                | Action::ToggleNames
+                | Action::ColumnLeft
+                | Action::ColumnRight
+        ) {
+            self.refresh_text();
+        }
+        true
+    }
+
+    // a read from the Poller, kept in the tab's order
+    pub fn set_connections(&mut self, mut listing: Listing, now: Instant) {
+        sort_sockets(&mut listing.sockets, self.conns_sort, self.conns_desc);
+        self.conns = listing;
+        self.conns_at = Some(now);
+        self.request_names(now);
+        self.conns_scroll = self.conns_scroll.min(self.shown_connections().len().saturating_sub(self.page_rows.get().max(1)));
+        self.dirty = true;
+    }
+
+    // the sockets the tab lists: the selected interface's unless conns_all, every one when
+    // nothing is selected
+    pub fn shown_connections(&self) -> Vec<&Socket> {
+        let addrs = self.connections_interface().map(|r| r.info.addrs.as_slice());
+        self.conns.sockets.iter().filter(|s| addrs.is_none_or(|addrs| s.on(addrs))).collect()
+    }
+
+    // a kernel that can't tell closes the tab again, with the reason in the status bar
+    pub fn set_processes(&mut self, reading: Reading, now: Instant) {
+        if let Some(e) = &reading.error
+            && self.view == View::Processes
+        {
+            self.view = View::Table;
+            self.message = Some((format!("no processes tab: {e}"), now));
+        }
+        self.procs = reading;
+        self.procs_at = Some(now);
+        self.dirty = true;
+    }
+
+    // the processes tab's lines, for the selected interface's addresses when one is selected
+    pub fn process_rates(&self) -> Vec<ProcRate> {
+        let addrs = self.selected_name().and_then(|name| self.rows.iter().find(|r| r.interface == name)).map(|r| r.info.addrs.as_slice());
+        procs::by_process(
+            self.procs.flows.iter().filter(|f| addrs.is_none_or(|addrs| f.local.is_unspecified() || addrs.iter().any(|a| a.ip == f.local))),
+        )
+    }
+
+    // the capture runs only while its tab is up, on the selected interface; dropping the
+    // old one stops its thread before a new selection opens the next
+    fn follow_capture(&mut self) {
+        let wanted = self.selected_name().filter(|_| self.view == View::Talkers).map(str::to_string);
+        if wanted == self.capture_on {
+            return;
+        }
+        self.capture = None;
+        self.capture_error = None;
+        self.tally = Tally::default();
+        self.talkers.clear();
+        self.talkers_at = None;
+        if let Some(name) = &wanted {
+            match Capture::start(name) {
+                Ok(capture) => self.capture = Some(capture),
+                Err(e) => self.capture_error = Some(e),
+            }
+        }
+        self.capture_on = wanted;
+    }
+
+    // what was captured since the last sample, as rates over the same interval
+    fn update_talkers(&mut self, now: Instant, elapsed: Duration) {
+        self.follow_capture();
+        let Some(capture) = &self.capture else {
+            return;
+        };
+        for packet in capture.drain() {
+            self.tally.add(packet);
+        }
+        // the first interval only goes back to when the capture opened
+        self.talkers = self.tally.rates(elapsed.min(now.saturating_duration_since(capture.started)));
+        self.talkers_at = Some(now);
+    }
+
+    // session totals of every bond and bridge port as of the last sample; the totals go on
+    // for interfaces the filter hides, which ports often are
+    fn port_totals(&self) -> HashMap<String, Totals> {
+        let ports = self.rows.iter().flat_map(|r| &r.info.members);
+        ports.filter_map(|p| Some((p.clone(), *self.totals.get(p)?))).collect()
+    }
+
+    // a port that just joined gets its rate from the next sample on
+    fn update_member_rates(&mut self, before: &HashMap<String, Totals>, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        let ports = self.rows.iter().flat_map(|r| &r.info.members);
+        self.member_rates = ports
+            .filter_map(|p| {
+                let (then, now) = (before.get(p)?, self.totals.get(p)?);
+                let rate = |now: u64, then: u64| now.saturating_sub(then) as f64 / secs;
+                Some((p.clone(), (rate(now.rx_bytes, then.rx_bytes), rate(now.tx_bytes, then.tx_bytes))))
+            })
+            .collect();
+    }
+
+    // peers only while a WireGuard interface's detail view is open; another interface
+    // starts over, its rates need two reads of their own
+    fn update_wireguard(&mut self, networks: &impl NetworkSource, elapsed: Duration) {
+        let wanted = self
+            .selected_name()
+            .filter(|_| self.view == View::Detail)
+            .filter(|&name| self.rows.iter().any(|r| r.interface == name && r.info.kind == IfaceKind::WireGuard))
+            .map(str::to_string);
+        let Some((name, peers)) = wanted.and_then(|name| networks.wg_peers(&name).map(|p| (name, p))) else {
+            self.wireguard = None;
+            return;
+        };
+        let tracker = match &mut self.wireguard {
+            Some(t) if t.interface == name => t,
+            other => other.insert(Tracker::new(&name)),
+        };
+        tracker.update(peers, elapsed.as_secs_f64());
+    }
+
+    // asks the resolver about the remote addresses the current tab lists, top first, as far
+    // as this sample's budget goes
+    fn request_names(&mut self, now: Instant) {
+        if !self.resolve || self.resolver.is_none() {
+            return;
+        }
+        let ips: Vec<IpAddr> = match self.view {
+            View::Connections => self.shown_connections().iter().skip(self.conns_scroll).map(|s| s.remote.ip()).collect(),
+            View::Talkers => self.talkers.iter().filter_map(|t| t.peer).map(|p| p.remote).collect(),
+            _ => return,
+        };
+        for ip in ips.into_iter().filter(|ip| !ip.is_unspecified() && !ip.is_loopback()) {
+            if self.lookups_left == 0 {
+                break;
+            }
+            if self.names.wants(ip, now) && self.resolver.as_ref().is_some_and(|r| r.request(ip)) {
+                self.names.asked(ip);
+                self.lookups_left -= 1;
+            }
+        }
+    }
+
+    // whether `name` carries a default route, the one traffic leaves by
+    pub fn is_default_route(&self, name: &str) -> bool {
+        route::default_interfaces(&self.routes).contains(&name)
+    }
+
+    // the gateways follow the routing table; results for a gateway no longer probed go
+    pub fn update_ping_targets(&mut self) {
+        let targets = ping::targets(&self.routes);
+        if targets == self.ping_targets {
+            return;
+        }
+        self.latency.retain(|name, l| targets.iter().any(|t| t.interface == *name && t.gateway == l.gateway));
+        if let Some(pinger) = &self.pinger {
+            pinger.set_targets(targets.clone());
+        }
+        self.ping_targets = targets;
+    }
+
+    // a probe from the Pinger; only the detail view shows them
+    pub fn set_ping(&mut self, p: Pinged) {
+        if !self.ping_targets.contains(&p.target) {
+            return;
+        }
+        let latency = self.latency.entry(p.target.interface.clone()).or_insert_with(|| Latency::new(&p));
+        latency.push(&p);
+        if self.view == View::Detail && self.selected_name() == Some(p.target.interface.as_str()) {
+            self.dirty = true;
+        }
+    }
+
+    // an answer from the resolver
+    pub fn set_name(&mut self, ip: IpAddr, name: Option<String>, ttl: Duration, now: Instant) {
+        self.names.insert(ip, name, ttl, now);
+        if self.resolve {
+            self.dirty = true;
+        }
+    }
+
+    // a remote address as the tabs show it: its name once one is known, if names are on
+    pub fn remote_label(&self, ip: IpAddr) -> String {
+        match self.names.name(ip).filter(|_| self.resolve) {
+            Some(name) => name.to_string(),
+            None => ip.to_string(),
+        }
+    }
+
+    // the interface the tab is narrowed to, if any
+    pub fn connections_interface(&self) -> Option<&RowData> {
+        let name = self.selected_name().filter(|_| !self.conns_all)?;
+        self.rows.iter().find(|r| r.interface == name)
+    }
+
+    fn scroll_connections(&mut self, action: Action) {
+        // no further than a full last page
+        let page = self.page_rows.get().max(1);
+        let last = self.shown_connections().len().saturating_sub(page);
+        self.conns_scroll = match action {
+            Action::Up => self.conns_scroll.saturating_sub(1),
+            Action::Down => self.conns_scroll + 1,
+            Action::PageUp => self.conns_scroll.saturating_sub(page),
+            Action::PageDown => self.conns_scroll + page,
+            Action::HalfPageUp => self.conns_scroll.saturating_sub((page / 2).max(1)),
+            Action::HalfPageDown => self.conns_scroll + (page / 2).max(1),
+            Action::Top => 0,
+            _ => last,
+        }
+        .min(last);
+    }
+
+    // re-formats the table cells, needed after anything that changes what they show
+    pub fn refresh_text(&mut self) {
+        self.text = TableText::new(self);
+    }
+
+    // an unpinned interface drops straight back to its sorted place
+    fn toggle_pin(&mut self) {
+        let Some(name) = self.selected.clone() else {
+            return;
+        };
+        match self.pins.iter().position(|p| *p == name) {
+            Some(i) => {
+                self.pins.remove(i);
+            }
+            None => self.pins.push(name),
+        }
+        self.sort();
+    }
+
+    // the row under the cursor goes away and the cursor stays at the same height
+    fn hide_selected(&mut self) {
+        let (Some(name), Some(idx), None) = (self.selected.clone(), self.selected_index(), self.cursor_group) else {
+            return;
+        };
+        self.hidden.push(name);
+        self.select_at(idx);
+    }
+
+    // up/down pick an entry, enter or x shows it again, esc or X closes the list
+    fn hidden_list_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.hidden_cursor = self.hidden_cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.hidden_cursor = (self.hidden_cursor + 1).min(self.hidden.len().saturating_sub(1))
+            }
+            KeyCode::Enter | KeyCode::Char('x') if self.hidden_cursor < self.hidden.len() => {
+                self.hidden.remove(self.hidden_cursor);
+                self.hidden_cursor = self.hidden_cursor.min(self.hidden.len().saturating_sub(1));
+                self.refresh_text();
+            }
+            KeyCode::Esc | KeyCode::Char('X') | KeyCode::Char('q') => self.show_hidden = false,
+            _ => {}
+        }
+    }
+
+    // up/down pick a column, space or enter turns it on or off and < > move it, each saved
+    // right away; esc or C closes the list
+    fn column_list_key(&mut self, code: KeyCode) {
+        let last = self.columns.entries().len() - 1;
+        let at = self.columns_cursor;
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.columns_cursor = at.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.columns_cursor = (at + 1).min(last),
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                self.columns.toggle(self.columns.entries()[at].0);
+                self.reserve_history();
+                self.save_columns();
+            }
+            KeyCode::Char('<') | KeyCode::Char('>') => {
+                let delta = if code == KeyCode::Char('<') { -1 } else { 1 };
+                self.columns.swap(at, delta);
+                self.columns_cursor = at.saturating_add_signed(delta).min(last);
+                self.save_columns();
+            }
+            KeyCode::Esc | KeyCode::Char('C') | KeyCode::Char('q') => self.show_columns = false,
+            _ => {}
+        }
+        self.refresh_text();
+    }
+
+    // onto the next shown column, starting from the name
+    fn move_header_cursor(&mut self, delta: isize) {
+        let shown = self.columns.shown();
+        let at = self.header_cursor.and_then(|c| shown.iter().position(|&s| s == c));
+        let next = match at {
+            Some(at) => at.saturating_add_signed(delta).min(shown.len() - 1),
+            None => 0,
+        };
+        self.header_cursor = Some(shown[next]);
+    }
+
+    fn save_columns(&mut self) {
+        let Some(path) = &self.config_path else {
+            return;
+        };
+        if let Err(e) = crate::config::save(path, "columns", &self.columns.names()) {
+            self.message = Some((format!("columns not saved: {e:#}"), Instant::now()));
+        }
+    }
+
+    // the alert pane scrolls through the log newest first; c clears it, esc, a or q closes it
+    fn alert_log_key(&mut self, code: KeyCode) {
+        let last = self.alerts.log.len().saturating_sub(1);
+        let page = self.page_rows.get().max(1);
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.alerts_scroll = self.alerts_scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.alerts_scroll = (self.alerts_scroll + 1).min(last),
+            KeyCode::PageUp => self.alerts_scroll = self.alerts_scroll.saturating_sub(page),
+            KeyCode::PageDown => self.alerts_scroll = (self.alerts_scroll + page).min(last),
+            KeyCode::Home | KeyCode::Char('g') => self.alerts_scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => self.alerts_scroll = last,
+            KeyCode::Char('c') | KeyCode::Delete => {
+                self.alerts.clear_log();
+                self.alerts_scroll = 0;
+            }
+            KeyCode::Esc | KeyCode::Char('a') | KeyCode::Char('q') => {
+                self.show_alerts = false;
+                self.alerts_seen = self.alerts.logged;
+                self.refresh_text();
+            }
+            _ => {}
+        }
+    }
+
+    // events logged since the pane was last open, for the header badge
+    pub fn unread_alerts(&self) -> u64 {
+        if self.show_alerts { 0 } else { self.alerts.logged - self.alerts_seen }
+    }
+
+    // "14:02:11 eth0 rx 94.1 MB/s exceeded 80 MB/s", in the units the table shows; the clock
+    // is UTC like the header's
+    pub fn alert_line(&self, e: &AlertEvent) -> String {
+        let metric = e.rule.metric;
+        let value = |v: f64| match metric {
+            Metric::Rx | Metric::Tx | Metric::Total => self.rate(v).trim().to_string(),
+            Metric::Packets => self.packet_rate(v).trim().to_string(),
+            Metric::Errors | Metric::Drops => self.pps(v).trim().to_string(),
+        };
+        let time = &snapshot::iso8601(e.at)[11..19];
+        match e.transition {
+            Transition::Fired => format!(
+                "{time} {} {} {} {} {}",
+                e.interface,
+                metric.label(),
+                value(e.value),
+                e.rule.op.verb(),
+                value(e.rule.threshold)
+            ),
+            Transition::Cleared => format!("{time} {} {} cleared at {}", e.interface, metric.label(), value(e.value)),
+        }
+    }
+
+    // line editing for the '/' prompt; the table follows every edit
+    fn search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.searching = false,
+            KeyCode::Esc => {
+                self.searching = false;
+                self.search.clear();
+                self.search_cursor = 0;
+            }
+            _ if edit_line(&mut self.search, &mut self.search_cursor, key) => {}
+            _ => return,
+        }
+        // keep the cursor on a row that is still listed
+        if self.selected_index().is_none() {
+            self.select_at(0);
+        }
+        self.refresh_text();
+    }
+
+    // the 'E' prompt: the path is edited like the search, tab switches the format and the
+    // extension along with it
+    fn export_key(&mut self, key: KeyEvent) {
+        // enter found a file there: y replaces it, any other key goes back to the path
+        if self.export_overwrite {
+            self.export_overwrite = false;
+            if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+                self.export_typed(true);
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Enter => self.export_typed(false),
+            KeyCode::Esc => self.exporting = false,
+            KeyCode::Tab => {
+                self.export_format = self.export_format.next();
+                let path = Path::new(&self.export_path);
+                if export::Format::of(path).is_some() {
+                    self.export_path = path.with_extension(self.export_format.extension()).to_string_lossy().into_owned();
+                    self.export_cursor = self.export_path.chars().count();
+                }
+            }
+            _ => {
+                edit_line(&mut self.export_path, &mut self.export_cursor, key);
+            }
+        }
+    }
+
+    // the path at the prompt; a file already there is only replaced once confirmed
+    fn export_typed(&mut self, overwrite: bool) {
+        let path = PathBuf::from(self.export_path.trim());
+        if path.as_os_str().is_empty() {
+            self.exporting = false;
+            self.message = Some(("nothing exported, no path given".to_string(), Instant::now()));
+            return;
+        }
+        let format = export::Format::of(&path).unwrap_or(self.export_format);
+        match export::write(self, &path, format, overwrite) {
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && !overwrite => {
+                // the question's keys in the status bar, not an old message
+                self.export_overwrite = true;
+                self.message = None;
+            }
+            result => {
+                self.exporting = false;
+                self.exported(&path, result.map(|n| (path.clone(), n)));
+            }
+        }
+    }
+
+    fn exported(&mut self, path: &Path, result: std::io::Result<(PathBuf, usize)>) {
+        let message = match result {
+            Ok((written, n)) => {
+                let written = std::fs::canonicalize(&written).unwrap_or(written);
+                format!("exported {n} interface{} to {}", if n == 1 { "" } else { "s" }, written.display())
+            }
+            Err(e) => format!("export failed: {}: {e}", path.display()),
+        };
+        self.message = Some((message, Instant::now()));
+    }
+
+    // (keys, what they do) for the status bar; the prompt and the overlays take over the keyboard,
+    // so they get their own, everything else comes from the keymap
+    pub fn hints(&self) -> Vec<(String, &'static str)> {
+        let fixed = |pairs: &[(String, &'static str)]| pairs.to_vec();
+        let g = self.glyphs;
+        if self.show_help {
+            return fixed(&[("any key".to_string(), "close")]);
+        }
+        if self.show_hidden {
+            return fixed(&[
+                (format!("{}/{}", g.up, g.down), "select"),
+                ("enter".to_string(), "show again"),
+                ("esc".to_string(), "close"),
+            ]);
+        }
+        if self.show_columns {
+            return fixed(&[
+                (format!("{}/{}", g.up, g.down), "select"),
+                ("space".to_string(), "on/off"),
+                ("</>".to_string(), "move"),
+                ("esc".to_string(), "close"),
+            ]);
+        }
+        if self.show_alerts {
+            return fixed(&[
+                (format!("{}/{}", g.up, g.down), "scroll"),
+                ("c".to_string(), "clear all"),
+                ("esc".to_string(), "close"),
+            ]);
+        }
+        if self.export_overwrite {
+            return fixed(&[("y".to_string(), "overwrite"), ("any key".to_string(), "keep editing")]);
+        }
+        if self.exporting {
+            return fixed(&[
+                ("enter".to_string(), "write"),
+                ("tab".to_string(), "format"),
+                ("esc".to_string(), "cancel"),
+            ]);
+        }
+        if self.searching {
+            return fixed(&[
+                ("enter".to_string(), "keep"),
+                ("esc".to_string(), "clear"),
+                (format!("{}/{}", g.left, g.right), "move"),
+                ("bksp".to_string(), "delete"),
+            ]);
+        }
+        let actions = match self.view {
+            View::Table => TABLE_HINTS,
+            View::Detail => DETAIL_HINTS,
+            View::Connections => CONNECTION_HINTS,
+            View::Processes => PROCESS_HINTS,
+            View::Talkers => TALKER_HINTS,
+        };
+        let label = |a: Action| self.keymap.keys_for(a).first().map(|&k| key_label(k, g));
+        let mut hints: Vec<(String, &'static str)> = actions.iter().filter_map(|&a| Some((label(a)?, a.hint()))).collect();
+        // the same keys drive playback under --replay
+        if self.replay.is_some() && matches!(self.view, View::Table | View::Detail) {
+            let taken = [Action::Pause, Action::Faster, Action::Slower].map(Action::hint);
+            hints.retain(|(_, hint)| !taken.contains(hint));
+            let playback = [
+                (Action::ColumnLeft, Action::ColumnRight, "step"),
+                (Action::Pause, Action::Pause, "play"),
+                (Action::Faster, Action::Slower, "speed"),
+            ];
+            let keys = playback.into_iter().filter_map(|(a, b, hint)| match (label(a)?, label(b)?) {
+                (a, b) if a == b => Some((a, hint)),
+                (a, b) => Some((format!("{a}/{b}"), hint)),
+            });
+            hints.splice(0..0, keys);
+        }
+        hints
+    }
+
+    // tab-separated for pasting into tickets and spreadsheets: interface, rx, tx, packets,
+    // errors and when it was copied; `all` is every row the table lists, under a header line
+    pub fn copy_text(&self, all: bool) -> Option<String> {
+        let time = snapshot::iso8601(snapshot::unix_now());
+        // the same numbers as the table: session totals in that view, rates otherwise
+        let line = |r: &RowData| {
+            let t = &r.totals;
+            let [rx, tx, packets, errors] = if self.cumulative {
+                [self.size(t.rx_bytes), self.size(t.tx_bytes), self.count(t.packets_in + t.packets_out), self.count(t.errors_in + t.errors_out)]
+            } else {
+                let (rx, tx) = self.shown_rates(r);
+                [self.rate(rx), self.rate(tx), self.packet_rate(r.packets_in + r.packets_out), self.pps(r.errors_in + r.errors_out)]
+            };
+            [r.interface.as_str(), &rx, &tx, &packets, &errors, &time].map(str::trim).join("\t") + "\n"
+        };
+        let shown = self.shown();
+        if all {
+            let header = "interface\trx\ttx\tpackets\terrors\ttime\n".to_string();
+            return Some(shown.into_iter().map(line).fold(header, |out, l| out + &l));
+        }
+        // a folded group's header has nothing to copy
+        let name = self.selected_name().filter(|_| self.cursor_group.is_none())?;
+        shown.into_iter().find(|r| r.interface == name).map(line)
+    }
+
+    fn copy(&mut self, all: bool) {
+        let message = match self.copy_text(all) {
+            None => "nothing selected to copy".to_string(),
+            Some(text) => {
+                let what = if all {
+                    format!("{} rows", text.lines().count() - 1)
+                } else {
+                    text.split('\t').next().unwrap_or_default().to_string()
+                };
+                match clipboard::copy(&text) {
+                    Ok(Copied::Clipboard) => format!("copied {what}"),
+                    Ok(Copied::File(path)) => format!("no clipboard, {what} saved to {}", path.display()),
+                    Err(e) => format!("copy failed: {e}"),
+                }
+            }
+        };
+        self.message = Some((message, Instant::now()));
+    }
+
+    // the message while it is fresh
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_ref().filter(|(_, at)| at.elapsed() < MESSAGE_FOR).map(|(m, _)| m.as_str())
+    }
+
+    pub fn is_pinned(&self, name: &str) -> bool {
+        self.pins.iter().any(|p| p == name)
+    }
+
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if self.paused {
+            self.frozen = self.rows.clone();
+        }
+    }
+}
+
+// cursor movement and editing for the one-line prompts; false for keys that aren't any of it
+fn edit_line(text: &mut String, cursor: &mut usize, key: KeyEvent) -> bool {
+    let len = text.chars().count();
+    let byte = |s: &str, at: usize| s.char_indices().nth(at).map_or(s.len(), |(i, _)| i);
+    match key.code {
+        KeyCode::Left => *cursor = cursor.saturating_sub(1),
+        KeyCode::Right => *cursor = (*cursor + 1).min(len),
+        KeyCode::Home => *cursor = 0,
+        KeyCode::End => *cursor = len,
+        KeyCode::Backspace if *cursor > 0 => {
+            *cursor -= 1;
+            text.remove(byte(text, *cursor));
+        }
+        KeyCode::Delete if *cursor < len => {
+            text.remove(byte(text, *cursor));
+        }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            text.insert(byte(text, *cursor), c);
+            *cursor += 1;
+        }
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iftype::IfaceKind;
+    use crate::net::IfaceInfo;
+    use crate::vlan::Vlan;
+
+    #[test]
+    fn esc_quits_only_from_the_table() {
+        let mut app = AppState::new();
+        app.view = View::Detail;
+        assert!(app.apply(Action::Back));
+        assert_eq!(app.view, View::Table);
+        assert!(!app.apply(Action::Back));
+
+        assert!(is_ctrl_c(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+        assert!(!is_ctrl_c(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn only_mapped_keys_need_a_redraw() {
+        let mut app = AppState::new();
+        app.dirty = false;
+        assert!(app.handle_key(KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE)));
+        assert!(!app.dirty);
+        assert!(app.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE)));
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn vim_keys_move_and_jump() {
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        let mut app = AppState::new();
+        app.rows = (0..10).map(|i| row(&format!("eth{i}"), 0.0)).collect();
+        app.page_rows.set(4);
+
+        app.handle_key(key('j'));
+        app.handle_key(key('j'));
+        assert_eq!(app.selected_index(), Some(1), "the first j selects the top row");
+        app.handle_key(key('k'));
+        assert_eq!(app.selected_index(), Some(0));
+        app.handle_key(ctrl('d'));
+        assert_eq!(app.selected_index(), Some(2), "half of a four-row page");
+        // terminals differ on whether 'G' comes with shift
+        app.handle_key(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT));
+        assert_eq!(app.selected_index(), Some(9));
+        app.handle_key(ctrl('u'));
+        assert_eq!(app.selected_index(), Some(7));
+        app.handle_key(key('g'));
+        assert_eq!(app.selected_index(), Some(0));
+        app.handle_key(KeyEvent::from(KeyCode::End));
+        assert_eq!(app.selected_index(), Some(9));
+        // ctrl+u is not u
+        assert!(!app.format.bits);
+        assert_eq!(key_label(app.keymap.keys_for(Action::HalfPageDown)[0], &crate::glyphs::UNICODE), "ctrl+d");
+    }
+
+    #[test]
+    fn every_hint_has_a_key() {
+        for &action in TABLE_HINTS.iter().chain(DETAIL_HINTS) {
+            assert!(!Keymap::default().keys_for(action).is_empty(), "{action:?} is hinted but unbound");
+        }
+    }
+
+    #[test]
+    fn keys_while_help_is_open() {
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let mut app = AppState::new();
+        assert!(app.handle_key(key('?')));
+        assert!(app.show_help);
+        assert!(app.handle_key(key('q')), "q only closes the help");
+        assert!(!app.show_help);
+        assert!(app.handle_key(key('+')));
+        assert_eq!(app.refresh_ms, 400);
+        app.show_help = true;
+        assert!(!app.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+    }
+
+    fn row(name: &str, rx_bps: f64) -> RowData {
+        RowData {
+            interface: name.to_string(),
+            rx_bps,
+            tx_bps: 0.0,
+            rx_smooth: 0.0,
+            tx_smooth: 0.0,
+            packets_in: 0.0,
+            packets_out: 0.0,
+            errors_in: 0.0,
+            errors_out: 0.0,
+            drops_in: 0.0,
+            drops_out: 0.0,
+            multicast_in: None,
+            totals: Totals::default(),
+            info: IfaceInfo::default(),
+        }
+    }
+
+    #[test]
+    fn copied_text_is_tab_separated() {
+        let mut app = AppState::new();
+        app.rows = vec![row("eth0", 2048.0), row("lo", 0.0)];
+        assert_eq!(app.copy_text(false), None, "nothing selected");
+        app.selected = Some("eth0".to_string());
+
+        let line = app.copy_text(false).unwrap();
+        let cells: Vec<&str> = line.trim_end().split('\t').collect();
+        assert_eq!(cells[..5], ["eth0", "2.0 KiB/s", "--", "--", "--"], "zeros read as in the table");
+        assert_eq!(cells[5].len(), "2026-01-01T00:00:00.000Z".len(), "{}", cells[5]);
+
+        let table = app.copy_text(true).unwrap();
+        assert!(table.starts_with("interface\trx\ttx\tpackets\terrors\ttime\neth0\t2.0 KiB/s\t"));
+        assert_eq!(table.lines().count(), 3);
+        app.hidden = vec!["lo".to_string()];
+        assert_eq!(app.copy_text(true).unwrap().lines().count(), 2, "only what the table lists");
+
+        app.cumulative = true;
+        app.rows[0].totals.rx_bytes = 1536;
+        assert!(app.copy_text(false).unwrap().starts_with("eth0\t1.50 KiB\t0 B\t0\t0\t"));
+    }
+
+    #[test]
+    fn name_sort_ignores_case() {
+        let mut rows = vec![row("wlan0", 0.0), row("Eth1", 0.0), row("eth0", 0.0)];
+        sort_rows(&mut rows, SortKey::Name, false, false);
+        let names: Vec<_> = rows.iter().map(|r| r.interface.as_str()).collect();
+        assert_eq!(names, ["eth0", "Eth1", "wlan0"]);
+    }
+
+    #[test]
+    fn pins_stay_on_top_in_pin_order() {
+        let names = |app: &AppState| app.rows.iter().map(|r| r.interface.clone()).collect::<Vec<_>>();
+        let mut app = AppState::new();
+        app.rows = vec![row("docker0", 900.0), row("eth0", 500.0), row("wg0", 10.0), row("lo", 1.0)];
+        app.pins = vec!["wg0".to_string()];
+        app.sort();
+        assert_eq!(names(&app), ["wg0", "docker0", "eth0", "lo"]);
+
+        app.selected = Some("lo".to_string());
+        app.apply(Action::TogglePin);
+        assert_eq!(names(&app), ["wg0", "lo", "docker0", "eth0"]);
+
+        app.selected = Some("wg0".to_string());
+        app.apply(Action::TogglePin);
+        assert_eq!(names(&app), ["lo", "docker0", "eth0", "wg0"], "back in its sorted place");
+        assert_eq!(app.pins, ["lo"]);
+    }
+
+    #[test]
+    fn idle_interfaces_hide_and_come_back() {
+        let mut app = AppState::new();
+        app.rows = vec![row("eth0", 0.0), row("veth1", 0.0)];
+        let push = |app: &mut AppState, name: &str, rx: f64| app.history.push(name, Sample { at: Instant::now(), rx, tx: 0.0 });
+        for _ in 0..IDLE_SAMPLES - 1 {
+            push(&mut app, "eth0", 5_000.0);
+            push(&mut app, "veth1", 0.0);
+        }
+        // one quiet sample doesn't make a busy interface idle
+        push(&mut app, "eth0", 0.0);
+        push(&mut app, "veth1", 10.0);
+        assert!(!app.is_idle("eth0"));
+        assert!(app.is_idle("veth1"));
+
+        assert_eq!(app.shown().len(), 2, "off by default");
+        app.apply(Action::ToggleIdle);
+        let names: Vec<&str> = app.shown().iter().map(|r| r.interface.as_str()).collect();
+        assert_eq!(names, ["eth0"]);
+        assert_eq!(app.hidden_idle(), 1);
+
+        push(&mut app, "veth1", 4_096.0);
+        assert_eq!(app.shown().len(), 2, "back on the next sample");
+    }
+
+    #[test]
+    fn hidden_interfaces_leave_the_table_until_unhidden() {
+        let names = |app: &AppState| app.shown().iter().map(|r| r.interface.clone()).collect::<Vec<_>>();
+        let mut app = AppState::new();
+        app.rows = vec![row("docker0", 900.0), row("eth0", 500.0), row("lo", 1.0)];
+        app.selected = Some("eth0".to_string());
+        app.apply(Action::Hide);
+        assert_eq!(names(&app), ["docker0", "lo"]);
+        assert_eq!(app.selected.as_deref(), Some("lo"), "cursor stays at the same height");
+        app.apply(Action::Hide);
+        assert_eq!(app.selected.as_deref(), Some("docker0"));
+        assert_eq!(app.hidden, ["eth0", "lo"]);
+
+        app.apply(Action::HiddenList);
+        assert!(app.show_hidden);
+        app.handle_key(KeyEvent::from(KeyCode::Down));
+        app.handle_key(KeyEvent::from(KeyCode::Down));
+        assert_eq!(app.hidden_cursor, 1, "clamped to the list");
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.hidden, ["eth0"]);
+        assert_eq!(names(&app), ["docker0", "lo"]);
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert!(!app.show_hidden);
+    }
+
+    #[test]
+    fn search_prompt_takes_keys_and_narrows_live() {
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let names = |app: &AppState| app.shown().iter().map(|r| r.interface.clone()).collect::<Vec<_>>();
+        let mut app = AppState::new();
+        app.rows = vec![row("docker0", 900.0), row("eth0", 500.0), row("wlan0", 1.0)];
+        app.selected = Some("docker0".to_string());
+
+        assert!(app.handle_key(key('/')));
+        assert!(app.searching);
+        // q types instead of quitting
+        for c in ['q', 'E', 't'] {
+            assert!(app.handle_key(key(c)));
+        }
+        assert!(names(&app).is_empty());
+        app.handle_key(KeyEvent::from(KeyCode::Home));
+        app.handle_key(KeyEvent::from(KeyCode::Delete));
+        assert_eq!(app.search, "Et");
+        assert_eq!(names(&app), ["eth0"], "case-insensitive");
+        assert_eq!(app.selected.as_deref(), Some("eth0"), "cursor moves to a listed row");
+
+        app.handle_key(KeyEvent::from(KeyCode::End));
+        app.handle_key(KeyEvent::from(KeyCode::Backspace));
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert!(!app.searching);
+        assert_eq!(names(&app), ["docker0", "eth0"]);
+
+        app.handle_key(key('/'));
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert!(!app.searching);
+        assert_eq!(names(&app).len(), 3, "esc clears");
+    }
+
+    #[test]
+    fn grouped_sections_fold_and_keep_the_sort() {
+        let kinded = |name, rx, kind| RowData { info: IfaceInfo { kind, ..Default::default() }, ..row(name, rx) };
+        let lines = |app: &AppState| {
+            app.entries()
+                .iter()
+                .map(|e| match e {
+                    Entry::Group(g, members) => format!("{}:{}", g.label(), members.len()),
+                    Entry::Row(r) => r.interface.clone(),
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut app = AppState::new();
+        app.rows = vec![
+            kinded("veth1", 900.0, IfaceKind::Container),
+            kinded("eth1", 500.0, IfaceKind::Ethernet),
+            kinded("veth2", 100.0, IfaceKind::Container),
+            kinded("eth0", 10.0, IfaceKind::Ethernet),
+        ];
+        app.apply(Action::ToggleGroups);
+        assert_eq!(lines(&app), ["Physical:2", "eth1", "eth0", "Containers:2", "veth1", "veth2"]);
+
+        // the cursor walks over headers too
+        app.apply(Action::Down);
+        assert_eq!(app.cursor_group, Some(Group::Physical));
+        for _ in 0..3 {
+            app.apply(Action::Down);
+        }
+        assert_eq!(app.cursor_group, Some(Group::Containers));
+        app.apply(Action::Open);
+        assert_eq!(lines(&app), ["Physical:2", "eth1", "eth0", "Containers:2"]);
+        assert_eq!(app.view, View::Table, "enter on a header folds instead of opening");
+        assert_eq!(app.selected_index(), Some(3));
+        app.apply(Action::Pause);
+        assert!(!app.paused, "space folds too");
+        assert_eq!(lines(&app).len(), 6);
+
+        app.apply(Action::ReverseSort);
+        assert_eq!(lines(&app), ["Physical:2", "eth0", "eth1", "Containers:2", "veth2", "veth1"]);
+
+        app.apply(Action::Up);
+        app.apply(Action::Hide);
+        assert_eq!(app.hidden, ["eth1"]);
+        assert_eq!(app.cursor_group, Some(Group::Containers), "the next line moves up under the cursor");
+        app.apply(Action::Hide);
+        assert_eq!(app.hidden, ["eth1"], "a header can't be hidden");
+    }
+
+    #[test]
+    fn ports_nest_under_their_master() {
+        let port = |name, rx, kind, members: &[&str]| RowData {
+            info: IfaceInfo { kind, members: members.iter().map(|m| m.to_string()).collect(), ..Default::default() },
+            ..row(name, rx)
+        };
+        let lines = |app: &AppState| {
+            app.entries()
+                .iter()
+                .map(|e| match e {
+                    Entry::Group(g, members) => format!("{}:{}", g.label(), members.len()),
+                    Entry::Row(r) => format!("{}{}", "-".repeat(app.nest_depth(&r.interface)), r.interface),
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut app = AppState::new();
+        app.rows = vec![
+            port("eth2", 900.0, IfaceKind::Ethernet, &[]),
+            port("eth1", 500.0, IfaceKind::Ethernet, &[]),
+            port("br0", 300.0, IfaceKind::Bridge, &["bond0", "veth1"]),
+            port("bond0", 200.0, IfaceKind::Bond, &["eth0", "eth1"]),
+            port("eth0", 10.0, IfaceKind::Ethernet, &[]),
+        ];
+        app.apply(Action::ToggleGroups);
+        assert_eq!(lines(&app), ["Physical:3", "eth2", "eth1", "eth0", "Bonds & VLANs:1", "bond0", "Bridges:1", "br0"]);
+
+        // ports follow their master in the sort order, veth1 isn't shown at all
+        app.apply(Action::NestMembers);
+        assert_eq!(lines(&app), ["Physical:1", "eth2", "Bridges:4", "br0", "-bond0", "--eth1", "--eth0"]);
+        app.apply(Action::ReverseSort);
+        assert_eq!(lines(&app), ["Physical:1", "eth2", "Bridges:4", "br0", "-bond0", "--eth0", "--eth1"]);
+
+        // a master filtered out leaves its ports where they were
+        app.hidden = vec!["br0".to_string()];
+        assert_eq!(lines(&app), ["Physical:1", "eth2", "Bonds & VLANs:3", "bond0", "-eth0", "-eth1"]);
+
+        // the flat list nests as well
+        app.apply(Action::ToggleGroups);
+        assert_eq!(lines(&app), ["bond0", "-eth0", "-eth1", "eth2"]);
+        app.apply(Action::NestMembers);
+        assert_eq!(lines(&app), ["eth0", "bond0", "eth1", "eth2"]);
+    }
+
+    #[test]
+    fn vlans_nest_under_their_parent() {
+        let vlan = |name, rx, parent: &str| RowData {
+            info: IfaceInfo { kind: IfaceKind::Vlan, vlan: Some(Vlan { id: 100, parent: parent.to_string() }), ..Default::default() },
+            ..row(name, rx)
+        };
+        let lines = |app: &AppState| {
+            app.entries()
+                .iter()
+                .filter_map(|e| match e {
+                    Entry::Row(r) => Some(format!("{}{}", "-".repeat(app.nest_depth(&r.interface)), r.interface)),
+                    Entry::Group(..) => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut app = AppState::new();
+        app.rows = vec![row("eth1", 800.0), row("eth0", 700.0), vlan("eth0.200", 650.0, "eth0"), vlan("eth0.100", 600.0, "eth0"), vlan("eth9.5", 1.0, "eth9")];
+        assert_eq!(lines(&app), ["eth1", "eth0", "eth0.200", "eth0.100", "eth9.5"]);
+
+        // the global sort key orders the VLANs under their parent; one whose parent isn't there stays on top
+        app.apply(Action::NestMembers);
+        assert_eq!(lines(&app), ["eth1", "eth0", "-eth0.200", "-eth0.100", "eth9.5"]);
+        app.apply(Action::ReverseSort);
+        assert_eq!(lines(&app), ["eth9.5", "eth0", "-eth0.100", "-eth0.200", "eth1"]);
+
+        // in grouped mode the VLANs leave their own section for the parent's
+        app.apply(Action::ToggleGroups);
+        assert_eq!(lines(&app), ["eth9.5", "eth0", "-eth0.100", "-eth0.200", "eth1"]);
+    }
+
+    #[test]
+    fn ema_follows_the_real_interval() {
+        let tau = Duration::from_secs(2);
+        assert_eq!(ema(0.0, 100.0, Duration::ZERO, tau), 0.0);
+        assert!((ema(0.0, 100.0, tau, tau) - 63.2).abs() < 0.1, "one time constant covers ~63%");
+        // after a stall the new sample dominates instead of the stale average
+        assert!(ema(0.0, 100.0, Duration::from_secs(60), tau) > 99.9);
+    }
+
+    #[test]
+    fn smoothing_seeds_new_interfaces_and_keeps_raw_rates() {
+        let seeded = |name, rx| RowData { rx_smooth: rx, ..row(name, rx) };
+        let mut app = AppState::new();
+        app.rows = vec![seeded("eth0", 1000.0), seeded("veth9", 500.0)];
+        app.smooth_rates(&[seeded("eth0", 0.0)], Duration::from_millis(500));
+
+        let eth0 = &app.rows[0];
+        assert!(eth0.rx_smooth > 200.0 && eth0.rx_smooth < 250.0);
+        assert_eq!(eth0.rx_bps, 1000.0, "the raw rate is untouched");
+        assert_eq!(app.rows[1].rx_smooth, 500.0, "seeded, not ramped up from zero");
+
+        assert_eq!(app.shown_rates(&app.rows[0]).0, 1000.0);
+        app.apply(Action::ToggleSmooth);
+        assert_eq!(app.shown_rates(&app.rows[0]).0, app.rows[0].rx_smooth);
+    }
+}
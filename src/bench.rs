@@ -0,0 +1,64 @@
+use std::time::{Duration, Instant};
+
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+
+use crate::app::{Action, AppState};
+use crate::net::{Counters, NetworkSource};
+use crate::ui::draw;
+
+// a hypervisor-sized host: many veths, each with a little traffic per read
+struct Synthetic {
+    ifaces: usize,
+    reads: u64,
+}
+
+impl NetworkSource for Synthetic {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.reads += 1;
+        (0..self.ifaces as u64)
+            .map(|i| Counters {
+                name: format!("veth{i:04x}"),
+                rx_bytes: self.reads * (i + 1) * 1500,
+                tx_bytes: self.reads * (i + 1) * 700,
+                rx_packets: self.reads * (i + 1),
+                tx_packets: self.reads * (i + 1),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+// --bench: time sampling (which formats the cells) and drawing separately on an
+// offscreen terminal, so the cost of a redraw without a new sample is visible
+pub fn run(ifaces: usize, frames: u32) -> String {
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    let mut source = Synthetic { ifaces, reads: 0 };
+    app.prime(&mut source);
+
+    let mut terminal = Terminal::new(TestBackend::new(200, 60)).expect("test backend never fails");
+    let mut sample_time = Duration::ZERO;
+    let mut draw_time = Duration::ZERO;
+    let frames = frames.max(1);
+    for i in 0..frames {
+        // a sample every tenth frame, cursor movement in between like a held arrow key
+        if i % 10 == 0 {
+            let start = Instant::now();
+            app.sample(&mut source, Instant::now(), Duration::from_millis(500));
+            sample_time += start.elapsed();
+        } else {
+            app.apply(Action::Down);
+        }
+        let start = Instant::now();
+        terminal.draw(|f| draw(f, &app)).expect("test backend never fails");
+        draw_time += start.elapsed();
+    }
+
+    let samples = frames.div_ceil(10);
+    format!(
+        "{ifaces} interfaces, {frames} frames: sample {:.1} us avg over {samples}, draw {:.1} us avg\n",
+        sample_time.as_secs_f64() * 1e6 / samples as f64,
+        draw_time.as_secs_f64() * 1e6 / frames as f64,
+    )
+}
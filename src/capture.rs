@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// bytes of each packet copied out of the kernel: the IP header and the ports, hardly any
+// payload; the packet's real length comes back separately
+const SNAPLEN: usize = 96;
+
+// packets waiting for the UI; past this the capture thread counts them as dropped instead
+// of queueing more
+const CHANNEL: usize = 8192;
+
+// distinct remote ends tallied per interval, the rest go into one "others" line
+const MAX_PEERS: usize = 1024;
+
+// how often a blocked read gives up, so a stop is noticed
+const READ_TIMEOUT: Duration = Duration::from_millis(200);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Transport {
+    Tcp,
+    Udp,
+    Icmp,
+    Other(u8),
+}
+
+impl Transport {
+    pub fn label(self) -> String {
+        match self {
+            Transport::Tcp => "tcp".to_string(),
+            Transport::Udp => "udp".to_string(),
+            Transport::Icmp => "icmp".to_string(),
+            Transport::Other(n) => format!("ip/{n}"),
+        }
+    }
+}
+
+// the far end of a packet as the interface sees it; the port is the remote one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Peer {
+    pub remote: IpAddr,
+    pub port: Option<u16>,
+    pub transport: Transport,
+}
+
+// what the capture thread hands over for each packet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Packet {
+    pub peer: Peer,
+    pub bytes: u64,
+    pub outgoing: bool,
+}
+
+// a line of the top talkers table; peer None is everyone past MAX_PEERS
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Talker {
+    pub peer: Option<Peer>,
+    pub rx_bps: f64,
+    pub tx_bps: f64,
+}
+
+// why capturing can't work in this build, checked before opening anything
+pub fn unavailable() -> Option<&'static str> {
+    if cfg!(not(target_os = "linux")) {
+        Some("packet capture is only supported on Linux")
+    } else if cfg!(not(feature = "pcap")) {
+        Some("built without the pcap feature")
+    } else {
+        None
+    }
+}
+
+// the remote end of an IP packet: the source of what came in, the destination of what
+// went out. None for anything but IPv4 and IPv6; fragments after the first have no ports
+pub fn parse_packet(header: &[u8], outgoing: bool) -> Option<Peer> {
+    let (proto, src, dst, l4) = match header.first()? >> 4 {
+        4 => {
+            let ihl = usize::from(header[0] & 0x0f) * 4;
+            let fragment = u16::from_be_bytes([*header.get(6)?, *header.get(7)?]) & 0x1fff;
+            let src = IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(header.get(12..16)?).ok()?));
+            let dst = IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(header.get(16..20)?).ok()?));
+            (*header.get(9)?, src, dst, header.get(ihl..).filter(|_| fragment == 0))
+        }
+        6 => {
+            let src = IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(header.get(8..24)?).ok()?));
+            let dst = IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(header.get(24..40)?).ok()?));
+            (*header.get(6)?, src, dst, header.get(40..))
+        }
+        _ => return None,
+    };
+    let transport = match proto {
+        6 => Transport::Tcp,
+        17 => Transport::Udp,
+        1 | 58 => Transport::Icmp,
+        n => Transport::Other(n),
+    };
+    let ports = l4.filter(|_| matches!(transport, Transport::Tcp | Transport::Udp)).and_then(|l4| l4.get(0..4));
+    let port = ports.map(|p| if outgoing { u16::from_be_bytes([p[2], p[3]]) } else { u16::from_be_bytes([p[0], p[1]]) });
+    Some(Peer { remote: if outgoing { dst } else { src }, port, transport })
+}
+
+// bytes per remote end since the last rates(); bounded by MAX_PEERS however many hosts a
+// scan or a flood brings
+#[derive(Default)]
+pub struct Tally {
+    peers: HashMap<Peer, (u64, u64)>,
+    others: (u64, u64),
+}
+
+impl Tally {
+    pub fn add(&mut self, p: Packet) {
+        let full = self.peers.len() >= MAX_PEERS && !self.peers.contains_key(&p.peer);
+        let (rx, tx) = if full { &mut self.others } else { self.peers.entry(p.peer).or_default() };
+        if p.outgoing {
+            *tx += p.bytes;
+        } else {
+            *rx += p.bytes;
+        }
+    }
+
+    // busiest first, and starts the next interval
+    pub fn rates(&mut self, elapsed: Duration) -> Vec<Talker> {
+        let secs = elapsed.as_secs_f64().max(1e-3);
+        let rate = |(rx, tx): (u64, u64)| (rx as f64 / secs, tx as f64 / secs);
+        let mut talkers: Vec<Talker> = self
+            .peers
+            .drain()
+            .map(|(peer, bytes)| {
+                let (rx_bps, tx_bps) = rate(bytes);
+                Talker { peer: Some(peer), rx_bps, tx_bps }
+            })
+            .collect();
+        if self.others != (0, 0) {
+            let (rx_bps, tx_bps) = rate(std::mem::take(&mut self.others));
+            talkers.push(Talker { peer: None, rx_bps, tx_bps });
+        }
+        talkers.sort_by(|a, b| (b.rx_bps + b.tx_bps).total_cmp(&(a.rx_bps + a.tx_bps)));
+        talkers
+    }
+}
+
+// a capture on one interface, read on its own thread into a bounded channel; dropping it
+// stops the thread and closes the socket before returning
+pub struct Capture {
+    pub interface: String,
+    pub started: Instant,
+    packets: Receiver<Packet>,
+    // packets the channel had no room for
+    dropped: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Capture {
+    pub fn start(interface: &str) -> Result<Self, String> {
+        let socket = system::open(interface, READ_TIMEOUT)?;
+        let (tx, packets) = mpsc::sync_channel(CHANNEL);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (dropped, stop) = (Arc::clone(&dropped), Arc::clone(&stop));
+            thread::spawn(move || {
+                let mut buf = [0u8; SNAPLEN];
+                while !stop.load(Ordering::Relaxed) {
+                    let (len, outgoing) = match socket.recv(&mut buf) {
+                        Ok(Some(read)) => read,
+                        // the read timed out
+                        Ok(None) => continue,
+                        // an interface going down, say; wait instead of spinning on it
+                        Err(_) => {
+                            thread::sleep(READ_TIMEOUT);
+                            continue;
+                        }
+                    };
+                    let Some(peer) = parse_packet(&buf[..len.min(SNAPLEN)], outgoing) else {
+                        continue;
+                    };
+                    match tx.try_send(Packet { peer, bytes: len as u64, outgoing }) {
+                        Ok(()) => {}
+                        Err(TrySendError::Full(_)) => {
+                            dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                        Err(TrySendError::Disconnected(_)) => break,
+                    }
+                }
+            })
+        };
+        Ok(Capture { interface: interface.to_string(), started: Instant::now(), packets, dropped, stop, thread: Some(thread) })
+    }
+
+    // everything captured since the last call
+    pub fn drain(&self) -> impl Iterator<Item = Packet> + '_ {
+        self.packets.try_iter()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// an AF_PACKET socket in cooked mode, so every link type hands over a bare IP packet, bound
+// to one interface; MSG_TRUNC makes recv report the packet's length while copying SNAPLEN
+#[cfg(all(feature = "pcap", target_os = "linux"))]
+mod system {
+    use std::ffi::CString;
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::time::Duration;
+
+    pub struct Socket(OwnedFd);
+
+    pub fn open(interface: &str, timeout: Duration) -> Result<Socket, String> {
+        let protocol = (libc::ETH_P_ALL as u16).to_be();
+        let fd = unsafe { libc::socket(libc::AF_PACKET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, i32::from(protocol)) };
+        if fd < 0 {
+            let e = io::Error::last_os_error();
+            if e.kind() == io::ErrorKind::PermissionDenied {
+                return Err("capturing needs CAP_NET_RAW: run as root or `sudo setcap cap_net_raw+ep $(which nettui)`".to_string());
+            }
+            return Err(format!("cannot open a capture socket: {e}"));
+        }
+        // closed when dropped, also on the errors below
+        let socket = Socket(unsafe { OwnedFd::from_raw_fd(fd) });
+        let name = CString::new(interface).map_err(|_| format!("bad interface name {interface:?}"))?;
+        let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if index == 0 {
+            return Err(format!("no interface {interface} to capture on"));
+        }
+        let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+        addr.sll_family = libc::AF_PACKET as u16;
+        addr.sll_protocol = protocol;
+        addr.sll_ifindex = index as i32;
+        let bound = unsafe {
+            libc::bind(
+                fd,
+                (&addr as *const libc::sockaddr_ll).cast(),
+                std::mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t,
+            )
+        };
+        if bound < 0 {
+            return Err(format!("cannot capture on {interface}: {}", io::Error::last_os_error()));
+        }
+        let tv = libc::timeval { tv_sec: timeout.as_secs() as libc::time_t, tv_usec: timeout.subsec_micros() as libc::suseconds_t };
+        let set = unsafe {
+            libc::setsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                (&tv as *const libc::timeval).cast(),
+                std::mem::size_of::<libc::timeval>() as libc::socklen_t,
+            )
+        };
+        if set < 0 {
+            return Err(format!("cannot capture on {interface}: {}", io::Error::last_os_error()));
+        }
+        Ok(socket)
+    }
+
+    impl Socket {
+        // (the packet's full length, whether it was going out); None when the timeout ran out
+        pub fn recv(&self, buf: &mut [u8]) -> io::Result<Option<(usize, bool)>> {
+            let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+            let mut addr_len = std::mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t;
+            let n = unsafe {
+                libc::recvfrom(
+                    self.0.as_raw_fd(),
+                    buf.as_mut_ptr().cast(),
+                    buf.len(),
+                    libc::MSG_TRUNC,
+                    (&mut addr as *mut libc::sockaddr_ll).cast(),
+                    &mut addr_len,
+                )
+            };
+            if n < 0 {
+                let e = io::Error::last_os_error();
+                return if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted) {
+                    Ok(None)
+                } else {
+                    Err(e)
+                };
+            }
+            Ok(Some((n as usize, addr.sll_pkttype == libc::PACKET_OUTGOING)))
+        }
+    }
+}
+
+#[cfg(not(all(feature = "pcap", target_os = "linux")))]
+mod system {
+    use std::io;
+    use std::time::Duration;
+
+    pub struct Socket;
+
+    pub fn open(_interface: &str, _timeout: Duration) -> Result<Socket, String> {
+        Err(super::unavailable().unwrap_or_default().to_string())
+    }
+
+    impl Socket {
+        pub fn recv(&self, _buf: &mut [u8]) -> io::Result<Option<(usize, bool)>> {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ipv4(proto: u8, src: [u8; 4], dst: [u8; 4], ports: (u16, u16)) -> Vec<u8> {
+        let mut p = vec![0x45, 0, 0, 60, 0, 0, 0, 0, 64, proto, 0, 0];
+        p.extend(src);
+        p.extend(dst);
+        p.extend(ports.0.to_be_bytes());
+        p.extend(ports.1.to_be_bytes());
+        p
+    }
+
+    #[test]
+    fn packets() {
+        let https = ipv4(6, [10, 0, 0, 1], [192, 168, 1, 10], (443, 50000));
+        assert_eq!(
+            parse_packet(&https, false),
+            Some(Peer { remote: "10.0.0.1".parse().unwrap(), port: Some(443), transport: Transport::Tcp })
+        );
+        let dns = ipv4(17, [192, 168, 1, 10], [1, 1, 1, 1], (50000, 53));
+        assert_eq!(parse_packet(&dns, true).map(|p| (p.remote.to_string(), p.port)), Some(("1.1.1.1".to_string(), Some(53))));
+
+        // a later fragment carries no ports, ICMP has none
+        let mut fragment = https.clone();
+        fragment[7] = 8;
+        assert_eq!(parse_packet(&fragment, false).unwrap().port, None);
+        assert_eq!(parse_packet(&ipv4(1, [8, 8, 8, 8], [0; 4], (0, 0)), false).unwrap().transport, Transport::Icmp);
+
+        let mut v6 = vec![0x60, 0, 0, 0, 0, 20, 17, 64];
+        v6.extend("2001:db8::1".parse::<Ipv6Addr>().unwrap().octets());
+        v6.extend("2001:db8::2".parse::<Ipv6Addr>().unwrap().octets());
+        v6.extend([0, 53, 0xc3, 0x50]);
+        assert_eq!(parse_packet(&v6, false).map(|p| (p.remote.to_string(), p.port)), Some(("2001:db8::1".to_string(), Some(53))));
+
+        assert_eq!(parse_packet(&https[..10], false), None, "cut short");
+        assert_eq!(parse_packet(&[0x20; 40], false), None, "not IP");
+    }
+
+    #[test]
+    fn tally_is_bounded() {
+        let peer = |i: u32| Peer { remote: IpAddr::V4(Ipv4Addr::from(i)), port: Some(443), transport: Transport::Tcp };
+        let mut tally = Tally::default();
+        tally.add(Packet { peer: peer(1), bytes: 1000, outgoing: false });
+        tally.add(Packet { peer: peer(1), bytes: 500, outgoing: true });
+        tally.add(Packet { peer: peer(2), bytes: 4000, outgoing: false });
+        let talkers = tally.rates(Duration::from_secs(2));
+        assert_eq!(talkers, [
+            Talker { peer: Some(peer(2)), rx_bps: 2000.0, tx_bps: 0.0 },
+            Talker { peer: Some(peer(1)), rx_bps: 500.0, tx_bps: 250.0 },
+        ]);
+        assert!(tally.rates(Duration::from_secs(1)).is_empty(), "a new interval");
+
+        for i in 0..MAX_PEERS as u32 + 10 {
+            tally.add(Packet { peer: peer(i), bytes: 1, outgoing: false });
+        }
+        let talkers = tally.rates(Duration::from_secs(1));
+        assert_eq!(talkers.len(), MAX_PEERS + 1);
+        assert!(talkers.contains(&Talker { peer: None, rx_bps: 10.0, tx_bps: 0.0 }));
+    }
+
+    #[cfg(not(all(feature = "pcap", target_os = "linux")))]
+    #[test]
+    fn says_why_it_cannot_capture() {
+        let Err(e) = Capture::start("eth0") else {
+            panic!("captured without the feature");
+        };
+        assert_eq!(Some(e.as_str()), unavailable());
+    }
+}
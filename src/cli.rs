@@ -0,0 +1,409 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use ratatui::style::Color;
+
+use crate::alert::{parse_rule, Rule};
+use crate::columns;
+use crate::datacap::{parse_cap, DataCap};
+use crate::app::{Action, MAX_REFRESH_MS, MIN_REFRESH_MS};
+use crate::format::{parse_rate, DigitSeparator};
+use crate::keymap::Key;
+use crate::theme::ThemeName;
+
+#[derive(Parser, Debug)]
+#[command(name="nettui", version, about="Network TUI Monitor")]
+pub struct Args {
+    /// interfaces to show, same as --interface
+    pub iface: Vec<String>,
+
+    /// only show interfaces matching this name or glob (eth*, wg?), repeatable
+    #[arg(short = 'i', long = "interface", value_name = "PATTERN")]
+    pub interface: Vec<String>,
+
+    /// hide interfaces matching this name or glob (docker0, veth*), repeatable; wins over --interface
+    #[arg(short = 'x', long, value_name = "PATTERN")]
+    pub exclude: Vec<String>,
+
+    /// show loopback, container and bridge interfaces from the start ('i' toggles at runtime)
+    #[arg(long, visible_alias = "all")]
+    pub show_virtual: bool,
+
+    /// sample for one interval, print a plain table to stdout and exit
+    #[arg(long)]
+    pub once: bool,
+
+    /// like --once but take N samples, one interval apart
+    #[arg(long, value_name = "N", conflicts_with = "once")]
+    pub count: Option<u32>,
+
+    /// print JSON instead of a table (implies --once unless --count is given)
+    #[arg(
+        long,
+        long_help = "Print one JSON array instead of a table; implies --once unless --count is given. Each element is one \
+                     interface at one sample:\n  {\"timestamp\": unix seconds, \"interface\": name, \
+                     \"rx_bps\"/\"tx_bps\": bytes/s, \"rx_pps\"/\"tx_pps\": packets/s, \
+                     \"rx_errors_ps\"/\"tx_errors_ps\": errors/s, \"rx_bytes_total\"/\"tx_bytes_total\": \
+                     bytes since start}\nNumbers are raw, never humanized."
+    )]
+    pub json: bool,
+
+    /// run headless, printing one record per interface per interval until interrupted
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with_all = ["once", "count", "json"])]
+    pub stream: Option<StreamFormat>,
+
+    /// on quit, print the session summary as one JSON object instead of a table
+    #[arg(
+        long,
+        conflicts_with_all = ["once", "count", "json", "stream"],
+        long_help = "On quit, print the session summary as one JSON object instead of a table:\n                       {\"started\": ISO 8601 time, \"duration_secs\": seconds, \"interfaces\": [{\"interface\": name,                      \"rx_bytes\"/\"tx_bytes\", \"rx_packets\"/\"tx_packets\", \"rx_errors\"/\"tx_errors\":                      totals since start, \"peak_rx_bps\"/\"peak_tx_bps\": highest bytes/s}]}\n                     Like the table it covers the interfaces the filters show at exit."
+    )]
+    pub summary_json: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// collect without a terminal, for running as a service: sample for --log-csv, --stream,
+    /// --prometheus, --influx and --statsd until SIGINT or SIGTERM, then flush and exit
+    #[arg(long, conflicts_with_all = ["once", "count", "json", "replay", "bench"])]
+    pub no_tui: bool,
+
+    /// append one CSV row per interface per sample to this file while running
+    #[arg(long, value_name = "PATH")]
+    pub log_csv: Option<PathBuf>,
+
+    /// play back a file written by --log-csv, --stream jsonl or --json instead of watching live
+    /// counters: Left/Right step a sample, space plays, +/- change the speed
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["once", "count", "json", "stream", "log_csv", "prometheus", "influx", "statsd", "bench", "netns", "all_netns"]
+    )]
+    pub replay: Option<PathBuf>,
+
+    /// answer `nettui query` (LIST, GET <interface>) on a Unix socket, by default
+    /// $XDG_RUNTIME_DIR/nettui.sock
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    pub socket: Option<Option<PathBuf>>,
+
+    /// serve Prometheus metrics on http://ADDR/metrics, e.g. 127.0.0.1:9184
+    #[arg(long, value_name = "ADDR")]
+    pub prometheus: Option<String>,
+
+    /// write each sample to InfluxDB at this http:// URL, e.g. http://localhost:8086, as line
+    /// protocol through /api/v2/write; failed writes are retried, then dropped and counted
+    #[arg(long, value_name = "URL")]
+    pub influx: Option<String>,
+
+    /// API token for --influx, else $INFLUX_TOKEN
+    #[arg(long, value_name = "TOKEN", requires = "influx")]
+    pub influx_token: Option<String>,
+
+    /// organization for --influx, else the token's default
+    #[arg(long, value_name = "ORG", requires = "influx")]
+    pub influx_org: Option<String>,
+
+    /// bucket for --influx, "nettui" unless given
+    #[arg(long, value_name = "BUCKET", requires = "influx")]
+    pub influx_bucket: Option<String>,
+
+    /// send each sample as statsd gauges over UDP to HOST:PORT, e.g. 127.0.0.1:8125, named
+    /// PREFIX.IFACE.METRIC (nettui.eth0.rx_bps)
+    #[arg(long, value_name = "HOST:PORT")]
+    pub statsd: Option<String>,
+
+    /// first part of every --statsd metric name, empty for none
+    #[arg(long, value_name = "PREFIX", default_value = "nettui")]
+    pub statsd_prefix: String,
+
+    /// initial sampling interval: plain milliseconds or a duration like 250ms, 2s, 1.5s
+    #[arg(short = 'n', long, default_value = "500ms", value_parser = parse_interval)]
+    pub interval: u64,
+
+    /// show rates in bits per second instead of bytes
+    #[arg(long)]
+    pub bits: bool,
+
+    /// use SI units (kB/s, powers of 1000) instead of IEC (KiB/s, powers of 1024)
+    #[arg(long)]
+    pub si: bool,
+
+    /// decimal places for rates below 100 in their unit
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(0..=3))]
+    pub precision: u8,
+
+    /// rate at which RX/TX turn yellow, e.g. 1M or 800KiB
+    #[arg(long, default_value = "1M", value_parser = parse_rate)]
+    pub warn: f64,
+
+    /// rate at which RX/TX turn red, e.g. 10M
+    #[arg(long, default_value = "10M", value_parser = parse_rate)]
+    pub crit: f64,
+
+    /// alert when iface:metric:op:value holds, repeatable; metric is rx, tx, total, packets,
+    /// errors or drops, e.g. "eth0:rx:>:80MB/s" or "*:errors:>:0"
+    #[arg(long, value_name = "RULE", value_parser = parse_rule)]
+    pub alert: Vec<Rule>,
+
+    /// samples in a row an alert's condition has to stop holding before it clears
+    #[arg(long, value_name = "N", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub alert_clear: u32,
+
+    /// track rx + tx on one interface against a budget, e.g. "wwan0:50GiB/30d" or
+    /// "wwan0:20G/month"; the running total survives restarts in $XDG_STATE_HOME/nettui/usage
+    #[arg(long, value_name = "IFACE:SIZE/PERIOD", value_parser = parse_cap)]
+    pub data_cap: Option<DataCap>,
+
+    /// desktop notification when an interface loses carrier or gets it back (needs the
+    /// notifications feature)
+    #[arg(long)]
+    pub notify: bool,
+
+    /// --notify, and also when an alert fires
+    #[arg(long)]
+    pub notify_alerts: bool,
+
+    /// at most one notification per interface this often, in seconds
+    #[arg(long, value_name = "SECS", default_value_t = 60)]
+    pub notify_every: u64,
+
+    /// keep this interface at the top of the table, repeatable; 'P' pins or unpins the
+    /// selected one and saves the list to the config file
+    #[arg(long, value_name = "NAME")]
+    pub pin: Vec<String>,
+
+    /// hide this interface from the table but keep recording it, repeatable ('x' hides the
+    /// selected one, 'X' lists them to show again)
+    #[arg(long, value_name = "NAME")]
+    pub hide: Vec<String>,
+
+    /// on quit, save the interfaces hidden with 'x' to the config file
+    #[arg(long)]
+    pub save_hidden: bool,
+
+    /// hide interfaces that moved next to nothing for the last few samples ('z' toggles)
+    #[arg(long)]
+    pub hide_idle: bool,
+
+    /// start with RX/TX smoothed by an exponential moving average ('~' toggles)
+    #[arg(long)]
+    pub smooth: bool,
+
+    /// time constant of the smoothing: plain milliseconds or a duration like 2s; longer is
+    /// steadier but slower to follow a change
+    #[arg(long, value_name = "DURATION", default_value = "2s", value_parser = parse_interval)]
+    pub smooth_tau: u64,
+
+    /// show peak and average rate columns
+    #[arg(long)]
+    pub stats: bool,
+
+    /// show multicast packets received per second ('M' toggles); Linux only
+    #[arg(long)]
+    pub multicast: bool,
+
+    /// the table columns to show in this order, comma separated: iface, rx, tx, err_in, ...
+    /// ('C' picks them, '<' and '>' move them); --stats, --multicast and --averages add theirs
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',', value_parser = columns::parse_name)]
+    pub columns: Vec<String>,
+
+    /// show each interface's average rx+tx over the last 1, 5 and 15 minutes ('L' toggles);
+    /// "~" marks one over less history than its window
+    #[arg(long)]
+    pub averages: bool,
+
+    /// how often the connections (Tab) and processes ('w') tabs re-read the sockets while
+    /// they are shown, independent of --interval
+    #[arg(long, value_name = "DURATION", default_value = "2s", value_parser = parse_interval)]
+    pub connections_interval: u64,
+
+    /// show remote addresses in the connections and top talkers tabs by their reverse DNS
+    /// names ('n' toggles); lookups run in the background and never hold up the display
+    #[arg(long)]
+    pub resolve: bool,
+
+    /// how many reverse DNS answers to keep, the least recently shown go first
+    #[arg(long, value_name = "N", default_value_t = crate::resolve::DEFAULT_CACHE)]
+    pub dns_cache: usize,
+
+    /// name the container behind each veth interface, as in "veth1a2b3c (nginx-1)", from
+    /// the container's network namespace; needs root to look into other processes
+    #[arg(long)]
+    pub containers: bool,
+
+    /// also show the interfaces of this network namespace, by its `ip netns` name or the pid
+    /// of a process in it, as "blue:eth0"; repeatable, Linux only
+    #[arg(long, value_name = "NAME")]
+    pub netns: Vec<String>,
+
+    /// also show the interfaces of every other network namespace that can be looked into;
+    /// namespaces created later show up within 10 seconds
+    #[arg(long)]
+    pub all_netns: bool,
+
+    /// where the counters come from: sysinfo, or /proc/net/dev read directly (Linux only),
+    /// which is cheaper and adds fifo, frame, compressed, multicast, collision and carrier counts
+    #[arg(long, value_enum, value_name = "BACKEND", default_value = "sysinfo")]
+    pub backend: Backend,
+
+    /// don't probe the default gateways for the detail view's latency (ping feature)
+    #[arg(long)]
+    pub no_ping: bool,
+
+    /// print numbers at their natural width instead of padding them into aligned columns
+    #[arg(long)]
+    pub compact: bool,
+
+    /// separator between digit groups of raw counters (packets, errors, drops)
+    #[arg(long, value_enum, value_name = "SEP", default_value = "comma")]
+    pub digit_separator: DigitSeparator,
+
+    /// color theme, 'T' cycles through them at runtime
+    #[arg(long, value_enum, value_name = "THEME", default_value = "dark")]
+    pub theme: ThemeName,
+
+    /// no colors in the TUI, only bold and reverse; NO_COLOR in the environment does the same
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// draw with plain ASCII only: +-| borders, ".:-=#" sparklines, no arrows or block characters
+    #[arg(long)]
+    pub ascii: bool,
+
+    /// leave the mouse to the terminal, so text can be selected the usual way
+    #[arg(long)]
+    pub no_mouse: bool,
+
+    /// the config file's [keys] table, no flag
+    #[arg(skip)]
+    pub keys: Vec<(Action, Vec<Key>)>,
+
+    /// per-color overrides from the config file (`color_<name> = "..."`), no flag
+    #[arg(skip)]
+    pub colors: Vec<(String, Color)>,
+
+    /// read settings from this file instead of $XDG_CONFIG_HOME/nettui/config.toml
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// print the effective settings (defaults < config file < flags) and exit
+    #[arg(long)]
+    pub dump_config: bool,
+
+    /// draw FRAMES frames of 200 fake interfaces offscreen, print the average sample and draw time, and exit
+    #[arg(long, value_name = "FRAMES", num_args = 0..=1, default_missing_value = "1000")]
+    pub bench: Option<u32>,
+}
+
+// flags for sampling and filtering go before the subcommand: nettui -n 1s serve
+#[derive(Subcommand, Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// sample here and stream to `nettui connect` clients instead of drawing anything
+    Serve {
+        /// address to accept clients on
+        #[arg(long, value_name = "ADDR", default_value = "0.0.0.0:7070")]
+        listen: String,
+        /// clients must present this; NETTUI_TOKEN is read when it's not given, and keeps it out of `ps`
+        #[arg(long, value_name = "TOKEN")]
+        token: Option<String>,
+    },
+    /// draw the samples of a `nettui serve` elsewhere, reconnecting when the connection drops
+    Connect {
+        /// where the server listens; the port defaults to 7070
+        #[arg(value_name = "HOST[:PORT]")]
+        addr: String,
+        /// the server's token; NETTUI_TOKEN is read when it's not given
+        #[arg(long, value_name = "TOKEN")]
+        token: Option<String>,
+    },
+    /// ask a nettui running with --socket and print the answer: LIST, or GET <interface>
+    Query {
+        /// the socket, if not the default one
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+        /// LIST when left out
+        #[arg(value_name = "QUERY")]
+        query: Vec<String>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    #[default]
+    Sysinfo,
+    Procfs,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum StreamFormat {
+    /// one JSON object per line, same fields as --json
+    Jsonl,
+}
+
+// "250" / "250ms" / "2s" / "1.5s" -> milliseconds, within the +/- key range
+pub fn parse_interval(s: &str) -> Result<u64, String> {
+    let t = s.trim();
+    let ms = if let Some(ms) = t.strip_suffix("ms") {
+        ms.trim().parse::<f64>()
+    } else if let Some(secs) = t.strip_suffix('s') {
+        secs.trim().parse::<f64>().map(|v| v * 1000.0)
+    } else {
+        t.parse::<f64>()
+    }
+    .map_err(|_| format!("invalid interval '{s}', expected e.g. 250ms or 2s"))?;
+
+    if !(MIN_REFRESH_MS as f64..=MAX_REFRESH_MS as f64).contains(&ms) {
+        return Err(format!("interval must be between {MIN_REFRESH_MS}ms and {}s", MAX_REFRESH_MS / 1000));
+    }
+    Ok(ms.round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_interval_forms() {
+        assert_eq!(parse_interval("250"), Ok(250));
+        assert_eq!(parse_interval("250ms"), Ok(250));
+        assert_eq!(parse_interval("2s"), Ok(2000));
+        assert_eq!(parse_interval("1.5s"), Ok(1500));
+        assert!(parse_interval("50ms").is_err());
+        assert!(parse_interval("1m").is_err());
+        assert!(parse_interval("11s").is_err());
+    }
+
+    #[test]
+    fn no_tui_is_a_collector() {
+        let args = Args::try_parse_from(["nettui", "--no-tui", "--log-csv", "out.csv", "--prometheus", "127.0.0.1:9184"]).unwrap();
+        assert!(args.no_tui);
+        assert!(Args::try_parse_from(["nettui", "--no-tui", "--stream", "jsonl"]).is_ok());
+        assert!(Args::try_parse_from(["nettui", "--no-tui", "--once"]).is_err(), "nothing to collect for");
+        assert!(Args::try_parse_from(["nettui", "--no-tui", "--replay", "x.csv"]).is_err());
+    }
+
+    #[test]
+    fn influx_options_need_a_url() {
+        let args = Args::try_parse_from(["nettui", "--influx", "http://localhost:8086", "--influx-bucket", "net"]).unwrap();
+        assert_eq!((args.influx.as_deref(), args.influx_bucket.as_deref()), (Some("http://localhost:8086"), Some("net")));
+        assert!(Args::try_parse_from(["nettui", "--influx-token", "t0ken"]).is_err());
+        assert!(Args::try_parse_from(["nettui", "--influx", "http://localhost:8086", "--replay", "x.csv"]).is_err());
+    }
+
+    #[test]
+    fn statsd_prefix_defaults() {
+        let args = Args::try_parse_from(["nettui", "--no-tui", "--statsd", "127.0.0.1:8125"]).unwrap();
+        assert_eq!((args.statsd.as_deref(), args.statsd_prefix.as_str()), (Some("127.0.0.1:8125"), "nettui"));
+        assert_eq!(Args::try_parse_from(["nettui", "--statsd-prefix", ""]).unwrap().statsd_prefix, "");
+    }
+
+    #[test]
+    fn socket_path_is_optional() {
+        assert_eq!(Args::try_parse_from(["nettui"]).unwrap().socket, None);
+        assert_eq!(Args::try_parse_from(["nettui", "--socket", "-n", "200ms"]).unwrap().socket, Some(None));
+        let args = Args::try_parse_from(["nettui", "--socket", "/tmp/n.sock"]).unwrap();
+        assert_eq!(args.socket, Some(Some(PathBuf::from("/tmp/n.sock"))));
+        let args = Args::try_parse_from(["nettui", "query", "GET", "eth0"]).unwrap();
+        assert!(matches!(args.command, Some(Command::Query { socket: None, query }) if query == ["GET", "eth0"]));
+    }
+}
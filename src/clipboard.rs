@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+// where 'y'/'Y' put the text, for the status bar
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Copied {
+    Clipboard,
+    // no clipboard to reach (SSH without X11, a bare console, a build without the feature)
+    File(PathBuf),
+}
+
+pub fn copy(text: &str) -> Result<Copied, String> {
+    if system::copy(text) {
+        return Ok(Copied::Clipboard);
+    }
+    let path = fallback_path();
+    std::fs::write(&path, text).map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+    Ok(Copied::File(path))
+}
+
+// $XDG_RUNTIME_DIR is private to the user and gone at logout; the temp dir only without one
+pub fn fallback_path() -> PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty()).map(PathBuf::from);
+    dir.unwrap_or_else(std::env::temp_dir).join("nettui-copy.txt")
+}
+
+// the desktop's own helpers, like `iw` for Wi-Fi: each one needs its display server, so the
+// first that is installed, has one to talk to and exits 0 wins
+#[cfg(feature = "clipboard")]
+mod system {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    // (program, arguments, the variable that says its display server is there)
+    const HELPERS: &[(&str, &[&str], Option<&str>)] = &[
+        ("wl-copy", &[], Some("WAYLAND_DISPLAY")),
+        ("xclip", &["-selection", "clipboard"], Some("DISPLAY")),
+        ("xsel", &["--clipboard", "--input"], Some("DISPLAY")),
+        ("pbcopy", &[], None),
+    ];
+
+    pub fn copy(text: &str) -> bool {
+        HELPERS
+            .iter()
+            .filter(|(_, _, display)| display.is_none_or(|var| std::env::var_os(var).is_some_and(|v| !v.is_empty())))
+            .any(|(program, args, _)| pipe(program, args, text))
+    }
+
+    fn pipe(program: &str, args: &[&str], text: &str) -> bool {
+        let spawned = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let Ok(mut child) = spawned else {
+            return false;
+        };
+        // stdin is dropped, and so closed, before waiting
+        let written = child.stdin.take().is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        child.wait().is_ok_and(|status| status.success()) && written
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+mod system {
+    pub fn copy(_text: &str) -> bool {
+        false
+    }
+}
@@ -0,0 +1,369 @@
+// the table's columns in the order they're drawn, each on or off: the chooser ('C'), the
+// header cursor ('<' and '>') and the `columns` setting all work on this one list, and the
+// renderer builds headers, widths and cells from it
+use crate::net::{HAS_DROPS, HAS_MULTICAST};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Column {
+    Iface,
+    Link,
+    Type,
+    Addr,
+    Rx,
+    Tx,
+    Util,
+    PktIn,
+    PktOut,
+    ErrIn,
+    ErrOut,
+    DropIn,
+    DropOut,
+    Nbrs,
+    McastIn,
+    PeakRx,
+    PeakTx,
+    AvgRx,
+    AvgTx,
+    Avg1m,
+    Avg5m,
+    Avg15m,
+    Trend,
+}
+
+// what 'A', 'L' and 'M' (and --stats, --averages, --multicast) turn on and off together
+pub const STATS: [Column; 4] = [Column::PeakRx, Column::PeakTx, Column::AvgRx, Column::AvgTx];
+pub const AVERAGES: [Column; 3] = [Column::Avg1m, Column::Avg5m, Column::Avg15m];
+pub const MULTICAST: [Column; 1] = [Column::McastIn];
+
+// names that stand for several columns, also what the chooser called them at first
+const GROUPS: &[(&str, &[Column])] = &[
+    ("address", &[Column::Addr]),
+    ("packets", &[Column::PktIn, Column::PktOut]),
+    ("errors", &[Column::ErrIn, Column::ErrOut]),
+    ("drops", &[Column::DropIn, Column::DropOut]),
+    ("neighbors", &[Column::Nbrs]),
+    ("multicast", &MULTICAST),
+    ("stats", &STATS),
+    ("averages", &AVERAGES),
+];
+
+impl Column {
+    // the default order
+    pub const ALL: [Column; 23] = [
+        Column::Iface,
+        Column::Link,
+        Column::Type,
+        Column::Addr,
+        Column::Rx,
+        Column::Tx,
+        Column::Util,
+        Column::PktIn,
+        Column::PktOut,
+        Column::ErrIn,
+        Column::ErrOut,
+        Column::DropIn,
+        Column::DropOut,
+        Column::Nbrs,
+        Column::McastIn,
+        Column::PeakRx,
+        Column::PeakTx,
+        Column::AvgRx,
+        Column::AvgTx,
+        Column::Avg1m,
+        Column::Avg5m,
+        Column::Avg15m,
+        Column::Trend,
+    ];
+
+    // what the config file and --columns call it
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Iface => "iface",
+            Column::Link => "link",
+            Column::Type => "type",
+            Column::Addr => "addr",
+            Column::Rx => "rx",
+            Column::Tx => "tx",
+            Column::Util => "util",
+            Column::PktIn => "pkt_in",
+            Column::PktOut => "pkt_out",
+            Column::ErrIn => "err_in",
+            Column::ErrOut => "err_out",
+            Column::DropIn => "drop_in",
+            Column::DropOut => "drop_out",
+            Column::Nbrs => "nbrs",
+            Column::McastIn => "mcast_in",
+            Column::PeakRx => "peak_rx",
+            Column::PeakTx => "peak_tx",
+            Column::AvgRx => "avg_rx",
+            Column::AvgTx => "avg_tx",
+            Column::Avg1m => "avg_1m",
+            Column::Avg5m => "avg_5m",
+            Column::Avg15m => "avg_15m",
+            Column::Trend => "trend",
+        }
+    }
+
+    // what the chooser lists
+    pub fn label(self) -> &'static str {
+        match self {
+            Column::Iface => "interface",
+            Column::Link => "link state",
+            Column::Type => "type",
+            Column::Addr => "address",
+            Column::Rx => "RX",
+            Column::Tx => "TX",
+            Column::Util => "utilization",
+            Column::PktIn => "packets in",
+            Column::PktOut => "packets out",
+            Column::ErrIn => "errors in",
+            Column::ErrOut => "errors out",
+            Column::DropIn => "drops in",
+            Column::DropOut => "drops out",
+            Column::Nbrs => "neighbors",
+            Column::McastIn => "multicast in",
+            Column::PeakRx => "peak RX",
+            Column::PeakTx => "peak TX",
+            Column::AvgRx => "session average RX",
+            Column::AvgTx => "session average TX",
+            Column::Avg1m => "1m average",
+            Column::Avg5m => "5m average",
+            Column::Avg15m => "15m average",
+            Column::Trend => "trend",
+        }
+    }
+
+    // drops and multicast need counters only Linux has
+    pub fn available(self) -> bool {
+        match self {
+            Column::DropIn | Column::DropOut => HAS_DROPS,
+            Column::McastIn => HAS_MULTICAST,
+            _ => true,
+        }
+    }
+
+    // the ones computed on demand start out off, the rest on
+    pub fn on_by_default(self) -> bool {
+        !(STATS.contains(&self) || AVERAGES.contains(&self) || MULTICAST.contains(&self))
+    }
+
+    // when the table is too narrow the lowest go first: errors, drops and multicast, then
+    // neighbors, packets, utilization, the type, the address, the trend, the averages, the
+    // stats and the link state. None is never dropped
+    pub fn priority(self) -> Option<u8> {
+        match self {
+            Column::Iface | Column::Rx | Column::Tx => None,
+            Column::ErrIn | Column::ErrOut | Column::DropIn | Column::DropOut | Column::McastIn => Some(0),
+            Column::Nbrs => Some(1),
+            Column::PktIn | Column::PktOut => Some(2),
+            Column::Util => Some(3),
+            Column::Type => Some(4),
+            Column::Addr => Some(5),
+            Column::Trend => Some(6),
+            Column::Avg1m | Column::Avg5m | Column::Avg15m => Some(7),
+            Column::PeakRx | Column::PeakTx | Column::AvgRx | Column::AvgTx => Some(8),
+            Column::Link => Some(9),
+        }
+    }
+
+    // text columns read left to right, numbers line up on the right
+    pub fn left_aligned(self) -> bool {
+        matches!(self, Column::Iface | Column::Link | Column::Type | Column::Addr)
+    }
+}
+
+// a column name or one of the group names; None when it's neither
+pub fn named(name: &str) -> Option<Vec<Column>> {
+    let name = name.trim().to_ascii_lowercase().replace('-', "_");
+    if let Some(c) = Column::ALL.into_iter().find(|c| c.name() == name) {
+        return Some(vec![c]);
+    }
+    GROUPS.iter().find(|(group, _)| *group == name).map(|(_, columns)| columns.to_vec())
+}
+
+// for error messages
+pub fn valid_names() -> String {
+    let names: Vec<&str> = Column::ALL.iter().map(|c| c.name()).chain(GROUPS.iter().map(|(group, _)| *group)).collect();
+    names.join(", ")
+}
+
+// --columns: a name the table knows, or an error listing them
+pub fn parse_name(name: &str) -> Result<String, String> {
+    match named(name) {
+        Some(_) => Ok(name.to_string()),
+        None => Err(format!("unknown column '{name}', expected one of {}", valid_names())),
+    }
+}
+
+// every column this platform has, in drawing order, each on or off
+#[derive(Clone, Debug, PartialEq)]
+pub struct Columns(Vec<(Column, bool)>);
+
+impl Default for Columns {
+    fn default() -> Self {
+        Columns(Column::ALL.into_iter().filter(|c| c.available()).map(|c| (c, c.on_by_default())).collect())
+    }
+}
+
+impl Columns {
+    // the named columns on and in that order, the rest off where they'd be by default;
+    // unknown names are skipped, the interface name is always there
+    pub fn from_names(names: &[String]) -> Self {
+        let mut listed: Vec<Column> = Vec::new();
+        for c in names.iter().filter_map(|n| named(n)).flatten() {
+            if c.available() && !listed.contains(&c) {
+                listed.push(c);
+            }
+        }
+        if !listed.contains(&Column::Iface) {
+            listed.insert(0, Column::Iface);
+        }
+        let mut columns = Columns(listed.into_iter().map(|c| (c, true)).collect());
+        for c in Column::ALL.into_iter().filter(|c| c.available()) {
+            if !columns.has(c) {
+                columns.insert(c, false);
+            }
+        }
+        columns
+    }
+
+    pub fn entries(&self) -> &[(Column, bool)] {
+        &self.0
+    }
+
+    // the ones drawn, in order
+    pub fn shown(&self) -> Vec<Column> {
+        self.0.iter().filter(|(_, on)| *on).map(|(c, _)| *c).collect()
+    }
+
+    pub fn shows(&self, column: Column) -> bool {
+        self.0.iter().any(|&(c, on)| c == column && on)
+    }
+
+    // what the `columns` setting saves
+    pub fn names(&self) -> Vec<String> {
+        self.shown().into_iter().map(|c| c.name().to_string()).collect()
+    }
+
+    fn has(&self, column: Column) -> bool {
+        self.0.iter().any(|(c, _)| *c == column)
+    }
+
+    // before the first column that comes after it by default
+    fn insert(&mut self, column: Column, on: bool) {
+        let rank = |c: Column| Column::ALL.iter().position(|&a| a == c);
+        let at = self.0.iter().position(|&(c, _)| rank(c) > rank(column)).unwrap_or(self.0.len());
+        self.0.insert(at, (column, on));
+    }
+
+    pub fn set(&mut self, column: Column, on: bool) {
+        // the name column is the row
+        let on = on || column == Column::Iface;
+        if let Some(entry) = self.0.iter_mut().find(|(c, _)| *c == column) {
+            entry.1 = on;
+        }
+    }
+
+    pub fn toggle(&mut self, column: Column) {
+        self.set(column, !self.shows(column));
+    }
+
+    // all of them off if any is on, otherwise all on
+    pub fn toggle_all(&mut self, group: &[Column]) {
+        let on = !group.iter().any(|&c| self.shows(c));
+        for &c in group {
+            self.set(c, on);
+        }
+    }
+
+    // swaps an entry with its neighbor in the full list, which is how the chooser moves them
+    pub fn swap(&mut self, index: usize, delta: isize) {
+        let Some(other) = index.checked_add_signed(delta).filter(|&i| i < self.0.len()) else {
+            return;
+        };
+        if index < self.0.len() {
+            self.0.swap(index, other);
+        }
+    }
+
+    // moves a shown column past the next shown one to its left or right, which is how the
+    // table's header cursor moves them; the columns that are off in between keep their place
+    pub fn shift(&mut self, column: Column, delta: isize) {
+        let shown = self.shown();
+        let Some(at) = shown.iter().position(|&c| c == column) else {
+            return;
+        };
+        let Some(&past) = at.checked_add_signed(delta).and_then(|i| shown.get(i)) else {
+            return;
+        };
+        let from = self.0.iter().position(|(c, _)| *c == column).unwrap();
+        let entry = self.0.remove(from);
+        let to = self.0.iter().position(|(c, _)| *c == past).unwrap();
+        self.0.insert(if delta > 0 { to + 1 } else { to }, entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn names_and_groups() {
+        for c in Column::ALL {
+            assert_eq!(named(c.name()), Some(vec![c]));
+        }
+        assert_eq!(named("ERR-IN"), Some(vec![Column::ErrIn]));
+        assert_eq!(named("packets"), Some(vec![Column::PktIn, Column::PktOut]));
+        assert_eq!(named("speed"), None);
+        let err = parse_name("speed").unwrap_err();
+        assert!(err.starts_with("unknown column 'speed', expected one of iface, link, type"), "{err}");
+    }
+
+    #[test]
+    fn defaults() {
+        let columns = Columns::default();
+        assert!(!columns.shows(Column::PeakRx) && !columns.shows(Column::McastIn));
+        assert_eq!(columns.shows(Column::DropIn), HAS_DROPS);
+        assert_eq!(columns.shown()[..6], [Column::Iface, Column::Link, Column::Type, Column::Addr, Column::Rx, Column::Tx]);
+    }
+
+    #[test]
+    fn order_from_names() {
+        let columns = Columns::from_names(&names(&["iface", "tx", "rx", "err_in", "bogus"]));
+        assert_eq!(columns.shown(), [Column::Iface, Column::Tx, Column::Rx, Column::ErrIn]);
+        assert_eq!(columns.names(), ["iface", "tx", "rx", "err_in"]);
+        // the rest are there to turn on, next to where they'd be by default
+        let all: Vec<Column> = columns.entries().iter().map(|(c, _)| *c).collect();
+        assert_eq!(all[..6], [Column::Iface, Column::Link, Column::Type, Column::Addr, Column::Tx, Column::Rx]);
+        assert_eq!(all.len(), Column::ALL.iter().filter(|c| c.available()).count());
+
+        let columns = Columns::from_names(&names(&["rx", "stats"]));
+        assert_eq!(columns.shown(), [Column::Iface, Column::Rx, Column::PeakRx, Column::PeakTx, Column::AvgRx, Column::AvgTx]);
+    }
+
+    #[test]
+    fn toggling_and_moving() {
+        let mut columns = Columns::from_names(&names(&["iface", "rx", "tx", "trend"]));
+        columns.toggle_all(&STATS);
+        assert_eq!(columns.shown(), [Column::Iface, Column::Rx, Column::Tx, Column::PeakRx, Column::PeakTx, Column::AvgRx, Column::AvgTx, Column::Trend]);
+        columns.toggle_all(&STATS);
+        columns.toggle(Column::Iface);
+        assert_eq!(columns.shown(), [Column::Iface, Column::Rx, Column::Tx, Column::Trend], "the name stays");
+
+        columns.shift(Column::Trend, -1);
+        columns.shift(Column::Trend, -1);
+        assert_eq!(columns.shown(), [Column::Iface, Column::Trend, Column::Rx, Column::Tx]);
+        columns.shift(Column::Iface, -1);
+        columns.shift(Column::Tx, 1);
+        assert_eq!(columns.shown(), [Column::Iface, Column::Trend, Column::Rx, Column::Tx], "nowhere to go");
+
+        columns.swap(0, 1);
+        assert_eq!(columns.entries()[0].0, Column::Link);
+        columns.swap(0, -1);
+        assert_eq!(columns.entries()[0].0, Column::Link);
+    }
+}
@@ -0,0 +1,932 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{ArgMatches, ValueEnum};
+use clap::parser::ValueSource;
+use ratatui::style::Color;
+
+use crate::alert::{parse_rule, Rule};
+use crate::app::Action;
+use crate::cli::{parse_interval, Args, Backend};
+use crate::columns;
+use crate::datacap::{parse_cap, DataCap};
+use crate::format::{parse_rate, DigitSeparator};
+use crate::glyphs::ASCII;
+use crate::keymap::{action_named, key_label, parse_key, Key, Keymap};
+use crate::theme::{parse_color, Theme, ThemeName};
+
+// file keys mirror the long flags; `interface` also covers the positional names
+const KEYS: &[(&str, &[&str])] = &[
+    ("interval", &["interval"]),
+    ("interface", &["iface", "interface"]),
+    ("exclude", &["exclude"]),
+    ("show_virtual", &["show_virtual"]),
+    ("bits", &["bits"]),
+    ("si", &["si"]),
+    ("precision", &["precision"]),
+    ("warn", &["warn"]),
+    ("crit", &["crit"]),
+    ("alerts", &["alert"]),
+    ("alert_clear", &["alert_clear"]),
+    ("data_cap", &["data_cap"]),
+    ("stats", &["stats"]),
+    ("multicast", &["multicast"]),
+    ("columns", &["columns"]),
+    ("compact", &["compact"]),
+    ("digit_separator", &["digit_separator"]),
+    ("theme", &["theme"]),
+    ("resolve", &["resolve"]),
+    ("dns_cache", &["dns_cache"]),
+    ("containers", &["containers"]),
+    ("netns", &["netns"]),
+    ("all_netns", &["all_netns"]),
+    ("backend", &["backend"]),
+    ("pins", &["pin"]),
+    ("hidden", &["hide"]),
+];
+
+// settings from config.toml; None means the file doesn't set it
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub interval: Option<u64>,
+    pub interface: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    pub show_virtual: Option<bool>,
+    pub bits: Option<bool>,
+    pub si: Option<bool>,
+    pub precision: Option<u8>,
+    pub warn: Option<f64>,
+    pub crit: Option<f64>,
+    pub alerts: Option<Vec<Rule>>,
+    pub alert_clear: Option<u32>,
+    pub data_cap: Option<DataCap>,
+    pub stats: Option<bool>,
+    pub multicast: Option<bool>,
+    pub columns: Option<Vec<String>>,
+    pub compact: Option<bool>,
+    pub digit_separator: Option<DigitSeparator>,
+    pub theme: Option<ThemeName>,
+    pub resolve: Option<bool>,
+    pub dns_cache: Option<usize>,
+    pub containers: Option<bool>,
+    pub netns: Option<Vec<String>>,
+    pub all_netns: Option<bool>,
+    pub backend: Option<Backend>,
+    // `color_<slot> = "..."` lines in file order, applied on top of whichever theme is active
+    pub colors: Vec<(String, Color)>,
+    // [keys], in file order
+    pub keys: Vec<(Action, Vec<Key>)>,
+    pub pins: Option<Vec<String>>,
+    pub hidden: Option<Vec<String>>,
+}
+
+// $XDG_CONFIG_HOME/nettui/config.toml, falling back to ~/.config
+pub fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(base.join("nettui").join("config.toml"))
+}
+
+impl Config {
+    // unknown keys are reported on stderr and otherwise ignored
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        let mut warnings = Vec::new();
+        let config = Config::parse(&text, &mut warnings).map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+        for w in warnings {
+            eprintln!("nettui: {}: {w}", path.display());
+        }
+        Ok(config)
+    }
+
+    // a small subset of TOML: `key = value` with strings, numbers, booleans and arrays, and
+    // one table, [keys], that has to come last
+    pub fn parse(text: &str, warnings: &mut Vec<String>) -> Result<Self, String> {
+        let mut config = Config::default();
+        let mut seen: Vec<String> = Vec::new();
+        let mut in_keys = false;
+        let mut lines = text.lines().enumerate();
+        while let Some((i, line)) = lines.next() {
+            let lineno = i + 1;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if trimmed.starts_with('[') {
+                if table_name(trimmed) != Some("keys") || in_keys {
+                    return Err(format!("line {lineno}: [keys] is the only table, other keys go at the top level"));
+                }
+                in_keys = true;
+                continue;
+            }
+            let Some((key, value)) = trimmed.split_once('=') else {
+                return Err(format!("line {lineno}: expected `key = value`"));
+            };
+            // `show-virtual` reads the same as `show_virtual`
+            let key = key.trim().replace('-', "_");
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("line {lineno}: invalid key '{}'", key));
+            }
+
+            // arrays may span several lines
+            let mut value = value.trim().to_string();
+            while value.starts_with('[') && !brackets_closed(&value) {
+                let Some((_, next)) = lines.next() else {
+                    return Err(format!("line {lineno}: key '{key}': unterminated array"));
+                };
+                value.push('\n');
+                value.push_str(next);
+            }
+
+            let parsed = parse_value(&value).map_err(|e| format!("line {lineno}: key '{key}': {e}"))?;
+            if seen.contains(&key) {
+                return Err(format!("line {lineno}: key '{key}' is set twice"));
+            }
+            seen.push(key.clone());
+            if in_keys {
+                let Some(action) = action_named(&key) else {
+                    warnings.push(format!("line {lineno}: unknown action '{key}' in [keys], ignored"));
+                    continue;
+                };
+                // one key or a list of them
+                let keys = match parsed {
+                    Value::Str(s) => Ok(vec![s]),
+                    other => other.strings(),
+                }
+                .and_then(|keys| keys.iter().map(|k| parse_key(k)).collect::<Result<Vec<_>, _>>())
+                .map_err(|e| format!("line {lineno}: key '{key}': {e}"))?;
+                config.keys.push((action, keys));
+                continue;
+            }
+            if let Some(slot) = key.strip_prefix("color_") {
+                let color = config.color(slot, parsed).map_err(|e| format!("line {lineno}: key '{key}': {e}"))?;
+                config.colors.push((slot.to_string(), color));
+                continue;
+            }
+            if !KEYS.iter().any(|(k, _)| *k == key) {
+                warnings.push(format!("line {lineno}: unknown key '{key}', ignored"));
+                continue;
+            }
+            config.set(&key, parsed).map_err(|e| format!("line {lineno}: key '{key}': {e}"))?;
+            // a misspelled column shouldn't keep nettui from starting
+            if key == "columns"
+                && let Some(names) = &mut config.columns
+            {
+                for name in names.iter().filter(|n| columns::named(n).is_none()) {
+                    warnings.push(format!("line {lineno}: unknown column '{name}', ignored; valid: {}", columns::valid_names()));
+                }
+                names.retain(|n| columns::named(n).is_some());
+            }
+        }
+        Keymap::new(&config.keys).map_err(|e| format!("[keys]: {e}"))?;
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: Value) -> Result<(), String> {
+        match key {
+            "interval" => {
+                self.interval = Some(match value {
+                    Value::Str(s) => parse_interval(&s)?,
+                    Value::Num(n) => parse_interval(&n.to_string())?,
+                    _ => return Err("expected a duration like \"2s\" or milliseconds".to_string()),
+                })
+            }
+            "interface" => self.interface = Some(value.strings()?),
+            "exclude" => self.exclude = Some(value.strings()?),
+            "show_virtual" => self.show_virtual = Some(value.bool()?),
+            "bits" => self.bits = Some(value.bool()?),
+            "si" => self.si = Some(value.bool()?),
+            "precision" => {
+                self.precision = match value {
+                    Value::Num(n) if (0.0..=3.0).contains(&n) && n.fract() == 0.0 => Some(n as u8),
+                    _ => return Err("expected a whole number from 0 to 3".to_string()),
+                }
+            }
+            "warn" => self.warn = Some(value.rate()?),
+            "crit" => self.crit = Some(value.rate()?),
+            "alerts" => self.alerts = Some(value.strings()?.iter().map(|s| parse_rule(s)).collect::<Result<_, _>>()?),
+            "alert_clear" => {
+                self.alert_clear = match value {
+                    Value::Num(n) if n >= 1.0 && n.fract() == 0.0 => Some(n as u32),
+                    _ => return Err("expected a whole number of samples, 1 or more".to_string()),
+                }
+            }
+            // "" is what --dump-config writes without a cap
+            "data_cap" => {
+                self.data_cap = match value {
+                    Value::Str(s) if s.is_empty() => None,
+                    Value::Str(s) => Some(parse_cap(&s)?),
+                    _ => return Err("expected a string like \"wwan0:50GiB/30d\"".to_string()),
+                }
+            }
+            "stats" => self.stats = Some(value.bool()?),
+            "multicast" => self.multicast = Some(value.bool()?),
+            "columns" => self.columns = Some(value.strings()?),
+            "compact" => self.compact = Some(value.bool()?),
+            "digit_separator" => {
+                let expected = || "expected \"comma\", \"underscore\", \"thin\" or \"none\"".to_string();
+                self.digit_separator = match value {
+                    Value::Str(s) => Some(DigitSeparator::from_str(&s, true).map_err(|_| expected())?),
+                    _ => return Err(expected()),
+                }
+            }
+            "theme" => {
+                let expected = || "expected \"dark\", \"light\" or \"monochrome\"".to_string();
+                self.theme = match value {
+                    Value::Str(s) => Some(ThemeName::from_str(&s, true).map_err(|_| expected())?),
+                    _ => return Err(expected()),
+                }
+            }
+            "resolve" => self.resolve = Some(value.bool()?),
+            "dns_cache" => {
+                self.dns_cache = match value {
+                    Value::Num(n) if n >= 1.0 && n.fract() == 0.0 => Some(n as usize),
+                    _ => return Err("expected a whole number of names, 1 or more".to_string()),
+                }
+            }
+            "containers" => self.containers = Some(value.bool()?),
+            "netns" => self.netns = Some(value.strings()?),
+            "all_netns" => self.all_netns = Some(value.bool()?),
+            "backend" => {
+                let expected = || "expected \"sysinfo\" or \"procfs\"".to_string();
+                self.backend = match value {
+                    Value::Str(s) => Some(Backend::from_str(&s, true).map_err(|_| expected())?),
+                    _ => return Err(expected()),
+                }
+            }
+            "pins" => self.pins = Some(value.strings()?),
+            "hidden" => self.hidden = Some(value.strings()?),
+            _ => unreachable!("key list and setter out of sync: {key}"),
+        }
+        Ok(())
+    }
+
+    // a name or "#rrggbb" for one of theme::SLOTS
+    fn color(&self, slot: &str, value: Value) -> Result<Color, String> {
+        let Value::Str(s) = value else {
+            return Err("expected a color like \"cyan\" or \"#rrggbb\"".to_string());
+        };
+        let color = parse_color(&s)?;
+        Theme::builtin(ThemeName::Dark).set(slot, color)?;
+        Ok(color)
+    }
+
+    fn has(&self, key: &str) -> bool {
+        match key {
+            "interval" => self.interval.is_some(),
+            "interface" => self.interface.is_some(),
+            "exclude" => self.exclude.is_some(),
+            "show_virtual" => self.show_virtual.is_some(),
+            "bits" => self.bits.is_some(),
+            "si" => self.si.is_some(),
+            "precision" => self.precision.is_some(),
+            "warn" => self.warn.is_some(),
+            "crit" => self.crit.is_some(),
+            "alerts" => self.alerts.is_some(),
+            "alert_clear" => self.alert_clear.is_some(),
+            "data_cap" => self.data_cap.is_some(),
+            "stats" => self.stats.is_some(),
+            "multicast" => self.multicast.is_some(),
+            "columns" => self.columns.is_some(),
+            "compact" => self.compact.is_some(),
+            "digit_separator" => self.digit_separator.is_some(),
+            "theme" => self.theme.is_some(),
+            "resolve" => self.resolve.is_some(),
+            "dns_cache" => self.dns_cache.is_some(),
+            "containers" => self.containers.is_some(),
+            "netns" => self.netns.is_some(),
+            "all_netns" => self.all_netns.is_some(),
+            "backend" => self.backend.is_some(),
+            "pins" => self.pins.is_some(),
+            "hidden" => self.hidden.is_some(),
+            _ => false,
+        }
+    }
+
+    // fills in every setting the command line didn't give explicitly
+    pub fn apply(&self, args: &mut Args, matches: &ArgMatches) {
+        let file = |key: &str| !from_cli(key, matches);
+        if let (Some(v), true) = (self.interval, file("interval")) {
+            args.interval = v;
+        }
+        if let (Some(v), true) = (&self.interface, file("interface")) {
+            args.interface = v.clone();
+        }
+        if let (Some(v), true) = (&self.exclude, file("exclude")) {
+            args.exclude = v.clone();
+        }
+        if let (Some(v), true) = (self.show_virtual, file("show_virtual")) {
+            args.show_virtual = v;
+        }
+        if let (Some(v), true) = (self.bits, file("bits")) {
+            args.bits = v;
+        }
+        if let (Some(v), true) = (self.si, file("si")) {
+            args.si = v;
+        }
+        if let (Some(v), true) = (self.precision, file("precision")) {
+            args.precision = v;
+        }
+        if let (Some(v), true) = (self.warn, file("warn")) {
+            args.warn = v;
+        }
+        if let (Some(v), true) = (self.crit, file("crit")) {
+            args.crit = v;
+        }
+        if let (Some(v), true) = (&self.alerts, file("alerts")) {
+            args.alert = v.clone();
+        }
+        if let (Some(v), true) = (self.alert_clear, file("alert_clear")) {
+            args.alert_clear = v;
+        }
+        if let (Some(v), true) = (&self.data_cap, file("data_cap")) {
+            args.data_cap = Some(v.clone());
+        }
+        if let (Some(v), true) = (self.stats, file("stats")) {
+            args.stats = v;
+        }
+        if let (Some(v), true) = (self.multicast, file("multicast")) {
+            args.multicast = v;
+        }
+        if let (Some(v), true) = (&self.columns, file("columns")) {
+            args.columns = v.clone();
+        }
+        if let (Some(v), true) = (self.compact, file("compact")) {
+            args.compact = v;
+        }
+        if let (Some(v), true) = (self.digit_separator, file("digit_separator")) {
+            args.digit_separator = v;
+        }
+        if let (Some(v), true) = (self.theme, file("theme")) {
+            args.theme = v;
+        }
+        if let (Some(v), true) = (self.resolve, file("resolve")) {
+            args.resolve = v;
+        }
+        if let (Some(v), true) = (self.dns_cache, file("dns_cache")) {
+            args.dns_cache = v;
+        }
+        if let (Some(v), true) = (self.containers, file("containers")) {
+            args.containers = v;
+        }
+        if let (Some(v), true) = (&self.netns, file("netns")) {
+            args.netns = v.clone();
+        }
+        if let (Some(v), true) = (self.all_netns, file("all_netns")) {
+            args.all_netns = v;
+        }
+        if let (Some(v), true) = (self.backend, file("backend")) {
+            args.backend = v;
+        }
+        // no flag sets single colors or key bindings
+        args.colors.extend(self.colors.iter().cloned());
+        args.keys.extend(self.keys.iter().cloned());
+        if let (Some(v), true) = (&self.pins, file("pins")) {
+            args.pin = v.clone();
+        }
+        if let (Some(v), true) = (&self.hidden, file("hidden")) {
+            args.hide = v.clone();
+        }
+    }
+}
+
+// writes one setting back into the config file, creating it if needed; everything else
+// in the file, comments included, stays as it was
+pub fn save(path: &Path, key: &str, value: &[String]) -> Result<()> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("cannot read {}", path.display())),
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    }
+    std::fs::write(path, set_line(&text, key, &array(value)))
+        .with_context(|| format!("cannot write {}", path.display()))
+}
+
+// replaces the top-level line (or multi-line array) setting `key`, or adds one before the
+// [keys] table
+fn set_line(text: &str, key: &str, value: &str) -> String {
+    let mut out = String::new();
+    let mut done = false;
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        if table_name(line.trim()).is_some() {
+            if !done {
+                out.push_str(&format!("{key} = {value}\n"));
+                done = true;
+            }
+            // nothing in a table is a top-level setting
+            for line in std::iter::once(line).chain(lines.by_ref()) {
+                out.push_str(line);
+                out.push('\n');
+            }
+            break;
+        }
+        let existing = line.split_once('=').filter(|(k, _)| k.trim().replace('-', "_") == key);
+        let Some((_, old)) = existing.filter(|_| !line.trim_start().starts_with('#')) else {
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        };
+        // skip the rest of an array spread over several lines
+        let mut old = old.trim().to_string();
+        while old.starts_with('[') && !brackets_closed(&old) {
+            let Some(next) = lines.next() else { break };
+            old.push('\n');
+            old.push_str(next);
+        }
+        if !done {
+            out.push_str(&format!("{key} = {value}\n"));
+            done = true;
+        }
+    }
+    if !done {
+        out.push_str(&format!("{key} = {value}\n"));
+    }
+    out
+}
+
+fn from_cli(key: &str, matches: &ArgMatches) -> bool {
+    let ids = KEYS.iter().find(|(k, _)| *k == key).map_or(&[][..], |(_, ids)| *ids);
+    ids.iter().any(|id| matches.value_source(id) == Some(ValueSource::CommandLine))
+}
+
+// --dump-config: the merged settings as a loadable config file, each line tagged with where it came from
+pub fn dump(args: &Args, config: &Config, matches: &ArgMatches, path: Option<&Path>) -> String {
+    let interfaces: Vec<String> = args.iface.iter().chain(&args.interface).cloned().collect();
+    let values = [
+        format!("\"{}ms\"", args.interval),
+        array(&interfaces),
+        array(&args.exclude),
+        args.show_virtual.to_string(),
+        args.bits.to_string(),
+        args.si.to_string(),
+        args.precision.to_string(),
+        args.warn.to_string(),
+        args.crit.to_string(),
+        array(&args.alert.iter().map(Rule::to_string).collect::<Vec<_>>()),
+        args.alert_clear.to_string(),
+        quote(&args.data_cap.as_ref().map_or(String::new(), DataCap::to_string)),
+        args.stats.to_string(),
+        args.multicast.to_string(),
+        array(&args.columns),
+        args.compact.to_string(),
+        quote(&args.digit_separator.to_possible_value().map_or(String::new(), |v| v.get_name().to_string())),
+        quote(args.theme.label()),
+        args.resolve.to_string(),
+        args.dns_cache.to_string(),
+        args.containers.to_string(),
+        array(&args.netns),
+        args.all_netns.to_string(),
+        quote(&args.backend.to_possible_value().map_or(String::new(), |v| v.get_name().to_string())),
+        array(&args.pin),
+        array(&args.hide),
+    ];
+
+    let mut out = match path {
+        Some(p) => format!("# config file: {}\n", p.display()),
+        None => "# config file: none\n".to_string(),
+    };
+    for ((key, _), value) in KEYS.iter().zip(values) {
+        let source = if from_cli(key, matches) {
+            "command line"
+        } else if config.has(key) {
+            "config file"
+        } else {
+            "default"
+        };
+        out.push_str(&format!("{:<40} # {source}\n", format!("{key} = {value}")));
+    }
+    for (slot, color) in &args.colors {
+        out.push_str(&format!("{:<40} # config file\n", format!("color_{slot} = {}", quote(&color.to_string()))));
+    }
+    if !args.keys.is_empty() {
+        out.push_str("\n[keys]\n");
+    }
+    for (action, keys) in &args.keys {
+        let keys: Vec<String> = keys.iter().map(|k| key_label(*k, &ASCII)).collect();
+        out.push_str(&format!("{:<40} # config file\n", format!("{} = {}", action.name(), array(&keys))));
+    }
+    out
+}
+
+fn array(items: &[String]) -> String {
+    let quoted: Vec<String> = items.iter().map(|s| quote(s)).collect();
+    format!("[{}]", quoted.join(", "))
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn bool(self) -> Result<bool, String> {
+        match self {
+            Value::Bool(b) => Ok(b),
+            _ => Err("expected true or false".to_string()),
+        }
+    }
+
+    fn strings(self) -> Result<Vec<String>, String> {
+        let expected = || "expected an array of strings, e.g. [\"eth*\", \"wg0\"]".to_string();
+        match self {
+            Value::Array(items) => items
+                .into_iter()
+                .map(|v| match v {
+                    Value::Str(s) => Ok(s),
+                    _ => Err(expected()),
+                })
+                .collect(),
+            _ => Err(expected()),
+        }
+    }
+
+    // bytes/s, either a number or a string like "1M"
+    fn rate(self) -> Result<f64, String> {
+        match self {
+            Value::Str(s) => parse_rate(&s),
+            Value::Num(n) if n >= 0.0 => Ok(n),
+            Value::Num(_) => Err("rate must not be negative".to_string()),
+            _ => Err("expected a rate like \"1M\" or bytes per second".to_string()),
+        }
+    }
+}
+
+// "keys" for "[keys]" (a comment may follow), None for anything else
+fn table_name(line: &str) -> Option<&str> {
+    let (name, rest) = line.strip_prefix('[')?.split_once(']')?;
+    let rest = rest.trim();
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    (valid && (rest.is_empty() || rest.starts_with('#'))).then_some(name.trim())
+}
+
+// true once every `[` outside strings and comments has its `]`
+fn brackets_closed(s: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => {
+                // skip to the end of the line
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            _ => {}
+        }
+    }
+    depth <= 0
+}
+
+// one value followed by nothing but whitespace or a comment
+fn parse_value(s: &str) -> Result<Value, String> {
+    let mut p = ValueParser { s, pos: 0 };
+    let v = p.value()?;
+    p.skip_blank();
+    if p.pos < s.len() {
+        return Err(format!("unexpected '{}' after the value", &s[p.pos..].trim_end()));
+    }
+    Ok(v)
+}
+
+struct ValueParser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl ValueParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    // whitespace, newlines and comments
+    fn skip_blank(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '#' {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.bump();
+                }
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_blank();
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::Str),
+            Some('\'') => self.literal_string().map(Value::Str),
+            Some('[') => self.array(),
+            Some(_) => self.bare(),
+            None => Err("missing value".to_string()),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.bump();
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.bump() {
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some(c) => return Err(format!("unsupported escape '\\{c}'")),
+                    None => return Err("unterminated string".to_string()),
+                },
+                Some('\n') | None => return Err("unterminated string".to_string()),
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.bump();
+        let start = self.pos;
+        loop {
+            match self.bump() {
+                Some('\'') => return Ok(self.s[start..self.pos - 1].to_string()),
+                Some('\n') | None => return Err("unterminated string".to_string()),
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.bump();
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.peek() == Some(']') {
+                self.bump();
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank();
+            match self.bump() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err("expected ',' or ']' in array".to_string()),
+            }
+        }
+    }
+
+    // true, false and numbers (underscores allowed, as in TOML)
+    fn bare(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || "+-._".contains(c)) {
+            self.bump();
+        }
+        let word = &self.s[start..self.pos];
+        match word {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            "" => Err(format!("unexpected '{}'", self.peek().unwrap_or(' '))),
+            _ => word
+                .replace('_', "")
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(Value::Num)
+                .ok_or_else(|| format!("invalid value '{word}' (strings need quotes)")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn parse(text: &str) -> Result<Config, String> {
+        Config::parse(text, &mut Vec::new())
+    }
+
+    #[test]
+    fn parses_every_key() {
+        let text = r#"
+            # my usual setup
+            interval = "2s"
+            interface = ["eth*", 'wg0']
+            exclude = [
+                "docker*",  # containers
+                "veth*",
+            ]
+            show-virtual = true
+            bits = true
+            si = false
+            precision = 2
+            warn = "500k"
+            crit = 20_000_000
+            alerts = ["eth0:rx:>:80MB/s", "*:errors:>:0"]
+            alert-clear = 5
+            data_cap = "wwan0:50GiB/30d"
+            stats = true
+            multicast = true
+            columns = ["iface", "tx", "rx", "packets", "trend"]
+            compact = false
+            digit-separator = "thin"
+            theme = "light"
+            resolve = true
+            dns-cache = 256
+            containers = true
+            netns = ["blue", "red"]
+            all-netns = false
+            backend = "procfs"
+            color_warn = '#ff8800'
+            color-link-up = "light-green"
+            pins = ["eth0", "wg0"]
+            hidden = ["veth1a2b"]
+
+            [keys]
+            quit = ["q", "esc"]
+            sort = "o"
+            pause = "space"
+        "#;
+        let config = parse(text).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                interval: Some(2000),
+                interface: Some(vec!["eth*".to_string(), "wg0".to_string()]),
+                exclude: Some(vec!["docker*".to_string(), "veth*".to_string()]),
+                show_virtual: Some(true),
+                bits: Some(true),
+                si: Some(false),
+                precision: Some(2),
+                warn: Some(500e3),
+                crit: Some(20e6),
+                alerts: Some(vec![parse_rule("eth0:rx:>:80M").unwrap(), parse_rule("*:errors:>:0").unwrap()]),
+                alert_clear: Some(5),
+                data_cap: Some(parse_cap("wwan0:50GiB/30d").unwrap()),
+                stats: Some(true),
+                multicast: Some(true),
+                columns: Some(["iface", "tx", "rx", "packets", "trend"].map(String::from).to_vec()),
+                compact: Some(false),
+                digit_separator: Some(DigitSeparator::Thin),
+                theme: Some(ThemeName::Light),
+                resolve: Some(true),
+                dns_cache: Some(256),
+                containers: Some(true),
+                netns: Some(vec!["blue".to_string(), "red".to_string()]),
+                all_netns: Some(false),
+                backend: Some(Backend::Procfs),
+                colors: vec![("warn".to_string(), Color::Rgb(0xff, 0x88, 0x00)), ("link_up".to_string(), Color::LightGreen)],
+                pins: Some(vec!["eth0".to_string(), "wg0".to_string()]),
+                hidden: Some(vec!["veth1a2b".to_string()]),
+                keys: vec![
+                    (Action::Quit, vec![parse_key("q").unwrap(), parse_key("esc").unwrap()]),
+                    (Action::CycleSort, vec![parse_key("o").unwrap()]),
+                    (Action::Pause, vec![parse_key("space").unwrap()]),
+                ],
+            }
+        );
+        assert_eq!(parse("interval = 250").unwrap().interval, Some(250));
+        assert!(parse("digit_separator = \"dot\"").is_err());
+        assert!(parse("theme = \"solarized\"").is_err());
+        assert!(parse("backend = \"netlink\"").is_err());
+        assert!(parse("alerts = [\"eth0:rx:>\"]").unwrap_err().contains("invalid alert"));
+        assert!(parse("alert_clear = 0").is_err());
+        assert!(parse("dns_cache = 0").is_err());
+        assert!(parse("data_cap = \"wwan0:50GiB\"").unwrap_err().contains("invalid data cap"));
+        assert_eq!(parse("data_cap = \"\"").unwrap().data_cap, None);
+        assert!(parse("color_background = \"red\"").unwrap_err().contains("unknown color 'background'"));
+        assert!(parse("color_warn = \"reddish\"").unwrap_err().contains("invalid color"));
+    }
+
+    #[test]
+    fn set_line_keeps_the_rest_of_the_file() {
+        let pins = array(&["wg0".to_string()]);
+        let text = "# mine\nbits = true\npins = [\n  \"eth0\",  # uplink\n]\nstats = true\n";
+        assert_eq!(set_line(text, "pins", &pins), "# mine\nbits = true\npins = [\"wg0\"]\nstats = true\n");
+        assert_eq!(set_line("bits = true", "pins", &pins), "bits = true\npins = [\"wg0\"]\n");
+        assert_eq!(set_line("# pins = [\"x\"]\n", "pins", "[]"), "# pins = [\"x\"]\npins = []\n");
+        assert_eq!(parse(&set_line(text, "pins", &pins)).unwrap().pins, Some(vec!["wg0".to_string()]));
+    }
+
+    #[test]
+    fn key_table() {
+        assert_eq!(
+            parse("[keys]\nsort = \"o\"\npause = [\"o\", \"p\"]").unwrap_err(),
+            "[keys]: 'o' is bound to both sort and pause"
+        );
+        assert!(parse("[keys]\nsort = \"hyper+o\"").unwrap_err().starts_with("line 2: key 'sort': invalid key"));
+        assert!(parse("[keys]\n[keys]").is_err());
+        assert!(parse("[keys] # bindings\nbits = \"B\"").is_ok());
+
+        let mut warnings = Vec::new();
+        Config::parse("[keys]\nfly = \"f\"", &mut warnings).unwrap();
+        assert_eq!(warnings, ["line 2: unknown action 'fly' in [keys], ignored"]);
+
+        // saving hidden interfaces must not touch the `hidden` action
+        let text = "bits = true\n\n[keys]\nhidden = \"H\"\n";
+        assert_eq!(
+            set_line(text, "hidden", "[\"eth0\"]"),
+            "bits = true\n\nhidden = [\"eth0\"]\n[keys]\nhidden = \"H\"\n"
+        );
+
+        // --dump-config writes the table back out
+        let config = parse("[keys]\nquit = [\"q\", \"shift+tab\"]\nup = \"ctrl+p\"").unwrap();
+        let matches = Args::command().try_get_matches_from(["nettui"]).unwrap();
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        config.apply(&mut args, &matches);
+        assert_eq!(parse(&dump(&args, &config, &matches, None)).unwrap().keys, config.keys);
+    }
+
+    #[test]
+    fn unknown_key_warns() {
+        let mut warnings = Vec::new();
+        let config = Config::parse("colour = \"blue\"\nbits = true\n", &mut warnings).unwrap();
+        assert_eq!(config.bits, Some(true));
+        assert_eq!(warnings, ["line 1: unknown key 'colour', ignored"]);
+
+        let mut warnings = Vec::new();
+        let config = Config::parse("columns = [\"iface\", \"rx\", \"speed\"]\n", &mut warnings).unwrap();
+        assert_eq!(config.columns, Some(vec!["iface".to_string(), "rx".to_string()]));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("line 1: unknown column 'speed', ignored; valid: iface, link, type, addr, rx"), "{}", warnings[0]);
+    }
+
+    #[test]
+    fn errors_name_line_and_key() {
+        assert_eq!(
+            parse("\nprecision = 7").unwrap_err(),
+            "line 2: key 'precision': expected a whole number from 0 to 3"
+        );
+        assert_eq!(parse("bits = yes").unwrap_err(), "line 1: key 'bits': invalid value 'yes' (strings need quotes)");
+        assert_eq!(parse("interface = \"eth0\"").unwrap_err().split(": ").nth(1), Some("key 'interface'"));
+        assert!(parse("interval = \"1m\"").unwrap_err().starts_with("line 1: key 'interval': invalid interval"));
+        assert!(parse("exclude = [\"a\",").unwrap_err().contains("unterminated array"));
+        assert!(parse("bits = true\nbits = false").unwrap_err().contains("set twice"));
+        assert!(parse("[ui]").is_err());
+    }
+
+    #[test]
+    fn command_line_wins_over_file() {
+        let config = parse("interval = \"2s\"\nbits = true\ninterface = [\"eth0\"]\nwarn = \"5M\"").unwrap();
+        let matches = Args::command().try_get_matches_from(["nettui", "-n", "1s", "wlan0"]).unwrap();
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        config.apply(&mut args, &matches);
+        assert_eq!(args.interval, 1000, "flag beats file");
+        assert_eq!(args.iface, ["wlan0"]);
+        assert!(args.interface.is_empty(), "positional names replace the file's list");
+        assert!(args.bits, "file beats default");
+        assert_eq!(args.warn, 5e6);
+        assert_eq!(args.crit, 10e6, "default kept");
+
+        let dump = dump(&args, &config, &matches, None);
+        assert!(dump.lines().any(|l| l.starts_with("interval = \"1000ms\"") && l.ends_with("# command line")));
+        assert!(dump.lines().any(|l| l.starts_with("bits = true") && l.ends_with("# config file")));
+        assert!(dump.lines().any(|l| l.starts_with("crit = 10000000") && l.ends_with("# default")));
+    }
+
+    #[test]
+    fn dump_round_trips() {
+        let matches = Args::command().try_get_matches_from(["nettui", "-x", "we\"ird", "--precision", "3"]).unwrap();
+        let args = Args::from_arg_matches(&matches).unwrap();
+        let dumped = dump(&args, &Config::default(), &matches, None);
+        let reparsed = parse(&dumped).unwrap();
+        assert_eq!(reparsed.exclude, Some(vec!["we\"ird".to_string()]));
+        assert_eq!(reparsed.precision, Some(3));
+        assert_eq!(reparsed.interval, Some(500));
+
+        let config = parse("color_tx = \"#00ffaa\"").unwrap();
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        config.apply(&mut args, &matches);
+        assert_eq!(parse(&dump(&args, &config, &matches, None)).unwrap().colors, config.colors);
+    }
+}
@@ -0,0 +1,327 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+
+use crate::net::IfaceAddr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Proto {
+    Tcp,
+    Udp,
+}
+
+impl Proto {
+    pub fn label(self) -> &'static str {
+        match self {
+            Proto::Tcp => "tcp",
+            Proto::Udp => "udp",
+        }
+    }
+}
+
+// one line of /proc/net/{tcp,udp}{,6}; IPv4-mapped IPv6 addresses read as plain IPv4
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Socket {
+    pub proto: Proto,
+    pub local: SocketAddr,
+    pub remote: SocketAddr,
+    // the kernel's TCP state number, see state_label
+    pub state: u8,
+    pub inode: u64,
+    // (pid, command name), None when the owner can't be seen or there is none (TIME-WAIT)
+    pub process: Option<(u32, String)>,
+}
+
+impl Socket {
+    // ss's names; a UDP socket is "closed" until it connects, which ss calls UNCONN
+    pub fn state_label(&self) -> &'static str {
+        match self.state {
+            1 => "ESTAB",
+            2 => "SYN-SENT",
+            3 => "SYN-RECV",
+            4 => "FIN-WAIT-1",
+            5 => "FIN-WAIT-2",
+            6 => "TIME-WAIT",
+            7 => "UNCONN",
+            8 => "CLOSE-WAIT",
+            9 => "LAST-ACK",
+            10 => "LISTEN",
+            11 => "CLOSING",
+            _ => "?",
+        }
+    }
+
+    // "sshd[812]", "--" when unknown
+    pub fn process_label(&self) -> String {
+        self.process.as_ref().map_or("--".to_string(), |(pid, name)| format!("{name}[{pid}]"))
+    }
+
+    // bound to this interface's addresses; one bound to 0.0.0.0 or :: takes connections on
+    // every interface, so it counts for each of them
+    pub fn on(&self, addrs: &[IfaceAddr]) -> bool {
+        let ip = self.local.ip();
+        ip.is_unspecified() || addrs.iter().any(|a| a.ip == ip)
+    }
+}
+
+// one read of every socket table
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Listing {
+    pub sockets: Vec<Socket>,
+    // processes whose open files couldn't be listed, usually other users' without root;
+    // their sockets show no owner
+    pub unreadable: usize,
+    // nothing could be read at all
+    pub error: Option<String>,
+}
+
+// what the connections tab sorts by; ties fall back to the remote end, then the local one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnSort {
+    State,
+    Remote,
+    Process,
+}
+
+impl ConnSort {
+    pub fn next(self) -> Self {
+        match self {
+            ConnSort::State => ConnSort::Remote,
+            ConnSort::Remote => ConnSort::Process,
+            ConnSort::Process => ConnSort::State,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ConnSort::State => "state",
+            ConnSort::Remote => "remote",
+            ConnSort::Process => "process",
+        }
+    }
+
+    fn compare(self, a: &Socket, b: &Socket) -> Ordering {
+        let addrs = |a: &Socket, b: &Socket| (a.remote.ip(), a.remote.port(), a.local).cmp(&(b.remote.ip(), b.remote.port(), b.local));
+        let first = match self {
+            ConnSort::State => a.state.cmp(&b.state),
+            ConnSort::Remote => Ordering::Equal,
+            // sockets without a known owner go last
+            ConnSort::Process => match (&a.process, &b.process) {
+                (Some((pa, na)), Some((pb, nb))) => (na.to_lowercase(), pa).cmp(&(nb.to_lowercase(), pb)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        };
+        first.then_with(|| addrs(a, b))
+    }
+}
+
+pub fn sort_sockets(sockets: &mut [Socket], key: ConnSort, desc: bool) {
+    sockets.sort_by(|a, b| {
+        let ord = key.compare(a, b);
+        if desc { ord.reverse() } else { ord }
+    });
+}
+
+#[cfg(target_os = "linux")]
+pub fn read() -> Listing {
+    read_from(Path::new("/proc"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read() -> Listing {
+    Listing { error: Some("active connections are only listed on Linux".to_string()), ..Default::default() }
+}
+
+// `root` is /proc outside of tests. A table that is missing (no IPv6, no UDP) is skipped;
+// only when none can be read is that an error
+pub fn read_from(root: &Path) -> Listing {
+    let mut sockets = Vec::new();
+    let mut first_error = None;
+    let mut read_any = false;
+    for (file, proto) in [("tcp", Proto::Tcp), ("tcp6", Proto::Tcp), ("udp", Proto::Udp), ("udp6", Proto::Udp)] {
+        let path = root.join("net").join(file);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                read_any = true;
+                sockets.extend(parse_table(&text, proto));
+            }
+            Err(e) => {
+                first_error.get_or_insert_with(|| format!("cannot read {}: {e}", path.display()));
+            }
+        }
+    }
+    if !read_any {
+        return Listing { error: first_error, ..Default::default() };
+    }
+    let (owners, unreadable) = owners(root);
+    for s in &mut sockets {
+        s.process = owners.get(&s.inode).cloned();
+    }
+    Listing { sockets, unreadable, error: None }
+}
+
+// the header line, then "sl local_address rem_address st tx_queue:rx_queue tr:tm->when
+// retrnsmt uid timeout inode ..." per socket
+fn parse_table(text: &str, proto: Proto) -> Vec<Socket> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            Some(Socket {
+                proto,
+                local: parse_addr(fields.get(1)?)?,
+                remote: parse_addr(fields.get(2)?)?,
+                state: u8::from_str_radix(fields.get(3)?, 16).ok()?,
+                inode: fields.get(9)?.parse().ok()?,
+                process: None,
+            })
+        })
+        .collect()
+}
+
+// "0100007F:0035" is 127.0.0.1:53: the address is the kernel's bytes printed as 32-bit words
+// in host order, the port plain hex
+fn parse_addr(s: &str) -> Option<SocketAddr> {
+    let (ip, port) = s.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let word = |i: usize| ip.get(i * 8..i * 8 + 8).and_then(|w| u32::from_str_radix(w, 16).ok()).map(u32::to_ne_bytes);
+    let ip = match ip.len() {
+        8 => IpAddr::V4(Ipv4Addr::from(word(0)?)),
+        32 => {
+            let mut bytes = [0u8; 16];
+            for (i, chunk) in bytes.chunks_mut(4).enumerate() {
+                chunk.copy_from_slice(&word(i)?);
+            }
+            IpAddr::V6(Ipv6Addr::from(bytes)).to_canonical()
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+// socket inode -> (pid, command name) from the "socket:[inode]" links in /proc/<pid>/fd,
+// and how many processes wouldn't let their fds be listed. A process that exits mid-scan
+// is simply missing
+pub(crate) fn owners(root: &Path) -> (HashMap<u64, (u32, String)>, usize) {
+    let mut owners = HashMap::new();
+    let mut unreadable = 0;
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return (owners, unreadable);
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+            continue;
+        };
+        let fds = match std::fs::read_dir(entry.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(e) => {
+                if e.kind() == std::io::ErrorKind::PermissionDenied {
+                    unreadable += 1;
+                }
+                continue;
+            }
+        };
+        let mut name = None;
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = target.to_str().and_then(|t| t.strip_prefix("socket:[")?.strip_suffix(']')?.parse::<u64>().ok());
+            let Some(inode) = inode else {
+                continue;
+            };
+            // comm only for processes that have sockets
+            let name = name.get_or_insert_with(|| {
+                std::fs::read_to_string(entry.path().join("comm")).map_or_else(|_| "?".to_string(), |c| c.trim_end().to_string())
+            });
+            owners.entry(inode).or_insert_with(|| (pid, name.clone()));
+        }
+    }
+    (owners, unreadable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0035 00000000:0000 0A 00000000:00000000 00:00000000 00000000   101        0 2001 1 0000000000000000 100 0 0 10 0
+   1: 0A01A8C0:D2F0 2200110A:01BB 01 00000000:00000000 02:000A7A5E 00000000  1000        0 2002 2 0000000000000000 20 4 30 10 -1
+   2: 0A01A8C0:D2F2 2200110A:01BB 06 00000000:00000000 03:00001524 00000000     0        0 0 3 0000000000000000
+";
+
+    const TCP6: &str = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 2003 1 0000000000000000 100 0 0 10 0
+   1: 0000000000000000FFFF00000A01A8C0:0016 0000000000000000FFFF00000501A8C0:C350 01 00000000:00000000 02:00099B2D 00000000     0        0 2004 4 0000000000000000 20 4 31 10 18
+";
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn tables() {
+        let tcp = parse_table(TCP, Proto::Tcp);
+        assert_eq!(tcp.len(), 3);
+        assert_eq!((tcp[0].local, tcp[0].remote, tcp[0].state_label()), (addr("127.0.0.1:53"), addr("0.0.0.0:0"), "LISTEN"));
+        assert_eq!((tcp[1].local, tcp[1].remote, tcp[1].inode), (addr("192.168.1.10:54000"), addr("10.17.0.34:443"), 2002));
+        assert_eq!(tcp[2].state_label(), "TIME-WAIT");
+
+        let tcp6 = parse_table(TCP6, Proto::Tcp);
+        assert_eq!(tcp6[0].local, addr("[::]:22"));
+        // v4-mapped reads as the IPv4 address it is
+        assert_eq!((tcp6[1].local, tcp6[1].remote), (addr("192.168.1.10:22"), addr("192.168.1.5:50000")));
+        assert!(parse_table("header\n  0: nonsense\n", Proto::Udp).is_empty());
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn filtering_and_sorting() {
+        let mut sockets = parse_table(TCP, Proto::Tcp);
+        sockets.extend(parse_table(TCP6, Proto::Tcp));
+        sockets[1].process = Some((40, "curl".to_string()));
+        sockets[4].process = Some((812, "Sshd".to_string()));
+
+        let eth0 = [IfaceAddr { ip: "192.168.1.10".parse().unwrap(), prefix: 24 }];
+        let inodes = |s: &[Socket]| s.iter().map(|s| s.inode).collect::<Vec<_>>();
+        let on: Vec<Socket> = sockets.iter().filter(|s| s.on(&eth0)).cloned().collect();
+        assert_eq!(inodes(&on), [2002, 0, 2003, 2004], "127.0.0.1 isn't eth0's, :: is everyone's");
+
+        sort_sockets(&mut sockets, ConnSort::State, false);
+        assert_eq!(inodes(&sockets), [2002, 2004, 0, 2001, 2003]);
+        sort_sockets(&mut sockets, ConnSort::Process, false);
+        assert_eq!(inodes(&sockets)[..2], [2002, 2004]);
+        sort_sockets(&mut sockets, ConnSort::Remote, true);
+        assert_eq!(inodes(&sockets)[..2], [2003, 2004]);
+        assert_eq!(sockets[0].process_label(), "--");
+        assert_eq!(sockets[1].process_label(), "Sshd[812]");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn owners_from_a_fake_proc() {
+        let root = std::env::temp_dir().join(format!("nettui-proc-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("net")).unwrap();
+        std::fs::write(root.join("net").join("tcp"), TCP).unwrap();
+        std::fs::create_dir_all(root.join("812").join("fd")).unwrap();
+        std::fs::write(root.join("812").join("comm"), "curl\n").unwrap();
+        std::os::unix::fs::symlink("socket:[2002]", root.join("812").join("fd").join("3")).unwrap();
+        std::os::unix::fs::symlink("/dev/null", root.join("812").join("fd").join("0")).unwrap();
+        std::fs::create_dir_all(root.join("self")).unwrap();
+
+        let listing = read_from(&root);
+        assert_eq!(listing.error, None);
+        assert_eq!(listing.sockets.len(), 3, "the missing tcp6 and udp tables are skipped");
+        let owned: Vec<_> = listing.sockets.iter().filter_map(|s| s.process.clone()).collect();
+        assert_eq!(owned, [(812, "curl".to_string())]);
+
+        let nothing = read_from(&root.join("missing"));
+        assert!(nothing.error.unwrap().contains("cannot read"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
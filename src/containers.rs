@@ -0,0 +1,220 @@
+// which container sits behind each veth: a container's own interfaces name their host side
+// peer in `iflink`, and the host's /sys/class/net has that ifindex; the container comes from
+// the cgroup of a process inside it. Everything is read below a root directory so fixture
+// trees can stand in for / in the tests
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::json::Json;
+
+// interface name -> container name
+pub type Containers = HashMap<String, String>;
+
+// how many characters of a container id `docker ps` shows
+const SHORT_ID: usize = 12;
+
+// the 64 hex digit container id in one line of /proc/<pid>/cgroup: "docker-<id>.scope"
+// (systemd), "/docker/<id>" (cgroupfs), "libpod-<id>.scope" (podman), "cri-containerd-<id>"
+pub fn container_id(cgroup: &str) -> Option<&str> {
+    cgroup.lines().flat_map(|line| line.split('/')).find_map(|part| {
+        let part = part.strip_suffix(".scope").unwrap_or(part);
+        let id = part.rsplit('-').next()?;
+        (id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit())).then_some(id)
+    })
+}
+
+fn read_file(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+// interface name by ifindex, from <root>/sys/class/net
+fn ifindexes(net: &Path) -> HashMap<u32, String> {
+    let Ok(dir) = fs::read_dir(net) else {
+        return HashMap::new();
+    };
+    dir.flatten()
+        .filter_map(|e| {
+            let index = read_file(&e.path().join("ifindex"))?.parse().ok()?;
+            Some((index, e.file_name().to_string_lossy().into_owned()))
+        })
+        .collect()
+}
+
+// the host side ifindexes of a container's interfaces; loopback and anything else that
+// is its own link point at themselves and are left out
+fn peer_indexes(net: &Path) -> Vec<u32> {
+    ifindexes(net)
+        .into_iter()
+        .filter_map(|(index, name)| {
+            let link: u32 = read_file(&net.join(name).join("iflink"))?.parse().ok()?;
+            (link != index).then_some(link)
+        })
+        .collect()
+}
+
+// container names Docker and Podman keep on disk, by id: Docker's config.v2.json holds
+// "Name": "/nginx-1", Podman's containers.json an array of {"id", "names"}
+pub fn names_on_disk(root: &Path) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    if let Ok(dir) = fs::read_dir(root.join("var/lib/docker/containers")) {
+        for e in dir.flatten() {
+            let config = read_file(&e.path().join("config.v2.json")).and_then(|t| Json::parse(&t));
+            if let Some(name) = config.as_ref().and_then(|c| c.get("Name")?.as_str()) {
+                names.insert(e.file_name().to_string_lossy().into_owned(), name.trim_start_matches('/').to_string());
+            }
+        }
+    }
+    let podman = read_file(&root.join("var/lib/containers/storage/overlay-containers/containers.json")).and_then(|t| Json::parse(&t));
+    for c in podman.as_ref().and_then(Json::as_array).unwrap_or_default() {
+        let name = c.get("names").and_then(Json::as_array).and_then(|n| n.first()?.as_str());
+        if let (Some(id), Some(name)) = (c.get("id").and_then(Json::as_str), name) {
+            names.insert(id.to_string(), name.to_string());
+        }
+    }
+    names
+}
+
+// every host interface with a container on the other end, named from `names` or by the
+// short id. One process per container is enough, the rest share its namespace
+pub fn scan(root: &Path, names: &HashMap<String, String>) -> Containers {
+    let host = ifindexes(&root.join("sys/class/net"));
+    let mut seen = Vec::new();
+    let mut containers = Containers::new();
+    let Ok(procs) = fs::read_dir(root.join("proc")) else {
+        return containers;
+    };
+    let mut pids: Vec<u32> = procs.flatten().filter_map(|e| e.file_name().to_str()?.parse().ok()).collect();
+    pids.sort_unstable();
+    for pid in pids {
+        let dir = root.join("proc").join(pid.to_string());
+        let Some(cgroup) = read_file(&dir.join("cgroup")) else {
+            continue;
+        };
+        let Some(id) = container_id(&cgroup).filter(|id| !seen.iter().any(|s| s == id)).map(str::to_string) else {
+            continue;
+        };
+        // no permission to look into this process's root; another of the container's
+        // processes may still allow it
+        let peers = peer_indexes(&dir.join("root/sys/class/net"));
+        if peers.is_empty() {
+            continue;
+        }
+        let name = names.get(&id).cloned().unwrap_or_else(|| id[..SHORT_ID].to_string());
+        for interface in peers.iter().filter_map(|index| host.get(index)) {
+            containers.insert(interface.clone(), name.clone());
+        }
+        seen.push(id);
+    }
+    containers
+}
+
+// `GET /containers/json` on the Docker socket, or Podman's compatible one: ids and names of
+// the running containers. Absent sockets and errors just mean no names from there
+#[cfg(all(feature = "docker", target_os = "linux"))]
+fn names_from_socket() -> HashMap<String, String> {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    const SOCKETS: [&str; 2] = ["/var/run/docker.sock", "/run/podman/podman.sock"];
+    let Some(mut stream) = SOCKETS.iter().find_map(|path| UnixStream::connect(path).ok()) else {
+        return HashMap::new();
+    };
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(1)));
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+    // HTTP/1.0 so the body comes whole, not chunked, and the connection closes after it
+    let mut reply = String::new();
+    if stream.write_all(b"GET /containers/json HTTP/1.0\r\nHost: localhost\r\n\r\n").is_err()
+        || stream.read_to_string(&mut reply).is_err()
+    {
+        return HashMap::new();
+    }
+    reply.split_once("\r\n\r\n").map(|(_, body)| parse_container_list(body)).unwrap_or_default()
+}
+
+// the API's container list: [{"Id": "...", "Names": ["/nginx-1"], ...}]
+pub fn parse_container_list(body: &str) -> HashMap<String, String> {
+    let list = Json::parse(body);
+    list.as_ref()
+        .and_then(Json::as_array)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|c| {
+            let name = c.get("Names")?.as_array()?.first()?.as_str()?;
+            Some((c.get("Id")?.as_str()?.to_string(), name.trim_start_matches('/').to_string()))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+pub fn read() -> Containers {
+    let root = Path::new("/");
+    let names = names_on_disk(root);
+    #[cfg(feature = "docker")]
+    let names = names.into_iter().chain(names_from_socket()).collect();
+    scan(root, &names)
+}
+
+// veths with containers behind them are a Linux thing
+#[cfg(not(target_os = "linux"))]
+pub fn read() -> Containers {
+    Containers::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "3f4e8c2b1a0d9e8f7a6b5c4d3e2f1a0b9c8d7e6f5a4b3c2d1e0f9a8b7c6d5e4f";
+
+    fn fixture() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/containers")
+    }
+
+    #[test]
+    fn ids_from_cgroups() {
+        assert_eq!(container_id(&format!("0::/system.slice/docker-{ID}.scope")), Some(ID));
+        assert_eq!(container_id(&format!("12:pids:/docker/{ID}\n0::/docker/{ID}")), Some(ID));
+        assert_eq!(container_id(&format!("0::/machine.slice/libpod-{ID}.scope/container")), Some(ID));
+        assert_eq!(container_id(&format!("0::/kubepods.slice/cri-containerd-{ID}.scope")), Some(ID));
+        assert_eq!(container_id("0::/user.slice/user-1000.slice/session-2.scope"), None);
+        assert_eq!(container_id("0::/init.scope"), None);
+    }
+
+    #[test]
+    fn names_from_disk() {
+        let names = names_on_disk(&fixture());
+        assert_eq!(names.get(ID).map(String::as_str), Some("nginx-1"));
+        assert_eq!(names.get(&"b".repeat(64)).map(String::as_str), Some("redis"));
+        assert_eq!(names.len(), 2);
+        assert!(names_on_disk(Path::new("/nonexistent")).is_empty());
+    }
+
+    #[test]
+    fn veths_to_containers() {
+        let root = fixture();
+        let containers = scan(&root, &names_on_disk(&root));
+        let name = |iface: &str| containers.get(iface).map(String::as_str);
+        assert_eq!(name("veth1a2b3c"), Some("nginx-1"));
+        assert_eq!(name("veth9f8e7d"), Some("redis"));
+        // nothing on disk names it
+        assert_eq!(name("vethc0ffee"), Some("cccccccccccc"));
+        assert_eq!(name("veth0dead0"), None, "its container is gone");
+        assert_eq!(name("eth0"), None);
+        assert_eq!(name("lo"), None, "a container's loopback points at itself");
+        assert_eq!(containers.len(), 3);
+
+        // names from the socket win over the short id
+        let names = HashMap::from([("c".repeat(64), "worker".to_string())]);
+        assert_eq!(scan(&root, &names).get("vethc0ffee").map(String::as_str), Some("worker"));
+        assert!(scan(Path::new("/nonexistent"), &names).is_empty());
+    }
+
+    #[test]
+    fn container_list() {
+        let body = format!(r#"[{{"Id":"{ID}","Names":["/nginx-1"],"State":"running","Ports":[{{"PrivatePort":80}}]}},{{"Id":"x"}}]"#);
+        assert_eq!(parse_container_list(&body), HashMap::from([(ID.to_string(), "nginx-1".to_string())]));
+        assert!(parse_container_list("{\"message\":\"page not found\"}").is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::net::RowData;
+use crate::snapshot::iso8601;
+
+pub const HEADER: &str = "timestamp,interface,rx_bps,tx_bps,packets_in_ps,packets_out_ps,errors_in_ps,errors_out_ps";
+
+// how long rows may sit in the buffer before hitting the disk
+const FLUSH_EVERY: Duration = Duration::from_secs(5);
+
+// appends one row per interface per sample; after the first write error (disk full,
+// file removed under a read-only mount...) logging stops and the error is kept for display
+pub struct CsvLog {
+    out: BufWriter<File>,
+    last_flush: Instant,
+    error: Option<String>,
+}
+
+impl CsvLog {
+    // appends to an existing file, writing the header only when the file is new or empty
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let fresh = file.metadata()?.len() == 0;
+        let mut out = BufWriter::new(file);
+        if fresh {
+            writeln!(out, "{HEADER}")?;
+        }
+        Ok(CsvLog { out, last_flush: Instant::now(), error: None })
+    }
+
+    pub fn write(&mut self, rows: &[RowData], unix_ts: f64) {
+        if self.error.is_some() {
+            return;
+        }
+        let ts = iso8601(unix_ts);
+        let result = rows.iter().try_for_each(|r| {
+            writeln!(
+                self.out,
+                "{ts},{},{:.1},{:.1},{:.2},{:.2},{:.2},{:.2}",
+                field(&r.interface),
+                r.rx_bps,
+                r.tx_bps,
+                r.packets_in,
+                r.packets_out,
+                r.errors_in,
+                r.errors_out
+            )
+        });
+        self.check(result);
+        if self.last_flush.elapsed() >= FLUSH_EVERY {
+            self.flush();
+        }
+    }
+
+    pub fn flush(&mut self) {
+        if self.error.is_none() {
+            let result = self.out.flush();
+            self.check(result);
+        }
+        self.last_flush = Instant::now();
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    fn check(&mut self, result: io::Result<()>) {
+        if let Err(e) = result {
+            self.error = Some(e.to_string());
+        }
+    }
+}
+
+impl Drop for CsvLog {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+// quotes fields containing separators, quotes or newlines (RFC 4180)
+pub(crate) fn field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::{IfaceInfo, Totals};
+
+    fn row(name: &str) -> RowData {
+        RowData {
+            interface: name.to_string(),
+            rx_bps: 1024.0,
+            tx_bps: 512.0,
+            rx_smooth: 0.0,
+            tx_smooth: 0.0,
+            packets_in: 2.0,
+            packets_out: 1.0,
+            errors_in: 0.0,
+            errors_out: 0.0,
+            drops_in: 0.0,
+            drops_out: 0.0,
+            multicast_in: None,
+            totals: Totals::default(),
+            info: IfaceInfo::default(),
+        }
+    }
+
+    #[test]
+    fn header_once_then_append() {
+        let path = std::env::temp_dir().join(format!("nettui-csvlog-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        CsvLog::open(&path).unwrap().write(&[row("eth0")], 0.0);
+        CsvLog::open(&path).unwrap().write(&[row("we,ird\"0")], 1.0);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], HEADER);
+        assert_eq!(lines[1], "1970-01-01T00:00:00.000Z,eth0,1024.0,512.0,2.00,1.00,0.00,0.00");
+        assert_eq!(lines[2], "1970-01-01T00:00:01.000Z,\"we,ird\"\"0\",1024.0,512.0,2.00,1.00,0.00,0.00");
+    }
+}
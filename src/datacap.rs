@@ -0,0 +1,353 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::format::parse_rate;
+use crate::net::Totals;
+use crate::snapshot::iso8601;
+
+// the state file is rewritten this often while bytes are counted, and on quit
+const SAVE_EVERY: Duration = Duration::from_secs(30);
+
+const DAY: f64 = 86_400.0;
+
+// how often the budget starts over
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    // every N days from the first run
+    Days(u32),
+    // calendar months, midnight UTC on the 1st
+    Month,
+}
+
+impl Period {
+    // "30-day", "monthly"
+    pub fn label(self) -> String {
+        match self {
+            Period::Days(days) => format!("{days}-day"),
+            Period::Month => "monthly".to_string(),
+        }
+    }
+
+    // where a fresh period starts
+    fn begin(self, unix: f64) -> f64 {
+        match self {
+            Period::Days(_) => unix.floor(),
+            Period::Month => {
+                let (year, month) = year_month(unix);
+                days_from_civil(year, month, 1) as f64 * DAY
+            }
+        }
+    }
+
+    // where the period starting at `start` ends
+    fn end(self, start: f64) -> f64 {
+        match self {
+            Period::Days(days) => start + f64::from(days) * DAY,
+            Period::Month => {
+                let (year, month) = year_month(start);
+                let (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+                days_from_civil(year, month, 1) as f64 * DAY
+            }
+        }
+    }
+}
+
+// what parse_cap reads back
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Period::Days(days) => write!(f, "{days}d"),
+            Period::Month => f.write_str("month"),
+        }
+    }
+}
+
+// "wwan0:50GiB/30d": rx + tx on one interface against a budget that starts over every period
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataCap {
+    pub interface: String,
+    pub bytes: u64,
+    pub period: Period,
+}
+
+impl fmt::Display for DataCap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}/{}", self.interface, self.bytes, self.period)
+    }
+}
+
+// `iface:size/period`, the period being a number of days ("30d") or "month"
+pub fn parse_cap(s: &str) -> Result<DataCap, String> {
+    let invalid = |why: &str| format!("invalid data cap '{s}': {why}, expected iface:size/period like \"wwan0:50GiB/30d\"");
+    let (rest, period) = s.trim().rsplit_once('/').ok_or_else(|| invalid("no period"))?;
+    let (interface, size) = rest.rsplit_once(':').ok_or_else(|| invalid("no interface"))?;
+    if interface.trim().is_empty() {
+        return Err(invalid("no interface"));
+    }
+    let bytes = parse_rate(size).map_err(|_| invalid("unknown size"))?;
+    if bytes < 1.0 {
+        return Err(invalid("the size must be more than 0"));
+    }
+    let period = match period.trim() {
+        "month" | "monthly" => Period::Month,
+        p => match p.strip_suffix('d').map(|d| d.trim().parse::<u32>()) {
+            Some(Ok(days)) if days >= 1 => Period::Days(days),
+            _ => return Err(invalid("the period is a number of days like 30d, or month")),
+        },
+    };
+    Ok(DataCap { interface: interface.trim().to_string(), bytes: bytes.round() as u64, period })
+}
+
+// $XDG_STATE_HOME/nettui/usage, falling back to ~/.local/state
+pub fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_STATE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local").join("state")))?;
+    Some(base.join("nettui").join("usage"))
+}
+
+// the running total of the current period, which is what the state file keeps
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Usage {
+    // unix seconds
+    pub period_start: f64,
+    pub used: u64,
+}
+
+// only ever adds what the session totals grew by since the last update, so a restart, a
+// second save or the clock moving can't count the same bytes twice. A clock that jumps
+// past the period's end starts a new one; one that goes back keeps counting into the
+// current period rather than reopening an old one
+pub struct Budget {
+    pub cap: DataCap,
+    pub usage: Usage,
+    path: Option<PathBuf>,
+    // the interface's session total at the last update
+    counted: u64,
+    last_save: Option<Instant>,
+    // the last save failed; counting goes on and the header says so
+    pub error: Option<String>,
+}
+
+impl Budget {
+    // carries on with the state file's total when it is for the same interface; a missing
+    // or unreadable file, or one for another interface, starts from zero
+    pub fn load(cap: DataCap, path: Option<PathBuf>, unix: f64) -> Self {
+        let saved = path.as_ref().and_then(|p| std::fs::read_to_string(p).ok()).and_then(|text| read_state(&text));
+        let usage = match saved {
+            Some((interface, usage)) if interface == cap.interface => usage,
+            _ => Usage { period_start: cap.period.begin(unix), used: 0 },
+        };
+        let mut budget = Budget { cap, usage, path, counted: 0, last_save: None, error: None };
+        budget.roll_over(unix);
+        budget
+    }
+
+    // `totals` is the interface's, None until it has been seen
+    pub fn update(&mut self, totals: Option<&Totals>, unix: f64, now: Instant) {
+        self.roll_over(unix);
+        let total = totals.map_or(0, |t| t.rx_bytes + t.tx_bytes);
+        self.usage.used += total.saturating_sub(self.counted);
+        self.counted = total;
+        if self.last_save.is_none_or(|at| now.duration_since(at) >= SAVE_EVERY) {
+            self.last_save = Some(now);
+            self.save();
+        }
+    }
+
+    fn roll_over(&mut self, unix: f64) {
+        let period = self.cap.period;
+        // a clock from before the period started was wrong then or is now (an RTC glitch to
+        // 2099, since corrected); the period starts over from today with what's been counted
+        if unix < self.usage.period_start {
+            self.usage.period_start = period.begin(unix);
+            return;
+        }
+        let mut start = self.usage.period_start;
+        while unix >= period.end(start) {
+            start = period.end(start);
+        }
+        if start != self.usage.period_start {
+            self.usage = Usage { period_start: start, used: 0 };
+        }
+    }
+
+    pub fn save(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let text = format!(
+            "# nettui --data-cap usage, rewritten while it runs\ninterface = \"{}\"\nperiod_start = {}\nused = {}\n",
+            self.cap.interface, self.usage.period_start, self.usage.used
+        );
+        // written aside and renamed, so a crash mid-write leaves the old total
+        let tmp = path.with_extension("tmp");
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&tmp, text))
+            .and_then(|_| std::fs::rename(&tmp, path));
+        self.error = written.err().map(|e| format!("cannot save {}: {e}", path.display()));
+    }
+
+    // share of the cap used, past 1 once it's over
+    pub fn fraction(&self) -> f64 {
+        self.usage.used as f64 / self.cap.bytes as f64
+    }
+
+    pub fn period_end(&self) -> f64 {
+        self.cap.period.end(self.usage.period_start)
+    }
+
+    // when the cap runs out at this period's average so far; None while too little of the
+    // period has gone by to tell, or when it lasts until the period ends
+    pub fn exhausted_at(&self, unix: f64) -> Option<f64> {
+        let elapsed = unix - self.usage.period_start;
+        if self.usage.used >= self.cap.bytes {
+            return Some(unix);
+        }
+        if elapsed < 60.0 || self.usage.used == 0 {
+            return None;
+        }
+        let at = unix + (self.cap.bytes - self.usage.used) as f64 / (self.usage.used as f64 / elapsed);
+        (at < self.period_end()).then_some(at)
+    }
+
+    // "full ~2026-10-30" or "resets 2026-11-01"
+    pub fn outlook(&self, unix: f64) -> String {
+        match self.exhausted_at(unix) {
+            Some(_) if self.usage.used >= self.cap.bytes => "cap reached".to_string(),
+            Some(at) => format!("full ~{}", &iso8601(at)[..10]),
+            None => format!("resets {}", &iso8601(self.period_end())[..10]),
+        }
+    }
+}
+
+// (interface, usage) from the state file, None if anything is missing
+fn read_state(text: &str) -> Option<(String, Usage)> {
+    let mut interface = None;
+    let (mut period_start, mut used) = (None, None);
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        let (key, value) = line.split_once('=')?;
+        let value = value.trim();
+        match key.trim() {
+            "interface" => interface = Some(value.trim_matches('"').to_string()),
+            "period_start" => period_start = value.parse::<f64>().ok().filter(|v| v.is_finite()),
+            "used" => used = value.parse::<u64>().ok(),
+            _ => {}
+        }
+    }
+    Some((interface?, Usage { period_start: period_start?, used: used? }))
+}
+
+fn year_month(unix: f64) -> (i64, u32) {
+    let date = iso8601(unix);
+    (date[..4].parse().unwrap_or(1970), date[5..7].parse().unwrap_or(1))
+}
+
+// days since the unix epoch, Howard Hinnant's days-from-civil
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-10-16T00:00:00Z
+    const OCT_16: f64 = 1_792_108_800.0;
+
+    fn totals(bytes: u64) -> Totals {
+        Totals { rx_bytes: bytes / 2, tx_bytes: bytes - bytes / 2, ..Default::default() }
+    }
+
+    #[test]
+    fn caps() {
+        let cap = parse_cap("wwan0:50GiB/30d").unwrap();
+        assert_eq!(cap, DataCap { interface: "wwan0".to_string(), bytes: 50 << 30, period: Period::Days(30) });
+        assert_eq!(parse_cap(&cap.to_string()), Ok(cap));
+        assert_eq!(parse_cap("eth0:1:2G/month").unwrap().interface, "eth0:1");
+        for bad in ["wwan0:50GiB", "50GiB/30d", "wwan0:lots/30d", "wwan0:50G/0d", "wwan0:50G/week", "wwan0:0/30d"] {
+            assert!(parse_cap(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn months() {
+        assert_eq!(iso8601(Period::Month.begin(OCT_16 + 3600.0)), "2026-10-01T00:00:00.000Z");
+        assert_eq!(iso8601(Period::Month.end(Period::Month.begin(OCT_16))), "2026-11-01T00:00:00.000Z");
+        let december = days_from_civil(2026, 12, 5) as f64 * DAY;
+        assert_eq!(iso8601(Period::Month.end(Period::Month.begin(december))), "2027-01-01T00:00:00.000Z");
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2024, 3, 1) - days_from_civil(2024, 2, 1), 29);
+    }
+
+    #[test]
+    fn counts_each_byte_once_across_restarts() {
+        let path = std::env::temp_dir().join(format!("nettui-usage-{}", std::process::id()));
+        let cap = parse_cap("wwan0:1000/month").unwrap();
+        let now = Instant::now();
+
+        let mut budget = Budget::load(cap.clone(), Some(path.clone()), OCT_16);
+        budget.update(Some(&totals(100)), OCT_16, now);
+        budget.update(Some(&totals(300)), OCT_16 + 1.0, now);
+        budget.save();
+        budget.save();
+        assert_eq!(budget.usage.used, 300);
+
+        // a new session's totals start from zero again
+        let mut budget = Budget::load(cap.clone(), Some(path.clone()), OCT_16 + 10.0);
+        assert_eq!(budget.usage.used, 300);
+        budget.update(None, OCT_16 + 11.0, now);
+        budget.update(Some(&totals(50)), OCT_16 + 12.0, now);
+        assert_eq!(budget.usage.used, 350);
+
+        // a clock glitch to 2099 starts a period there, and once corrected it's this month again
+        // rather than 2099's for good; the bytes counted meanwhile stay counted
+        budget.update(Some(&totals(55)), 4_080_000_000.0, now);
+        assert!(iso8601(budget.usage.period_start).starts_with("2099-"));
+        assert_eq!(budget.usage.used, 5);
+        budget.update(Some(&totals(65)), OCT_16 + 13.0, now);
+        assert_eq!((iso8601(budget.usage.period_start), budget.usage.used), ("2026-10-01T00:00:00.000Z".to_string(), 15));
+
+        // the same going back before the period started
+        budget.update(Some(&totals(75)), OCT_16 - 40.0 * DAY, now);
+        assert_eq!((iso8601(budget.usage.period_start), budget.usage.used), ("2026-09-01T00:00:00.000Z".to_string(), 25));
+
+        // into November, and a state file for another interface is ignored
+        budget.update(Some(&totals(85)), OCT_16 + 20.0 * DAY, now);
+        assert_eq!((iso8601(budget.usage.period_start), budget.usage.used), ("2026-11-01T00:00:00.000Z".to_string(), 10));
+        budget.save();
+        let other = Budget::load(parse_cap("eth0:1000/month").unwrap(), Some(path.clone()), OCT_16 + 20.0 * DAY);
+        assert_eq!(other.usage.used, 0);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn projection() {
+        let cap = parse_cap("wwan0:1000/30d").unwrap();
+        let mut budget = Budget::load(cap, None, OCT_16);
+        let now = Instant::now();
+        budget.update(Some(&totals(250)), OCT_16 + 5.0 * DAY, now);
+        assert_eq!(budget.fraction(), 0.25);
+        // 50 a day leaves 750 for another 15 days
+        assert_eq!(budget.exhausted_at(OCT_16 + 5.0 * DAY), Some(OCT_16 + 20.0 * DAY));
+        assert_eq!(budget.outlook(OCT_16 + 5.0 * DAY), "full ~2026-11-05");
+
+        let mut slow = Budget::load(parse_cap("wwan0:1000/30d").unwrap(), None, OCT_16);
+        slow.update(Some(&totals(10)), OCT_16 + 5.0 * DAY, now);
+        assert_eq!(slow.exhausted_at(OCT_16 + 5.0 * DAY), None);
+        assert_eq!(slow.outlook(OCT_16 + 5.0 * DAY), "resets 2026-11-15");
+
+        budget.update(Some(&totals(1200)), OCT_16 + 6.0 * DAY, now);
+        assert_eq!(budget.outlook(OCT_16 + 6.0 * DAY), "cap reached");
+    }
+}
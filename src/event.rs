@@ -0,0 +1,198 @@
+use std::io;
+use std::net::IpAddr;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
+
+use crate::conns::Listing;
+use crate::ping::Pinged;
+use crate::procs::Reading;
+use crate::remote;
+
+// everything the TUI loop reacts to, each source feeds the same channel from its own thread
+#[derive(Clone, Debug, PartialEq)]
+pub enum AppEvent {
+    Key(KeyEvent),
+    // clicks and the wheel; moves and drags never get this far
+    Mouse(MouseEvent),
+    // time to take a sample
+    Tick,
+    // a fresh read of the sockets for the connections tab
+    Connections(Listing),
+    // per-process rates for the processes tab
+    Processes(Reading),
+    // a reverse lookup's answer, None for no name, and how long to keep it
+    Resolved(IpAddr, Option<String>, Duration),
+    // a gateway probe from the Pinger
+    Ping(Pinged),
+    // `nettui connect`: the server's samples and the connection coming and going
+    Remote(remote::Update),
+    Resize(u16, u16),
+    // SIGINT or SIGTERM
+    Quit,
+}
+
+// blocks on terminal input; the thread ends with the process or once the receiver is gone
+pub fn spawn_input(tx: Sender<AppEvent>) {
+    thread::spawn(move || {
+        while let Ok(ev) = event::read() {
+            let ev = match ev {
+                // some platforms report releases too, one press is one action
+                Event::Key(key) if key.kind != KeyEventKind::Release => AppEvent::Key(key),
+                Event::Mouse(mouse)
+                    if matches!(
+                        mouse.kind,
+                        MouseEventKind::Down(MouseButton::Left) | MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+                    ) =>
+                {
+                    AppEvent::Mouse(mouse)
+                }
+                Event::Resize(w, h) => AppEvent::Resize(w, h),
+                _ => continue,
+            };
+            if tx.send(ev).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+// turns SIGINT/SIGTERM into a Quit event so they leave through the normal shutdown path
+pub fn spawn_signals(tx: Sender<AppEvent>) -> io::Result<()> {
+    let mut signals = Signals::new([SIGINT, SIGTERM])?;
+    thread::spawn(move || {
+        if signals.forever().next().is_some() {
+            let _ = tx.send(AppEvent::Quit);
+        }
+    });
+    Ok(())
+}
+
+// sends Tick at a fixed cadence; ticks that can't be delivered on time are dropped, not replayed
+pub struct Ticker {
+    ctrl: Sender<Duration>,
+}
+
+impl Ticker {
+    pub fn start(tx: Sender<AppEvent>, interval: Duration) -> Self {
+        let (ctrl, ctrl_rx) = mpsc::channel::<Duration>();
+        thread::spawn(move || {
+            let mut interval = interval;
+            let mut next = Instant::now() + interval;
+            loop {
+                match ctrl_rx.recv_timeout(next.saturating_duration_since(Instant::now())) {
+                    // a new interval counts from now, so speeding up takes effect immediately
+                    Ok(new) => {
+                        interval = new;
+                        next = Instant::now() + interval;
+                    }
+                    Err(RecvTimeoutError::Timeout) => {
+                        if tx.send(AppEvent::Tick).is_err() {
+                            break;
+                        }
+                        next += interval;
+                        let now = Instant::now();
+                        if next <= now {
+                            next = now + interval;
+                        }
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+        Ticker { ctrl }
+    }
+
+    pub fn set_interval(&self, interval: Duration) {
+        let _ = self.ctrl.send(interval);
+    }
+}
+
+// calls `read` on its own thread every `every` while active and not at all otherwise, and
+// sends what it returns; for the tabs whose data is too slow to gather on the sampling tick,
+// like scanning /proc/*/fd. The thread ends when the Poller is dropped
+pub struct Poller {
+    ctrl: Sender<bool>,
+}
+
+impl Poller {
+    pub fn start<F>(tx: Sender<AppEvent>, every: Duration, mut read: F) -> Self
+    where
+        F: FnMut() -> Option<AppEvent> + Send + 'static,
+    {
+        let (ctrl, ctrl_rx) = mpsc::channel::<bool>();
+        thread::spawn(move || {
+            let mut active = false;
+            loop {
+                let next = if active {
+                    ctrl_rx.recv_timeout(every)
+                } else {
+                    ctrl_rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+                };
+                match next {
+                    // switching to the tab reads right away instead of after `every`
+                    Ok(on) => {
+                        active = on;
+                        if !active {
+                            continue;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if let Some(ev) = read()
+                    && tx.send(ev).is_err()
+                {
+                    break;
+                }
+            }
+        });
+        Poller { ctrl }
+    }
+
+    pub fn set_active(&self, active: bool) {
+        let _ = self.ctrl.send(active);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticker_can_be_reprogrammed() {
+        let (tx, rx) = mpsc::channel();
+        let ticker = Ticker::start(tx, Duration::from_secs(60));
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+
+        ticker.set_interval(Duration::from_millis(10));
+        for _ in 0..3 {
+            assert_eq!(rx.recv_timeout(Duration::from_secs(2)), Ok(AppEvent::Tick));
+        }
+
+        // dropping the ticker stops the thread and closes the channel
+        drop(ticker);
+        while rx.recv_timeout(Duration::from_secs(2)).is_ok() {}
+        assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn poller_reads_only_while_active() {
+        let (tx, rx) = mpsc::channel();
+        let poller = Poller::start(tx, Duration::from_secs(60), || Some(AppEvent::Tick));
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+
+        // right away, not a minute later
+        poller.set_active(true);
+        assert_eq!(rx.recv_timeout(Duration::from_secs(2)), Ok(AppEvent::Tick));
+        poller.set_active(false);
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+
+        drop(poller);
+        assert_eq!(rx.recv_timeout(Duration::from_secs(2)), Err(RecvTimeoutError::Disconnected));
+    }
+}
@@ -0,0 +1,260 @@
+// 'e' and 'E': the table as it stands written to a file. The text export is what the screen
+// shows; CSV and JSON carry the numbers behind it, bytes and packets rather than "1.2 KiB/s"
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::app::{AppState, Entry};
+use crate::columns::{self, Column};
+use crate::csvlog;
+use crate::history::AVERAGE_WINDOWS;
+use crate::neigh;
+use crate::net::RowData;
+use crate::snapshot::{self, iso8601};
+use crate::ui;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Txt,
+    Csv,
+    Json,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Txt, Format::Csv, Format::Json];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Txt => "txt",
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+
+    // what 'E' switches to on tab
+    pub fn next(self) -> Format {
+        match self {
+            Format::Txt => Format::Csv,
+            Format::Csv => Format::Json,
+            Format::Json => Format::Txt,
+        }
+    }
+
+    // by the extension, case aside; None for anything else
+    pub fn of(path: &Path) -> Option<Format> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        Format::ALL.into_iter().find(|f| f.extension() == ext)
+    }
+}
+
+// nettui-2026-10-15T18-17-10.txt, in UTC like every other timestamp nettui writes; no
+// colons, which Windows doesn't allow in a file name
+pub fn default_path(unix: f64, format: Format) -> PathBuf {
+    let time = iso8601(unix);
+    PathBuf::from(format!("nettui-{}.{}", time[..19].replace(':', "-"), format.extension()))
+}
+
+// the file's contents; rows are the ones the table lists, in its order, and the columns the
+// ones it shows. The sparkline has no number behind it and is left out of CSV and JSON
+pub fn render(app: &AppState, format: Format) -> String {
+    if format == Format::Txt {
+        return ui::view_text(app);
+    }
+    let columns: Vec<Column> = app.columns.shown().into_iter().filter(|&c| c != Column::Trend).collect();
+    let keys: Vec<&str> = columns.iter().map(|&c| key(c, app.cumulative)).collect();
+    let entries = app.entries();
+    let rows = entries.iter().flat_map(|e| match e {
+        Entry::Row(r) => vec![*r],
+        // a collapsed group still stands for its members
+        Entry::Group(_, members) => members.clone(),
+    });
+    let mut out = String::new();
+    match format {
+        Format::Csv => {
+            out.push_str(&keys.join(","));
+            out.push('\n');
+            for r in rows {
+                let cells: Vec<String> = columns
+                    .iter()
+                    .map(|&c| match value(app, r, c) {
+                        Value::Text(s) => csvlog::field(&s),
+                        Value::Number(n) if n.is_finite() => format!("{n}"),
+                        _ => String::new(),
+                    })
+                    .collect();
+                out.push_str(&cells.join(","));
+                out.push('\n');
+            }
+        }
+        _ => {
+            let objects: Vec<String> = rows
+                .map(|r| {
+                    let mut o = String::from("{");
+                    for (i, (&c, k)) in columns.iter().zip(&keys).enumerate() {
+                        let v = match value(app, r, c) {
+                            Value::Text(s) => snapshot::string(&s),
+                            Value::Number(n) => snapshot::number(n),
+                            Value::Missing => "null".to_string(),
+                        };
+                        let _ = write!(o, "{}\"{k}\":{v}", if i == 0 { "" } else { "," });
+                    }
+                    o + "}"
+                })
+                .collect();
+            out = format!("[{}]\n", objects.join(","));
+        }
+    }
+    out
+}
+
+// writes the export, returning how many interfaces went into it. A file already at the path
+// is an AlreadyExists error unless `overwrite`
+pub fn write(app: &AppState, path: &Path, format: Format, overwrite: bool) -> io::Result<usize> {
+    let mut file = if overwrite { File::create(path)? } else { OpenOptions::new().write(true).create_new(true).open(path)? };
+    file.write_all(render(app, format).as_bytes())?;
+    Ok(app.shown().len())
+}
+
+// 'e' under the default name, or the first of name-1.txt, name-2.txt, ... that's free when two
+// exports land in the same second
+pub fn write_numbered(app: &AppState, path: &Path, format: Format) -> io::Result<(PathBuf, usize)> {
+    let mut n = 0;
+    loop {
+        let candidate = numbered(path, n);
+        match write(app, &candidate, format, false) {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+            result => return result.map(|count| (candidate, count)),
+        }
+    }
+}
+
+// the first of path, path-1, path-2, ... with nothing there yet, what 'E' suggests
+pub fn unused(path: &Path) -> PathBuf {
+    (0..).map(|n| numbered(path, n)).find(|p| std::fs::symlink_metadata(p).is_err()).unwrap_or_else(|| path.to_path_buf())
+}
+
+// -n before the extension, the path itself for 0
+fn numbered(path: &Path, n: usize) -> PathBuf {
+    if n == 0 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}-{n}.{}", ext.to_string_lossy()),
+        None => format!("{stem}-{n}"),
+    };
+    path.with_file_name(name)
+}
+
+enum Value {
+    Text(String),
+    Number(f64),
+    // "--" on screen
+    Missing,
+}
+
+// the CSV header and JSON key: per second in the rate view, totals since start in the
+// totals view, the names --json and --log-csv already use where there is one
+fn key(c: Column, totals: bool) -> &'static str {
+    let (rate, total) = match c {
+        Column::Iface => ("interface", "interface"),
+        Column::Link => ("link", "link"),
+        Column::Type => ("type", "type"),
+        Column::Addr => ("address", "address"),
+        Column::Rx => ("rx_bps", "rx_bytes"),
+        Column::Tx => ("tx_bps", "tx_bytes"),
+        Column::Util => ("util_pct", "util_pct"),
+        Column::PktIn => ("rx_pps", "rx_packets"),
+        Column::PktOut => ("tx_pps", "tx_packets"),
+        Column::ErrIn => ("rx_errors_ps", "rx_errors"),
+        Column::ErrOut => ("tx_errors_ps", "tx_errors"),
+        Column::DropIn => ("rx_drops_ps", "rx_drops"),
+        Column::DropOut => ("tx_drops_ps", "tx_drops"),
+        Column::Nbrs => ("neighbors", "neighbors"),
+        Column::McastIn => ("rx_multicast_pps", "rx_multicast"),
+        Column::PeakRx => ("peak_rx_bps", "peak_rx_bps"),
+        Column::PeakTx => ("peak_tx_bps", "peak_tx_bps"),
+        Column::AvgRx => ("avg_rx_bps", "avg_rx_bps"),
+        Column::AvgTx => ("avg_tx_bps", "avg_tx_bps"),
+        Column::Avg1m => ("avg_1m_bps", "avg_1m_bps"),
+        Column::Avg5m => ("avg_5m_bps", "avg_5m_bps"),
+        Column::Avg15m => ("avg_15m_bps", "avg_15m_bps"),
+        Column::Trend => ("trend", "trend"),
+    };
+    if totals { total } else { rate }
+}
+
+// the same choices as the table's cells, before formatting
+fn value(app: &AppState, r: &RowData, c: Column) -> Value {
+    let t = &r.totals;
+    let totals = app.cumulative;
+    let (rx, tx) = app.shown_rates(r);
+    let count = |n: u64| Value::Number(n as f64);
+    match c {
+        Column::Iface => Value::Text(r.interface.clone()),
+        Column::Link => Value::Text(r.info.state.label().to_string()),
+        Column::Type => Value::Text(r.info.kind.label().to_string()),
+        Column::Addr => r.info.primary_addr().map_or(Value::Missing, |ip| Value::Text(ip.to_string())),
+        Column::Rx if totals => count(t.rx_bytes),
+        Column::Rx => Value::Number(rx),
+        Column::Tx if totals => count(t.tx_bytes),
+        Column::Tx => Value::Number(tx),
+        Column::Util if totals => Value::Missing,
+        Column::Util => r.utilization().map_or(Value::Missing, Value::Number),
+        Column::PktIn if totals => count(t.packets_in),
+        Column::PktIn => Value::Number(r.packets_in),
+        Column::PktOut if totals => count(t.packets_out),
+        Column::PktOut => Value::Number(r.packets_out),
+        Column::ErrIn if totals => count(t.errors_in),
+        Column::ErrIn => Value::Number(r.errors_in),
+        Column::ErrOut if totals => count(t.errors_out),
+        Column::ErrOut => Value::Number(r.errors_out),
+        Column::DropIn if totals => count(t.drops_in),
+        Column::DropIn => Value::Number(r.drops_in),
+        Column::DropOut if totals => count(t.drops_out),
+        Column::DropOut => Value::Number(r.drops_out),
+        Column::Nbrs if app.neighbors.is_empty() => Value::Missing,
+        Column::Nbrs => count(neigh::count(&app.neighbors, &r.interface) as u64),
+        Column::McastIn => match r.multicast_in {
+            None => Value::Missing,
+            Some(_) if totals => count(t.multicast_in),
+            Some(pps) => Value::Number(pps),
+        },
+        Column::PeakRx => Value::Number(app.peak(r).0),
+        Column::PeakTx => Value::Number(app.peak(r).1),
+        Column::AvgRx => Value::Number(app.average(r).0),
+        Column::AvgTx => Value::Number(app.average(r).1),
+        Column::Avg1m | Column::Avg5m | Column::Avg15m => {
+            let i = columns::AVERAGES.iter().position(|&a| a == c).unwrap_or_default();
+            let now = app.last_sample.unwrap_or_else(Instant::now);
+            app.history.average(&r.interface, now, AVERAGE_WINDOWS[i].1).map_or(Value::Missing, |a| Value::Number(a.rate))
+        }
+        Column::Trend => Value::Missing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_and_paths() {
+        assert_eq!(default_path(1_792_088_230.0, Format::Csv), PathBuf::from("nettui-2026-10-15T18-17-10.csv"));
+        assert_eq!(Format::of(Path::new("out/today.JSON")), Some(Format::Json));
+        assert_eq!(Format::of(Path::new("today.log")), None);
+        assert_eq!(Format::of(Path::new("today")), None);
+        assert_eq!(Format::Json.next(), Format::Txt);
+        assert_eq!(numbered(Path::new("out/nettui-x.txt"), 0), PathBuf::from("out/nettui-x.txt"));
+        assert_eq!(numbered(Path::new("out/nettui-x.txt"), 2), PathBuf::from("out/nettui-x-2.txt"));
+        assert_eq!(numbered(Path::new("view"), 1), PathBuf::from("view-1"));
+    }
+
+    #[test]
+    fn keys_follow_the_view() {
+        assert_eq!(key(Column::Rx, false), "rx_bps");
+        assert_eq!(key(Column::Rx, true), "rx_bytes");
+        assert_eq!(key(Column::Avg5m, true), "avg_5m_bps");
+    }
+}
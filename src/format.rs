@@ -0,0 +1,298 @@
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnitStandard {
+    // powers of 1024, KiB/s
+    Iec,
+    // powers of 1000, kB/s
+    Si,
+}
+
+impl UnitStandard {
+    pub fn base(self) -> f64 {
+        match self {
+            UnitStandard::Iec => 1024.0,
+            UnitStandard::Si => 1000.0,
+        }
+    }
+
+    pub fn byte_units(self) -> &'static [&'static str] {
+        match self {
+            UnitStandard::Iec => &["B/s", "KiB/s", "MiB/s", "GiB/s", "TiB/s"],
+            UnitStandard::Si => &["B/s", "kB/s", "MB/s", "GB/s", "TB/s"],
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            UnitStandard::Iec => "IEC",
+            UnitStandard::Si => "SI",
+        }
+    }
+}
+
+// between groups of three digits in raw counters; the same whatever the locale
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum DigitSeparator {
+    Comma,
+    Underscore,
+    // U+2009, reads as a gap without looking like a decimal point anywhere
+    Thin,
+    None,
+}
+
+impl DigitSeparator {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DigitSeparator::Comma => ",",
+            DigitSeparator::Underscore => "_",
+            DigitSeparator::Thin => "\u{2009}",
+            DigitSeparator::None => "",
+        }
+    }
+}
+
+// 48211093421 -> "48,211,093,421"
+pub fn group_digits(n: u64, sep: DigitSeparator) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() * 2);
+    for (i, d) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push_str(sep.as_str());
+        }
+        out.push(d);
+    }
+    out
+}
+
+const BIT_UNITS: &[&str] = &["b/s", "Kb/s", "Mb/s", "Gb/s", "Tb/s"];
+
+#[derive(Clone, Copy, Debug)]
+pub struct FormatOptions {
+    pub standard: UnitStandard,
+    // network-style bit units, always powers of 1000
+    pub bits: bool,
+    // decimals for values below 100; the base unit and anything >= 100 stay whole
+    pub precision: usize,
+    // always `precision` decimals, padded so columns don't jitter between refreshes
+    pub fixed: bool,
+    pub separator: DigitSeparator,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions { standard: UnitStandard::Iec, bits: false, precision: 1, fixed: true, separator: DigitSeparator::Comma }
+    }
+}
+
+fn decimals(v: f64, unit: usize, opts: &FormatOptions) -> usize {
+    if unit == 0 {
+        return 0;
+    }
+    let whole_from = 100.0 - 0.5 / 10f64.powi(opts.precision as i32);
+    if !opts.fixed && v >= whole_from { 0 } else { opts.precision }
+}
+
+// widest number a unit can hold before promotion ("1023.9"), used for fixed-width padding
+pub fn number_width(precision: usize) -> usize {
+    if precision == 0 { 4 } else { 5 + precision }
+}
+
+// the unit is picked after rounding, so 999.9 B/s in SI reads "1.0 kB/s" rather than "1000 B/s";
+// values past the last unit just grow digits
+fn scale(value: f64, base: f64, units: &[&str], opts: &FormatOptions) -> String {
+    let mut v = value;
+    let mut i = 0;
+    while i < units.len() - 1 {
+        let p = 10f64.powi(decimals(v, i, opts) as i32);
+        if (v * p).round() / p < base {
+            break;
+        }
+        v /= base;
+        i += 1;
+    }
+    if opts.fixed {
+        let unit_width = units.iter().map(|u| u.len()).max().unwrap_or(0);
+        format!(
+            "{:>w$.*} {:<uw$}",
+            decimals(v, i, opts),
+            v,
+            units[i],
+            w = number_width(opts.precision),
+            uw = unit_width
+        )
+    } else {
+        format!("{:.*} {}", decimals(v, i, opts), v, units[i])
+    }
+}
+
+pub fn format_rate(bps: f64, opts: &FormatOptions) -> String {
+    if bps < 1.0 { return "--".to_string(); } // show --
+    if opts.bits {
+        scale(bps * 8.0, 1000.0, BIT_UNITS, opts)
+    } else {
+        scale(bps, opts.standard.base(), opts.standard.byte_units(), opts)
+    }
+}
+
+pub fn human_pps(pps: f64, fixed: bool) -> String {
+    if pps < 0.05 { return "--".to_string(); }
+
+    let units = ["/s", "k/s", "M/s", "G/s"];
+    let mut v = pps;
+    let mut i = 0;
+    while v >= 999.95 && i < units.len() - 1 {
+        v /= 1000.0;
+        i += 1;
+    }
+
+    if fixed {
+        return format!("{:>5.1}{:<3}", v, units[i]);
+    }
+    if i == 0 && v >= 10.0 {
+        format!("{:.0}{}", v, units[i])
+    } else {
+        format!("{:.1}{}", v, units[i])
+    }
+}
+
+// events per second with a unit suffix, "512 pps", "12.4 Kpps", "1.2 Mpps"; promoted by
+// powers of 1000 after rounding like `scale`, so 999.96 reads "1.0 Kpps"
+pub fn human_count_rate(per_sec: f64, unit: &str, fixed: bool) -> String {
+    if per_sec < 0.05 {
+        return "--".to_string();
+    }
+    let prefixes = ["", "K", "M", "G"];
+    let mut v = per_sec;
+    let mut i = 0;
+    while v >= 999.95 && i < prefixes.len() - 1 {
+        v /= 1000.0;
+        i += 1;
+    }
+    let decimals = if !fixed && i == 0 && v >= 10.0 { 0 } else { 1 };
+    if fixed {
+        format!("{v:>5.1} {:<w$}", format!("{}{unit}", prefixes[i]), w = unit.len() + 1)
+    } else {
+        format!("{v:.decimals$} {}{unit}", prefixes[i])
+    }
+}
+
+// "500k", "1M", "1.5MB", "2MiB/s" -> bytes/s; k/M/G/T are powers of 1000, Ki/Mi/Gi/Ti of 1024
+pub fn parse_rate(s: &str) -> Result<f64, String> {
+    let t = s.trim();
+    let t = t.strip_suffix("/s").unwrap_or(t);
+    let t = t.strip_suffix(['B', 'b']).unwrap_or(t);
+    let (t, base) = match t.strip_suffix('i') {
+        Some(rest) => (rest, 1024f64),
+        None => (t, 1000f64),
+    };
+    let (num, exp) = match t.chars().last() {
+        Some('k' | 'K') => (&t[..t.len() - 1], 1),
+        Some('M') => (&t[..t.len() - 1], 2),
+        Some('G') => (&t[..t.len() - 1], 3),
+        Some('T') => (&t[..t.len() - 1], 4),
+        _ => (t, 0),
+    };
+    let v: f64 = num.trim().parse().map_err(|_| format!("invalid rate '{s}', expected e.g. 500k, 1M, 2MiB"))?;
+    if v < 0.0 {
+        return Err(format!("rate '{s}' must not be negative"));
+    }
+    Ok(v * base.powi(exp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_rate_table() {
+        use UnitStandard::{Iec, Si};
+        let opts = |standard, bits, precision| FormatOptions { standard, bits, precision, fixed: false, ..Default::default() };
+        let cases = [
+            (0.5, opts(Si, false, 1), "--"),
+            (0.0, opts(Iec, true, 1), "--"),
+            (999.4, opts(Si, false, 1), "999 B/s"),
+            (999.9, opts(Si, false, 1), "1.0 kB/s"),
+            (1000.0, opts(Si, false, 1), "1.0 kB/s"),
+            (1000.0, opts(Iec, false, 1), "1000 B/s"),
+            (1023.6, opts(Iec, false, 1), "1.0 KiB/s"),
+            (1024.0, opts(Iec, false, 1), "1.0 KiB/s"),
+            (1024.0, opts(Si, false, 1), "1.0 kB/s"),
+            (1536.0, opts(Iec, false, 2), "1.50 KiB/s"),
+            (1536.0, opts(Iec, false, 0), "2 KiB/s"),
+            (99.96 * 1024.0, opts(Iec, false, 1), "100 KiB/s"),
+            (1024f64.powi(2), opts(Iec, false, 1), "1.0 MiB/s"),
+            (12.5e9, opts(Si, false, 1), "12.5 GB/s"),
+            (2.5 * 1024f64.powi(4), opts(Iec, false, 1), "2.5 TiB/s"),
+            (1e12, opts(Si, false, 1), "1.0 TB/s"),
+            (5e15, opts(Si, false, 1), "5000 TB/s"),
+            (125.0, opts(Iec, true, 1), "1.0 Kb/s"),
+            (12.5e6, opts(Iec, true, 1), "100 Mb/s"),
+            (12.5e9, opts(Si, true, 1), "100 Gb/s"),
+            (1.25e12, opts(Si, true, 1), "10.0 Tb/s"),
+        ];
+        for (bps, o, want) in cases {
+            assert_eq!(format_rate(bps, &o), want, "{bps} with {o:?}");
+        }
+    }
+
+    #[test]
+    fn fixed_width_rates_line_up() {
+        let opts = FormatOptions::default();
+        let formatted: Vec<String> = [512.0, 1536.0, 99.99 * 1024.0, 1023.9 * 1024.0, 3.0 * 1024f64.powi(3)]
+            .iter()
+            .map(|bps| format_rate(*bps, &opts))
+            .collect();
+        assert_eq!(formatted, ["   512 B/s  ", "   1.5 KiB/s", " 100.0 KiB/s", "1023.9 KiB/s", "   3.0 GiB/s"]);
+    }
+
+    #[test]
+    fn grouped_digits() {
+        use DigitSeparator::{Comma, None, Thin, Underscore};
+        assert_eq!(group_digits(0, Comma), "0");
+        assert_eq!(group_digits(999, Comma), "999");
+        assert_eq!(group_digits(1000, Comma), "1,000");
+        assert_eq!(group_digits(48_211_093_421, Comma), "48,211,093,421");
+        assert_eq!(group_digits(123_456, Underscore), "123_456");
+        assert_eq!(group_digits(1_234_567, Thin), "1\u{2009}234\u{2009}567");
+        assert_eq!(group_digits(1_234_567, None), "1234567");
+        assert_eq!(group_digits(u64::MAX, Comma), "18,446,744,073,709,551,615");
+    }
+
+    #[test]
+    fn count_rates() {
+        let cases = [
+            (0.0, "--"),
+            (0.04, "--"),
+            (0.5, "0.5 pps"),
+            (9.94, "9.9 pps"),
+            (512.0, "512 pps"),
+            (999.4, "999 pps"),
+            (999.96, "1.0 Kpps"),
+            (1000.0, "1.0 Kpps"),
+            (12_400.0, "12.4 Kpps"),
+            (999_949.0, "999.9 Kpps"),
+            (999_950.0, "1.0 Mpps"),
+            (1.2e6, "1.2 Mpps"),
+            (3.5e9, "3.5 Gpps"),
+            (5e12, "5000.0 Gpps"),
+        ];
+        for (v, want) in cases {
+            assert_eq!(human_count_rate(v, "pps", false), want, "{v}");
+        }
+        assert_eq!(human_count_rate(512.0, "pps", true), "512.0 pps ");
+        assert_eq!(human_count_rate(12_400.0, "pps", true), " 12.4 Kpps");
+        assert_eq!(human_count_rate(0.0, "pps", true), "--");
+    }
+
+    #[test]
+    fn parse_rate_units() {
+        assert_eq!(parse_rate("500"), Ok(500.0));
+        assert_eq!(parse_rate("500k"), Ok(500e3));
+        assert_eq!(parse_rate("1.5MB"), Ok(1.5e6));
+        assert_eq!(parse_rate("2MiB/s"), Ok(2.0 * 1024.0 * 1024.0));
+        assert_eq!(parse_rate("1G"), Ok(1e9));
+        assert!(parse_rate("fast").is_err());
+        assert!(parse_rate("-1M").is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use ratatui::symbols::border;
+
+// every non-letter symbol the UI draws; --ascii swaps the whole set for serial consoles
+// and terminals that mangle box drawing and block elements
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyphs {
+    pub ascii: bool,
+    pub border: border::Set,
+    // sparkline levels, lowest first
+    pub bars: &'static [char],
+    // the utilization gauge: a full cell, then partial cells by eighths
+    pub gauge_full: &'static str,
+    pub gauge_eighths: [&'static str; 8],
+    pub sort_desc: &'static str,
+    pub sort_asc: &'static str,
+    pub pinned: &'static str,
+    // the interface with the default route
+    pub default_route: &'static str,
+    pub folded: &'static str,
+    pub unfolded: &'static str,
+    // a bond or bridge port nested under its master
+    pub member: &'static str,
+    // one column wide, marks text cut short
+    pub ellipsis: &'static str,
+    // between an adapter's name and its description
+    pub dash: &'static str,
+    // "showing 1–20 of 40"
+    pub range: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+    pub left: &'static str,
+    pub right: &'static str,
+}
+
+pub const UNICODE: Glyphs = Glyphs {
+    ascii: false,
+    border: border::ROUNDED,
+    bars: &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'],
+    gauge_full: "█",
+    gauge_eighths: ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"],
+    sort_desc: "▼",
+    sort_asc: "▲",
+    pinned: "★",
+    default_route: "▸",
+    folded: "▸",
+    unfolded: "▾",
+    member: "└",
+    ellipsis: "…",
+    dash: "—",
+    range: "–",
+    up: "↑",
+    down: "↓",
+    left: "←",
+    right: "→",
+};
+
+pub const ASCII: Glyphs = Glyphs {
+    ascii: true,
+    border: border::Set {
+        top_left: "+",
+        top_right: "+",
+        bottom_left: "+",
+        bottom_right: "+",
+        vertical_left: "|",
+        vertical_right: "|",
+        horizontal_top: "-",
+        horizontal_bottom: "-",
+    },
+    bars: &['.', ':', '-', '=', '#'],
+    gauge_full: "#",
+    gauge_eighths: ["", "", "", "", "=", "=", "=", "="],
+    sort_desc: "v",
+    sort_asc: "^",
+    pinned: "*",
+    default_route: ">",
+    folded: ">",
+    unfolded: "v",
+    member: "`-",
+    ellipsis: "~",
+    dash: "-",
+    range: "-",
+    up: "up",
+    down: "down",
+    left: "left",
+    right: "right",
+};
+
+impl Glyphs {
+    pub fn new(ascii: bool) -> &'static Glyphs {
+        if ascii { &ASCII } else { &UNICODE }
+    }
+
+    // stand-ins for what ratatui's chart draws on its own (axes, the legend box, dots);
+    // None when the symbol can stay
+    pub fn fallback(&self, symbol: &str) -> Option<&'static str> {
+        if !self.ascii || symbol.is_ascii() {
+            return None;
+        }
+        Some(match symbol {
+            "│" | "┃" | "║" => "|",
+            "─" | "━" | "═" => "-",
+            "•" => "*",
+            _ => "+",
+        })
+    }
+}
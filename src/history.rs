@@ -0,0 +1,318 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+// samples kept per interface, 5 minutes at the default refresh
+pub const DEFAULT_CAPACITY: usize = 600;
+
+// the rolling average columns, like load averages
+pub const AVERAGE_WINDOWS: [(&str, Duration); 3] =
+    [("1m", Duration::from_secs(60)), ("5m", Duration::from_secs(5 * 60)), ("15m", Duration::from_secs(15 * 60))];
+
+// an interface gone for this long loses its ring; shorter gaps (a flapping link, a
+// container restart) keep it so the sparkline picks up where it left off
+pub const STALE_AFTER: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    pub at: Instant,
+    pub rx: f64,
+    pub tx: f64,
+}
+
+impl Sample {
+    pub fn total(&self) -> f64 {
+        self.rx + self.tx
+    }
+}
+
+// lowest, highest and mean of one direction over a window
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Range {
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+impl Range {
+    fn of(values: impl Iterator<Item = f64>) -> Option<Range> {
+        let (mut min, mut max, mut sum, mut n) = (f64::INFINITY, f64::NEG_INFINITY, 0.0, 0usize);
+        for v in values {
+            min = min.min(v);
+            max = max.max(v);
+            sum += v;
+            n += 1;
+        }
+        (n > 0).then(|| Range { min, max, avg: sum / n as f64 })
+    }
+}
+
+// mean rx+tx over a window; `partial` when the ring doesn't reach back over all of it yet
+// (a new interface, a fresh start), so the mean only covers the span there is
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Average {
+    pub rate: f64,
+    pub partial: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowStats {
+    pub rx: Range,
+    pub tx: Range,
+    pub samples: usize,
+}
+
+// fixed-size ring of recent rates per interface, keyed by name
+pub struct History {
+    capacity: usize,
+    series: HashMap<String, VecDeque<Sample>>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        History { capacity: capacity.max(1), series: HashMap::new() }
+    }
+
+    // grows every ring to hold `capacity` samples; never shrinks, so nothing recorded is lost
+    pub fn reserve(&mut self, capacity: usize) {
+        self.capacity = self.capacity.max(capacity);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // appends a sample, evicting the oldest one once the ring is full
+    pub fn push(&mut self, name: &str, sample: Sample) {
+        let ring = self
+            .series
+            .entry(name.to_string())
+            .or_insert_with(|| VecDeque::with_capacity(self.capacity));
+        if ring.len() == self.capacity {
+            ring.pop_front();
+        }
+        ring.push_back(sample);
+    }
+
+    // oldest first
+    pub fn get(&self, name: &str) -> Option<&VecDeque<Sample>> {
+        self.series.get(name)
+    }
+
+    // samples no older than `window` before `now`, oldest first
+    pub fn window<'a>(&'a self, name: &str, now: Instant, window: Duration) -> impl Iterator<Item = &'a Sample> + 'a {
+        let from = now.checked_sub(window);
+        self.get(name).into_iter().flatten().filter(move |s| from.is_none_or(|from| s.at >= from))
+    }
+
+    // min/max/avg over the last `window`, None without a sample in it
+    pub fn window_stats(&self, name: &str, now: Instant, window: Duration) -> Option<WindowStats> {
+        let samples = self.window(name, now, window).count();
+        Some(WindowStats {
+            rx: Range::of(self.window(name, now, window).map(|s| s.rx))?,
+            tx: Range::of(self.window(name, now, window).map(|s| s.tx))?,
+            samples,
+        })
+    }
+
+    // the oldest sample stands for the time since the one before it, which is gone, so the
+    // gap to its successor stands in; with a single sample the window counts as partial
+    pub fn average(&self, name: &str, now: Instant, window: Duration) -> Option<Average> {
+        let ring = self.get(name)?;
+        let rate = Range::of(self.window(name, now, window).map(Sample::total))?.avg;
+        let oldest = ring.front()?;
+        let step = ring.get(1).map(|next| next.at.saturating_duration_since(oldest.at));
+        let covered = step.map(|step| now.saturating_duration_since(oldest.at) + step);
+        Some(Average { rate, partial: covered.is_none_or(|covered| covered < window) })
+    }
+
+    // drops the rings of interfaces without a sample in the last `max_age`, so hosts that
+    // churn through veth interfaces don't grow the map forever
+    pub fn prune_stale(&mut self, now: Instant, max_age: Duration) {
+        self.series.retain(|_, ring| ring.back().is_some_and(|s| now.saturating_duration_since(s.at) <= max_age));
+    }
+
+    pub fn len(&self) -> usize {
+        self.series.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+
+    // largest rx+tx sample of one interface
+    pub fn max(&self, name: &str) -> f64 {
+        self.get(name)
+            .map(|ring| ring.iter().map(Sample::total).fold(0.0, f64::max))
+            .unwrap_or(0.0)
+    }
+
+    // largest rx+tx sample across every interface
+    pub fn global_max(&self) -> f64 {
+        self.series.keys().map(|name| self.max(name)).fold(0.0, f64::max)
+    }
+}
+
+// sparkline of the newest `width` values, scaled against `max` onto `bars` (lowest level
+// first); left-padded with spaces while the ring is still filling up
+pub fn sparkline<'a>(samples: impl DoubleEndedIterator<Item = &'a Sample>, max: f64, width: usize, bars: &[char]) -> String {
+    let mut line: Vec<char> = samples
+        .rev()
+        .take(width)
+        .map(|s| {
+            if max <= 0.0 || s.total() <= 0.0 {
+                ' '
+            } else {
+                let level = (s.total() / max * (bars.len() - 1) as f64).round() as usize;
+                bars[level.min(bars.len() - 1)]
+            }
+        })
+        .collect();
+    line.resize(width, ' ');
+    line.reverse();
+    line.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glyphs::{ASCII, UNICODE};
+
+    fn s(total: f64) -> Sample {
+        Sample { at: Instant::now(), rx: total, tx: 0.0 }
+    }
+
+    #[test]
+    fn push_evicts_oldest() {
+        let mut h = History::new(3);
+        for v in 1..=5 {
+            h.push("eth0", s(v as f64));
+        }
+        let kept: Vec<f64> = h.get("eth0").unwrap().iter().map(Sample::total).collect();
+        assert_eq!(kept, [3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn wraparound_keeps_order_over_many_laps() {
+        let mut h = History::new(4);
+        for v in 0..1_000 {
+            h.push("eth0", s(v as f64));
+        }
+        let kept: Vec<f64> = h.get("eth0").unwrap().iter().map(Sample::total).collect();
+        assert_eq!(kept, [996.0, 997.0, 998.0, 999.0]);
+        assert_eq!(History::new(0).capacity, 1, "a zero capacity still keeps the newest sample");
+    }
+
+    #[test]
+    fn empty_history() {
+        let h = History::new(3);
+        let now = Instant::now();
+        assert!(h.is_empty());
+        assert!(h.get("eth0").is_none());
+        assert_eq!(h.window("eth0", now, Duration::from_secs(60)).count(), 0);
+        assert_eq!(h.window_stats("eth0", now, Duration::from_secs(60)), None);
+        assert_eq!(h.global_max(), 0.0);
+    }
+
+    #[test]
+    fn window_queries() {
+        let start = Instant::now();
+        let mut h = History::new(10);
+        for (secs, rx, tx) in [(0, 100.0, 0.0), (10, 40.0, 4.0), (20, 10.0, 8.0), (30, 70.0, 6.0)] {
+            h.push("eth0", Sample { at: start + Duration::from_secs(secs), rx, tx });
+        }
+        let now = start + Duration::from_secs(30);
+
+        let last = h.window_stats("eth0", now, Duration::from_secs(20)).unwrap();
+        assert_eq!(last.samples, 3);
+        assert_eq!(last.rx, Range { min: 10.0, max: 70.0, avg: 40.0 });
+        assert_eq!(last.tx, Range { min: 4.0, max: 8.0, avg: 6.0 });
+
+        // a window reaching before the first sample covers all of them
+        assert_eq!(h.window_stats("eth0", now, Duration::from_secs(3600)).unwrap().samples, 4);
+        // nothing recent enough
+        let later = now + Duration::from_secs(60);
+        assert_eq!(h.window_stats("eth0", later, Duration::from_secs(10)), None);
+    }
+
+    // one sample a second, rx = the second it was taken at
+    fn seconds(h: &mut History, start: Instant, range: std::ops::Range<u64>) {
+        for secs in range {
+            h.push("eth0", Sample { at: start + Duration::from_secs(secs), rx: secs as f64, tx: 1.0 });
+        }
+    }
+
+    #[test]
+    fn averages_over_a_young_ring_are_partial() {
+        let start = Instant::now();
+        let mut h = History::new(100);
+        let minute = Duration::from_secs(60);
+        assert_eq!(h.average("eth0", start, minute), None);
+        seconds(&mut h, start, 0..1);
+        assert_eq!(h.average("eth0", start, minute), Some(Average { rate: 1.0, partial: true }));
+
+        seconds(&mut h, start, 1..30);
+        let now = start + Duration::from_secs(29);
+        // 0..=29 averages 14.5, plus 1 tx each
+        assert_eq!(h.average("eth0", now, minute), Some(Average { rate: 15.5, partial: true }));
+
+        seconds(&mut h, start, 30..61);
+        let now = start + Duration::from_secs(60);
+        // a full minute back, both ends included: 0..=60
+        assert_eq!(h.average("eth0", now, minute), Some(Average { rate: 31.0, partial: false }));
+        assert!(h.average("eth0", now, Duration::from_secs(5 * 60)).unwrap().partial);
+    }
+
+    #[test]
+    fn averages_after_the_ring_wraps() {
+        let start = Instant::now();
+        let mut h = History::new(90);
+        seconds(&mut h, start, 0..1000);
+        let now = start + Duration::from_secs(999);
+        // 940..=999, the laps before are long evicted
+        assert_eq!(h.average("eth0", now, Duration::from_secs(59)).unwrap().rate, 970.5);
+        assert!(!h.average("eth0", now, Duration::from_secs(60)).unwrap().partial);
+        // the ring only reaches back 90 samples, so five minutes is a partial 910..=999
+        let five = h.average("eth0", now, Duration::from_secs(5 * 60)).unwrap();
+        assert_eq!(five, Average { rate: 955.5, partial: true });
+
+        // growing the ring keeps what is there and makes room for more
+        h.reserve(400);
+        seconds(&mut h, start, 1000..1300);
+        let now = start + Duration::from_secs(1299);
+        assert_eq!(h.get("eth0").unwrap().len(), 390);
+        assert!(!h.average("eth0", now, Duration::from_secs(5 * 60)).unwrap().partial);
+        h.reserve(10);
+        assert_eq!(h.capacity(), 400);
+    }
+
+    #[test]
+    fn prune_drops_only_stale_interfaces() {
+        let start = Instant::now();
+        let mut h = History::new(3);
+        h.push("eth0", Sample { at: start + Duration::from_secs(400), rx: 1.0, tx: 0.0 });
+        h.push("veth1", Sample { at: start, rx: 1.0, tx: 0.0 });
+        h.push("veth2", Sample { at: start + Duration::from_secs(200), rx: 1.0, tx: 0.0 });
+        h.prune_stale(start + Duration::from_secs(400), STALE_AFTER);
+        assert!(h.get("eth0").is_some());
+        assert!(h.get("veth1").is_none(), "gone for more than five minutes");
+        assert!(h.get("veth2").is_some(), "a short gap keeps the ring");
+        assert_eq!(h.len(), 2);
+    }
+
+    #[test]
+    fn row_and_global_scale() {
+        let mut h = History::new(4);
+        h.push("eth0", Sample { at: Instant::now(), rx: 10.0, tx: 30.0 });
+        h.push("eth0", s(5.0));
+        h.push("wlan0", s(100.0));
+        assert_eq!(h.max("eth0"), 40.0);
+        assert_eq!(h.global_max(), 100.0);
+        assert_eq!(h.max("gone0"), 0.0);
+
+        let ring = h.get("eth0").unwrap();
+        assert_eq!(sparkline(ring.iter(), h.max("eth0"), 4, UNICODE.bars), "  █▂");
+        assert_eq!(sparkline(ring.iter(), h.global_max(), 4, UNICODE.bars), "  ▄▁");
+        assert_eq!(sparkline(ring.iter(), h.max("eth0"), 1, UNICODE.bars), "▂");
+        assert_eq!(sparkline(ring.iter(), h.max("eth0"), 4, ASCII.bars), "  #:");
+    }
+}
@@ -0,0 +1,93 @@
+// the machine nettui runs on, for the header's top border: with several terminals open the
+// hostname is what tells them apart. Read once at startup, none of it changes
+use std::process::Command;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Host {
+    pub name: String,
+    // "Debian GNU/Linux 12 (bookworm)", "macOS 14.5"
+    pub os: String,
+    // the kernel release, empty where there's no cheap way to ask
+    pub kernel: String,
+}
+
+impl Host {
+    #[cfg(target_os = "linux")]
+    pub fn read() -> Self {
+        let file = |path| std::fs::read_to_string(path).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        Host {
+            name: file("/proc/sys/kernel/hostname").or_else(|| output("hostname", &[])).unwrap_or_default(),
+            os: file("/etc/os-release")
+                .or_else(|| file("/usr/lib/os-release"))
+                .and_then(|text| parse_os_release(&text))
+                .unwrap_or_else(|| "Linux".to_string()),
+            kernel: file("/proc/sys/kernel/osrelease").unwrap_or_default(),
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn read() -> Self {
+        Host {
+            name: output("scutil", &["--get", "ComputerName"]).or_else(|| output("hostname", &[])).unwrap_or_default(),
+            os: output("sw_vers", &["-productVersion"]).map_or_else(|| "macOS".to_string(), |v| format!("macOS {v}")),
+            kernel: output("uname", &["-r"]).unwrap_or_default(),
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn read() -> Self {
+        Host {
+            name: std::env::var("COMPUTERNAME").ok().or_else(|| output("hostname", &[])).unwrap_or_default(),
+            os: "Windows".to_string(),
+            kernel: String::new(),
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    pub fn read() -> Self {
+        Host {
+            name: output("hostname", &[]).unwrap_or_default(),
+            os: output("uname", &["-s"]).unwrap_or_else(|| std::env::consts::OS.to_string()),
+            kernel: output("uname", &["-r"]).unwrap_or_default(),
+        }
+    }
+}
+
+// first line of a command's output, None when it can't run or prints nothing
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let out = Command::new(program).args(args).output().ok().filter(|out| out.status.success())?;
+    let text = String::from_utf8_lossy(&out.stdout);
+    text.lines().next().map(|line| line.trim().to_string()).filter(|line| !line.is_empty())
+}
+
+// PRETTY_NAME, or NAME and VERSION_ID when a distribution leaves it out
+pub fn parse_os_release(text: &str) -> Option<String> {
+    let field = |key: &str| {
+        text.lines()
+            .filter_map(|line| line.split_once('='))
+            .find(|(k, _)| k.trim() == key)
+            .map(|(_, v)| v.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+            .filter(|v| !v.is_empty())
+    };
+    field("PRETTY_NAME").or_else(|| {
+        let name = field("NAME")?;
+        Some(match field("VERSION_ID") {
+            Some(version) => format!("{name} {version}"),
+            None => name,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn os_release() {
+        let debian = "PRETTY_NAME=\"Debian GNU/Linux 12 (bookworm)\"\nNAME=\"Debian GNU/Linux\"\nVERSION_ID=\"12\"\n";
+        assert_eq!(parse_os_release(debian).as_deref(), Some("Debian GNU/Linux 12 (bookworm)"));
+        assert_eq!(parse_os_release("NAME=Alpine Linux\nVERSION_ID=3.20.1\n").as_deref(), Some("Alpine Linux 3.20.1"));
+        assert_eq!(parse_os_release("NAME='Void'\nPRETTY_NAME=\"\"\n").as_deref(), Some("Void"));
+        assert_eq!(parse_os_release("# nothing here\n"), None);
+    }
+}
@@ -0,0 +1,325 @@
+// what kind of device an interface is, from what sysfs says about it and, failing that,
+// its name; classify is pure so fixtures can stand in for /sys, and the name tables are per
+// OS so macOS names can be checked on any machine
+use crate::net::glob_match;
+
+// ARPHRD_* values from the kernel's `type` file
+const ARPHRD_ETHER: u16 = 1;
+const ARPHRD_LOOPBACK: u16 = 772;
+const ARPHRD_NONE: u16 = 65534;
+
+// everything read about one interface under /sys/class/net; all empty off Linux or when
+// the interface isn't there (another namespace, gone since it was listed)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Probe {
+    // /sys/class/net/<iface> exists
+    pub present: bool,
+    pub arphrd: Option<u16>,
+    // DEVTYPE= from uevent: wlan, bridge, bond, vlan, wireguard, ...
+    pub devtype: Option<String>,
+    // a `device` link, only hardware has one
+    pub device: bool,
+    pub bridge: bool,
+    pub bonding: bool,
+    pub wireless: bool,
+    // a tun_flags file, tun and tap devices have one
+    pub tun: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IfaceKind {
+    Ethernet,
+    Wifi,
+    Loopback,
+    Bridge,
+    Bond,
+    Vlan,
+    Tun,
+    WireGuard,
+    // macOS utun and friends, where VPN clients put their traffic
+    Vpn,
+    // macOS's AirDrop, low-latency WLAN, Thunderbolt and Internet Sharing interfaces: always
+    // there, rarely carrying anything of interest
+    Auxiliary,
+    // veth pairs and docker's own interfaces
+    Container,
+    #[default]
+    Unknown,
+}
+
+impl IfaceKind {
+    // short enough for a narrow TYPE column
+    pub fn label(self) -> &'static str {
+        match self {
+            IfaceKind::Ethernet => "ether",
+            IfaceKind::Wifi => "wifi",
+            IfaceKind::Loopback => "lo",
+            IfaceKind::Bridge => "bridge",
+            IfaceKind::Bond => "bond",
+            IfaceKind::Vlan => "vlan",
+            IfaceKind::Tun => "tun",
+            IfaceKind::WireGuard => "wg",
+            IfaceKind::Vpn => "vpn",
+            IfaceKind::Auxiliary => "aux",
+            IfaceKind::Container => "veth",
+            IfaceKind::Unknown => "?",
+        }
+    }
+
+    // hidden until 'i': plumbing that mirrors traffic counted elsewhere. Bonds, VLANs and
+    // tunnels like WireGuard carry real traffic of their own and stay visible. None when
+    // the kind doesn't say
+    pub fn is_virtual(self) -> Option<bool> {
+        match self {
+            IfaceKind::Loopback | IfaceKind::Bridge | IfaceKind::Tun | IfaceKind::Container | IfaceKind::Auxiliary => {
+                Some(true)
+            }
+            IfaceKind::Ethernet
+            | IfaceKind::Wifi
+            | IfaceKind::Bond
+            | IfaceKind::Vlan
+            | IfaceKind::WireGuard
+            | IfaceKind::Vpn => Some(false),
+            IfaceKind::Unknown => None,
+        }
+    }
+
+    pub fn group(self) -> Group {
+        match self {
+            IfaceKind::Ethernet => Group::Physical,
+            IfaceKind::Wifi => Group::Wireless,
+            IfaceKind::Bond | IfaceKind::Vlan => Group::Aggregates,
+            IfaceKind::Tun | IfaceKind::WireGuard | IfaceKind::Vpn => Group::Tunnels,
+            IfaceKind::Bridge => Group::Bridges,
+            IfaceKind::Container => Group::Containers,
+            IfaceKind::Loopback => Group::Loopback,
+            IfaceKind::Auxiliary | IfaceKind::Unknown => Group::Other,
+        }
+    }
+}
+
+// sections of the grouped table ('t'), listed in this order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Group {
+    Physical,
+    Wireless,
+    Aggregates,
+    Tunnels,
+    Bridges,
+    Containers,
+    Loopback,
+    Other,
+}
+
+impl Group {
+    pub const ALL: [Group; 8] = [
+        Group::Physical,
+        Group::Wireless,
+        Group::Aggregates,
+        Group::Tunnels,
+        Group::Bridges,
+        Group::Containers,
+        Group::Loopback,
+        Group::Other,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Group::Physical => "Physical",
+            Group::Wireless => "Wi-Fi",
+            Group::Aggregates => "Bonds & VLANs",
+            Group::Tunnels => "Tunnels",
+            Group::Bridges => "Bridges",
+            Group::Containers => "Containers",
+            Group::Loopback => "Loopback",
+            Group::Other => "Other",
+        }
+    }
+}
+
+// whose interface names to go by when sysfs has nothing to say
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Os {
+    Linux,
+    Darwin,
+}
+
+impl Os {
+    // everything that isn't macOS gets Linux's names, the closest there is
+    pub fn current() -> Os {
+        if cfg!(target_os = "macos") { Os::Darwin } else { Os::Linux }
+    }
+
+    // first matching glob wins
+    fn names(self) -> &'static [(&'static str, IfaceKind)] {
+        match self {
+            Os::Linux => LINUX_NAMES,
+            Os::Darwin => DARWIN_NAMES,
+        }
+    }
+}
+
+const LINUX_NAMES: &[(&str, IfaceKind)] = &[
+    ("lo", IfaceKind::Loopback),
+    ("veth*", IfaceKind::Container),
+    ("docker*", IfaceKind::Container),
+    ("wg*", IfaceKind::WireGuard),
+    ("tun*", IfaceKind::Tun),
+    ("tap*", IfaceKind::Tun),
+    ("br-*", IfaceKind::Bridge),
+    ("virbr*", IfaceKind::Bridge),
+    ("bridge*", IfaceKind::Bridge),
+    ("bond*", IfaceKind::Bond),
+    ("wlan*", IfaceKind::Wifi),
+    ("wlp*", IfaceKind::Wifi),
+    ("wlo*", IfaceKind::Wifi),
+];
+
+// en* is Ethernet and Wi-Fi alike here; the service names tell them apart
+const DARWIN_NAMES: &[(&str, IfaceKind)] = &[
+    ("lo*", IfaceKind::Loopback),
+    ("awdl*", IfaceKind::Auxiliary),
+    ("llw*", IfaceKind::Auxiliary),
+    ("anpi*", IfaceKind::Auxiliary),
+    ("ap*", IfaceKind::Auxiliary),
+    ("gif*", IfaceKind::Auxiliary),
+    ("stf*", IfaceKind::Auxiliary),
+    // Internet Sharing and virtual machines get bridge100 and up
+    ("bridge1??", IfaceKind::Auxiliary),
+    ("bridge*", IfaceKind::Bridge),
+    ("utun*", IfaceKind::Vpn),
+    ("ipsec*", IfaceKind::Vpn),
+    ("ppp*", IfaceKind::Vpn),
+    ("vlan*", IfaceKind::Vlan),
+    ("bond*", IfaceKind::Bond),
+    ("en*", IfaceKind::Ethernet),
+];
+
+pub fn classify(name: &str, probe: &Probe) -> IfaceKind {
+    classify_on(Os::current(), name, probe)
+}
+
+// sysfs facts win; the name only decides what they leave open, and "eth*" only counts as
+// Ethernet when sysfs isn't there to say it's really a veth inside a container
+pub fn classify_on(os: Os, name: &str, probe: &Probe) -> IfaceKind {
+    match probe.devtype.as_deref() {
+        Some("wlan") => return IfaceKind::Wifi,
+        Some("bridge") => return IfaceKind::Bridge,
+        Some("bond") => return IfaceKind::Bond,
+        Some("vlan") => return IfaceKind::Vlan,
+        Some("wireguard") => return IfaceKind::WireGuard,
+        _ => {}
+    }
+    if probe.wireless {
+        return IfaceKind::Wifi;
+    }
+    if probe.bridge {
+        return IfaceKind::Bridge;
+    }
+    if probe.bonding {
+        return IfaceKind::Bond;
+    }
+    if probe.tun {
+        return IfaceKind::Tun;
+    }
+    if probe.arphrd == Some(ARPHRD_LOOPBACK) {
+        return IfaceKind::Loopback;
+    }
+    if probe.arphrd == Some(ARPHRD_ETHER) && probe.device {
+        return IfaceKind::Ethernet;
+    }
+
+    if let Some(&(_, kind)) = os.names().iter().find(|(pattern, _)| glob_match(pattern, name)) {
+        kind
+    } else if probe.present && probe.arphrd == Some(ARPHRD_NONE) {
+        IfaceKind::Tun
+    } else if name.contains('.') {
+        // eth0.100
+        IfaceKind::Vlan
+    } else if !probe.present && (name.starts_with("eth") || name.starts_with("en")) {
+        IfaceKind::Ethernet
+    } else {
+        IfaceKind::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Linux's names, whatever this runs on
+    fn classify(name: &str, probe: &Probe) -> IfaceKind {
+        classify_on(Os::Linux, name, probe)
+    }
+
+    fn sys(arphrd: u16) -> Probe {
+        Probe { present: true, arphrd: Some(arphrd), ..Default::default() }
+    }
+
+    #[test]
+    fn sysfs_facts() {
+        let nic = Probe { device: true, ..sys(ARPHRD_ETHER) };
+        assert_eq!(classify("enp3s0", &nic), IfaceKind::Ethernet);
+        assert_eq!(classify("wlp2s0", &Probe { wireless: true, ..nic.clone() }), IfaceKind::Wifi);
+        assert_eq!(classify("wlan0", &Probe { devtype: Some("wlan".to_string()), ..nic.clone() }), IfaceKind::Wifi);
+        assert_eq!(classify("lo", &sys(ARPHRD_LOOPBACK)), IfaceKind::Loopback);
+        assert_eq!(classify("docker0", &Probe { bridge: true, ..sys(ARPHRD_ETHER) }), IfaceKind::Bridge);
+        assert_eq!(classify("uplink", &Probe { bonding: true, ..sys(ARPHRD_ETHER) }), IfaceKind::Bond);
+        assert_eq!(classify("mgmt", &Probe { devtype: Some("vlan".to_string()), ..sys(ARPHRD_ETHER) }), IfaceKind::Vlan);
+        assert_eq!(classify("vpn", &Probe { devtype: Some("wireguard".to_string()), ..sys(ARPHRD_NONE) }), IfaceKind::WireGuard);
+        assert_eq!(classify("tap0", &Probe { tun: true, ..sys(ARPHRD_ETHER) }), IfaceKind::Tun);
+        assert_eq!(classify("ppp-ish", &sys(ARPHRD_NONE)), IfaceKind::Tun);
+    }
+
+    #[test]
+    fn names_fill_the_gaps() {
+        let none = Probe::default();
+        assert_eq!(classify("eth0", &none), IfaceKind::Ethernet);
+        assert_eq!(classify("veth1a2b", &none), IfaceKind::Container);
+        assert_eq!(classify("wg0", &none), IfaceKind::WireGuard);
+        assert_eq!(classify("eth0.100", &none), IfaceKind::Vlan);
+        assert_eq!(classify("br-9f8e7d", &none), IfaceKind::Bridge);
+        assert_eq!(classify("en0", &none), IfaceKind::Ethernet);
+        assert_eq!(classify("awdl0", &none), IfaceKind::Unknown);
+        // inside a container eth0 is one end of a veth pair: no device link
+        assert_eq!(classify("eth0", &sys(ARPHRD_ETHER)), IfaceKind::Unknown);
+        assert_eq!(classify("veth9", &sys(ARPHRD_ETHER)), IfaceKind::Container);
+    }
+
+    // names as `ifconfig -l` lists them on a MacBook with a USB adapter, a VPN up and
+    // Internet Sharing on
+    #[test]
+    fn darwin_names() {
+        let none = Probe::default();
+        let kinds: Vec<_> = "lo0 gif0 stf0 anpi0 anpi1 en0 en5 ap1 awdl0 llw0 bridge0 bridge100 utun0 utun3 ipsec0"
+            .split(' ')
+            .map(|name| classify_on(Os::Darwin, name, &none).label())
+            .collect();
+        assert_eq!(kinds, ["lo", "aux", "aux", "aux", "aux", "ether", "ether", "aux", "aux", "aux", "bridge", "aux", "vpn", "vpn", "vpn"]);
+
+        let hidden: Vec<_> = "lo0 awdl0 llw0 anpi0 bridge101 en0 utun2"
+            .split(' ')
+            .filter(|name| classify_on(Os::Darwin, name, &none).is_virtual() == Some(true))
+            .collect();
+        assert_eq!(hidden, ["lo0", "awdl0", "llw0", "anpi0", "bridge101"], "utun stays in sight");
+    }
+
+    // the same names mean nothing special on Linux
+    #[test]
+    fn linux_names() {
+        let none = Probe::default();
+        let kinds: Vec<_> = "lo eth0 enp3s0 wlan0 docker0 virbr0 tap0 wg0 bond0 eth0.100 utun3 awdl0"
+            .split(' ')
+            .map(|name| classify_on(Os::Linux, name, &none).label())
+            .collect();
+        assert_eq!(kinds, ["lo", "ether", "ether", "wifi", "veth", "bridge", "tun", "wg", "bond", "vlan", "?", "?"]);
+    }
+
+    #[test]
+    fn virtual_kinds() {
+        assert_eq!(IfaceKind::Container.is_virtual(), Some(true));
+        assert_eq!(IfaceKind::Bond.is_virtual(), Some(false));
+        assert_eq!(IfaceKind::WireGuard.is_virtual(), Some(false));
+        assert_eq!(IfaceKind::Unknown.is_virtual(), None);
+    }
+}
@@ -0,0 +1,328 @@
+// --influx: every sample as InfluxDB line protocol, POSTed to /api/v2/write (InfluxDB 2, or
+// 1.8+ through its compatibility endpoint) from a thread of its own. Batches wait in a short
+// queue; when the server is slow or gone the newest ones are dropped and counted rather than
+// piling up, and sampling never waits on the network
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::json::Json;
+use crate::net::RowData;
+use crate::snapshot::SnapshotRow;
+
+// batches waiting to be sent, a minute's worth at the default interval
+const QUEUE: usize = 120;
+// tries per batch, and the pause before each retry
+const ATTEMPTS: usize = 3;
+const RETRY: [Duration; ATTEMPTS - 1] = [Duration::from_secs(1), Duration::from_secs(4)];
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+// where the writes go: host:port to connect to, and the request path with its query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub host: String,
+    pub path: String,
+}
+
+// http://host[:port][/prefix], the port 8086 unless given; there's no TLS here, https wants a
+// proxy in front
+pub fn target(url: &str, org: Option<&str>, bucket: &str) -> Result<Target, String> {
+    let rest = match url.split_once("://") {
+        Some((scheme, rest)) if scheme.eq_ignore_ascii_case("http") => rest,
+        Some((scheme, _)) => return Err(format!("{scheme}:// isn't supported, only http://")),
+        None => url,
+    };
+    let (authority, prefix) = match rest.find('/') {
+        Some(i) => (&rest[..i], rest[i..].trim_end_matches('/')),
+        None => (rest, ""),
+    };
+    if authority.is_empty() {
+        return Err(format!("no host in {url}"));
+    }
+    // a port is after the last colon, unless that colon is inside an IPv6 [address]
+    let has_port = authority.rfind(':').is_some_and(|i| authority.rfind(']').is_none_or(|j| i > j));
+    let host = if has_port { authority.to_string() } else { format!("{authority}:8086") };
+    let mut path = format!("{prefix}/api/v2/write?bucket={}&precision=ns", query_value(bucket));
+    if let Some(org) = org {
+        let _ = write!(path, "&org={}", query_value(org));
+    }
+    Ok(Target { host, path })
+}
+
+// percent-encoding for a query string value
+fn query_value(s: &str) -> String {
+    let mut out = String::new();
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            _ => {
+                let _ = write!(out, "%{b:02X}");
+            }
+        }
+    }
+    out
+}
+
+// tag keys, tag values and field keys: commas, equals signs and spaces take a backslash.
+// Line protocol has no escape for a line break, so control characters become '_'
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            ',' | '=' | ' ' => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if c.is_control() => out.push('_'),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// one line per interface: `net,iface=eth0 rx_bps=2048,...,rx_bytes_total=4096i <ns>`; rates
+// are floats, totals integers. A rate that isn't finite is left out, the format has no NaN
+pub fn lines(rows: &[SnapshotRow]) -> String {
+    let mut out = String::new();
+    for r in rows {
+        let floats = [
+            ("rx_bps", r.rx_bps),
+            ("tx_bps", r.tx_bps),
+            ("rx_pps", r.rx_pps),
+            ("tx_pps", r.tx_pps),
+            ("rx_errors_ps", r.rx_errors_ps),
+            ("tx_errors_ps", r.tx_errors_ps),
+        ];
+        let mut fields: Vec<String> = floats.iter().filter(|(_, v)| v.is_finite()).map(|(k, v)| format!("{k}={v}")).collect();
+        fields.push(format!("rx_bytes_total={}i", r.rx_bytes_total));
+        fields.push(format!("tx_bytes_total={}i", r.tx_bytes_total));
+        // through whole microseconds, an f64 of unix seconds holds no more than that
+        let ns = (r.timestamp * 1e6).round() as i64 * 1000;
+        let _ = writeln!(out, "net,iface={} {} {ns}", escape(&r.interface), fields.join(","));
+    }
+    out
+}
+
+// what the status bar shows: the last failure until a write gets through again, and how many
+// batches never made it
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Status {
+    pub error: Option<String>,
+    pub dropped: u64,
+}
+
+impl Status {
+    pub fn warning(&self) -> Option<String> {
+        let dropped = match self.dropped {
+            0 => String::new(),
+            1 => "1 batch dropped".to_string(),
+            n => format!("{n} batches dropped"),
+        };
+        match (&self.error, dropped.is_empty()) {
+            (None, true) => None,
+            (None, false) => Some(format!("influx: {dropped}")),
+            (Some(e), true) => Some(format!("influx: {e}")),
+            (Some(e), false) => Some(format!("influx: {e}, {dropped}")),
+        }
+    }
+}
+
+// the sink sample() feeds
+pub struct Influx {
+    pub target: Target,
+    queue: Option<SyncSender<String>>,
+    status: Arc<Mutex<Status>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Influx {
+    pub fn start(target: Target, token: Option<String>) -> Influx {
+        let (queue, batches) = mpsc::sync_channel(QUEUE);
+        let status = Arc::new(Mutex::new(Status::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let (target, status, stop) = (target.clone(), Arc::clone(&status), Arc::clone(&stop));
+            thread::spawn(move || send(batches, &target, token.as_deref(), &status, &stop))
+        };
+        Influx { target, queue: Some(queue), status, stop, handle: Some(handle) }
+    }
+
+    pub fn update(&self, rows: &[RowData], unix: f64, interval_secs: f64) {
+        if rows.is_empty() {
+            return;
+        }
+        let rows: Vec<SnapshotRow> = rows.iter().map(|r| SnapshotRow::new(r, unix, interval_secs)).collect();
+        let sent = self.queue.as_ref().map(|q| q.try_send(lines(&rows)));
+        if let Some(Err(TrySendError::Full(_) | TrySendError::Disconnected(_))) = sent {
+            self.lock().dropped += 1;
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        self.lock().clone()
+    }
+
+    // sends what's queued, giving up on the rest at the first failure, and says how it went
+    pub fn close(mut self) -> Status {
+        self.finish();
+        self.status()
+    }
+
+    fn finish(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.queue = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Status> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for Influx {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+fn send(batches: Receiver<String>, target: &Target, token: Option<&str>, status: &Mutex<Status>, stop: &AtomicBool) {
+    let lock = || status.lock().unwrap_or_else(|e| e.into_inner());
+    // once stopping and the server isn't answering, what's left isn't worth waiting on
+    let mut give_up = false;
+    for batch in batches {
+        if give_up {
+            lock().dropped += 1;
+            continue;
+        }
+        // the pause before the next try, none after the last
+        for pause in RETRY.into_iter().map(Some).chain([None]) {
+            match post(target, token, &batch) {
+                Ok(()) => {
+                    lock().error = None;
+                    break;
+                }
+                Err((message, retry)) => {
+                    lock().error = Some(message);
+                    let stopping = stop.load(Ordering::Relaxed);
+                    let Some(mut left) = pause.filter(|_| retry && !stopping) else {
+                        lock().dropped += 1;
+                        give_up = stopping;
+                        break;
+                    };
+                    // short naps so closing doesn't sit out the whole pause
+                    while !left.is_zero() && !stop.load(Ordering::Relaxed) {
+                        let nap = left.min(Duration::from_millis(50));
+                        thread::sleep(nap);
+                        left -= nap;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// one write; an error says what went wrong and whether trying again could help. A rejected
+// token, org or bucket (4xx) won't fix itself, an unreachable or overloaded server might
+fn post(target: &Target, token: Option<&str>, body: &str) -> Result<(), (String, bool)> {
+    let io = |e: io::Error| (e.to_string(), true);
+    let mut stream = connect(&target.host).map_err(io)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT)).map_err(io)?;
+    stream.set_write_timeout(Some(IO_TIMEOUT)).map_err(io)?;
+    let auth = token.map(|t| format!("Authorization: Token {t}\r\n")).unwrap_or_default();
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\n{auth}Content-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        target.path,
+        target.host,
+        body.len()
+    )
+    .and_then(|_| stream.flush())
+    .map_err(io)?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line).map_err(io)?;
+    let code: u16 = match status_line.split_whitespace().nth(1).and_then(|c| c.parse().ok()) {
+        Some(code) => code,
+        None => return Err(("not an HTTP response".to_string(), true)),
+    };
+    if (200..300).contains(&code) {
+        return Ok(());
+    }
+    // the headers, then the body for InfluxDB's {"code": ..., "message": ...}
+    let mut line = String::new();
+    while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+        line.clear();
+    }
+    let mut text = String::new();
+    let _ = reader.take(4096).read_to_string(&mut text);
+    let message = Json::parse(&text).and_then(|j| j.get("message").and_then(Json::as_str).map(str::to_string));
+    let message = match message {
+        Some(m) => format!("HTTP {code}: {m}"),
+        None => format!("HTTP {code}"),
+    };
+    Err((message, code == 429 || code >= 500))
+}
+
+fn connect(host: &str) -> io::Result<TcpStream> {
+    let mut last = io::Error::new(io::ErrorKind::NotFound, format!("cannot resolve {host}"));
+    for addr in host.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last = e,
+        }
+    }
+    Err(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets() {
+        let t = target("http://localhost:8086", Some("my org"), "net").unwrap();
+        assert_eq!(t, Target { host: "localhost:8086".to_string(), path: "/api/v2/write?bucket=net&precision=ns&org=my%20org".to_string() });
+        assert_eq!(target("influx.lan/proxy/", None, "a/b").unwrap().path, "/proxy/api/v2/write?bucket=a%2Fb&precision=ns");
+        assert_eq!(target("influx.lan", None, "n").unwrap().host, "influx.lan:8086");
+        assert_eq!(target("http://[::1]", None, "n").unwrap().host, "[::1]:8086");
+        assert_eq!(target("http://[::1]:9999", None, "n").unwrap().host, "[::1]:9999");
+        assert!(target("https://cloud2.influxdata.com", None, "n").unwrap_err().contains("only http://"));
+        assert!(target("http:///x", None, "n").is_err());
+    }
+
+    #[test]
+    fn line_protocol() {
+        assert_eq!(escape("my if,a=b c"), "my\\ if\\,a\\=b\\ c");
+        assert_eq!(escape("a\nb"), "a_b");
+        let row = SnapshotRow {
+            timestamp: 1_792_088_230.25,
+            tx_bps: 0.5,
+            rx_pps: f64::NAN,
+            rx_bytes_total: 4096,
+            tx_bytes_total: 1,
+            ..SnapshotRow::sample("eth0", 2048.0)
+        };
+        assert_eq!(
+            lines(&[row]),
+            "net,iface=eth0 rx_bps=2048,tx_bps=0.5,tx_pps=0,rx_errors_ps=0,tx_errors_ps=0,rx_bytes_total=4096i,tx_bytes_total=1i 1792088230250000000\n"
+        );
+    }
+
+    #[test]
+    fn warnings() {
+        assert_eq!(Status::default().warning(), None);
+        let status = Status { error: Some("connection refused".to_string()), dropped: 2 };
+        assert_eq!(status.warning().as_deref(), Some("influx: connection refused, 2 batches dropped"));
+        assert_eq!(Status { error: None, dropped: 1 }.warning().as_deref(), Some("influx: 1 batch dropped"));
+    }
+}
@@ -0,0 +1,161 @@
+// just enough JSON for `ip -j` and the Docker API: no serde in the tree for a couple of
+// small documents
+#[derive(Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(text: &str) -> Option<Json> {
+        let mut chars = text.chars().peekable();
+        let value = Json::value(&mut chars)?;
+        Json::skip_space(&mut chars);
+        chars.peek().is_none().then_some(value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    fn skip_space(chars: &mut Chars) {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn value(chars: &mut Chars) -> Option<Json> {
+        Json::skip_space(chars);
+        match *chars.peek()? {
+            '{' => {
+                chars.next();
+                let mut fields = Vec::new();
+                loop {
+                    Json::skip_space(chars);
+                    if fields.is_empty() && chars.next_if_eq(&'}').is_some() {
+                        break;
+                    }
+                    let key = Json::string(chars)?;
+                    Json::skip_space(chars);
+                    chars.next_if_eq(&':')?;
+                    fields.push((key, Json::value(chars)?));
+                    Json::skip_space(chars);
+                    match chars.next()? {
+                        ',' => {}
+                        '}' => break,
+                        _ => return None,
+                    }
+                }
+                Some(Json::Object(fields))
+            }
+            '[' => {
+                chars.next();
+                let mut items = Vec::new();
+                loop {
+                    Json::skip_space(chars);
+                    if items.is_empty() && chars.next_if_eq(&']').is_some() {
+                        break;
+                    }
+                    items.push(Json::value(chars)?);
+                    Json::skip_space(chars);
+                    match chars.next()? {
+                        ',' => {}
+                        ']' => break,
+                        _ => return None,
+                    }
+                }
+                Some(Json::Array(items))
+            }
+            '"' => Json::string(chars).map(Json::String),
+            _ => {
+                let mut word = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || "+-.".contains(*c)) {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "null" => Some(Json::Null),
+                    "true" => Some(Json::Bool(true)),
+                    "false" => Some(Json::Bool(false)),
+                    _ => word.parse().ok().map(Json::Number),
+                }
+            }
+        }
+    }
+
+    fn string(chars: &mut Chars) -> Option<String> {
+        chars.next_if_eq(&'"')?;
+        let mut s = String::new();
+        loop {
+            match chars.next()? {
+                '"' => return Some(s),
+                '\\' => match chars.next()? {
+                    'n' => s.push('\n'),
+                    't' => s.push('\t'),
+                    'r' => s.push('\r'),
+                    'b' => s.push('\u{8}'),
+                    'f' => s.push('\u{c}'),
+                    'u' => {
+                        let hex: String = (0..4).filter_map(|_| chars.next()).collect();
+                        s.push(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).unwrap_or('\u{fffd}'));
+                    }
+                    c => s.push(c),
+                },
+                c => s.push(c),
+            }
+        }
+    }
+}
+
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Json::parse(" [ ] "), Some(Json::Array(Vec::new())));
+        assert_eq!(Json::parse(r#"{"a":[1,true,null],"b":"x\"A"}"#), Some(Json::Object(vec![
+            ("a".to_string(), Json::Array(vec![Json::Number(1.0), Json::Bool(true), Json::Null])),
+            ("b".to_string(), Json::String("x\"A".to_string())),
+        ])));
+        for bad in ["", "[", "[1,]", "{\"a\" 1}", "[1] 2", "nul"] {
+            assert_eq!(Json::parse(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn lookups() {
+        let doc = Json::parse(r#"{"Names": ["/web"], "Id": "abc"}"#).unwrap();
+        assert_eq!(doc.get("Id").and_then(Json::as_str), Some("abc"));
+        assert_eq!(doc.get("Names").and_then(Json::as_array).map(<[Json]>::len), Some(1));
+        assert_eq!(doc.get("missing"), None);
+        assert_eq!(Json::Null.as_str(), None);
+    }
+}
@@ -0,0 +1,295 @@
+// which keys do what: the built-in bindings, the config file's [keys] on top of them, and
+// the key names both the config file and the help overlay use
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::Action;
+use crate::glyphs::{Glyphs, ASCII};
+
+// a key press as the keymap sees it; shift is already part of a character ('G') or of
+// back-tab, so it only counts for the other keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Key { code, modifiers }
+    }
+
+    pub fn from_event(event: &KeyEvent) -> Self {
+        let mut modifiers = event.modifiers;
+        if matches!(event.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Key { code: event.code, modifiers }
+    }
+}
+
+const fn key(c: char) -> Key {
+    Key::new(KeyCode::Char(c), KeyModifiers::NONE)
+}
+
+const fn code(code: KeyCode) -> Key {
+    Key::new(code, KeyModifiers::NONE)
+}
+
+const fn ctrl(c: char) -> Key {
+    Key::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+}
+
+const CTRL_C: Key = ctrl('c');
+
+// the bindings without a config file, in the order the help overlay lists them
+pub const DEFAULT: &[(&[Key], Action)] = &[
+    (&[key('q')], Action::Quit),
+    (&[key('?'), key('h')], Action::Help),
+    (&[code(KeyCode::Enter)], Action::Open),
+    (&[code(KeyCode::Esc)], Action::Back),
+    (&[code(KeyCode::Up), key('k')], Action::Up),
+    (&[code(KeyCode::Down), key('j')], Action::Down),
+    (&[code(KeyCode::PageUp)], Action::PageUp),
+    (&[code(KeyCode::PageDown)], Action::PageDown),
+    (&[ctrl('u')], Action::HalfPageUp),
+    (&[ctrl('d')], Action::HalfPageDown),
+    (&[key('g'), code(KeyCode::Home)], Action::Top),
+    (&[key('G'), code(KeyCode::End)], Action::Bottom),
+    (&[key('+')], Action::Faster),
+    (&[key('-')], Action::Slower),
+    (&[key('i')], Action::ToggleVirtual),
+    (&[key('c')], Action::ToggleTotals),
+    (&[key('p'), key(' ')], Action::Pause),
+    (&[key('u')], Action::ToggleBits),
+    (&[key('U')], Action::ToggleStandard),
+    (&[key('z')], Action::ToggleIdle),
+    (&[key('Z')], Action::ToggleSparkScale),
+    (&[key('P')], Action::TogglePin),
+    (&[key('x')], Action::Hide),
+    (&[key('X')], Action::HiddenList),
+    (&[key('/')], Action::Search),
+    (&[key('t')], Action::ToggleGroups),
+    (&[key('T')], Action::CycleTheme),
+    (&[key('H')], Action::ToggleChart),
+    (&[key('C')], Action::ChooseColumns),
+    (&[code(KeyCode::Left)], Action::ColumnLeft),
+    (&[code(KeyCode::Right)], Action::ColumnRight),
+    (&[key('<')], Action::MoveColumnLeft),
+    (&[key('>')], Action::MoveColumnRight),
+    (&[key('s')], Action::CycleSort),
+    (&[key('S')], Action::ReverseSort),
+    (&[key('f')], Action::FreezeOrder),
+    (&[key('A')], Action::ToggleStats),
+    (&[key('L')], Action::ToggleAverages),
+    (&[key('M')], Action::ToggleMulticast),
+    (&[key('r')], Action::ResetStats),
+    (&[key('b')], Action::ToggleGauge),
+    (&[key('~')], Action::ToggleSmooth),
+    (&[key('y')], Action::Copy),
+    (&[key('Y')], Action::CopyTable),
+    (&[key('e')], Action::Export),
+    (&[key('E')], Action::ExportAs),
+    (&[key('a')], Action::AlertLog),
+    (&[code(KeyCode::Tab)], Action::Connections),
+    (&[key('F')], Action::ConnectionFilter),
+    (&[key('w')], Action::Processes),
+    (&[key('o')], Action::TopTalkers),
+    (&[key('n')], Action::ToggleNames),
+    (&[key('m')], Action::NestMembers),
+];
+
+// the bindings in effect, looked up for every key press
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(Vec<Key>, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap { bindings: DEFAULT.iter().map(|(keys, action)| (keys.to_vec(), *action)).collect() }
+    }
+}
+
+impl Keymap {
+    // an action the user binds loses its default keys, and a key the user binds leaves the
+    // action that had it by default; actions the user doesn't mention keep their defaults.
+    // Two user bindings sharing a key are an error
+    pub fn new(overrides: &[(Action, Vec<Key>)]) -> Result<Self, String> {
+        for (i, (action, keys)) in overrides.iter().enumerate() {
+            for key in keys {
+                if *key == CTRL_C {
+                    return Err(format!("ctrl+c can't be bound to {}, it always quits", action.name()));
+                }
+                if let Some((other, _)) = overrides[i + 1..].iter().find(|(a, k)| a != action && k.contains(key)) {
+                    return Err(format!(
+                        "'{}' is bound to both {} and {}",
+                        key_label(*key, &ASCII),
+                        action.name(),
+                        other.name()
+                    ));
+                }
+            }
+        }
+        let taken: Vec<Key> = overrides.iter().flat_map(|(_, keys)| keys.iter().copied()).collect();
+        let bindings = DEFAULT
+            .iter()
+            .map(|(keys, action)| match overrides.iter().find(|(a, _)| a == action) {
+                Some((_, mine)) => (mine.clone(), *action),
+                None => (keys.iter().copied().filter(|k| !taken.contains(k)).collect(), *action),
+            })
+            .collect();
+        Ok(Keymap { bindings })
+    }
+
+    pub fn action_for(&self, key: Key) -> Option<Action> {
+        self.bindings.iter().find(|(keys, _)| keys.contains(&key)).map(|(_, a)| *a)
+    }
+
+    // every key bound to `action`, empty if it has none
+    pub fn keys_for(&self, action: Action) -> &[Key] {
+        self.bindings.iter().find(|(_, a)| *a == action).map_or(&[], |(keys, _)| keys)
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = (&[Key], Action)> {
+        self.bindings.iter().map(|(keys, action)| (keys.as_slice(), *action))
+    }
+}
+
+// the action a [keys] entry names
+pub fn action_named(name: &str) -> Option<Action> {
+    DEFAULT.iter().map(|(_, action)| *action).find(|a| a.name() == name)
+}
+
+// what the help overlay and status bar show; with the ASCII glyphs it's also what
+// parse_key reads back
+pub fn key_label(key: Key, glyphs: &Glyphs) -> String {
+    let name = match key.code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::BackTab => "shift+tab".to_string(),
+        KeyCode::Up => glyphs.up.to_string(),
+        KeyCode::Down => glyphs.down.to_string(),
+        KeyCode::Left => glyphs.left.to_string(),
+        KeyCode::Right => glyphs.right.to_string(),
+        KeyCode::PageUp => "pgup".to_string(),
+        KeyCode::PageDown => "pgdn".to_string(),
+        KeyCode::F(n) => format!("f{n}"),
+        other => format!("{other:?}").to_lowercase(),
+    };
+    let mut label = String::new();
+    for (modifier, prefix) in
+        [(KeyModifiers::CONTROL, "ctrl+"), (KeyModifiers::ALT, "alt+"), (KeyModifiers::SHIFT, "shift+")]
+    {
+        if key.modifiers.contains(modifier) {
+            label.push_str(prefix);
+        }
+    }
+    label + &name
+}
+
+// "q", "G", "space", "ctrl+d", "alt+enter", "f5", "shift+tab"; modifier and key names
+// ignore case, single characters don't
+pub fn parse_key(s: &str) -> Result<Key, String> {
+    let invalid = || format!("invalid key '{s}', expected e.g. \"q\", \"ctrl+d\", \"f5\" or \"shift+tab\"");
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = s;
+    // a '+' that ends the string is the plus key: "+", "ctrl++"
+    while let Some((modifier, tail)) = rest.split_once('+').filter(|(_, tail)| !tail.is_empty()) {
+        modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return Err(invalid()),
+        };
+        rest = tail;
+    }
+    let mut code = match rest.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" | "bksp" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pgup" | "pageup" => KeyCode::PageUp,
+        "pgdn" | "pagedown" => KeyCode::PageDown,
+        "up" | "↑" => KeyCode::Up,
+        "down" | "↓" => KeyCode::Down,
+        "left" | "←" => KeyCode::Left,
+        "right" | "→" => KeyCode::Right,
+        name if name.len() > 1 && name.starts_with('f') => match name[1..].parse() {
+            Ok(n @ 1..=24) => KeyCode::F(n),
+            _ => return Err(invalid()),
+        },
+        _ => {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return Err(invalid()),
+            }
+        }
+    };
+    // the way terminals report them: shift+a is 'A', shift+tab is back-tab
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        match code {
+            KeyCode::Char(c) => code = KeyCode::Char(c.to_ascii_uppercase()),
+            KeyCode::Tab => code = KeyCode::BackTab,
+            _ => {}
+        }
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+    }
+    Ok(Key::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_names() {
+        assert_eq!(parse_key("q"), Ok(key('q')));
+        assert_eq!(parse_key("G"), Ok(key('G')));
+        assert_eq!(parse_key("shift+g"), Ok(key('G')));
+        assert_eq!(parse_key("space"), Ok(key(' ')));
+        assert_eq!(parse_key("CTRL+d"), Ok(ctrl('d')));
+        assert_eq!(parse_key("ctrl+d"), Ok(ctrl('d')));
+        assert_eq!(parse_key("F5"), Ok(code(KeyCode::F(5))));
+        assert_eq!(parse_key("shift+tab"), Ok(code(KeyCode::BackTab)));
+        assert_eq!(parse_key("alt+enter"), Ok(Key::new(KeyCode::Enter, KeyModifiers::ALT)));
+        assert_eq!(parse_key("+"), Ok(key('+')));
+        assert_eq!(parse_key("ctrl++"), Ok(Key::new(KeyCode::Char('+'), KeyModifiers::CONTROL)));
+        for bad in ["", "ctrl+", "hyper+x", "f99", "qq"] {
+            assert!(parse_key(bad).is_err(), "{bad:?}");
+        }
+        // labels read back as the same key
+        for (keys, _) in DEFAULT {
+            for &k in *keys {
+                assert_eq!(parse_key(&key_label(k, &ASCII)), Ok(k));
+            }
+        }
+    }
+
+    #[test]
+    fn user_bindings_replace_defaults() {
+        let quit = (Action::Quit, vec![key('q'), code(KeyCode::Esc)]);
+        let sort = (Action::CycleSort, vec![key('o')]);
+        let map = Keymap::new(&[quit.clone(), sort.clone()]).unwrap();
+        assert_eq!(map.action_for(code(KeyCode::Esc)), Some(Action::Quit));
+        assert!(map.keys_for(Action::Back).is_empty(), "esc moved to quit");
+        assert_eq!(map.action_for(key('o')), Some(Action::CycleSort));
+        assert_eq!(map.action_for(key('s')), None, "s no longer sorts");
+        assert_eq!(map.keys_for(Action::Pause), [key('p'), key(' ')], "untouched defaults");
+
+        let clash = (Action::Pause, vec![key('o')]);
+        assert_eq!(Keymap::new(&[sort, clash]).unwrap_err(), "'o' is bound to both sort and pause");
+        assert!(Keymap::new(&[(Action::Help, vec![ctrl('c')])]).is_err());
+    }
+}
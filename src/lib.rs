@@ -0,0 +1,46 @@
+pub mod adapters;
+pub mod alert;
+pub mod app;
+pub mod bench;
+pub mod capture;
+pub mod cli;
+pub mod clipboard;
+pub mod columns;
+pub mod config;
+pub mod conns;
+pub mod containers;
+pub mod csvlog;
+pub mod datacap;
+pub mod event;
+pub mod export;
+pub mod format;
+pub mod glyphs;
+pub mod history;
+pub mod host;
+pub mod iftype;
+pub mod influx;
+pub mod json;
+pub mod keymap;
+pub mod neigh;
+pub mod net;
+pub mod netns;
+pub mod notify;
+pub mod ping;
+pub mod procfs;
+pub mod procs;
+pub mod prometheus;
+pub mod quantile;
+pub mod remote;
+pub mod replay;
+pub mod resolve;
+pub mod route;
+pub mod snapshot;
+pub mod socket;
+pub mod statsd;
+pub mod summary;
+pub mod sysfs;
+pub mod theme;
+pub mod ui;
+pub mod vlan;
+pub mod wg;
+pub mod wifi;
@@ -1,227 +1,456 @@
-use std::io;
+use std::io::{self, Write};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode};
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use anyhow::{Context, Result};
+use clap::{CommandFactory, FromArgMatches};
+use crossterm::cursor::Show;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::execute;
-
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Constraint, Direction, Layout};
-use ratatui::style::{Modifier, Style};
-use ratatui::text::Span;
-use ratatui::widgets::{Block, BorderType, Borders, Paragraph, Row, Table};
 use ratatui::Terminal;
-
-use sysinfo::Networks;
-use clap::Parser;
-
-#[derive(Parser, Debug)]
-#[command(name="nettui", about="Network TUI Monitor")]
-struct Args {
-    iface: Vec<String>,
+use signal_hook::consts::{SIGINT, SIGTERM};
+
+use nettui::app::{AppState, View};
+use nettui::bench;
+use nettui::cli::{Args, Backend, Command, StreamFormat};
+use nettui::config::{self, Config};
+use nettui::conns;
+use nettui::host::Host;
+use nettui::net::NetworkSource;
+use nettui::netns::Namespaced;
+use nettui::ping::{self, Pinger, PING_EVERY};
+use nettui::remote::{self, Hello, Remote, Server};
+use nettui::replay::{self, Replay, PLAY_TICK};
+use nettui::procfs::ProcNetDev;
+use nettui::procs::Meter;
+use nettui::resolve::Resolver;
+use nettui::csvlog::CsvLog;
+use nettui::event::{self, AppEvent, Poller, Ticker};
+use nettui::prometheus::Exporter;
+use nettui::snapshot::{self, SnapshotRow};
+use nettui::influx::Influx;
+use nettui::socket::QuerySocket;
+use nettui::statsd::Statsd;
+use nettui::summary::Summary;
+use nettui::ui::{draw, plain_table};
+
+// the host's interfaces from the --backend, plus whatever --netns/--all-netns asked for
+fn networks(app: &AppState) -> Namespaced<Box<dyn NetworkSource>> {
+    let host: Box<dyn NetworkSource> = match app.backend {
+        Backend::Sysinfo => Box::new(sysinfo::Networks::new_with_refreshed_list()),
+        Backend::Procfs => Box::new(ProcNetDev::default()),
+    };
+    Namespaced::new(host, app.netns.clone())
 }
 
-#[derive(Clone, Debug)]
-struct RowData {
-    interface: String,
-    rx_bps: f64,
-    tx_bps: f64,
-    packets_in: u64,
-    packets_out: u64,
-    errors_in: u64,
-    errors_out: u64,
-}
-
-fn human_bps(bps: f64) -> String {
-    if bps < 1.0 { return "--".to_string(); } // show --
-    if bps < 1024.0 { return format!("{:.0} B/s", bps); } // just show bytes
+// --once/--count: plain tables or JSON on stdout, no raw mode or alternate screen
+fn run_batch(mut app: AppState, count: u32, json: bool) {
+    let mut networks = networks(&app);
+    app.prime(&mut networks);
+    let mut last = Instant::now();
+    let mut snapshots = Vec::new();
+    for i in 0..count {
+        std::thread::sleep(Duration::from_millis(app.refresh_ms));
+        let now = Instant::now();
+        let elapsed = now.duration_since(last);
+        app.sample(&mut networks, now, elapsed);
+        last = now;
+        //no status bar to show it in
+        if i == 0 {
+            for (ns, e) in &app.netns_errors {
+                eprintln!("nettui: netns {ns}: {e}");
+            }
+        }
 
-    //units we support
-    let units = ["KB/s", "MB/s", "GB/s"];
-    let mut v = bps / 1024.0; //convert bytes -> KB
-    let mut i = 0;
-    while v >= 1024.0 && i < units.len() - 1 {
-        v /= 1024.0;
-        i += 1;
+        if json {
+            let ts = snapshot::unix_now();
+            snapshots.extend(app.rows.iter().map(|r| SnapshotRow::new(r, ts, elapsed.as_secs_f64())));
+        } else {
+            if i > 0 {
+                println!();
+            }
+            print!("{}", plain_table(&app));
+        }
     }
-
-    // formatting (1 decimal unless big enough)
-    if v >=100.0 {
-        format!("{:.0} {}", v, units[i])
-    } else {
-        format!("{:.1} {}", v, units[i])
+    if json {
+        println!("{}", snapshot::to_json_array(&snapshots));
     }
 }
 
-fn collect(networks: &mut Networks, interval_secs: f64, _show_virtual: bool, iface_filters: &Vec<String>) -> Vec<RowData> {
-    //if interval is 0, convert to 1, as we will be divinding it, can't divide by zero
-    let interval_secs = if interval_secs <=0.0 {1.0} else { interval_secs };
+// interface count --bench renders, roughly a busy container host
+const BENCH_INTERFACES: usize = 200;
 
-    //refresh network counters
-    networks.refresh(true);
+// set once SIGINT or SIGTERM arrives, the headless loop polls it and shuts down normally
+fn stop_flag() -> io::Result<Arc<AtomicBool>> {
+    let stop = Arc::new(AtomicBool::new(false));
+    for sig in [SIGINT, SIGTERM] {
+        signal_hook::flag::register(sig, Arc::clone(&stop))?;
+    }
+    Ok(stop)
+}
 
-    let mut rows: Vec<RowData> = Vec::new();
+// --stream and --no-tui: the same sampling as the TUI without a terminal, until SIGINT/SIGTERM
+// or the --stream reader goes away; --log-csv and --prometheus are fed from sample()
+fn run_headless(mut app: AppState, stream: Option<StreamFormat>) -> io::Result<()> {
+    let stop = stop_flag()?;
 
-    for(name, data) in networks.iter() {
-        let is_virtual = name.starts_with("lo")
-            || name.starts_with("veth")
-            || name.starts_with("docker")
-            || name.starts_with("br-")
-            || name.starts_with("vmnet")
-            || name.starts_with("virbr");
+    let mut networks = networks(&app);
+    app.prime(&mut networks);
+    let interval = Duration::from_millis(app.refresh_ms);
+    let mut last = Instant::now();
+    let mut next = last + interval;
+    let mut out = io::stdout().lock();
 
-        if !is_virtual && is_virtual {
+    while !stop.load(Ordering::Relaxed) {
+        //short naps so a signal is noticed promptly
+        let now = Instant::now();
+        if now < next {
+            std::thread::sleep((next - now).min(Duration::from_millis(100)));
             continue;
         }
 
-        if !iface_filters.is_empty() && !iface_filters.contains(&name.to_string()) {
-            continue;
+        let elapsed = now.duration_since(last);
+        app.sample(&mut networks, now, elapsed);
+        last = now;
+
+        let ts = snapshot::unix_now();
+        let written = app.rows.iter().try_for_each(|r| match stream {
+            Some(StreamFormat::Jsonl) => writeln!(out, "{}", SnapshotRow::new(r, ts, elapsed.as_secs_f64()).to_json()),
+            None => Ok(()),
+        });
+        match written.and_then(|_| if stream.is_some() { out.flush() } else { Ok(()) }) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => break,
+            other => other?,
         }
 
-        //recieved/transmitted return bytes since last refresh
-        let rx_bps = data.received() as f64 / interval_secs;
-        let tx_bps = data.transmitted() as f64 / interval_secs;
-
-        let row = RowData {
-            interface: name.to_string(),
-            rx_bps,
-            tx_bps,
-            packets_in: data.packets_received(),
-            packets_out: data.packets_transmitted(),
-            errors_in: data.errors_on_received(),
-            errors_out: data.errors_on_transmitted(),
-        };
-
-        rows.push(row);
+        //keep a fixed cadence; ticks missed while stdout was blocked are dropped, not replayed
+        next += interval;
+        let now = Instant::now();
+        if next <= now {
+            next = now + interval;
+        }
     }
+    if let Some(csv) = &mut app.csv {
+        csv.flush();
+        if let Some(e) = csv.error() {
+            eprintln!("nettui: csv log stopped: {e}");
+        }
+    }
+    if let Some(warning) = app.influx.take().and_then(|i| i.close().warning()) {
+        eprintln!("nettui: {warning}");
+    }
+    save_budget(&mut app);
+    Ok(())
+}
 
-    //sort by descending, highest traffic network appears first
-    rows.sort_by(|a, b| {
-        let a_total = a.rx_bps + a.tx_bps;
-        let b_total = b.rx_bps + b.tx_bps;
-
-        b_total.partial_cmp(&a_total).unwrap_or(std::cmp::Ordering::Equal)
+fn save_budget(app: &mut AppState) {
+    if let Some(budget) = &mut app.budget {
+        budget.save();
+        if let Some(e) = &budget.error {
+            eprintln!("nettui: data cap usage not saved: {e}");
+        }
+    }
+}
 
-    });
+// raw mode, the alternate screen and mouse capture live exactly as long as this guard: dropping it
+// restores the terminal on `?` returns, and the panic hook does the same before the
+// message is printed, so errors land on the normal screen where they can be read
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter(mouse: bool) -> io::Result<Self> {
+        enable_raw_mode()?;
+        //from here on a failure still gets raw mode undone
+        let guard = TerminalGuard;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        if mouse {
+            execute!(io::stdout(), EnableMouseCapture)?;
+        }
 
-    rows
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            default_hook(info);
+        }));
+        Ok(guard)
+    }
+}
 
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
 }
 
-fn main() -> Result<(), io::Error> {
+// best effort and safe to repeat, it runs from both the panic hook and Drop
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    //harmless when capture was never turned on
+    let _ = execute!(io::stdout(), DisableMouseCapture, LeaveAlternateScreen, Show);
+}
 
-    //Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+// the interactive UI until the user quits or a signal arrives; the terminal is back to
+// normal once this returns, however it returns
+fn run_tui(app: &mut AppState) -> Result<()> {
+    //Setup terminal; the guard puts it back however this function is left
+    let _guard = TerminalGuard::enter(app.mouse)?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
-    // Create networks once the new_with_refreshed_list seeds the list of interfaces
-    let mut networks = sysinfo::Networks::new_with_refreshed_list();
+    // Create networks once, the first read is the baseline for the first sample
+    let mut networks = networks(app);
+    app.prime(&mut networks);
+    let mut last_sample = Instant::now();
+
+    //keys, ticks and signals all arrive on one channel; SIGTERM (timeout, systemd) quits like 'q'
+    let (tx, rx) = mpsc::channel();
+    event::spawn_input(tx.clone());
+    event::spawn_signals(tx.clone())?;
+    //a replay's frames come due at their own pace, the ticker only has to look often enough
+    let every = if app.replay.is_some() { PLAY_TICK } else { Duration::from_millis(app.refresh_ms) };
+    let ticker = Ticker::start(tx.clone(), every);
+    if let Some(remote) = &app.remote {
+        remote::connect(tx.clone(), remote.addr.clone(), remote.token.clone());
+    }
+    let connections = Poller::start(tx.clone(), app.conns_every, || Some(AppEvent::Connections(conns::read())));
+    let mut meter = Meter::new(app.conns_every);
+    app.resolver = Some(Resolver::start(tx.clone()));
+    if app.ping && ping::unavailable().is_none() {
+        app.pinger = Some(Pinger::start(tx.clone(), PING_EVERY));
+    }
+    let processes = Poller::start(tx, app.conns_every, move || meter.read(Instant::now()).map(AppEvent::Processes));
+
+    terminal.draw(|f| draw(f, app))?;
+    app.dirty = false;
+    'events: while let Ok(first) = rx.recv() {
+        //everything already queued is handled before the next frame, so a held key
+        //can't pile up behind draws and keep moving the cursor after it's released
+        for ev in std::iter::once(first).chain(std::iter::from_fn(|| rx.try_recv().ok())) {
+            match ev {
+                //rates are measured against the previous sample, not the nominal interval
+                AppEvent::Tick if app.replay.is_some() => app.play(Instant::now()),
+                //the server's samples arrive on their own, ticks only count down a reconnect
+                AppEvent::Tick if app.remote.is_some() => app.dirty |= app.remote.as_ref().is_some_and(|r| !r.is_connected()),
+                AppEvent::Remote(update) => app.remote_update(update, Instant::now()),
+                AppEvent::Tick => {
+                    let now = Instant::now();
+                    app.sample(&mut networks, now, now.duration_since(last_sample));
+                    last_sample = now;
+                }
+                AppEvent::Connections(listing) => app.set_connections(listing, Instant::now()),
+                AppEvent::Processes(reading) => app.set_processes(reading, Instant::now()),
+                AppEvent::Resolved(ip, name, ttl) => app.set_name(ip, name, ttl, Instant::now()),
+                AppEvent::Ping(p) => app.set_ping(p),
+                AppEvent::Key(key) => {
+                    let refresh_ms = app.refresh_ms;
+                    let view = app.view;
+                    if !app.handle_key(key) {
+                        break 'events;
+                    }
+                    if app.refresh_ms != refresh_ms {
+                        ticker.set_interval(Duration::from_millis(app.refresh_ms));
+                    }
+                    //the sockets are only read while their tab is up
+                    for (tab, poller) in [(View::Connections, &connections), (View::Processes, &processes)] {
+                        if (app.view == tab) != (view == tab) {
+                            poller.set_active(app.view == tab);
+                        }
+                    }
+                }
+                AppEvent::Mouse(mouse) => app.handle_mouse(mouse, Instant::now()),
+                //start from a blank screen so nothing of the old layout survives the resize
+                AppEvent::Resize(..) => {
+                    terminal.clear()?;
+                    app.dirty = true;
+                }
+                AppEvent::Quit => break 'events,
+            }
+        }
+
+        //render only after a sample, a state change or a resize; otherwise stay asleep in recv
+        if app.dirty {
+            terminal.draw(|f| draw(f, app))?;
+            app.dirty = false;
+        }
+        //a new alert; rate-limited where it's raised
+        if std::mem::take(&mut app.bell) {
+            terminal.backend_mut().write_all(b"\x07")?;
+            terminal.backend_mut().flush()?;
+        }
+    }
+    Ok(())
+}
 
-    let mut refresh_ms: u64 = 500;
-    let mut show_virtual = false;
-    let mut last = Instant::now();
+fn main() -> Result<()> {
 
-    let args = Args::parse();
-    let iface_filters = args.iface;
+    //parse before touching the terminal so --help and usage errors print normally
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
-// collect snapshot used by the UI
+    //a client of another nettui, nothing here samples or reads the config
+    if let Some(Command::Query { socket, query }) = &args.command {
+        let path = socket.clone().unwrap_or_else(nettui::socket::default_path);
+        let line = if query.is_empty() { "LIST".to_string() } else { query.join(" ") };
+        let reply = nettui::socket::query(&path, &line).with_context(|| format!("cannot ask {}", path.display()))?;
+        println!("{reply}");
+        //scripts can tell a missing interface from a reading by the exit status
+        std::process::exit(if reply.starts_with("{\"error\"") { 1 } else { 0 });
+    }
 
-    loop {
-        let now = Instant::now();
-        let elapsed = now.duration_since(last).as_secs_f64();
-        if elapsed <= 0.0 {
-            std::thread::sleep(Duration::from_millis(10));
-            continue;
-        }
-    let rows = collect(&mut networks, elapsed, show_virtual, &iface_filters);
+    //flags win over the config file, the file wins over defaults; a missing default file is fine
+    let config_path = args.config.clone().or_else(config::default_path);
+    let config = match &config_path {
+        Some(path) if args.config.is_some() || path.exists() => Config::load(path)?,
+        _ => Config::default(),
+    };
+    config.apply(&mut args, &matches);
+    if args.dump_config {
+        let loaded = config_path.as_deref().filter(|p| args.config.is_some() || p.exists());
+        print!("{}", config::dump(&args, &config, &matches, loaded));
+        return Ok(());
+    }
+    if args.backend == Backend::Procfs && !cfg!(target_os = "linux") {
+        anyhow::bail!("--backend procfs reads /proc/net/dev, which only Linux has");
+    }
+    if let Some(frames) = args.bench {
+        print!("{}", bench::run(BENCH_INTERFACES, frames));
+        return Ok(());
+    }
 
+    //--json on its own is a single snapshot
+    let count = if args.once || (args.json && args.count.is_none()) { Some(1) } else { args.count };
+    let json = args.json;
+    let stream = args.stream;
+    let no_tui = args.no_tui;
+    let command = args.command.clone();
+    let token = |given: &Option<String>| given.clone().or_else(|| std::env::var("NETTUI_TOKEN").ok()).filter(|t| !t.is_empty());
+    match &command {
+        Some(Command::Serve { .. }) if count.is_some() || args.replay.is_some() => {
+            anyhow::bail!("serve samples until stopped, it doesn't take --once, --count, --json or --replay")
+        }
+        Some(Command::Connect { .. }) if count.is_some() || stream.is_some() || no_tui || args.replay.is_some() => {
+            anyhow::bail!("connect draws the server's samples, it doesn't take --once, --count, --json, --stream, --no-tui or --replay")
+        }
+        _ => {}
+    }
+    if no_tui && stream.is_none() && args.log_csv.is_none() && args.prometheus.is_none() && args.influx.is_none() && args.statsd.is_none() {
+        anyhow::bail!("--no-tui collects for --log-csv, --stream, --prometheus, --influx or --statsd, give at least one");
+    }
+    let summary_json = args.summary_json;
+    let save_hidden = args.save_hidden;
+    let recording = match &args.replay {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+            let recording = replay::parse(&text);
+            if recording.frames.is_empty() {
+                anyhow::bail!("{}: no samples recorded by --log-csv, --stream jsonl or --json", path.display());
+            }
+            Some(recording)
+        }
+        None => None,
+    };
+    let csv = match &args.log_csv {
+        Some(path) => Some(CsvLog::open(path).with_context(|| format!("cannot open {}", path.display()))?),
+        None => None,
+    };
+    let socket = match &args.socket {
+        Some(path) => {
+            let path = path.clone().unwrap_or_else(nettui::socket::default_path);
+            Some(QuerySocket::start(&path).with_context(|| format!("cannot listen on {}", path.display()))?)
+        }
+        None => None,
+    };
+    let exporter = match &args.prometheus {
+        Some(addr) => Some(Exporter::start(addr.as_str()).with_context(|| format!("cannot listen on {addr}"))?),
+        None => None,
+    };
+    let influx = match &args.influx {
+        Some(url) => {
+            let bucket = args.influx_bucket.as_deref().unwrap_or("nettui");
+            let target = nettui::influx::target(url, args.influx_org.as_deref(), bucket)
+                .map_err(|e| anyhow::anyhow!("--influx {url}: {e}"))?;
+            let token = args.influx_token.clone().or_else(|| std::env::var("INFLUX_TOKEN").ok()).filter(|t| !t.is_empty());
+            Some(Influx::start(target, token))
+        }
+        None => None,
+    };
+    let statsd = match &args.statsd {
+        Some(addr) => Some(Statsd::start(addr, &args.statsd_prefix).with_context(|| format!("cannot send to {addr}"))?),
+        None => None,
+    };
+    let mut app = AppState::from_args(args);
+    app.csv = csv;
+    app.exporter = exporter;
+    app.socket = socket;
+    app.influx = influx;
+    app.statsd = statsd;
+    //the column chooser saves as soon as something changes
+    app.config_path = config_path.clone();
+    if let Some(recording) = recording {
+        app.replay = Some(Replay::new(recording, Instant::now()));
+        app.seek(0);
+    }
 
-        //check for quit event
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.code == KeyCode::Char('q') {
-                    break;
-                }
+    if let Some(count) = count {
+        run_batch(app, count, json);
+        return Ok(());
+    }
+    match command {
+        Some(Command::Serve { listen, token: given }) => {
+            let token = token(&given);
+            let hello = Hello { host: Host::read(), interval_ms: app.refresh_ms };
+            let server = Server::start(&listen, token.clone(), hello).with_context(|| format!("cannot listen on {listen}"))?;
+            eprintln!("nettui: serving on {}", server.addr);
+            if token.is_none() {
+                eprintln!("nettui: no --token, anyone who can reach {} can watch", server.addr);
             }
+            app.server = Some(server);
+            return Ok(run_headless(app, stream)?);
         }
+        Some(Command::Connect { addr, token: given }) => app.remote = Some(Remote::new(addr, token(&given))),
+        //answered before anything else
+        Some(Command::Query { .. }) | None => {}
+    }
+    if no_tui || stream.is_some() {
+        return Ok(run_headless(app, stream)?);
+    }
 
-        //refresh data
-        networks.refresh(true);
-
-        //Render
-        terminal.draw(|f| {
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Length(3), Constraint::Min(3)].as_ref())
-                .split(f.size());
-
-            //build table rows from network stats
-            let title = format!(
-                " Nettui - live (q:quit  +/-:rate  i:virtual)   refresh: {} ms   ifaces: {} ",
-                refresh_ms,
-                rows.len()
-            );
-
-            let header = Paragraph::new(Span::raw(title))
-                .block(
-                    Block::default()
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded),
-                );
-            f.render_widget(header, chunks[0]);
-
-            // table header
-            let header_row = Row::new(vec!["IINTERFACE", "RX/s", "TX/s", "PKTS In", "PKTS Out", "Err In", "Err Out"])
-                .style(Style::default().add_modifier(Modifier::BOLD));
-
-
-            let table_rows = rows.iter().map(|r| {
-                Row::new(vec![
-                    r.interface.clone(),
-                    human_bps(r.rx_bps),
-                    human_bps(r.tx_bps),
-                    r.packets_in.to_string(),
-                    r.packets_out.to_string(),
-                    r.errors_in.to_string(),
-                    r.errors_out.to_string(),
-                ])
-            });
-
-            let widths = [
-                Constraint::Length(16),
-                Constraint::Length(12),
-                Constraint::Length(12),
-                Constraint::Length(10),
-                Constraint::Length(10),
-                Constraint::Length(8),
-                Constraint::Length(8),
-            ];
-
-            let table = Table::new(table_rows, widths)
-                .header(header_row)
-                .block(
-                    Block::default()
-                        .title(Span::from("Interfaces"))
-                        .borders(Borders::ALL)
-                        .border_type(BorderType::Rounded),
-                )
-                .column_spacing(1);
-
-        // render into the second chunk (chunks[0] is header)
-        f.render_widget(table, chunks[1]);
-
-        })?;
-    }
-
-    //restore terminal
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+    //only the header shows these, and they don't change while running; a server sends its own
+    if app.remote.is_none() {
+        app.host = Host::read();
+    }
+    let started = snapshot::unix_now();
+    let start = Instant::now();
+    let pins = app.pins.clone();
+    run_tui(&mut app)?;
+
+    //pins changed with 'P' survive a restart
+    if app.pins != pins
+        && let Some(path) = &config_path
+        && let Err(e) = config::save(path, "pins", &app.pins)
+    {
+        eprintln!("nettui: pins not saved: {e:#}");
+    }
+    if save_hidden
+        && let Some(path) = &config_path
+        && let Err(e) = config::save(path, "hidden", &app.hidden)
+    {
+        eprintln!("nettui: hidden interfaces not saved: {e:#}");
+    }
+
+    save_budget(&mut app);
 
+    //the normal screen is back, so the summary stays in the scrollback to copy from
+    let summary = Summary::new(&app, started, start.elapsed());
+    if summary_json {
+        println!("{}", summary.to_json());
+    } else {
+        print!("{}", summary.to_text(&app));
+    }
     Ok(())
 
 }
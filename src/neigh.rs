@@ -0,0 +1,191 @@
+// the ARP and NDP neighbor tables: /proc/net/arp for IPv4 and `ip -j neigh` for IPv6, whose
+// table the kernel only hands out over netlink; the parsers are pure so fixtures can stand
+// in for both
+use std::net::IpAddr;
+
+use crate::json::Json;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NeighState {
+    Reachable,
+    Stale,
+    Delay,
+    Probe,
+    Incomplete,
+    Failed,
+    Permanent,
+    Noarp,
+    Unknown,
+}
+
+impl NeighState {
+    // the state names `ip neigh` prints
+    fn parse(s: &str) -> NeighState {
+        match s {
+            "REACHABLE" => NeighState::Reachable,
+            "STALE" => NeighState::Stale,
+            "DELAY" => NeighState::Delay,
+            "PROBE" => NeighState::Probe,
+            "INCOMPLETE" => NeighState::Incomplete,
+            "FAILED" => NeighState::Failed,
+            "PERMANENT" => NeighState::Permanent,
+            "NOARP" => NeighState::Noarp,
+            _ => NeighState::Unknown,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NeighState::Reachable => "reachable",
+            NeighState::Stale => "stale",
+            NeighState::Delay => "delay",
+            NeighState::Probe => "probe",
+            NeighState::Incomplete => "incomplete",
+            NeighState::Failed => "failed",
+            NeighState::Permanent => "permanent",
+            NeighState::Noarp => "noarp",
+            NeighState::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Neighbor {
+    pub ip: IpAddr,
+    // None until resolved, and for failed entries
+    pub mac: Option<String>,
+    pub interface: String,
+    pub state: NeighState,
+}
+
+// the headline number: failed entries are hosts that didn't answer, not neighbors
+pub fn count(neighbors: &[Neighbor], interface: &str) -> usize {
+    neighbors.iter().filter(|n| n.interface == interface && n.state != NeighState::Failed).count()
+}
+
+const ATF_COM: u32 = 0x02;
+const ATF_PERM: u32 = 0x04;
+
+// /proc/net/arp: a header, then address, hardware type, flags, MAC, mask, device. The file
+// has no states past complete or not, and an incomplete entry shows an all-zero MAC
+pub fn parse_proc_arp(text: &str) -> Vec<Neighbor> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let f: Vec<&str> = line.split_whitespace().collect();
+            let flags = u32::from_str_radix(f.get(2)?.trim_start_matches("0x"), 16).ok()?;
+            let state = if flags & ATF_PERM != 0 {
+                NeighState::Permanent
+            } else if flags & ATF_COM != 0 {
+                NeighState::Reachable
+            } else {
+                NeighState::Incomplete
+            };
+            Some(Neighbor {
+                ip: f.first()?.parse().ok()?,
+                mac: Some(f.get(3)?.to_string()).filter(|_| flags & ATF_COM != 0),
+                interface: f.get(5)?.to_string(),
+                state,
+            })
+        })
+        .collect()
+}
+
+// `ip -j neigh`: an array of {"dst", "dev", "lladdr", "router", "state": [...]}; the first
+// state listed is the one that matters ("REACHABLE", "NOARP" on some entries)
+pub fn parse_ip_json(text: &str) -> Vec<Neighbor> {
+    let Some(Json::Array(entries)) = Json::parse(text) else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|e| {
+            let state = match e.get("state") {
+                Some(Json::Array(states)) => states.first().and_then(Json::as_str).map_or(NeighState::Unknown, NeighState::parse),
+                _ => NeighState::Unknown,
+            };
+            Some(Neighbor {
+                ip: e.get("dst")?.as_str()?.parse().ok()?,
+                mac: e.get("lladdr").and_then(Json::as_str).map(str::to_string),
+                interface: e.get("dev")?.as_str()?.to_string(),
+                state,
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn ip_neigh(family: &str) -> Vec<Neighbor> {
+    std::process::Command::new("ip")
+        .args([family, "-j", "neigh"])
+        .output()
+        .map(|out| parse_ip_json(&String::from_utf8_lossy(&out.stdout)))
+        .unwrap_or_default()
+}
+
+// without /proc/net/arp (a locked-down container) `ip` gives IPv4 as well
+#[cfg(target_os = "linux")]
+pub fn read() -> Vec<Neighbor> {
+    let mut neighbors = match std::fs::read_to_string("/proc/net/arp") {
+        Ok(text) => parse_proc_arp(&text),
+        Err(_) => ip_neigh("-4"),
+    };
+    neighbors.extend(ip_neigh("-6"));
+    neighbors
+}
+
+// no neighbor table elsewhere yet, the column shows "--"
+#[cfg(not(target_os = "linux"))]
+pub fn read() -> Vec<Neighbor> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))).unwrap()
+    }
+
+    fn states(neighbors: &[Neighbor], interface: &str) -> Vec<(String, NeighState)> {
+        neighbors.iter().filter(|n| n.interface == interface).map(|n| (n.ip.to_string(), n.state)).collect()
+    }
+
+    #[test]
+    fn proc_net_arp() {
+        let neighbors = parse_proc_arp(&fixture("proc_net_arp"));
+        assert_eq!(states(&neighbors, "eth0"), [
+            ("192.168.1.1".to_string(), NeighState::Reachable),
+            ("192.168.1.23".to_string(), NeighState::Incomplete),
+            ("192.168.1.50".to_string(), NeighState::Permanent),
+        ]);
+        assert_eq!(neighbors[0].mac.as_deref(), Some("aa:bb:cc:dd:ee:01"));
+        assert_eq!(neighbors[2].mac, None, "the zero MAC of an incomplete entry");
+        assert_eq!(states(&neighbors, "wlan0"), [("10.0.0.1".to_string(), NeighState::Reachable)]);
+        assert_eq!(count(&neighbors, "eth0"), 3);
+    }
+
+    #[test]
+    fn ip_json() {
+        let neighbors = parse_ip_json(&fixture("ip_neigh.json"));
+        assert_eq!(states(&neighbors, "eth0"), [
+            ("192.168.1.1".to_string(), NeighState::Reachable),
+            ("192.168.1.77".to_string(), NeighState::Failed),
+            ("fe80::1".to_string(), NeighState::Stale),
+            ("2001:db8::5".to_string(), NeighState::Delay),
+        ]);
+        assert_eq!(neighbors[1].mac, None);
+        assert_eq!(neighbors[3].mac.as_deref(), Some("aa:bb:cc:dd:ee:01"));
+        assert_eq!(states(&neighbors, "wlan0"), [("10.0.0.9".to_string(), NeighState::Incomplete)]);
+        // the failed entry isn't counted
+        assert_eq!(count(&neighbors, "eth0"), 3);
+        assert_eq!(count(&neighbors, "lo"), 0);
+    }
+
+    #[test]
+    fn not_json() {
+        assert!(parse_ip_json("Object \"neigh\" is unknown").is_empty());
+        assert!(parse_ip_json("{}").is_empty());
+    }
+}
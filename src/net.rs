@@ -0,0 +1,921 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+
+use sysinfo::Networks;
+
+use crate::adapters::Adapters;
+use crate::containers::Containers;
+use crate::iftype::{classify, IfaceKind, Probe};
+use crate::neigh::Neighbor;
+use crate::route::Route;
+use crate::vlan::Vlan;
+use crate::wg::Peers;
+use crate::wifi::WifiInfo;
+
+#[derive(Clone, Debug, Default)]
+pub struct RowData {
+    pub interface: String,
+    pub rx_bps: f64,
+    pub tx_bps: f64,
+    // moving averages of the two above, shown instead of them with smoothing on
+    pub rx_smooth: f64,
+    pub tx_smooth: f64,
+    pub packets_in: f64,
+    pub packets_out: f64,
+    pub errors_in: f64,
+    pub errors_out: f64,
+    // always 0 where HAS_DROPS is false
+    pub drops_in: f64,
+    pub drops_out: f64,
+    // None where the source has no multicast counter
+    pub multicast_in: Option<f64>,
+    pub totals: Totals,
+    pub info: IfaceInfo,
+}
+
+impl RowData {
+    // percent of line rate; full duplex, so the busier direction counts, not the sum
+    pub fn utilization(&self) -> Option<f64> {
+        let line_bps = self.info.speed_mbps? as f64 * 1e6 / 8.0;
+        Some(self.rx_bps.max(self.tx_bps) / line_bps * 100.0)
+    }
+}
+
+// running counters since nettui started, the OS counters include traffic from before
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Totals {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub packets_in: u64,
+    pub packets_out: u64,
+    pub errors_in: u64,
+    pub errors_out: u64,
+    pub drops_in: u64,
+    pub drops_out: u64,
+    pub multicast_in: u64,
+    // only from sources that read them
+    pub extra: Option<Extra>,
+}
+
+impl Totals {
+    pub fn add(&mut self, d: &Counters) {
+        self.rx_bytes += d.rx_bytes;
+        self.tx_bytes += d.tx_bytes;
+        self.packets_in += d.rx_packets;
+        self.packets_out += d.tx_packets;
+        self.errors_in += d.rx_errors;
+        self.errors_out += d.tx_errors;
+        self.drops_in += d.rx_dropped.unwrap_or(0);
+        self.drops_out += d.tx_dropped.unwrap_or(0);
+        self.multicast_in += d.rx_multicast.unwrap_or(0);
+        if let Some(e) = &d.extra {
+            self.extra.get_or_insert_default().add(e);
+        }
+    }
+}
+
+// the rest of /proc/net/dev, which sysinfo doesn't read
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Extra {
+    pub rx_fifo: u64,
+    pub tx_fifo: u64,
+    pub rx_frame: u64,
+    pub rx_compressed: u64,
+    pub tx_compressed: u64,
+    pub multicast: u64,
+    pub collisions: u64,
+    pub carrier: u64,
+}
+
+impl Extra {
+    fn add(&mut self, d: &Extra) {
+        self.rx_fifo += d.rx_fifo;
+        self.tx_fifo += d.tx_fifo;
+        self.rx_frame += d.rx_frame;
+        self.rx_compressed += d.rx_compressed;
+        self.tx_compressed += d.tx_compressed;
+        self.multicast += d.multicast;
+        self.collisions += d.collisions;
+        self.carrier += d.carrier;
+    }
+
+    // like Counters::since, a counter that went backwards counts as zero
+    fn since(&self, prev: &Extra) -> Extra {
+        let d = |now: u64, before: u64| now.saturating_sub(before);
+        Extra {
+            rx_fifo: d(self.rx_fifo, prev.rx_fifo),
+            tx_fifo: d(self.tx_fifo, prev.tx_fifo),
+            rx_frame: d(self.rx_frame, prev.rx_frame),
+            rx_compressed: d(self.rx_compressed, prev.rx_compressed),
+            tx_compressed: d(self.tx_compressed, prev.tx_compressed),
+            multicast: d(self.multicast, prev.multicast),
+            collisions: d(self.collisions, prev.collisions),
+            carrier: d(self.carrier, prev.carrier),
+        }
+    }
+}
+
+// counters for one interface: cumulative in a snapshot, per interval once diffed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Counters {
+    pub name: String,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    // from sysfs, not sysinfo; None when unsupported or the read failed
+    pub rx_dropped: Option<u64>,
+    pub tx_dropped: Option<u64>,
+    // multicast packets received, from the same places as the drops
+    pub rx_multicast: Option<u64>,
+    pub extra: Option<Extra>,
+}
+
+impl Counters {
+    // a counter that went backwards was reset (driver reload, a container's veth torn
+    // down and re-created under the same name) or wrapped; neither tells how much
+    // really went through, so that interval counts as zero and the new value is the
+    // baseline for the next one, never a negative or multi-terabit rate
+    pub fn since(&self, prev: &Counters) -> Counters {
+        let d = |now: u64, before: u64| now.saturating_sub(before);
+        // a failed read on either side would look like a reset, so that interval has no delta
+        let opt = |now: Option<u64>, before: Option<u64>| Some(d(now?, before?));
+        Counters {
+            name: self.name.clone(),
+            rx_bytes: d(self.rx_bytes, prev.rx_bytes),
+            tx_bytes: d(self.tx_bytes, prev.tx_bytes),
+            rx_packets: d(self.rx_packets, prev.rx_packets),
+            tx_packets: d(self.tx_packets, prev.tx_packets),
+            rx_errors: d(self.rx_errors, prev.rx_errors),
+            tx_errors: d(self.tx_errors, prev.tx_errors),
+            rx_dropped: opt(self.rx_dropped, prev.rx_dropped),
+            tx_dropped: opt(self.tx_dropped, prev.tx_dropped),
+            rx_multicast: opt(self.rx_multicast, prev.rx_multicast),
+            extra: self.extra.zip(prev.extra).map(|(now, before)| now.since(&before)),
+        }
+    }
+}
+
+// an assigned address with its prefix length
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IfaceAddr {
+    pub ip: IpAddr,
+    pub prefix: u8,
+}
+
+impl fmt::Display for IfaceAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.ip, self.prefix)
+    }
+}
+
+// whether the link has carrier, from the kernel's operstate
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LinkState {
+    Up,
+    Down,
+    // no sysfs (not Linux) or the kernel won't say, loopback reports this too
+    #[default]
+    Unknown,
+}
+
+impl LinkState {
+    pub fn label(self) -> &'static str {
+        match self {
+            LinkState::Up => "up",
+            LinkState::Down => "down",
+            LinkState::Unknown => "?",
+        }
+    }
+}
+
+// what an interface is rather than what it's doing, as of the latest read
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IfaceInfo {
+    pub addrs: Vec<IfaceAddr>,
+    // all zeros for devices without one (loopback, tunnels)
+    pub mac: [u8; 6],
+    // 0 when the source doesn't know
+    pub mtu: u64,
+    pub state: LinkState,
+    // negotiated link speed, None for virtual, down and wireless interfaces
+    pub speed_mbps: Option<u64>,
+    pub kind: IfaceKind,
+    // None for wired interfaces
+    pub wifi: Option<WifiInfo>,
+    // the ports of a bond or bridge, empty for anything else
+    pub members: Vec<String>,
+    // the tag and the interface underneath, for VLANs
+    pub vlan: Option<Vlan>,
+    // the network namespace, None for the host's own
+    pub netns: Option<String>,
+}
+
+impl IfaceInfo {
+    // colon-separated lowercase hex, None for the all-zero placeholder
+    pub fn mac_string(&self) -> Option<String> {
+        if self.mac == [0; 6] {
+            return None;
+        }
+        let parts: Vec<String> = self.mac.iter().map(|b| format!("{b:02x}")).collect();
+        Some(parts.join(":"))
+    }
+
+    // the first IPv4, else the first IPv6 that isn't link-local (fe80::/10)
+    pub fn primary_addr(&self) -> Option<IpAddr> {
+        let ips = || self.addrs.iter().map(|a| a.ip);
+        ips().find(IpAddr::is_ipv4).or_else(|| {
+            ips().find(|ip| matches!(ip, IpAddr::V6(v6) if v6.segments()[0] & 0xffc0 != 0xfe80))
+        })
+    }
+}
+
+// where counters come from; sysinfo in the app, a mock in tests
+pub trait NetworkSource {
+    // cumulative counters of every interface present right now
+    fn snapshot(&mut self) -> Vec<Counters>;
+
+    // addresses and the like as of the last snapshot; sources without them report nothing
+    fn info(&self, _name: &str) -> IfaceInfo {
+        IfaceInfo::default()
+    }
+
+    // the routing table; sources without one report nothing
+    fn routes(&self) -> Vec<Route> {
+        Vec::new()
+    }
+
+    // the ARP and NDP neighbor tables, likewise
+    fn neighbors(&self) -> Vec<Neighbor> {
+        Vec::new()
+    }
+
+    // the peers of a WireGuard interface; None where they can't be read at all
+    fn wg_peers(&self, _name: &str) -> Option<Peers> {
+        None
+    }
+
+    // container names by the veth that leads to them
+    fn containers(&self) -> Containers {
+        Containers::new()
+    }
+
+    // (namespace, reason) for every --netns one that couldn't be read in the last snapshot
+    fn netns_errors(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    // friendly names and descriptions of Windows adapters, macOS service names
+    fn adapters(&self) -> Adapters {
+        Adapters::new()
+    }
+}
+
+// main picks the backend at runtime
+impl<S: NetworkSource + ?Sized> NetworkSource for Box<S> {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        (**self).snapshot()
+    }
+
+    fn info(&self, name: &str) -> IfaceInfo {
+        (**self).info(name)
+    }
+
+    fn routes(&self) -> Vec<Route> {
+        (**self).routes()
+    }
+
+    fn neighbors(&self) -> Vec<Neighbor> {
+        (**self).neighbors()
+    }
+
+    fn wg_peers(&self, name: &str) -> Option<Peers> {
+        (**self).wg_peers(name)
+    }
+
+    fn containers(&self) -> Containers {
+        (**self).containers()
+    }
+
+    fn netns_errors(&self) -> Vec<(String, String)> {
+        (**self).netns_errors()
+    }
+
+    fn adapters(&self) -> Adapters {
+        (**self).adapters()
+    }
+}
+
+// whether the drop columns mean anything here: sysinfo has no drop counters, only Linux
+// sysfs does, and elsewhere the columns are left out rather than showing zeros
+pub const HAS_DROPS: bool = cfg!(target_os = "linux");
+
+// the same goes for the multicast column
+pub const HAS_MULTICAST: bool = cfg!(target_os = "linux");
+
+impl NetworkSource for Networks {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        //re-reads the interface list every sample: drops interfaces that went away and picks
+        //up new ones, so a freshly created veth shows on the next tick without a restart
+        self.refresh(true);
+        self.iter()
+            .map(|(name, data)| {
+                let (rx_dropped, tx_dropped) = dropped(name);
+                Counters {
+                    name: name.to_string(),
+                    rx_bytes: data.total_received(),
+                    tx_bytes: data.total_transmitted(),
+                    rx_packets: data.total_packets_received(),
+                    tx_packets: data.total_packets_transmitted(),
+                    rx_errors: data.total_errors_on_received(),
+                    tx_errors: data.total_errors_on_transmitted(),
+                    rx_dropped,
+                    tx_dropped,
+                    rx_multicast: multicast(name),
+                    extra: None,
+                }
+            })
+            .collect()
+    }
+
+    // sysinfo re-reads all of this when the interface list is refreshed, which happens with
+    // every snapshot, so a DHCP renewal or an MTU change shows up on the next sample
+    fn info(&self, name: &str) -> IfaceInfo {
+        let Some(data) = self.list().get(name) else {
+            return IfaceInfo::default();
+        };
+        IfaceInfo {
+            addrs: data.ip_networks().iter().map(|n| IfaceAddr { ip: n.addr, prefix: n.prefix }).collect(),
+            mac: data.mac_address().0,
+            mtu: data.mtu(),
+            ..link_info(name)
+        }
+    }
+
+    fn routes(&self) -> Vec<Route> {
+        crate::route::read()
+    }
+
+    fn neighbors(&self) -> Vec<Neighbor> {
+        crate::neigh::read()
+    }
+
+    fn wg_peers(&self, name: &str) -> Option<Peers> {
+        crate::wg::show(name)
+    }
+
+    fn containers(&self) -> Containers {
+        crate::containers::read()
+    }
+
+    fn adapters(&self) -> Adapters {
+        crate::adapters::read()
+    }
+}
+
+// what every backend gets from sysfs and the like rather than from its own reads: all of
+// IfaceInfo but the addresses, MAC and MTU
+pub fn link_info(name: &str) -> IfaceInfo {
+    let kind = classify(name, &probe(name));
+    IfaceInfo {
+        state: link_state(name),
+        speed_mbps: link_speed(name),
+        kind,
+        wifi: crate::wifi::wireless(name),
+        members: link_members(name),
+        vlan: if kind == IfaceKind::Vlan { crate::vlan::lookup(name) } else { None },
+        ..Default::default()
+    }
+}
+
+// read once per sample along with the rest of the info, so a renegotiated speed shows up
+// on the next one
+#[cfg(target_os = "linux")]
+fn link_state(name: &str) -> LinkState {
+    crate::sysfs::SysFs::default().operstate(name)
+}
+
+#[cfg(target_os = "linux")]
+fn link_speed(name: &str) -> Option<u64> {
+    crate::sysfs::SysFs::default().speed_mbps(name)
+}
+
+#[cfg(target_os = "linux")]
+fn link_members(name: &str) -> Vec<String> {
+    crate::sysfs::SysFs::default().members(name)
+}
+
+#[cfg(target_os = "linux")]
+fn probe(name: &str) -> Probe {
+    crate::sysfs::SysFs::default().probe(name)
+}
+
+#[cfg(target_os = "linux")]
+fn dropped(name: &str) -> (Option<u64>, Option<u64>) {
+    let sys = crate::sysfs::SysFs::default();
+    (sys.statistic(name, "rx_dropped"), sys.statistic(name, "tx_dropped"))
+}
+
+#[cfg(target_os = "linux")]
+fn multicast(name: &str) -> Option<u64> {
+    crate::sysfs::SysFs::default().statistic(name, "multicast")
+}
+
+#[cfg(not(target_os = "linux"))]
+fn link_state(_name: &str) -> LinkState {
+    LinkState::Unknown
+}
+
+#[cfg(not(target_os = "linux"))]
+fn link_speed(_name: &str) -> Option<u64> {
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn link_members(_name: &str) -> Vec<String> {
+    Vec::new()
+}
+
+// names are all there is to go on
+#[cfg(not(target_os = "linux"))]
+fn probe(_name: &str) -> Probe {
+    Probe::default()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn dropped(_name: &str) -> (Option<u64>, Option<u64>) {
+    (None, None)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn multicast(_name: &str) -> Option<u64> {
+    None
+}
+
+// shell-style match supporting `*` (any run) and `?` (one character)
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    // position of the last `*` and the name index it is currently absorbing up to
+    let mut star: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ni));
+            pi += 1;
+        } else if let Some((sp, sn)) = star {
+            pi = sp + 1;
+            ni = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+// which interfaces make it into the table
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    // --interface patterns, empty keeps everything
+    pub include: Vec<String>,
+    // --exclude patterns
+    pub exclude: Vec<String>,
+    pub show_virtual: bool,
+}
+
+impl Filter {
+    // an interface is shown only if it passes all three checks, so an exclude
+    // pattern wins over an include pattern matching the same name, and virtual
+    // interfaces stay hidden until toggled on even when included
+    pub fn keep(&self, name: &str) -> bool {
+        if self.exclude.iter().any(|p| glob_match(p, name)) {
+            return false;
+        }
+        if !self.include.is_empty() && !self.include.iter().any(|p| glob_match(p, name)) {
+            return false;
+        }
+        self.show_virtual || !is_virtual_interface(name)
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.include.is_empty() || !self.exclude.is_empty()
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.include.is_empty() {
+            parts.push(self.include.join(","));
+        }
+        if !self.exclude.is_empty() {
+            parts.push(format!("not {}", self.exclude.join(",")));
+        }
+        parts.join(" ")
+    }
+}
+
+// name prefixes of loopback, container, bridge and hypervisor interfaces
+const VIRTUAL_PREFIXES: &[&str] = &["lo", "veth", "docker", "br-", "vmnet", "virbr"];
+
+fn has_virtual_prefix(name: &str) -> bool {
+    VIRTUAL_PREFIXES.iter().any(|p| name.starts_with(p))
+}
+
+// sysfs only has the host's interfaces, one in another namespace goes by its own name
+fn is_virtual_interface(name: &str) -> bool {
+    match crate::netns::split(name) {
+        Some((_, local)) => is_virtual(local, &Probe::default()),
+        None => is_virtual(name, &probe(name)),
+    }
+}
+
+// the kind decides where it can; otherwise physical NICs have a `device` link under
+// /sys/class/net/<name>, and interfaces missing from sysfs entirely (no /sys mounted,
+// other namespaces, not Linux) fall back to the prefix list
+fn is_virtual(name: &str, probe: &Probe) -> bool {
+    classify(name, probe).is_virtual().unwrap_or_else(|| {
+        if probe.present { !probe.device } else { has_virtual_prefix(name) }
+    })
+}
+
+// per-interface counters from the previous snapshot, what the next one is diffed against
+pub type Baseline = HashMap<String, Counters>;
+
+// one snapshot diffed against `baseline`, which then moves forward; interfaces seen for
+// the first time only get a baseline, and ones that vanished are forgotten so they start
+// fresh if they come back
+pub fn deltas(source: &mut impl NetworkSource, baseline: &mut Baseline) -> Vec<Counters> {
+    let snapshot = source.snapshot();
+    let deltas = snapshot
+        .iter()
+        .map(|now| match baseline.get(&now.name) {
+            Some(prev) => now.since(prev),
+            None => Counters { name: now.name.clone(), ..Default::default() },
+        })
+        .collect();
+    *baseline = snapshot.into_iter().map(|c| (c.name.clone(), c)).collect();
+    deltas
+}
+
+pub fn collect(
+    networks: &mut impl NetworkSource,
+    baseline: &mut Baseline,
+    totals: &mut HashMap<String, Totals>,
+    interval_secs: f64,
+    filter: &Filter,
+) -> Vec<RowData> {
+    //if interval is 0, convert to 1, as we will be divinding it, can't divide by zero
+    let interval_secs = if interval_secs <=0.0 {1.0} else { interval_secs };
+
+    let mut rows: Vec<RowData> = Vec::new();
+
+    //read network counters, the only read per sample
+    for data in deltas(networks, baseline) {
+        //accumulate before filtering so totals stay correct for hidden interfaces
+        let total = totals.entry(data.name.clone()).or_default();
+        total.add(&data);
+        let total = *total;
+
+        // every interface is still read above, so a hidden one keeps its
+        // baseline and shows a normal rate as soon as it is toggled back on
+        if !filter.keep(&data.name) {
+            continue;
+        }
+
+        //deltas are bytes since the previous snapshot
+        let rx_bps = data.rx_bytes as f64 / interval_secs;
+        let tx_bps = data.tx_bytes as f64 / interval_secs;
+
+        let row = RowData {
+            interface: data.name.clone(),
+            rx_bps,
+            tx_bps,
+            // seeded with the sample itself, the app averages it against the previous row
+            rx_smooth: rx_bps,
+            tx_smooth: tx_bps,
+            packets_in: data.rx_packets as f64 / interval_secs,
+            packets_out: data.tx_packets as f64 / interval_secs,
+            errors_in: data.rx_errors as f64 / interval_secs,
+            errors_out: data.tx_errors as f64 / interval_secs,
+            drops_in: data.rx_dropped.unwrap_or(0) as f64 / interval_secs,
+            drops_out: data.tx_dropped.unwrap_or(0) as f64 / interval_secs,
+            multicast_in: data.rx_multicast.map(|n| n as f64 / interval_secs),
+            totals: total,
+            info: networks.info(&data.name),
+        };
+
+        rows.push(row);
+    }
+
+    rows
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // interfaces with cumulative counters that tests bump, reset or remove between reads
+    #[derive(Default)]
+    struct MockSource {
+        ifaces: Vec<Counters>,
+        reads: usize,
+    }
+
+    impl MockSource {
+        fn with(names: &[&str]) -> Self {
+            let mut source = MockSource::default();
+            for name in names {
+                source.add(name, 0, 0);
+            }
+            source
+        }
+
+        // traffic since the last read; adds the interface if it isn't there yet
+        fn add(&mut self, name: &str, rx: u64, tx: u64) {
+            let iface = match self.ifaces.iter().position(|c| c.name == name) {
+                Some(i) => &mut self.ifaces[i],
+                None => {
+                    self.ifaces.push(Counters { name: name.to_string(), ..Default::default() });
+                    self.ifaces.last_mut().unwrap()
+                }
+            };
+            iface.rx_bytes += rx;
+            iface.tx_bytes += tx;
+            iface.rx_packets += rx / 1000;
+            iface.tx_packets += tx / 1000;
+        }
+
+        // counters start over, as after a driver reload or a 32-bit wrap
+        fn reset(&mut self, name: &str, rx: u64) {
+            let iface = self.ifaces.iter_mut().find(|c| c.name == name).unwrap();
+            *iface = Counters { name: name.to_string(), rx_bytes: rx, ..Default::default() };
+        }
+
+        fn remove(&mut self, name: &str) {
+            self.ifaces.retain(|c| c.name != name);
+        }
+    }
+
+    impl NetworkSource for MockSource {
+        fn snapshot(&mut self) -> Vec<Counters> {
+            self.reads += 1;
+            self.ifaces.clone()
+        }
+    }
+
+    // keeps the baseline and totals between samples like the app does
+    #[derive(Default)]
+    struct Sampler {
+        baseline: Baseline,
+        totals: HashMap<String, Totals>,
+    }
+
+    impl Sampler {
+        fn primed(source: &mut MockSource) -> Self {
+            let mut sampler = Sampler::default();
+            deltas(source, &mut sampler.baseline);
+            sampler
+        }
+
+        fn sample(&mut self, source: &mut MockSource, secs: f64) -> Vec<RowData> {
+            collect(source, &mut self.baseline, &mut self.totals, secs, &Filter { show_virtual: true, ..Default::default() })
+        }
+
+        fn rx(&mut self, source: &mut MockSource, secs: f64) -> Vec<(String, f64)> {
+            self.sample(source, secs).into_iter().map(|r| (r.interface, r.rx_bps)).collect()
+        }
+    }
+
+    fn rates(v: &[(&str, f64)]) -> Vec<(String, f64)> {
+        v.iter().map(|(n, r)| (n.to_string(), *r)).collect()
+    }
+
+    #[test]
+    fn one_sample_reads_once() {
+        let mut source = MockSource::with(&["eth0"]);
+        let mut sampler = Sampler::primed(&mut source);
+        source.add("eth0", 2048, 0);
+        let rows = sampler.sample(&mut source, 2.0);
+        assert_eq!(source.reads, 2);
+        assert_eq!(rows[0].rx_bps, 1024.0);
+        assert_eq!(rows[0].packets_in, 1.0);
+    }
+
+    #[test]
+    fn first_read_is_only_a_baseline() {
+        // counters since boot are not traffic of the first interval
+        let mut source = MockSource::with(&["eth0"]);
+        source.add("eth0", 1 << 40, 0);
+        let mut sampler = Sampler::default();
+        assert_eq!(sampler.rx(&mut source, 1.0), rates(&[("eth0", 0.0)]));
+        source.add("eth0", 500, 0);
+        assert_eq!(sampler.rx(&mut source, 1.0), rates(&[("eth0", 500.0)]));
+        assert_eq!(sampler.totals["eth0"].rx_bytes, 500);
+    }
+
+    #[test]
+    fn rates_follow_the_measured_interval() {
+        let mut source = MockSource::with(&["eth0"]);
+        let mut sampler = Sampler::primed(&mut source);
+
+        // a burst over a short tick, then idle, then a slow tick
+        source.add("eth0", 10_000_000, 1_000);
+        let row = &sampler.sample(&mut source, 0.5)[0];
+        assert_eq!((row.rx_bps, row.tx_bps), (20_000_000.0, 2_000.0));
+        assert_eq!(sampler.rx(&mut source, 0.5), rates(&[("eth0", 0.0)]));
+        source.add("eth0", 3_000, 0);
+        assert_eq!(sampler.rx(&mut source, 3.0), rates(&[("eth0", 1_000.0)]));
+        assert_eq!(sampler.totals["eth0"].rx_bytes, 10_003_000);
+
+        // a zero interval must not divide by zero
+        source.add("eth0", 100, 0);
+        assert_eq!(sampler.rx(&mut source, 0.0), rates(&[("eth0", 100.0)]));
+    }
+
+    #[test]
+    fn interfaces_appear_and_disappear() {
+        let mut source = MockSource::with(&["eth0"]);
+        let mut sampler = Sampler::primed(&mut source);
+
+        // wg0 comes up with traffic already on its counters
+        source.add("wg0", 5_000, 0);
+        source.add("eth0", 100, 0);
+        assert_eq!(sampler.rx(&mut source, 1.0), rates(&[("eth0", 100.0), ("wg0", 0.0)]));
+        source.add("wg0", 200, 0);
+        assert_eq!(sampler.rx(&mut source, 1.0), rates(&[("eth0", 0.0), ("wg0", 200.0)]));
+
+        // gone, then back with fresh counters: a new baseline, no negative or stale rate
+        source.remove("wg0");
+        assert_eq!(sampler.rx(&mut source, 1.0), rates(&[("eth0", 0.0)]));
+        source.add("wg0", 50, 0);
+        assert_eq!(sampler.rx(&mut source, 1.0), rates(&[("eth0", 0.0), ("wg0", 0.0)]));
+        source.add("wg0", 70, 0);
+        assert_eq!(sampler.rx(&mut source, 1.0), rates(&[("eth0", 0.0), ("wg0", 70.0)]));
+        assert_eq!(sampler.totals["wg0"].rx_bytes, 270, "totals survive the gap");
+    }
+
+    #[test]
+    fn counter_reset_or_wrap_is_not_a_spike() {
+        let mut source = MockSource::with(&["eth0"]);
+        source.add("eth0", u32::MAX as u64 - 100, 0);
+        let mut sampler = Sampler::primed(&mut source);
+
+        source.reset("eth0", 400);
+        assert_eq!(sampler.rx(&mut source, 1.0), rates(&[("eth0", 0.0)]));
+        source.add("eth0", 600, 0);
+        assert_eq!(sampler.rx(&mut source, 1.0), rates(&[("eth0", 600.0)]));
+        assert_eq!(sampler.totals["eth0"].rx_bytes, 600, "nothing invented for the reset interval");
+    }
+
+    #[test]
+    fn recreated_interface_keeps_its_row() {
+        // a container restart: veth0 goes away and comes back under the same name between
+        // two reads, with fresh counters below the old ones
+        let mut source = MockSource::with(&["eth0", "veth0"]);
+        source.add("veth0", 50_000_000, 20_000_000);
+        let mut sampler = Sampler::primed(&mut source);
+
+        source.remove("veth0");
+        source.add("veth0", 3_000, 1_000);
+        let rows = sampler.sample(&mut source, 0.5);
+        let veth = rows.iter().find(|r| r.interface == "veth0").expect("row stays");
+        assert_eq!((veth.rx_bps, veth.tx_bps, veth.packets_in), (0.0, 0.0, 0.0));
+
+        source.add("veth0", 1_000, 0);
+        assert_eq!(sampler.rx(&mut source, 0.5), rates(&[("eth0", 0.0), ("veth0", 2_000.0)]));
+    }
+
+    #[test]
+    fn failed_drop_reads_are_not_a_spike() {
+        let prev = Counters { name: "eth0".to_string(), rx_dropped: Some(5_000), tx_dropped: None, ..Default::default() };
+        let now = Counters { rx_dropped: Some(5_003), tx_dropped: Some(7), ..prev.clone() };
+        let d = now.since(&prev);
+        assert_eq!((d.rx_dropped, d.tx_dropped), (Some(3), None));
+        // the read failed this time, the next one diffs against nothing
+        let gone = Counters { rx_dropped: None, ..prev.clone() };
+        assert_eq!(gone.since(&prev).rx_dropped, None);
+        assert_eq!(now.since(&gone).rx_dropped, None);
+
+        let mut totals = Totals::default();
+        totals.add(&d);
+        assert_eq!((totals.drops_in, totals.drops_out), (3, 0));
+    }
+
+    #[test]
+    fn multicast_rates() {
+        let mut source = MockSource::with(&["eth0", "tun0"]);
+        source.ifaces[0].rx_multicast = Some(u64::MAX - 10);
+        let mut sampler = Sampler::primed(&mut source);
+        source.ifaces[0].rx_multicast = Some(u64::MAX);
+        let rows = sampler.sample(&mut source, 0.5);
+        let multicast: Vec<_> = rows.iter().map(|r| (r.interface.as_str(), r.multicast_in)).collect();
+        assert_eq!(multicast, [("eth0", Some(20.0)), ("tun0", None)]);
+        // wrapped: zero for that interval, never a spike
+        source.ifaces[0].rx_multicast = Some(4);
+        let rows = sampler.sample(&mut source, 1.0);
+        assert_eq!(rows[0].multicast_in, Some(0.0));
+        assert_eq!(rows[0].totals.multicast_in, 10);
+    }
+
+    #[test]
+    fn hidden_interfaces_keep_counting() {
+        let mut source = MockSource::with(&["eth0", "docker0"]);
+        let mut sampler = Sampler::primed(&mut source);
+        source.add("docker0", 1_000, 0);
+        let filter = Filter { exclude: vec!["docker*".to_string()], show_virtual: true, ..Default::default() };
+        let rows = collect(&mut source, &mut sampler.baseline, &mut sampler.totals, 1.0, &filter);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(sampler.totals["docker0"].rx_bytes, 1_000);
+
+        // shown again: a normal rate, not everything since it was hidden
+        source.add("docker0", 300, 0);
+        assert_eq!(sampler.rx(&mut source, 1.0), rates(&[("eth0", 0.0), ("docker0", 300.0)]));
+    }
+
+    #[test]
+    fn primary_address() {
+        let addr = |s: &str| {
+            let (ip, prefix) = s.split_once('/').unwrap();
+            IfaceAddr { ip: ip.parse().unwrap(), prefix: prefix.parse().unwrap() }
+        };
+        let info = |addrs: &[&str]| IfaceInfo { addrs: addrs.iter().map(|a| addr(a)).collect(), ..Default::default() };
+        assert_eq!(info(&[]).primary_addr(), None);
+        assert_eq!(info(&["fe80::1/64", "2001:db8::5/64", "10.0.0.2/8"]).primary_addr(), Some("10.0.0.2".parse().unwrap()));
+        assert_eq!(info(&["fe80::1/64", "2001:db8::5/64"]).primary_addr(), Some("2001:db8::5".parse().unwrap()));
+        assert_eq!(info(&["fe80::1/64"]).primary_addr(), None);
+        assert_eq!(addr("192.168.1.10/24").to_string(), "192.168.1.10/24");
+    }
+
+    #[test]
+    fn mac_formatting() {
+        let mut info = IfaceInfo::default();
+        assert_eq!(info.mac_string(), None);
+        info.mac = [0x02, 0x42, 0xac, 0x11, 0x00, 0x0a];
+        assert_eq!(info.mac_string().as_deref(), Some("02:42:ac:11:00:0a"));
+    }
+
+    #[test]
+    fn filter_precedence() {
+        let patterns = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let mut filter = Filter { include: patterns(&["eth*", "wg0"]), exclude: patterns(&["eth1"]), show_virtual: true };
+        assert!(filter.keep("eth0"));
+        assert!(filter.keep("wg0"));
+        assert!(!filter.keep("eth1"), "exclude wins over include");
+        assert!(!filter.keep("wlan0"), "not included");
+
+        filter.include.clear();
+        filter.exclude.clear();
+        filter.show_virtual = false;
+        assert!(!filter.keep("docker0"), "virtual hidden by default");
+        filter.show_virtual = true;
+        assert!(filter.keep("docker0"));
+        filter.exclude = patterns(&["docker*"]);
+        assert!(!filter.keep("docker0"), "exclude wins over the virtual toggle");
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_match("eth0", "eth0"));
+        assert!(!glob_match("eth0", "eth01"));
+        assert!(glob_match("eth*", "eth0"));
+        assert!(glob_match("eth*", "eth"));
+        assert!(glob_match("*0", "wlan0"));
+        assert!(glob_match("w*n?", "wlan0"));
+        assert!(glob_match("*a*a*", "banana"));
+        assert!(!glob_match("wg?", "wg10"));
+        assert!(!glob_match("eth*", "veth0"));
+    }
+
+    #[test]
+    fn virtual_interface_prefixes() {
+        for name in ["lo", "veth1a2b3c", "docker0", "br-9f8e7d", "vmnet8", "virbr0"] {
+            assert!(has_virtual_prefix(name), "{name} should be virtual");
+        }
+        for name in ["eth0", "enp3s0", "wlan0", "wlp2s0", "bridge0"] {
+            assert!(!has_virtual_prefix(name), "{name} should not be virtual");
+        }
+    }
+
+    #[test]
+    fn virtual_interface_sysfs() {
+        let sys = |arphrd, device| Probe { present: true, arphrd: Some(arphrd), device, ..Default::default() };
+        // eth0 is a real NIC, tap0 and lo have no device link, ghost0 isn't in sysfs
+        assert!(!is_virtual("eth0", &sys(1, true)));
+        assert!(is_virtual("tap0", &Probe { tun: true, ..sys(1, false) }));
+        assert!(is_virtual("lo", &sys(772, false)));
+        assert!(!is_virtual("ghost0", &Probe::default()));
+        assert!(is_virtual("veth9", &Probe::default()));
+        // no device link, but a VPN's traffic is its own
+        assert!(!is_virtual("wg0", &Probe { devtype: Some("wireguard".to_string()), ..sys(65534, false) }));
+        assert!(!is_virtual("bond0", &Probe { bonding: true, ..sys(1, false) }));
+        // unclassified and not hardware
+        assert!(is_virtual("dummy0", &sys(1, false)));
+    }
+}
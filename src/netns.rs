@@ -0,0 +1,316 @@
+// interfaces in other network namespaces: the ones `ip netns add` bind-mounts under
+// /var/run/netns and the ones only a process holds (containers), told apart by the inode
+// of their nsfs file. Counters come from /proc/<pid>/net/dev of a process inside, or, for a
+// namespace nothing runs in, from a helper thread that enters it with setns. Names get the
+// namespace in front, "blue:eth0"
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::adapters::Adapters;
+use crate::containers::Containers;
+use crate::iftype::{classify, Probe};
+use crate::neigh::Neighbor;
+use crate::net::{Counters, IfaceInfo, NetworkSource};
+use crate::route::Route;
+use crate::wg::Peers;
+
+// namespaces come and go with containers; listing them walks every process
+pub const NETNS_EVERY: Duration = Duration::from_secs(10);
+
+// --netns names (or pids) and --all-netns
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selection {
+    pub names: Vec<String>,
+    pub all: bool,
+}
+
+impl Selection {
+    pub fn is_empty(&self) -> bool {
+        !self.all && self.names.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Namespace {
+    pub name: String,
+    // a process inside, whose /proc view shows the namespace's interfaces
+    pub pid: Option<u32>,
+    // the bind mount of a named namespace, what setns needs when no process is inside
+    pub path: Option<PathBuf>,
+}
+
+// "blue:eth0" -> ("blue", "eth0"); interface names can't hold a ':' of their own
+pub fn split(name: &str) -> Option<(&str, &str)> {
+    name.split_once(':')
+}
+
+// the namespaces to read out of what's on the system: `named` are the files under
+// /var/run/netns with their inodes, `procs` (pid, command, inode) every process that
+// could be looked at, in pid order. The host's own namespace is never one of them, and a
+// namespace with no name is called after its first process, "nginx-4321". Names asked
+// for that match nothing come back as errors
+pub fn pick(
+    named: &[(String, u64)],
+    procs: &[(u32, String, u64)],
+    host: Option<u64>,
+    selection: &Selection,
+) -> (Vec<Namespace>, Vec<(String, String)>) {
+    let mut first: HashMap<u64, (u32, &str)> = HashMap::new();
+    for (pid, comm, inode) in procs {
+        first.entry(*inode).or_insert((*pid, comm));
+    }
+    let mut spaces: Vec<(Namespace, u64)> = named
+        .iter()
+        .filter(|(_, inode)| Some(*inode) != host)
+        .map(|(name, inode)| {
+            let ns = Namespace { name: name.clone(), pid: first.get(inode).map(|f| f.0), path: Some(netns_dir().join(name)) };
+            (ns, *inode)
+        })
+        .collect();
+    let mut unnamed: Vec<(u64, u32, &str)> = first
+        .iter()
+        .filter(|(inode, _)| Some(**inode) != host && !named.iter().any(|(_, i)| i == *inode))
+        .map(|(inode, (pid, comm))| (*inode, *pid, *comm))
+        .collect();
+    unnamed.sort_by_key(|u| u.1);
+    spaces.extend(unnamed.into_iter().map(|(inode, pid, comm)| {
+        (Namespace { name: format!("{comm}-{pid}"), pid: Some(pid), path: None }, inode)
+    }));
+    if selection.all {
+        return (spaces.into_iter().map(|(ns, _)| ns).collect(), Vec::new());
+    }
+    // a pid asks for whatever namespace that process is in, under the name given
+    let by_pid = |want: &str| {
+        let pid: u32 = want.parse().ok()?;
+        let inode = procs.iter().find(|p| p.0 == pid)?.2;
+        let (ns, _) = spaces.iter().find(|(_, i)| *i == inode)?;
+        Some(Namespace { name: want.to_string(), ..ns.clone() })
+    };
+    let mut picked = Vec::new();
+    let mut missing = Vec::new();
+    for want in &selection.names {
+        match spaces.iter().find(|(ns, _)| ns.name == *want).map(|(ns, _)| ns.clone()).or_else(|| by_pid(want)) {
+            Some(ns) => picked.push(ns),
+            None => missing.push((want.clone(), "no such namespace".to_string())),
+        }
+    }
+    (picked, missing)
+}
+
+fn netns_dir() -> PathBuf {
+    PathBuf::from("/var/run/netns")
+}
+
+// the part of an error worth a status line
+fn describe(e: &io::Error) -> String {
+    match e.kind() {
+        io::ErrorKind::PermissionDenied => "permission denied (needs root)".to_string(),
+        io::ErrorKind::NotFound => "gone".to_string(),
+        _ => e.to_string(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn list(selection: &Selection) -> (Vec<Namespace>, Vec<(String, String)>) {
+    use std::os::unix::fs::MetadataExt;
+
+    let inode = |path: PathBuf| std::fs::metadata(path).ok().map(|m| m.ino());
+    let named: Vec<(String, u64)> = std::fs::read_dir(netns_dir())
+        .map(|dir| dir.flatten().filter_map(|e| Some((e.file_name().to_str()?.to_string(), inode(e.path())?))).collect())
+        .unwrap_or_default();
+    // other users' processes can't be looked into without root and are skipped
+    let mut procs: Vec<(u32, String, u64)> = std::fs::read_dir("/proc")
+        .map(|dir| {
+            dir.flatten()
+                .filter_map(|e| {
+                    let pid: u32 = e.file_name().to_str()?.parse().ok()?;
+                    let comm = std::fs::read_to_string(e.path().join("comm")).ok()?;
+                    Some((pid, comm.trim().to_string(), inode(e.path().join("ns/net"))?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    procs.sort_unstable_by_key(|p| p.0);
+    pick(&named, &procs, inode(PathBuf::from("/proc/self/ns/net")), selection)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn list(selection: &Selection) -> (Vec<Namespace>, Vec<(String, String)>) {
+    let missing = selection.names.iter().map(|n| (n.clone(), "network namespaces are Linux only".to_string())).collect();
+    (Vec::new(), missing)
+}
+
+// the namespace's /proc/net/dev: through a process inside if there is one, else from inside
+fn read(ns: &Namespace) -> Result<String, String> {
+    if let Some(pid) = ns.pid {
+        match std::fs::read_to_string(format!("/proc/{pid}/net/dev")) {
+            Ok(text) => return Ok(text),
+            // the process may have exited since the listing
+            Err(e) if ns.path.is_none() => return Err(describe(&e)),
+            Err(_) => {}
+        }
+    }
+    match &ns.path {
+        Some(path) => enter(path.clone()),
+        None => Err("gone".to_string()),
+    }
+}
+
+// setns only moves the calling thread, so a short-lived one does the read and the rest of
+// the process never leaves the host's namespace
+#[cfg(all(feature = "netns", target_os = "linux"))]
+fn enter(path: PathBuf) -> Result<String, String> {
+    use std::os::fd::AsRawFd;
+
+    std::thread::spawn(move || {
+        let file = std::fs::File::open(&path).map_err(|e| describe(&e))?;
+        // SAFETY: a valid namespace fd for the duration of the call; the thread ends right after
+        if unsafe { libc::setns(file.as_raw_fd(), libc::CLONE_NEWNET) } != 0 {
+            return Err(describe(&io::Error::last_os_error()));
+        }
+        // /proc/self/net follows the main thread, thread-self this one
+        std::fs::read_to_string("/proc/thread-self/net/dev").map_err(|e| describe(&e))
+    })
+    .join()
+    .unwrap_or_else(|_| Err("reader thread panicked".to_string()))
+}
+
+#[cfg(not(all(feature = "netns", target_os = "linux")))]
+fn enter(_path: PathBuf) -> Result<String, String> {
+    Err("no process inside; entering it needs the netns feature".to_string())
+}
+
+// the host's interfaces from `host` plus those of the selected namespaces; with nothing
+// selected it's `host` and nothing more
+pub struct Namespaced<S> {
+    host: S,
+    selection: Selection,
+    spaces: Vec<Namespace>,
+    missing: Vec<(String, String)>,
+    errors: Vec<(String, String)>,
+    listed_at: Option<Instant>,
+}
+
+impl<S: NetworkSource> Namespaced<S> {
+    pub fn new(host: S, selection: Selection) -> Namespaced<S> {
+        Namespaced { host, selection, spaces: Vec::new(), missing: Vec::new(), errors: Vec::new(), listed_at: None }
+    }
+}
+
+impl<S: NetworkSource> NetworkSource for Namespaced<S> {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        let mut counters = self.host.snapshot();
+        if self.selection.is_empty() {
+            return counters;
+        }
+        let now = Instant::now();
+        if self.listed_at.is_none_or(|at| now.saturating_duration_since(at) >= NETNS_EVERY) {
+            (self.spaces, self.missing) = list(&self.selection);
+            self.listed_at = Some(now);
+        }
+        self.errors = self.missing.clone();
+        for ns in &self.spaces {
+            match read(ns) {
+                Ok(text) => counters.extend(
+                    crate::procfs::parse_net_dev(&text)
+                        .into_iter()
+                        .map(|c| Counters { name: format!("{}:{}", ns.name, c.name), ..c }),
+                ),
+                Err(e) => self.errors.push((ns.name.clone(), e)),
+            }
+        }
+        counters
+    }
+
+    // sysfs and sysinfo only see the host's namespace, the name is all there is to go on
+    fn info(&self, name: &str) -> IfaceInfo {
+        match split(name).filter(|(ns, _)| self.spaces.iter().any(|s| s.name == *ns)) {
+            Some((ns, local)) => {
+                IfaceInfo { kind: classify(local, &Probe::default()), netns: Some(ns.to_string()), ..Default::default() }
+            }
+            None => self.host.info(name),
+        }
+    }
+
+    fn routes(&self) -> Vec<Route> {
+        self.host.routes()
+    }
+
+    fn neighbors(&self) -> Vec<Neighbor> {
+        self.host.neighbors()
+    }
+
+    fn wg_peers(&self, name: &str) -> Option<Peers> {
+        self.host.wg_peers(name)
+    }
+
+    fn containers(&self) -> Containers {
+        self.host.containers()
+    }
+
+    fn netns_errors(&self) -> Vec<(String, String)> {
+        self.errors.clone()
+    }
+
+    fn adapters(&self) -> Adapters {
+        self.host.adapters()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(spaces: &[Namespace]) -> Vec<(&str, Option<u32>)> {
+        spaces.iter().map(|ns| (ns.name.as_str(), ns.pid)).collect()
+    }
+
+    #[test]
+    fn picks_namespaces() {
+        let named = [("blue".to_string(), 20), ("red".to_string(), 30), ("host".to_string(), 10)];
+        let procs = [
+            (1, "systemd".to_string(), 10),
+            (400, "dnsmasq".to_string(), 20),
+            (500, "nginx".to_string(), 40),
+            (501, "nginx".to_string(), 40),
+            (600, "redis".to_string(), 50),
+        ];
+        let all = Selection { all: true, ..Default::default() };
+        let (spaces, missing) = pick(&named, &procs, Some(10), &all);
+        // the host's namespace is left out even when it has a name
+        assert_eq!(names(&spaces), [("blue", Some(400)), ("red", None), ("nginx-500", Some(500)), ("redis-600", Some(600))]);
+        assert_eq!(spaces[1].path, Some(PathBuf::from("/var/run/netns/red")));
+        assert_eq!(spaces[2].path, None);
+        assert!(missing.is_empty());
+
+        let some = Selection { names: vec!["red".to_string(), "501".to_string(), "green".to_string()], all: false };
+        let (spaces, missing) = pick(&named, &procs, Some(10), &some);
+        assert_eq!(names(&spaces), [("red", None), ("501", Some(500))]);
+        assert_eq!(missing, [("green".to_string(), "no such namespace".to_string())]);
+        assert!(pick(&named, &procs, Some(10), &Selection::default()).0.is_empty());
+    }
+
+    // the host's counters plus nothing, a selection that matches nothing reports it
+    struct Host;
+
+    impl NetworkSource for Host {
+        fn snapshot(&mut self) -> Vec<Counters> {
+            vec![Counters { name: "eth0".to_string(), ..Default::default() }]
+        }
+    }
+
+    #[test]
+    fn host_only_without_a_selection() {
+        let mut source = Namespaced::new(Host, Selection::default());
+        assert_eq!(source.snapshot().len(), 1);
+        assert!(source.netns_errors().is_empty());
+        assert_eq!(split("blue:eth0"), Some(("blue", "eth0")));
+        assert_eq!(source.info("blue:eth0"), IfaceInfo::default(), "not a namespace it knows");
+
+        let mut source = Namespaced::new(Host, Selection { names: vec!["no-such-namespace-here".to_string()], all: false });
+        assert_eq!(source.snapshot().len(), 1);
+        assert_eq!(source.netns_errors().len(), 1);
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::net::{LinkState, RowData};
+
+// one desktop notification
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Note {
+    pub interface: String,
+    pub summary: String,
+    pub body: String,
+}
+
+// decides what is worth a notification: carrier lost or back on an interface the filter
+// keeps, and with `alerts` each alert that fires. No more than one per interface per
+// `every`; the rest are dropped rather than queued, the table still shows them
+pub struct Watch {
+    every: Duration,
+    alerts: bool,
+    last: HashMap<String, Instant>,
+}
+
+impl Watch {
+    pub fn new(every: Duration, alerts: bool) -> Self {
+        Watch { every, alerts, last: HashMap::new() }
+    }
+
+    // `fired` is (interface, description) for the alerts that fired this sample; an interface
+    // that only just appeared has nothing to compare against
+    pub fn notes(&mut self, prev: &[RowData], rows: &[RowData], fired: &[(String, String)], now: Instant) -> Vec<Note> {
+        let mut notes = Vec::new();
+        for r in rows {
+            let Some(p) = prev.iter().find(|p| p.interface == r.interface) else {
+                continue;
+            };
+            let summary = match (p.info.state, r.info.state) {
+                (LinkState::Up, LinkState::Down) => format!("{} is down", r.interface),
+                (LinkState::Down, LinkState::Up) => format!("{} is back up", r.interface),
+                _ => continue,
+            };
+            let body = if r.info.state == LinkState::Down { "lost carrier" } else { "carrier restored" };
+            notes.push(Note { interface: r.interface.clone(), summary, body: body.to_string() });
+        }
+        if self.alerts {
+            notes.extend(fired.iter().map(|(interface, what)| Note {
+                interface: interface.clone(),
+                summary: format!("{interface} alert"),
+                body: what.clone(),
+            }));
+        }
+        notes.retain(|n| {
+            let due = self.last.get(&n.interface).is_none_or(|&at| now.duration_since(at) >= self.every);
+            if due {
+                self.last.insert(n.interface.clone(), now);
+            }
+            due
+        });
+        notes
+    }
+}
+
+// sends from a worker thread so a slow or missing notification daemon never holds up a
+// sample; the thread ends when the Notifier is dropped
+pub struct Notifier {
+    watch: Watch,
+    tx: Sender<Note>,
+    // the first failure to send, taken once by warning(); later ones are ignored
+    failure: Arc<Mutex<Option<String>>>,
+}
+
+impl Notifier {
+    pub fn start(every: Duration, alerts: bool) -> Self {
+        let (tx, rx) = mpsc::channel::<Note>();
+        let failure = Arc::new(Mutex::new(None));
+        {
+            let failure = Arc::clone(&failure);
+            thread::spawn(move || {
+                let mut failed = false;
+                for note in rx {
+                    if let Err(e) = system::send(&note.summary, &note.body)
+                        && !failed
+                    {
+                        failed = true;
+                        if let Ok(mut f) = failure.lock() {
+                            *f = Some(e);
+                        }
+                    }
+                }
+            });
+        }
+        Notifier { watch: Watch::new(every, alerts), tx, failure }
+    }
+
+    pub fn update(&mut self, prev: &[RowData], rows: &[RowData], fired: &[(String, String)], now: Instant) {
+        for note in self.watch.notes(prev, rows, fired, now) {
+            let _ = self.tx.send(note);
+        }
+    }
+
+    // why notifications don't arrive, once
+    pub fn warning(&self) -> Option<String> {
+        self.failure.lock().ok().and_then(|mut f| f.take())
+    }
+}
+
+// notify-send on the desktop session bus, or osascript on macOS
+#[cfg(feature = "notifications")]
+mod system {
+    use std::process::{Command, Stdio};
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn send(summary: &str, body: &str) -> Result<(), String> {
+        run(Command::new("notify-send").args(["--app-name", "nettui", summary, body]))
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn send(summary: &str, body: &str) -> Result<(), String> {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        let script = format!("display notification {} with title {}", quote(body), quote(&format!("nettui: {summary}")));
+        run(Command::new("osascript").args(["-e", &script]))
+    }
+
+    fn run(command: &mut Command) -> Result<(), String> {
+        let program = command.get_program().to_string_lossy().into_owned();
+        let status = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|e| format!("cannot run {program}: {e}"))?;
+        if status.success() { Ok(()) } else { Err(format!("{program} failed ({status})")) }
+    }
+}
+
+#[cfg(not(feature = "notifications"))]
+mod system {
+    pub fn send(_summary: &str, _body: &str) -> Result<(), String> {
+        Err("built without the notifications feature".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::IfaceInfo;
+
+    fn row(name: &str, state: LinkState) -> RowData {
+        RowData { interface: name.to_string(), info: IfaceInfo { state, ..Default::default() }, ..Default::default() }
+    }
+
+    #[test]
+    fn carrier_changes_and_alerts() {
+        let mut watch = Watch::new(Duration::from_secs(60), false);
+        let now = Instant::now();
+        let prev = [row("eth0", LinkState::Up), row("wlan0", LinkState::Down), row("lo", LinkState::Unknown)];
+        let rows = [row("eth0", LinkState::Down), row("wlan0", LinkState::Up), row("lo", LinkState::Down), row("tun0", LinkState::Down)];
+        let fired = [("eth0".to_string(), "eth0 rx 2.0 KiB/s exceeded 1000 B/s".to_string())];
+        let summaries: Vec<String> = watch.notes(&prev, &rows, &fired, now).into_iter().map(|n| n.summary).collect();
+        assert_eq!(summaries, ["eth0 is down", "wlan0 is back up"], "alerts are off");
+
+        let mut watch = Watch::new(Duration::from_secs(60), true);
+        let notes = watch.notes(&[], &rows, &fired, now);
+        assert_eq!(notes, [Note { interface: "eth0".to_string(), summary: "eth0 alert".to_string(), body: fired[0].1.clone() }]);
+    }
+
+    #[test]
+    fn one_per_interface_per_period() {
+        let mut watch = Watch::new(Duration::from_secs(60), false);
+        let start = Instant::now();
+        let (up, down) = ([row("eth0", LinkState::Up)], [row("eth0", LinkState::Down)]);
+        let flaps = [(0, &up, &down), (10, &down, &up), (59, &up, &down), (60, &down, &up), (61, &up, &down)];
+        let sent: Vec<u64> = flaps
+            .into_iter()
+            .filter(|(at, prev, rows)| !watch.notes(*prev, *rows, &[], start + Duration::from_secs(*at)).is_empty())
+            .map(|(at, _, _)| at)
+            .collect();
+        assert_eq!(sent, [0, 60]);
+    }
+
+    #[cfg(not(feature = "notifications"))]
+    #[test]
+    fn failures_warn_once() {
+        let mut notifier = Notifier::start(Duration::ZERO, false);
+        let now = Instant::now();
+        let (up, down) = ([row("eth0", LinkState::Up)], [row("eth0", LinkState::Down)]);
+        notifier.update(&up, &down, &[], now);
+        notifier.update(&down, &up, &[], now);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let warning = loop {
+            if let Some(w) = notifier.warning() {
+                break w;
+            }
+            assert!(Instant::now() < deadline, "no warning");
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert!(warning.contains("notifications feature"));
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(notifier.warning(), None);
+    }
+}
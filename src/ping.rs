@@ -0,0 +1,369 @@
+use std::collections::VecDeque;
+use std::io;
+use std::net::{IpAddr, SocketAddr, SocketAddrV6, UdpSocket};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::event::AppEvent;
+use crate::route::Route;
+
+// one round of probes this often, whatever --interval is
+pub const PING_EVERY: Duration = Duration::from_secs(1);
+
+// a probe that takes longer than this counts as lost
+const TIMEOUT: Duration = Duration::from_millis(900);
+
+// probes kept per gateway for loss and the sparkline
+pub const WINDOW: usize = 60;
+
+// where UDP probes go: the traceroute port, nothing listens there and the gateway answers
+// with port unreachable
+const UDP_PORT: u16 = 33434;
+
+// why there is no latency monitor in this build
+pub fn unavailable() -> Option<&'static str> {
+    if cfg!(not(feature = "ping")) { Some("built without the ping feature") } else { None }
+}
+
+// an interface and the gateway its default route goes through
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Target {
+    pub interface: String,
+    pub gateway: IpAddr,
+}
+
+// every interface with a default route via a gateway: its lowest metric one, IPv4 first
+pub fn targets(routes: &[Route]) -> Vec<Target> {
+    let mut defaults: Vec<&Route> = routes.iter().filter(|r| r.is_default() && r.gateway.is_some()).collect();
+    defaults.sort_by_key(|r| (r.gateway.is_some_and(|g| g.is_ipv6()), r.metric));
+    let mut targets: Vec<Target> = Vec::new();
+    for r in defaults {
+        if let Some(gateway) = r.gateway
+            && !targets.iter().any(|t| t.interface == r.interface)
+        {
+            targets.push(Target { interface: r.interface.clone(), gateway });
+        }
+    }
+    targets
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    Icmp,
+    // neither a ping socket nor a raw one could be opened
+    Udp,
+}
+
+impl Method {
+    pub fn label(self) -> &'static str {
+        match self {
+            Method::Icmp => "icmp",
+            Method::Udp => "udp",
+        }
+    }
+}
+
+// one probe's outcome, None for lost
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pinged {
+    pub target: Target,
+    pub method: Method,
+    pub rtt: Option<Duration>,
+}
+
+// a gateway's recent probes, oldest first
+#[derive(Clone, Debug, PartialEq)]
+pub struct Latency {
+    pub gateway: IpAddr,
+    pub method: Method,
+    pub samples: VecDeque<Option<Duration>>,
+}
+
+impl Latency {
+    pub fn new(p: &Pinged) -> Self {
+        Latency { gateway: p.target.gateway, method: p.method, samples: VecDeque::with_capacity(WINDOW) }
+    }
+
+    pub fn push(&mut self, p: &Pinged) {
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(p.rtt);
+        self.method = p.method;
+    }
+
+    pub fn last(&self) -> Option<Duration> {
+        self.samples.back().copied().flatten()
+    }
+
+    pub fn loss_pct(&self) -> f64 {
+        let lost = self.samples.iter().filter(|s| s.is_none()).count();
+        if self.samples.is_empty() { 0.0 } else { lost as f64 * 100.0 / self.samples.len() as f64 }
+    }
+
+    // the last `width` probes scaled to the slowest of them, an x for each lost one
+    pub fn sparkline(&self, width: usize, bars: &[char]) -> String {
+        let recent: Vec<Option<Duration>> = self.samples.iter().rev().take(width).rev().copied().collect();
+        let max = recent.iter().flatten().max().copied().unwrap_or_default().as_secs_f64();
+        recent
+            .iter()
+            .map(|rtt| match rtt {
+                None => 'x',
+                Some(_) if max <= 0.0 => bars[0],
+                Some(rtt) => bars[((rtt.as_secs_f64() / max * (bars.len() - 1) as f64).round() as usize).min(bars.len() - 1)],
+            })
+            .collect()
+    }
+}
+
+// probes every target once per PING_EVERY on its own thread and sends each result as
+// AppEvent::Ping; the targets follow the routing table through set_targets. The thread
+// ends when the Pinger is dropped
+pub struct Pinger {
+    ctrl: Sender<Vec<Target>>,
+}
+
+impl Pinger {
+    pub fn start(tx: Sender<AppEvent>, every: Duration) -> Self {
+        let (ctrl, ctrl_rx) = mpsc::channel::<Vec<Target>>();
+        thread::spawn(move || {
+            let mut targets: Vec<Target> = Vec::new();
+            let mut seq: u16 = 0;
+            let mut next = Instant::now();
+            loop {
+                match ctrl_rx.recv_timeout(next.saturating_duration_since(Instant::now())) {
+                    Ok(new) => targets = new,
+                    Err(RecvTimeoutError::Timeout) => {
+                        next = Instant::now() + every;
+                        for target in &targets {
+                            seq = seq.wrapping_add(1);
+                            let (method, rtt) = probe(target, seq);
+                            if tx.send(AppEvent::Ping(Pinged { target: target.clone(), method, rtt })).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+        Pinger { ctrl }
+    }
+
+    pub fn set_targets(&self, targets: Vec<Target>) {
+        let _ = self.ctrl.send(targets);
+    }
+}
+
+// an ICMP echo when a socket for it can be had, else a UDP probe
+fn probe(target: &Target, seq: u16) -> (Method, Option<Duration>) {
+    let addr = match target.gateway {
+        // a link-local gateway only means something on its interface
+        IpAddr::V6(v6) => SocketAddr::V6(SocketAddrV6::new(v6, UDP_PORT, 0, system::scope_id(&target.interface))),
+        v4 => SocketAddr::new(v4, UDP_PORT),
+    };
+    match system::icmp_socket(addr.is_ipv6()) {
+        Ok((socket, raw)) => (Method::Icmp, echo(&socket, raw, addr, seq).ok().flatten()),
+        Err(_) => (Method::Udp, udp(addr).ok().flatten()),
+    }
+}
+
+// None when the reply didn't come in time
+fn echo(socket: &UdpSocket, raw: bool, addr: SocketAddr, seq: u16) -> io::Result<Option<Duration>> {
+    let id = std::process::id() as u16;
+    socket.connect(addr)?;
+    let start = Instant::now();
+    socket.send(&icmp::echo_request(addr.is_ipv6(), id, seq))?;
+    let mut buf = [0u8; 1500];
+    // other replies to a raw socket, and late ones to earlier probes, are skipped
+    while let Some(left) = TIMEOUT.checked_sub(start.elapsed()).filter(|d| !d.is_zero()) {
+        socket.set_read_timeout(Some(left))?;
+        let n = match socket.recv(&mut buf) {
+            Ok(n) => n,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        // ping sockets pick the id themselves
+        let reply = icmp::echo_reply(&buf[..n], addr.is_ipv6(), raw && addr.is_ipv4());
+        if reply.is_some_and(|(got_id, got_seq)| got_seq == seq && (!raw || got_id == id)) {
+            return Ok(Some(start.elapsed()));
+        }
+    }
+    Ok(None)
+}
+
+// the gateway's port unreachable comes back as a refused recv; the round trip is as long
+fn udp(addr: SocketAddr) -> io::Result<Option<Duration>> {
+    let local: SocketAddr = if addr.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+    let socket = UdpSocket::bind(local)?;
+    socket.connect(addr)?;
+    socket.set_read_timeout(Some(TIMEOUT))?;
+    let start = Instant::now();
+    socket.send(&[0])?;
+    match socket.recv(&mut [0u8; 64]) {
+        Ok(_) => Ok(Some(start.elapsed())),
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => Ok(Some(start.elapsed())),
+        Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// echo requests and replies, by hand
+mod icmp {
+    const ECHO_REQUEST_V4: u8 = 8;
+    const ECHO_REPLY_V4: u8 = 0;
+    const ECHO_REQUEST_V6: u8 = 128;
+    const ECHO_REPLY_V6: u8 = 129;
+
+    // the kernel fills in the checksum of ICMPv6, which covers a pseudo-header
+    pub fn echo_request(v6: bool, id: u16, seq: u16) -> Vec<u8> {
+        let mut msg = vec![if v6 { ECHO_REQUEST_V6 } else { ECHO_REQUEST_V4 }, 0, 0, 0];
+        msg.extend(id.to_be_bytes());
+        msg.extend(seq.to_be_bytes());
+        msg.extend(b"nettui");
+        if !v6 {
+            let sum = checksum(&msg);
+            msg[2..4].copy_from_slice(&sum.to_be_bytes());
+        }
+        msg
+    }
+
+    // (id, seq) of an echo reply; raw IPv4 sockets hand over the IP header too
+    pub fn echo_reply(buf: &[u8], v6: bool, ip_header: bool) -> Option<(u16, u16)> {
+        let msg = if ip_header { buf.get(usize::from(buf.first()? & 0x0f) * 4..)? } else { buf };
+        let word = |at: usize| msg.get(at..at + 2).map(|w| u16::from_be_bytes([w[0], w[1]]));
+        let reply = if v6 { ECHO_REPLY_V6 } else { ECHO_REPLY_V4 };
+        if *msg.first()? != reply {
+            return None;
+        }
+        Some((word(4)?, word(6)?))
+    }
+
+    // the internet checksum, RFC 1071
+    pub fn checksum(data: &[u8]) -> u16 {
+        let mut sum: u32 = data.chunks(2).map(|c| u32::from(u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]))).sum();
+        while sum > 0xffff {
+            sum = (sum & 0xffff) + (sum >> 16);
+        }
+        !(sum as u16)
+    }
+}
+
+// an ICMP socket: an unprivileged ping socket where net.ipv4.ping_group_range allows one,
+// else a raw socket, which takes root or CAP_NET_RAW. Either is driven through UdpSocket,
+// whose send/recv/connect are plain socket calls
+#[cfg(all(feature = "ping", target_os = "linux"))]
+mod system {
+    use std::ffi::CString;
+    use std::io;
+    use std::net::UdpSocket;
+    use std::os::fd::{FromRawFd, OwnedFd};
+
+    // the socket and whether it is raw
+    pub fn icmp_socket(v6: bool) -> io::Result<(UdpSocket, bool)> {
+        let (domain, protocol) = if v6 { (libc::AF_INET6, libc::IPPROTO_ICMPV6) } else { (libc::AF_INET, libc::IPPROTO_ICMP) };
+        for (kind, raw) in [(libc::SOCK_DGRAM, false), (libc::SOCK_RAW, true)] {
+            let fd = unsafe { libc::socket(domain, kind | libc::SOCK_CLOEXEC, protocol) };
+            if fd >= 0 {
+                return Ok((UdpSocket::from(unsafe { OwnedFd::from_raw_fd(fd) }), raw));
+            }
+        }
+        Err(io::Error::last_os_error())
+    }
+
+    pub fn scope_id(interface: &str) -> u32 {
+        CString::new(interface).map_or(0, |name| unsafe { libc::if_nametoindex(name.as_ptr()) })
+    }
+}
+
+#[cfg(not(all(feature = "ping", target_os = "linux")))]
+mod system {
+    use std::io;
+    use std::net::UdpSocket;
+
+    pub fn icmp_socket(_v6: bool) -> io::Result<(UdpSocket, bool)> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn scope_id(_interface: &str) -> u32 {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(interface: &str, gateway: Option<&str>, metric: u32) -> Route {
+        let v6 = gateway.is_some_and(|g| g.contains(':'));
+        Route {
+            destination: if v6 { "::".parse().unwrap() } else { "0.0.0.0".parse().unwrap() },
+            prefix: 0,
+            gateway: gateway.map(|g| g.parse().unwrap()),
+            interface: interface.to_string(),
+            metric,
+        }
+    }
+
+    #[test]
+    fn one_target_per_interface() {
+        let routes = [
+            route("eth0", Some("fe80::1"), 100),
+            route("wlan0", Some("192.168.1.1"), 600),
+            route("eth0", Some("10.0.0.1"), 100),
+            route("eth0", Some("10.0.0.254"), 50),
+            route("wg0", None, 0),
+        ];
+        let targets = targets(&routes);
+        let listed: Vec<(&str, String)> = targets.iter().map(|t| (t.interface.as_str(), t.gateway.to_string())).collect();
+        assert_eq!(listed, [("eth0", "10.0.0.254".to_string()), ("wlan0", "192.168.1.1".to_string())]);
+    }
+
+    #[test]
+    fn echo_packets() {
+        let req = icmp::echo_request(false, 0x1234, 7);
+        assert_eq!(&req[..8], &[8, 0, req[2], req[3], 0x12, 0x34, 0, 7]);
+        // a correct checksum sums to zero with itself
+        assert_eq!(icmp::checksum(&req), 0);
+        assert_eq!(&icmp::echo_request(true, 1, 2)[..4], &[128, 0, 0, 0]);
+
+        let mut reply = req.clone();
+        reply[0] = 0;
+        assert_eq!(icmp::echo_reply(&reply, false, false), Some((0x1234, 7)));
+        assert_eq!(icmp::echo_reply(&req, false, false), None, "our own request");
+        let mut with_header = vec![0x45; 20];
+        with_header.extend(&reply);
+        assert_eq!(icmp::echo_reply(&with_header, false, true), Some((0x1234, 7)));
+        assert_eq!(icmp::echo_reply(&[129, 0, 0, 0, 0, 1, 0, 2], true, false), Some((1, 2)));
+        assert_eq!(icmp::echo_reply(&[129, 0, 0], true, false), None, "cut short");
+    }
+
+    #[test]
+    fn loss_over_the_window() {
+        let p = |rtt: Option<u64>| Pinged {
+            target: Target { interface: "eth0".to_string(), gateway: "10.0.0.1".parse().unwrap() },
+            method: Method::Icmp,
+            rtt: rtt.map(Duration::from_millis),
+        };
+        let mut latency = Latency::new(&p(None));
+        assert_eq!(latency.loss_pct(), 0.0);
+        latency.push(&p(Some(2)));
+        latency.push(&p(None));
+        assert_eq!((latency.last(), latency.loss_pct()), (None, 50.0));
+        for _ in 0..WINDOW {
+            latency.push(&p(Some(3)));
+        }
+        assert_eq!((latency.samples.len(), latency.last(), latency.loss_pct()), (WINDOW, Some(Duration::from_millis(3)), 0.0));
+
+        latency.push(&p(Some(6)));
+        latency.push(&p(None));
+        assert_eq!(latency.sparkline(4, &['.', ':', '-', '=', '#']), "--#x");
+    }
+
+    // the port unreachable from loopback comes back at once
+    #[test]
+    fn udp_probe() {
+        assert!(udp(([127, 0, 0, 1], UDP_PORT).into()).unwrap().is_some());
+    }
+}
@@ -0,0 +1,263 @@
+// --backend procfs: counters straight from /proc/net/dev, one read for every interface and
+// with the fields sysinfo leaves out (fifo, frame, compressed, multicast, collisions,
+// carrier). Each network namespace has its own file, /proc/<pid>/net/dev shows the one that
+// process lives in
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+
+use crate::containers::Containers;
+use crate::neigh::Neighbor;
+use crate::net::{link_info, Counters, Extra, IfaceAddr, IfaceInfo, NetworkSource};
+use crate::route::{self, Route};
+use crate::sysfs::SysFs;
+use crate::wg::Peers;
+
+// the columns since 2.6; older kernels had fewer, the header says which
+const RECEIVE: [&str; 8] = ["bytes", "packets", "errs", "drop", "fifo", "frame", "compressed", "multicast"];
+const TRANSMIT: [&str; 8] = ["bytes", "packets", "errs", "drop", "fifo", "colls", "carrier", "compressed"];
+
+// two header lines, the second naming the receive and transmit columns between '|'s, then
+// "  eth0: 1234 ..." per interface. A wide counter runs into the name ("eth0:123456789")
+// and names are up to 15 characters, so the name is everything before the first ':'
+pub fn parse_net_dev(text: &str) -> Vec<Counters> {
+    let columns = text.lines().find_map(|line| {
+        let mut parts = line.split('|');
+        if parts.next()?.trim() != "face" {
+            return None;
+        }
+        let (rx, tx) = (parts.next()?, parts.next()?);
+        Some((rx.split_whitespace().collect::<Vec<_>>(), tx.split_whitespace().collect::<Vec<_>>()))
+    });
+    let (rx, tx) = columns.unwrap_or_else(|| (RECEIVE.to_vec(), TRANSMIT.to_vec()));
+    text.lines()
+        .filter(|line| !line.contains('|'))
+        .filter_map(|line| {
+            let (name, fields) = line.split_once(':')?;
+            let f: Vec<u64> = fields.split_whitespace().map(str::parse).collect::<Result<_, _>>().ok()?;
+            if name.trim().is_empty() || f.len() != rx.len() + tx.len() {
+                return None;
+            }
+            let (f_rx, f_tx) = f.split_at(rx.len());
+            let get = |cols: &[&str], values: &[u64], col: &str| cols.iter().position(|c| *c == col).map(|i| values[i]);
+            let r = |col| get(&rx, f_rx, col);
+            let t = |col| get(&tx, f_tx, col);
+            Some(Counters {
+                name: name.trim().to_string(),
+                rx_bytes: r("bytes").unwrap_or(0),
+                tx_bytes: t("bytes").unwrap_or(0),
+                rx_packets: r("packets").unwrap_or(0),
+                tx_packets: t("packets").unwrap_or(0),
+                rx_errors: r("errs").unwrap_or(0),
+                tx_errors: t("errs").unwrap_or(0),
+                rx_dropped: r("drop"),
+                tx_dropped: t("drop"),
+                rx_multicast: r("multicast"),
+                extra: Some(Extra {
+                    rx_fifo: r("fifo").unwrap_or(0),
+                    tx_fifo: t("fifo").unwrap_or(0),
+                    rx_frame: r("frame").unwrap_or(0),
+                    rx_compressed: r("compressed").unwrap_or(0),
+                    tx_compressed: t("compressed").unwrap_or(0),
+                    multicast: r("multicast").unwrap_or(0),
+                    collisions: t("colls").unwrap_or(0),
+                    carrier: t("carrier").unwrap_or(0),
+                }),
+            })
+        })
+        .collect()
+}
+
+// the local IPv4 addresses in /proc/net/fib_trie: a "|-- 192.168.1.10" line followed by
+// "/32 host LOCAL". The trie doesn't say which interface, that comes from the routes
+pub fn parse_fib_trie(text: &str) -> Vec<Ipv4Addr> {
+    let mut addrs = Vec::new();
+    let mut last = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(ip) = line.strip_prefix("|-- ") {
+            last = ip.parse().ok();
+        } else if line.starts_with("/32 host LOCAL")
+            && let Some(ip) = last
+            && !addrs.contains(&ip)
+        {
+            addrs.push(ip);
+        }
+    }
+    addrs
+}
+
+// /proc/net/if_inet6: address, ifindex, prefix length, scope and flags (hex), name
+pub fn parse_if_inet6(text: &str) -> Vec<(String, IfaceAddr)> {
+    text.lines()
+        .filter_map(|line| {
+            let f: Vec<&str> = line.split_whitespace().collect();
+            let [addr, _, prefix, _, _, name] = f.as_slice() else {
+                return None;
+            };
+            let ip = Ipv6Addr::from(u128::from_str_radix(addr, 16).ok()?);
+            Some((name.to_string(), IfaceAddr { ip: IpAddr::V6(ip), prefix: u8::from_str_radix(prefix, 16).ok()? }))
+        })
+        .collect()
+}
+
+// each local IPv4 address goes to the interface of the narrowest directly connected route
+// that covers it, with that route's prefix; loopback has no such route in the main table
+pub fn addresses(local: &[Ipv4Addr], routes: &[Route], v6: Vec<(String, IfaceAddr)>) -> HashMap<String, Vec<IfaceAddr>> {
+    let mut addrs: HashMap<String, Vec<IfaceAddr>> = HashMap::new();
+    for ip in local {
+        let covers = |r: &&Route| {
+            let IpAddr::V4(dest) = r.destination else { return false };
+            let mask = u32::MAX.checked_shl(32 - u32::from(r.prefix)).unwrap_or(0);
+            r.gateway.is_none() && r.prefix > 0 && u32::from(*ip) & mask == u32::from(dest) & mask
+        };
+        let found = match routes.iter().filter(covers).max_by_key(|r| r.prefix) {
+            Some(r) => Some((r.interface.clone(), r.prefix)),
+            None if ip.is_loopback() => Some(("lo".to_string(), 8)),
+            None => None,
+        };
+        if let Some((name, prefix)) = found {
+            addrs.entry(name).or_default().push(IfaceAddr { ip: IpAddr::V4(*ip), prefix });
+        }
+    }
+    for (name, addr) in v6 {
+        addrs.entry(name).or_default().push(addr);
+    }
+    addrs
+}
+
+pub struct ProcNetDev {
+    root: PathBuf,
+    sysfs: SysFs,
+    // worked out with each snapshot, for info()
+    addrs: HashMap<String, Vec<IfaceAddr>>,
+}
+
+impl Default for ProcNetDev {
+    fn default() -> Self {
+        ProcNetDev::at("/proc/net", SysFs::default())
+    }
+}
+
+impl ProcNetDev {
+    // a directory laid out like /proc/net, tests point this at a fixture
+    pub fn at(root: impl AsRef<Path>, sysfs: SysFs) -> Self {
+        ProcNetDev { root: root.as_ref().to_path_buf(), sysfs, addrs: HashMap::new() }
+    }
+
+    fn read(&self, file: &str) -> String {
+        std::fs::read_to_string(self.root.join(file)).unwrap_or_default()
+    }
+}
+
+impl NetworkSource for ProcNetDev {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        let routes = route::parse_v4(&self.read("route"));
+        self.addrs = addresses(&parse_fib_trie(&self.read("fib_trie")), &routes, parse_if_inet6(&self.read("if_inet6")));
+        parse_net_dev(&self.read("dev"))
+    }
+
+    fn info(&self, name: &str) -> IfaceInfo {
+        IfaceInfo {
+            addrs: self.addrs.get(name).cloned().unwrap_or_default(),
+            mac: self.sysfs.mac(name).unwrap_or_default(),
+            mtu: self.sysfs.mtu(name).unwrap_or(0),
+            ..link_info(name)
+        }
+    }
+
+    fn routes(&self) -> Vec<Route> {
+        route::read()
+    }
+
+    fn neighbors(&self) -> Vec<Neighbor> {
+        crate::neigh::read()
+    }
+
+    fn wg_peers(&self, name: &str) -> Option<Peers> {
+        crate::wg::show(name)
+    }
+
+    fn containers(&self) -> Containers {
+        crate::containers::read()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))).unwrap()
+    }
+
+    fn find<'a>(counters: &'a [Counters], name: &str) -> &'a Counters {
+        counters.iter().find(|c| c.name == name).unwrap()
+    }
+
+    #[test]
+    fn net_dev_6_1() {
+        let counters = parse_net_dev(&fixture("proc_net_dev_6.1"));
+        assert_eq!(counters.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["lo", "enp0s31f6", "wlp2s0", "br-4f2a9c1d7e3b", "veth7a1c2e9"]);
+        assert_eq!(find(&counters, "enp0s31f6"), &Counters {
+            name: "enp0s31f6".to_string(),
+            rx_bytes: 9_876_543_210,
+            tx_bytes: 1_234_567_890,
+            rx_packets: 7_654_321,
+            tx_packets: 2_345_678,
+            rx_errors: 2,
+            tx_errors: 1,
+            rx_dropped: Some(31),
+            tx_dropped: Some(4),
+            rx_multicast: Some(4321),
+            extra: Some(Extra { rx_fifo: 5, tx_fifo: 6, rx_frame: 7, rx_compressed: 0, tx_compressed: 0, multicast: 4321, collisions: 8, carrier: 9 }),
+        });
+        // a 15 character name with the counter run into it
+        assert_eq!(find(&counters, "br-4f2a9c1d7e3b").rx_bytes, 123_456_789_012);
+    }
+
+    #[test]
+    fn net_dev_4_19() {
+        let counters = parse_net_dev(&fixture("proc_net_dev_4.19"));
+        assert_eq!(counters.len(), 3);
+        let eth0 = find(&counters, "eth0");
+        assert_eq!((eth0.rx_bytes, eth0.tx_packets, eth0.rx_dropped), (48_213_771, 301_115, Some(12)));
+        assert_eq!(eth0.extra.map(|e| e.multicast), Some(880));
+    }
+
+    #[test]
+    fn net_dev_without_bytes() {
+        // 2.2 kernels counted packets only, and the header says so
+        let counters = parse_net_dev(&fixture("proc_net_dev_2.2"));
+        let eth0 = find(&counters, "eth0");
+        assert_eq!((eth0.rx_bytes, eth0.rx_packets, eth0.tx_packets, eth0.tx_errors), (0, 1819, 1536, 3));
+        assert_eq!(eth0.extra.map(|e| (e.collisions, e.carrier, e.multicast)), Some((12, 1, 0)));
+        assert_eq!(eth0.rx_dropped, Some(0));
+    }
+
+    #[test]
+    fn net_dev_oddities() {
+        assert!(parse_net_dev("").is_empty());
+        // no header at all: the usual columns
+        let bare = "eth0: 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16\n";
+        assert_eq!(parse_net_dev(bare)[0].tx_bytes, 9);
+        // truncated and unparseable lines are skipped, the rest still read
+        let text = format!("{bare}eth1: 1 2 3\neth2: 1 2 x 4 5 6 7 8 9 10 11 12 13 14 15 16\n: 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16\n");
+        assert_eq!(parse_net_dev(&text).len(), 1);
+    }
+
+    #[test]
+    fn addresses_from_proc() {
+        let local = parse_fib_trie(&fixture("proc_net_fib_trie"));
+        let ip = |s: &str| s.parse::<Ipv4Addr>().unwrap();
+        assert_eq!(local, [ip("10.0.0.5"), ip("10.8.0.2"), ip("127.0.0.1"), ip("192.168.1.10")]);
+        let routes = route::parse_v4(&fixture("proc_net_route"));
+        let v6 = parse_if_inet6("fe800000000000000a1b2cfffe3d4e5f 02 40 20 80 eth0\n00000000000000000000000000000001 01 80 10 80       lo\n");
+        let addrs = addresses(&local, &routes, v6);
+        let shown = |name: &str| addrs.get(name).map(|a| a.iter().map(ToString::to_string).collect::<Vec<_>>()).unwrap_or_default();
+        assert_eq!(shown("eth0"), ["10.0.0.5/24", "fe80::a1b:2cff:fe3d:4e5f/64"]);
+        assert_eq!(shown("wlan0"), ["192.168.1.10/24"]);
+        assert_eq!(shown("lo"), ["127.0.0.1/8", "::1/128"]);
+        // no connected route covers it
+        assert!(!addrs.values().flatten().any(|a| a.ip.to_string() == "10.8.0.2"));
+    }
+}
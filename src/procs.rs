@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::conns;
+
+// one TCP socket's byte counters: what the peer acknowledged and what arrived, payload only
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Counted {
+    pub inode: u64,
+    pub local: IpAddr,
+    pub rx: u64,
+    pub tx: u64,
+}
+
+// a socket's rates over the last interval and who owns it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Flow {
+    // (pid, command name), None for sockets of processes we can't look into
+    pub process: Option<(u32, String)>,
+    pub local: IpAddr,
+    pub rx_bps: f64,
+    pub tx_bps: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Reading {
+    pub flows: Vec<Flow>,
+    // the kernel interface isn't there or the build can't use it
+    pub error: Option<String>,
+}
+
+// one line of the processes tab, flows summed by pid; pid None sums the sockets without
+// a known owner
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcRate {
+    pub pid: Option<u32>,
+    pub name: String,
+    pub rx_bps: f64,
+    pub tx_bps: f64,
+}
+
+// why the tab can't be opened in this build, checked before any read
+pub fn unavailable() -> Option<&'static str> {
+    if cfg!(not(target_os = "linux")) {
+        Some("per-process rates need Linux's sock_diag")
+    } else if cfg!(not(feature = "procs")) {
+        Some("built without the procs feature")
+    } else {
+        None
+    }
+}
+
+// turns two reads of the counters into rates: a socket counts from its second read on, so
+// one that opened between reads doesn't land its whole history in one interval. A baseline
+// older than `stale` (the tab was closed for a while) starts over the same way
+pub struct Meter {
+    last: HashMap<u64, (u64, u64)>,
+    last_at: Option<Instant>,
+    stale: Duration,
+}
+
+impl Meter {
+    pub fn new(every: Duration) -> Self {
+        Meter { last: HashMap::new(), last_at: None, stale: every * 3 }
+    }
+
+    // None while only a baseline was taken
+    pub fn read(&mut self, now: Instant) -> Option<Reading> {
+        match system::tcp_counters() {
+            Ok(sockets) => {
+                let (owners, _) = conns::owners(Path::new("/proc"));
+                self.update(sockets, &owners, now).map(|flows| Reading { flows, error: None })
+            }
+            Err(e) => Some(Reading { flows: Vec::new(), error: Some(e) }),
+        }
+    }
+
+    pub fn update(&mut self, sockets: Vec<Counted>, owners: &HashMap<u64, (u32, String)>, now: Instant) -> Option<Vec<Flow>> {
+        let secs = self.last_at.map(|at| now.saturating_duration_since(at)).filter(|gap| *gap <= self.stale);
+        let last = std::mem::take(&mut self.last);
+        self.last = sockets.iter().map(|s| (s.inode, (s.rx, s.tx))).collect();
+        self.last_at = Some(now);
+        let secs = secs?.as_secs_f64().max(1e-3);
+        let flows = sockets
+            .into_iter()
+            .filter_map(|s| {
+                let (rx, tx) = last.get(&s.inode)?;
+                Some(Flow {
+                    process: owners.get(&s.inode).cloned(),
+                    local: s.local,
+                    rx_bps: s.rx.saturating_sub(*rx) as f64 / secs,
+                    tx_bps: s.tx.saturating_sub(*tx) as f64 / secs,
+                })
+            })
+            .collect();
+        Some(flows)
+    }
+}
+
+// busiest first; processes that moved nothing are left out
+pub fn by_process<'a>(flows: impl Iterator<Item = &'a Flow>) -> Vec<ProcRate> {
+    let mut rates: Vec<ProcRate> = Vec::new();
+    for f in flows.filter(|f| f.rx_bps + f.tx_bps > 0.0) {
+        let pid = f.process.as_ref().map(|(pid, _)| *pid);
+        match rates.iter_mut().find(|r| r.pid == pid) {
+            Some(r) => {
+                r.rx_bps += f.rx_bps;
+                r.tx_bps += f.tx_bps;
+            }
+            None => rates.push(ProcRate {
+                pid,
+                name: f.process.as_ref().map_or_else(|| "other users'".to_string(), |(_, name)| name.clone()),
+                rx_bps: f.rx_bps,
+                tx_bps: f.tx_bps,
+            }),
+        }
+    }
+    rates.sort_by(|a, b| (b.rx_bps + b.tx_bps).total_cmp(&(a.rx_bps + a.tx_bps)));
+    rates
+}
+
+// sock_diag's replies, taken apart by hand like /proc/net/tcp
+#[cfg(any(test, all(feature = "procs", target_os = "linux")))]
+mod diag {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use super::Counted;
+
+    pub const NLMSG_ERROR: u16 = 2;
+    pub const NLMSG_DONE: u16 = 3;
+    pub const SOCK_DIAG_BY_FAMILY: u16 = 20;
+    pub const INET_DIAG_INFO: u16 = 2;
+    pub const AF_INET: u8 = 2;
+    pub const AF_INET6: u8 = 10;
+    // where tcp_info keeps tcpi_bytes_acked and tcpi_bytes_received, there since Linux 4.2
+    pub const BYTES_ACKED: usize = 120;
+    pub const BYTES_RECEIVED: usize = 128;
+
+    // netlink messages from one recv, sockets with byte counters appended to `out`; true once
+    // the dump is done
+    pub fn parse_dump(buf: &[u8], out: &mut Vec<Counted>) -> Result<bool, String> {
+        let u16_at = |b: &[u8], at: usize| b.get(at..at + 2).map_or(0, |v| u16::from_ne_bytes([v[0], v[1]]));
+        let u32_at = |b: &[u8], at: usize| b.get(at..at + 4).map_or(0, |v| u32::from_ne_bytes([v[0], v[1], v[2], v[3]]));
+        let mut at = 0;
+        while at + 16 <= buf.len() {
+            let len = u32_at(buf, at) as usize;
+            if len < 16 || at + len > buf.len() {
+                return Err("truncated sock_diag reply".to_string());
+            }
+            let payload = &buf[at + 16..at + len];
+            match u16_at(buf, at + 4) {
+                NLMSG_DONE => return Ok(true),
+                NLMSG_ERROR => {
+                    let errno = u32_at(payload, 0) as i32;
+                    return Err(format!("sock_diag: {}", std::io::Error::from_raw_os_error(-errno)));
+                }
+                SOCK_DIAG_BY_FAMILY => out.extend(parse_socket(payload)),
+                _ => {}
+            }
+            at += len.next_multiple_of(4);
+        }
+        Ok(false)
+    }
+
+    // an inet_diag_msg and its attributes; None without tcp_info or from a kernel whose
+    // tcp_info doesn't count bytes yet
+    fn parse_socket(msg: &[u8]) -> Option<Counted> {
+        let u64_at = |b: &[u8], at: usize| b.get(at..at + 8).and_then(|v| v.try_into().ok()).map(u64::from_ne_bytes);
+        let local = match *msg.first()? {
+            AF_INET => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(msg.get(8..12)?).ok()?)),
+            AF_INET6 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(msg.get(8..24)?).ok()?)).to_canonical(),
+            _ => return None,
+        };
+        let inode = u32::from_ne_bytes(msg.get(68..72)?.try_into().ok()?);
+        // rtattrs: length and type, then the data, each padded to 4 bytes
+        let mut at = 72;
+        while at + 4 <= msg.len() {
+            let len = u16::from_ne_bytes([msg[at], msg[at + 1]]) as usize;
+            let kind = u16::from_ne_bytes([msg[at + 2], msg[at + 3]]);
+            if len < 4 || at + len > msg.len() {
+                return None;
+            }
+            if kind == INET_DIAG_INFO {
+                let info = &msg[at + 4..at + len];
+                return Some(Counted { inode: u64::from(inode), local, rx: u64_at(info, BYTES_RECEIVED)?, tx: u64_at(info, BYTES_ACKED)? });
+            }
+            at += len.next_multiple_of(4);
+        }
+        None
+    }
+}
+
+// a SOCK_DIAG_BY_FAMILY dump of every TCP socket with tcp_info attached, which any user may
+// ask for; reading other users' sockets' owners is what needs root
+#[cfg(all(feature = "procs", target_os = "linux"))]
+mod system {
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    use super::diag::{parse_dump, AF_INET, AF_INET6, INET_DIAG_INFO, SOCK_DIAG_BY_FAMILY};
+    use super::Counted;
+
+    pub fn tcp_counters() -> Result<Vec<Counted>, String> {
+        let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, libc::NETLINK_SOCK_DIAG) };
+        if fd < 0 {
+            return Err(format!("no sock_diag socket: {}", io::Error::last_os_error()));
+        }
+        // closed when dropped
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let mut out = Vec::new();
+        for family in [AF_INET, AF_INET6] {
+            dump(&fd, family, &mut out)?;
+        }
+        Ok(out)
+    }
+
+    fn dump(fd: &OwnedFd, family: u8, out: &mut Vec<Counted>) -> Result<(), String> {
+        // nlmsghdr, then inet_diag_req_v2 asking for every state with tcp_info and a zeroed
+        // inet_diag_sockid
+        let mut req = Vec::with_capacity(72);
+        req.extend(72u32.to_ne_bytes());
+        req.extend(SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+        req.extend(((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16).to_ne_bytes());
+        req.extend([0u8; 8]);
+        req.extend([family, libc::IPPROTO_TCP as u8, 1 << (INET_DIAG_INFO - 1), 0]);
+        req.extend(u32::MAX.to_ne_bytes());
+        req.extend([0u8; 48]);
+        let sent = unsafe { libc::send(fd.as_raw_fd(), req.as_ptr().cast(), req.len(), 0) };
+        if sent < 0 {
+            return Err(format!("sock_diag request failed: {}", io::Error::last_os_error()));
+        }
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let n = unsafe { libc::recv(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len(), 0) };
+            if n < 0 {
+                return Err(format!("sock_diag reply failed: {}", io::Error::last_os_error()));
+            }
+            if n == 0 || parse_dump(&buf[..n as usize], out)? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(not(all(feature = "procs", target_os = "linux")))]
+mod system {
+    use super::Counted;
+
+    pub fn tcp_counters() -> Result<Vec<Counted>, String> {
+        Err(super::unavailable().unwrap_or_default().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diag::*;
+    use super::*;
+
+    fn counted(inode: u64, rx: u64, tx: u64) -> Counted {
+        Counted { inode, local: "192.168.1.10".parse().unwrap(), rx, tx }
+    }
+
+    // one SOCK_DIAG_BY_FAMILY message for an IPv4 socket, with tcp_info when `bytes` is given
+    fn message(inode: u32, bytes: Option<(u64, u64)>) -> Vec<u8> {
+        let mut msg = vec![AF_INET, 1, 0, 0];
+        msg.extend([0u8; 4]);
+        msg.extend([192, 168, 1, 10]);
+        msg.resize(68, 0);
+        msg.extend(inode.to_ne_bytes());
+        if let Some((rx, tx)) = bytes {
+            let mut info = vec![0u8; 232];
+            info[BYTES_ACKED..BYTES_ACKED + 8].copy_from_slice(&tx.to_ne_bytes());
+            info[BYTES_RECEIVED..BYTES_RECEIVED + 8].copy_from_slice(&rx.to_ne_bytes());
+            // another attribute first, with padding
+            msg.extend(7u16.to_ne_bytes());
+            msg.extend(1u16.to_ne_bytes());
+            msg.extend([9, 0, 0, 0]);
+            msg.extend((4 + info.len() as u16).to_ne_bytes());
+            msg.extend(INET_DIAG_INFO.to_ne_bytes());
+            msg.extend(info);
+        }
+        let mut out = (16 + msg.len() as u32).to_ne_bytes().to_vec();
+        out.extend(SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+        out.extend([0u8; 10]);
+        out.extend(msg);
+        out.resize(out.len().next_multiple_of(4), 0);
+        out
+    }
+
+    #[test]
+    fn sock_diag_replies() {
+        let mut buf = message(41, Some((1000, 2000)));
+        buf.extend(message(42, None));
+        let mut out = Vec::new();
+        assert_eq!(parse_dump(&buf, &mut out), Ok(false));
+        assert_eq!(out, [Counted { inode: 41, local: "192.168.1.10".parse().unwrap(), rx: 1000, tx: 2000 }]);
+
+        let mut done = 16u32.to_ne_bytes().to_vec();
+        done.extend(NLMSG_DONE.to_ne_bytes());
+        done.extend([0u8; 10]);
+        assert_eq!(parse_dump(&done, &mut out), Ok(true));
+
+        let mut error = 20u32.to_ne_bytes().to_vec();
+        error.extend(NLMSG_ERROR.to_ne_bytes());
+        error.extend([0u8; 10]);
+        error.extend((-1i32).to_ne_bytes());
+        assert!(parse_dump(&error, &mut out).unwrap_err().starts_with("sock_diag: "));
+        assert!(parse_dump(&buf[..40], &mut out).is_err());
+    }
+
+    #[test]
+    fn rates_from_the_second_read_on() {
+        let mut meter = Meter::new(Duration::from_secs(2));
+        let owners = HashMap::from([(1, (40, "curl".to_string()))]);
+        let start = Instant::now();
+        assert_eq!(meter.update(vec![counted(1, 0, 0)], &owners, start), None, "a baseline");
+
+        let later = start + Duration::from_secs(2);
+        let flows = meter.update(vec![counted(1, 4000, 1000), counted(2, 1 << 30, 0)], &owners, later).unwrap();
+        assert_eq!(flows.len(), 1, "socket 2 only just showed up");
+        assert_eq!((flows[0].rx_bps, flows[0].tx_bps, flows[0].process.clone()), (2000.0, 500.0, Some((40, "curl".to_string()))));
+
+        let flows = meter.update(vec![counted(2, (1 << 30) + 100, 0)], &owners, later + Duration::from_secs(1)).unwrap();
+        assert_eq!((flows[0].rx_bps, flows[0].process.clone()), (100.0, None));
+
+        // closed for a minute, starts over
+        assert_eq!(meter.update(vec![counted(2, 1 << 31, 0)], &owners, later + Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn summed_by_process() {
+        let flow = |pid: Option<u32>, rx_bps, tx_bps| Flow {
+            process: pid.map(|p| (p, format!("p{p}"))),
+            local: "192.168.1.10".parse().unwrap(),
+            rx_bps,
+            tx_bps,
+        };
+        let flows = [flow(Some(1), 10.0, 0.0), flow(Some(2), 50.0, 0.0), flow(Some(1), 30.0, 20.0), flow(None, 5.0, 0.0), flow(Some(3), 0.0, 0.0)];
+        let rates = by_process(flows.iter());
+        let names: Vec<(&str, f64)> = rates.iter().map(|r| (r.name.as_str(), r.rx_bps + r.tx_bps)).collect();
+        assert_eq!(names, [("p1", 60.0), ("p2", 50.0), ("other users'", 5.0)]);
+    }
+}
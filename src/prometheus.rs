@@ -0,0 +1,160 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::net::RowData;
+
+// minimal HTTP exporter serving the latest rendered sample on GET /metrics
+pub struct Exporter {
+    body: Arc<Mutex<String>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Exporter {
+    pub fn start(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        // polled so the thread can notice `stop` and exit with the app
+        listener.set_nonblocking(true)?;
+
+        let body = Arc::new(Mutex::new(String::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let body = Arc::clone(&body);
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || serve(listener, body, stop))
+        };
+        Ok(Exporter { body, stop, handle: Some(handle) })
+    }
+
+    // swaps in a fully rendered page, so scrapes never see half a sample
+    pub fn update(&self, rows: &[RowData]) {
+        let text = render(rows);
+        if let Ok(mut body) = self.body.lock() {
+            *body = text;
+        }
+    }
+}
+
+impl Drop for Exporter {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn serve(listener: TcpListener, body: Arc<Mutex<String>>, stop: Arc<AtomicBool>) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let page = body.lock().map(|b| b.clone()).unwrap_or_default();
+                // a misbehaving client only loses its own response
+                let _ = respond(stream, &page);
+            }
+            // WouldBlock when idle; transient accept errors are retried the same way
+            Err(_) => thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+fn respond(mut stream: TcpStream, page: &str) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", "text/plain; version=0.0.4", page),
+        (Some("GET"), Some(_)) => ("404 Not Found", "text/plain", "try /metrics\n"),
+        _ => ("405 Method Not Allowed", "text/plain", "only GET is supported\n"),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+// name, help text, value getter
+type Metric<T> = (&'static str, &'static str, fn(&RowData) -> T);
+
+// text exposition format: one HELP/TYPE block per metric, one line per interface
+pub fn render(rows: &[RowData]) -> String {
+    let gauges: [Metric<f64>; 2] = [
+        ("nettui_rx_bytes_per_second", "Receive rate over the last sample", |r| r.rx_bps),
+        ("nettui_tx_bytes_per_second", "Transmit rate over the last sample", |r| r.tx_bps),
+    ];
+    let counters: [Metric<u64>; 6] = [
+        ("nettui_rx_bytes_total", "Bytes received since nettui started", |r| r.totals.rx_bytes),
+        ("nettui_tx_bytes_total", "Bytes transmitted since nettui started", |r| r.totals.tx_bytes),
+        ("nettui_rx_packets_total", "Packets received since nettui started", |r| r.totals.packets_in),
+        ("nettui_tx_packets_total", "Packets transmitted since nettui started", |r| r.totals.packets_out),
+        ("nettui_rx_errors_total", "Receive errors since nettui started", |r| r.totals.errors_in),
+        ("nettui_tx_errors_total", "Transmit errors since nettui started", |r| r.totals.errors_out),
+    ];
+
+    let mut out = String::new();
+    for (name, help, value) in gauges {
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} gauge");
+        for r in rows {
+            let _ = writeln!(out, "{name}{{interface=\"{}\"}} {}", escape_label(&r.interface), value(r));
+        }
+    }
+    for (name, help, value) in counters {
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter");
+        for r in rows {
+            let _ = writeln!(out, "{name}{{interface=\"{}\"}} {}", escape_label(&r.interface), value(r));
+        }
+    }
+    out
+}
+
+fn escape_label(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::{IfaceInfo, Totals};
+
+    #[test]
+    fn renders_gauges_and_counters() {
+        let row = RowData {
+            interface: "eth0".to_string(),
+            rx_bps: 1500.0,
+            tx_bps: 0.5,
+            rx_smooth: 0.0,
+            tx_smooth: 0.0,
+            packets_in: 0.0,
+            packets_out: 0.0,
+            errors_in: 0.0,
+            errors_out: 0.0,
+            drops_in: 0.0,
+            drops_out: 0.0,
+            multicast_in: None,
+            totals: Totals { rx_bytes: 3000, packets_in: 7, ..Default::default() },
+            info: IfaceInfo::default(),
+        };
+        let text = render(&[row]);
+        assert!(text.contains("# TYPE nettui_rx_bytes_per_second gauge\n"));
+        assert!(text.contains("nettui_rx_bytes_per_second{interface=\"eth0\"} 1500\n"));
+        assert!(text.contains("nettui_tx_bytes_per_second{interface=\"eth0\"} 0.5\n"));
+        assert!(text.contains("# TYPE nettui_rx_packets_total counter\n"));
+        assert!(text.contains("nettui_rx_packets_total{interface=\"eth0\"} 7\n"));
+        assert!(text.contains("nettui_rx_bytes_total{interface=\"eth0\"} 3000\n"));
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
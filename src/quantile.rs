@@ -0,0 +1,159 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+// percentiles cover the session up to this long
+pub const WINDOW: Duration = Duration::from_secs(60 * 60);
+
+// one reservoir per this long, so the oldest minute can be dropped as a whole
+const BUCKET: Duration = Duration::from_secs(60);
+
+// samples kept per bucket; 500ms sampling sees 120 a minute
+const RESERVOIR: usize = 32;
+
+// a minute of (rx, tx) samples, every one of them equally likely to be kept
+struct Bucket {
+    start: Instant,
+    seen: u64,
+    kept: Vec<(f64, f64)>,
+}
+
+// p50/p95/p99 and the like of rx and tx, in the order they were asked for
+#[derive(Clone, Debug, PartialEq)]
+pub struct Percentiles {
+    pub rx: Vec<f64>,
+    pub tx: Vec<f64>,
+    // how far back the samples go, at most WINDOW
+    pub span: Duration,
+}
+
+// percentile estimates over the last WINDOW in bounded memory: a fixed-size reservoir per
+// minute instead of every sample. Each kept sample stands for seen/kept of its minute
+pub struct Quantiles {
+    series: HashMap<String, VecDeque<Bucket>>,
+    // xorshift state, no need for anything better to pick reservoir slots
+    rng: u64,
+}
+
+impl Default for Quantiles {
+    fn default() -> Self {
+        Quantiles::new()
+    }
+}
+
+impl Quantiles {
+    pub fn new() -> Self {
+        Quantiles { series: HashMap::new(), rng: 0x9e37_79b9_7f4a_7c15 }
+    }
+
+    pub fn push(&mut self, name: &str, at: Instant, rx: f64, tx: f64) {
+        let slot = self.next_random();
+        let buckets = self.series.entry(name.to_string()).or_default();
+        if buckets.back().is_none_or(|b| at.saturating_duration_since(b.start) >= BUCKET) {
+            buckets.push_back(Bucket { start: at, seen: 0, kept: Vec::with_capacity(RESERVOIR) });
+        }
+        let Some(bucket) = buckets.back_mut() else {
+            return;
+        };
+        // Algorithm R: the n-th sample replaces a kept one with probability RESERVOIR/n
+        bucket.seen += 1;
+        if bucket.kept.len() < RESERVOIR {
+            bucket.kept.push((rx, tx));
+        } else if let Some(kept) = bucket.kept.get_mut((slot % bucket.seen) as usize) {
+            *kept = (rx, tx);
+        }
+    }
+
+    // drops minutes older than WINDOW, and interfaces left with none
+    pub fn prune(&mut self, now: Instant) {
+        for buckets in self.series.values_mut() {
+            while buckets.front().is_some_and(|b| now.saturating_duration_since(b.start) > WINDOW) {
+                buckets.pop_front();
+            }
+        }
+        self.series.retain(|_, buckets| !buckets.is_empty());
+    }
+
+    // `qs` from 0 to 1; None without samples
+    pub fn percentiles(&self, name: &str, now: Instant, qs: &[f64]) -> Option<Percentiles> {
+        let buckets = self.series.get(name)?;
+        let recent: Vec<&Bucket> = buckets.iter().filter(|b| now.saturating_duration_since(b.start) <= WINDOW).collect();
+        let first = recent.first()?;
+        let weighted = |pick: fn(&(f64, f64)) -> f64| {
+            let mut values: Vec<(f64, f64)> = recent
+                .iter()
+                .flat_map(|b| {
+                    let weight = b.seen as f64 / b.kept.len() as f64;
+                    b.kept.iter().map(move |s| (pick(s), weight))
+                })
+                .collect();
+            values.sort_by(|a, b| a.0.total_cmp(&b.0));
+            qs.iter().map(|&q| weighted_quantile(&values, q)).collect()
+        };
+        Some(Percentiles { rx: weighted(|s| s.0), tx: weighted(|s| s.1), span: now.saturating_duration_since(first.start) })
+    }
+
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+}
+
+// the smallest value with at least `q` of the total weight at or below it; `values` sorted
+fn weighted_quantile(values: &[(f64, f64)], q: f64) -> f64 {
+    let total: f64 = values.iter().map(|v| v.1).sum();
+    let mut below = 0.0;
+    for &(value, weight) in values {
+        below += weight;
+        if below >= q * total {
+            return value;
+        }
+    }
+    values.last().map_or(0.0, |v| v.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_while_everything_fits() {
+        let mut q = Quantiles::new();
+        let start = Instant::now();
+        for v in 1..=20 {
+            q.push("eth0", start, v as f64, 100.0 - v as f64);
+        }
+        let p = q.percentiles("eth0", start, &[0.5, 0.95, 1.0]).unwrap();
+        assert_eq!(p.rx, [10.0, 19.0, 20.0]);
+        assert_eq!(p.tx, [89.0, 98.0, 99.0]);
+        assert_eq!(q.percentiles("wlan0", start, &[0.5]), None);
+    }
+
+    #[test]
+    fn reservoirs_estimate_a_long_session() {
+        let mut q = Quantiles::new();
+        let start = Instant::now();
+        // two hours at 500ms of a uniform 0..1000 sweep; only the last hour counts
+        let samples = 2 * 60 * 60 * 2;
+        for i in 0..samples {
+            let at = start + Duration::from_millis(500 * i);
+            let rx = if i < samples / 2 { 1e9 } else { (i % 1000) as f64 };
+            q.push("eth0", at, rx, 0.0);
+        }
+        let now = start + Duration::from_millis(500 * (samples - 1));
+        q.prune(now);
+        assert!(q.series["eth0"].iter().all(|b| b.kept.len() <= RESERVOIR));
+        assert!(q.series["eth0"].len() <= 61);
+
+        let p = q.percentiles("eth0", now, &[0.5, 0.95, 0.99]).unwrap();
+        for (got, want) in p.rx.iter().zip([500.0, 950.0, 990.0]) {
+            assert!((got - want).abs() < 40.0, "{got} vs {want}");
+        }
+        assert!(p.span <= WINDOW);
+
+        // gone for over an hour
+        q.prune(now + WINDOW + BUCKET * 2);
+        assert_eq!(q.percentiles("eth0", now, &[0.5]), None);
+    }
+}
@@ -0,0 +1,326 @@
+// `nettui serve` and `nettui connect`: samples taken on one machine, drawn on another. Every
+// message is a 4-byte big-endian length and that many bytes of JSON. The client speaks first
+// with {"token": ...}, the server answers with a hello naming its host, or {"error": ...} and
+// hangs up; after that each sample is a --json array, the same schema --json prints
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::event::AppEvent;
+use crate::host::Host;
+use crate::json::Json;
+use crate::net::{Counters, NetworkSource, RowData};
+use crate::replay::{self, Frame, Integrator};
+use crate::snapshot::{self, SnapshotRow};
+
+pub const DEFAULT_PORT: u16 = 7070;
+// a sample of a few hundred interfaces is tens of KiB, anything near this is not nettui
+const MAX_MESSAGE: usize = 16 << 20;
+// how long the handshake, or a write to a stalled client, may take
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+// the longest interval is 10s, a silent server is gone well before this
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+// the wait between reconnects doubles from the first to the last
+pub const BACKOFF: (Duration, Duration) = (Duration::from_secs(1), Duration::from_secs(30));
+// samples waiting for the writer; when clients are this far behind newer samples are dropped
+const QUEUE: usize = 4;
+
+pub fn write_message(out: &mut impl Write, json: &str) -> io::Result<()> {
+    let len = u32::try_from(json.len()).ok().filter(|&n| n as usize <= MAX_MESSAGE);
+    let len = len.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "message too large"))?;
+    out.write_all(&len.to_be_bytes())?;
+    out.write_all(json.as_bytes())?;
+    out.flush()
+}
+
+pub fn read_message(input: &mut impl Read) -> io::Result<Json> {
+    let mut len = [0; 4];
+    input.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("a {len} byte message, not a nettui server")));
+    }
+    let mut body = vec![0; len];
+    input.read_exact(&mut body)?;
+    let text = String::from_utf8(body).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "message isn't UTF-8"))?;
+    Json::parse(&text).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "message isn't JSON"))
+}
+
+// what the server says about itself once the token checks out
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hello {
+    pub host: Host,
+    pub interval_ms: u64,
+}
+
+impl Hello {
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"nettui\":1,\"host\":{},\"os\":{},\"kernel\":{},\"interval_ms\":{}}}",
+            snapshot::string(&self.host.name),
+            snapshot::string(&self.host.os),
+            snapshot::string(&self.host.kernel),
+            self.interval_ms
+        )
+    }
+
+    fn parse(j: &Json) -> Option<Hello> {
+        j.get("nettui")?;
+        let text = |key| j.get(key).and_then(Json::as_str).unwrap_or_default().to_string();
+        Some(Hello {
+            host: Host { name: text("host"), os: text("os"), kernel: text("kernel") },
+            interval_ms: j.get("interval_ms").and_then(Json::as_f64).unwrap_or(0.0) as u64,
+        })
+    }
+}
+
+// the same bytes whatever the token, so the time taken doesn't tell how much of a guess was right
+fn token_matches(expected: &str, given: &str) -> bool {
+    let (a, b) = (expected.as_bytes(), given.as_bytes());
+    let diff = (0..a.len().max(b.len())).fold(0, |d, i| d | (a.get(i).copied().unwrap_or(0) ^ b.get(i).copied().unwrap_or(0)));
+    diff == 0 && a.len() == b.len()
+}
+
+// the sink `nettui serve` feeds from sample(), like the Prometheus exporter: clients are
+// accepted and written to on their own threads, so a slow one never holds up sampling
+pub struct Server {
+    pub addr: SocketAddr,
+    queue: SyncSender<Arc<String>>,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl Server {
+    pub fn start(addr: &str, token: Option<String>, hello: Hello) -> io::Result<Server> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let accepted = Arc::clone(&clients);
+        let hello = hello.to_json();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (token, hello, accepted) = (token.clone(), hello.clone(), Arc::clone(&accepted));
+                // the handshake waits on the client, the next one needn't
+                thread::spawn(move || {
+                    if let Ok(stream) = handshake(stream, token.as_deref(), &hello) {
+                        accepted.lock().unwrap_or_else(|e| e.into_inner()).push(stream);
+                    }
+                });
+            }
+        });
+        let (queue, samples) = mpsc::sync_channel::<Arc<String>>(QUEUE);
+        let writing = Arc::clone(&clients);
+        thread::spawn(move || {
+            for sample in samples {
+                // a client that can't take the sample within the timeout is dropped
+                writing.lock().unwrap_or_else(|e| e.into_inner()).retain_mut(|c| write_message(c, &sample).is_ok());
+            }
+        });
+        Ok(Server { addr, queue, clients })
+    }
+
+    pub fn clients(&self) -> usize {
+        self.clients.lock().map_or(0, |c| c.len())
+    }
+
+    pub fn update(&self, rows: &[RowData], unix: f64, interval_secs: f64) {
+        let rows: Vec<SnapshotRow> = rows.iter().map(|r| SnapshotRow::new(r, unix, interval_secs)).collect();
+        let _ = self.queue.try_send(Arc::new(snapshot::to_json_array(&rows)));
+    }
+}
+
+fn handshake(mut stream: TcpStream, token: Option<&str>, hello: &str) -> io::Result<TcpStream> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    stream.set_write_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    stream.set_nodelay(true)?;
+    let given = read_message(&mut stream)?;
+    let given = given.get("token").and_then(Json::as_str).unwrap_or_default();
+    if let Some(token) = token
+        && !token_matches(token, given)
+    {
+        write_message(&mut stream, "{\"error\":\"bad token\"}")?;
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, "bad token"));
+    }
+    write_message(&mut stream, hello)?;
+    Ok(stream)
+}
+
+// what the client thread tells the TUI
+#[derive(Clone, Debug, PartialEq)]
+pub enum Update {
+    Connected(Hello),
+    Sample(Frame),
+    // the reason, and how long until the next attempt
+    Lost(String, Duration),
+}
+
+// connects, and reconnects with backoff whenever the connection drops, until the TUI is gone
+pub fn connect(tx: Sender<AppEvent>, addr: String, token: Option<String>) {
+    thread::spawn(move || {
+        let mut wait = BACKOFF.0;
+        loop {
+            let (connected, reason) = match session(&addr, token.as_deref(), &tx) {
+                Ok(connected) => (connected, "the server closed the connection".to_string()),
+                Err((connected, e)) => (connected, e),
+            };
+            if connected {
+                wait = BACKOFF.0;
+            }
+            if tx.send(AppEvent::Remote(Update::Lost(reason, wait))).is_err() {
+                return;
+            }
+            thread::sleep(wait);
+            wait = (wait * 2).min(BACKOFF.1);
+        }
+    });
+}
+
+// one connection until it ends; true when it got past the handshake, so the backoff starts over
+fn session(addr: &str, token: Option<&str>, tx: &Sender<AppEvent>) -> Result<bool, (bool, String)> {
+    let fail = |connected| move |e: io::Error| (connected, e.to_string());
+    let mut stream = open(addr).map_err(fail(false))?;
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).map_err(fail(false))?;
+    stream.set_write_timeout(Some(HANDSHAKE_TIMEOUT)).map_err(fail(false))?;
+    let hello = format!("{{\"token\":{}}}", token.map_or_else(|| "null".to_string(), snapshot::string));
+    write_message(&mut stream, &hello).map_err(fail(false))?;
+    let answer = read_message(&mut stream).map_err(fail(false))?;
+    if let Some(e) = answer.get("error").and_then(Json::as_str) {
+        return Err((false, format!("refused: {e}")));
+    }
+    let hello = Hello::parse(&answer).ok_or((false, "not a nettui server".to_string()))?;
+    stream.set_read_timeout(Some(READ_TIMEOUT)).map_err(fail(true))?;
+    if tx.send(AppEvent::Remote(Update::Connected(hello))).is_err() {
+        return Ok(true);
+    }
+    loop {
+        let message = match read_message(&mut stream) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(true),
+            other => other.map_err(fail(true))?,
+        };
+        let frame = replay::frame(&message).ok_or((true, "a sample that isn't a --json array".to_string()))?;
+        if tx.send(AppEvent::Remote(Update::Sample(frame))).is_err() {
+            return Ok(true);
+        }
+    }
+}
+
+// host:port to connect to; a bare address, v6 ones included, or a name gets the default port
+fn with_port(addr: &str) -> String {
+    if let Ok(ip) = addr.parse::<IpAddr>() {
+        return SocketAddr::new(ip, DEFAULT_PORT).to_string();
+    }
+    // a name or [v6] with a port after the last colon
+    if addr.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok()) && !addr.ends_with(']') {
+        addr.to_string()
+    } else {
+        format!("{addr}:{DEFAULT_PORT}")
+    }
+}
+
+fn open(addr: &str) -> io::Result<TcpStream> {
+    let with_port = with_port(addr);
+    let mut last = io::Error::new(io::ErrorKind::NotFound, format!("{addr}: no address"));
+    for a in with_port.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&a, HANDSHAKE_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last = e,
+        }
+    }
+    Err(last)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Link {
+    Connecting,
+    Connected(Hello),
+    // why, and when the next attempt is
+    Lost(String, Instant),
+}
+
+// `nettui connect`'s side: the server's samples as a NetworkSource, integrated back into
+// counters like a replay so they go through sample() like a local read
+#[derive(Clone, Debug)]
+pub struct Remote {
+    pub addr: String,
+    pub token: Option<String>,
+    pub link: Link,
+    // attempts since the last good connection
+    pub failures: u32,
+    integrator: Integrator,
+    counters: Vec<Counters>,
+}
+
+impl Remote {
+    pub fn new(addr: String, token: Option<String>) -> Self {
+        Remote { addr, token, link: Link::Connecting, failures: 0, integrator: Integrator::default(), counters: Vec::new() }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        matches!(self.link, Link::Connected(_))
+    }
+
+    // snapshot() reads the counters as they were before `frame`, for priming with the first one
+    pub fn before(&mut self, frame: &Frame) {
+        self.counters = self.integrator.before(frame);
+    }
+
+    // and from here on as they are after it
+    pub fn advance(&mut self, frame: &Frame) {
+        self.counters = self.integrator.add(frame);
+    }
+}
+
+impl NetworkSource for Remote {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.counters.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn default_port() {
+        assert_eq!(with_port("::1"), "[::1]:7070");
+        assert_eq!(with_port("fe80::1"), "[fe80::1]:7070");
+        assert_eq!(with_port("[::1]:7000"), "[::1]:7000");
+        assert_eq!(with_port("[::1]"), "[::1]:7070");
+        assert_eq!(with_port("10.0.0.2"), "10.0.0.2:7070");
+        assert_eq!(with_port("host"), "host:7070");
+        assert_eq!(with_port("host:7000"), "host:7000");
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let mut buf = Vec::new();
+        write_message(&mut buf, "{\"token\":\"s3cret\"}").unwrap();
+        write_message(&mut buf, "[]").unwrap();
+        assert_eq!(&buf[..4], &[0, 0, 0, 18]);
+        let mut input = Cursor::new(buf);
+        assert_eq!(read_message(&mut input).unwrap().get("token").and_then(Json::as_str), Some("s3cret"));
+        assert_eq!(read_message(&mut input).unwrap(), Json::Array(Vec::new()));
+        assert_eq!(read_message(&mut input).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        // an HTTP client or the like
+        let mut garbage = Cursor::new(b"GET / HTTP/1.1\r\n".to_vec());
+        assert_eq!(read_message(&mut garbage).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn hello_round_trip() {
+        let hello = Hello { host: Host { name: "web-01".to_string(), os: "Debian".to_string(), kernel: "6.1".to_string() }, interval_ms: 500 };
+        assert_eq!(Hello::parse(&Json::parse(&hello.to_json()).unwrap()), Some(hello));
+        assert_eq!(Hello::parse(&Json::parse("{\"error\":\"bad token\"}").unwrap()), None);
+    }
+
+    #[test]
+    fn tokens() {
+        assert!(token_matches("s3cret", "s3cret"));
+        assert!(!token_matches("s3cret", "s3cre"));
+        assert!(!token_matches("s3cret", "s3cret!"));
+        assert!(!token_matches("s3cret", ""));
+    }
+}
@@ -0,0 +1,373 @@
+// --replay: a session recorded with --log-csv, --stream jsonl or --json read back as a
+// NetworkSource. The recorded rates are integrated into counters again, so every sample goes
+// through the same sample() as the live ones and the table, history and alerts come out alike
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::csvlog;
+use crate::json::Json;
+use crate::net::{Counters, NetworkSource};
+use crate::snapshot::parse_iso8601;
+
+// fastest --replay playback, doubled from 1x by '+'
+pub const MAX_SPEED: u32 = 64;
+// how often playback checks for frames that came due
+pub const PLAY_TICK: Duration = Duration::from_millis(100);
+// a recorded interval_secs longer than a day is a broken line, not a sampler that slow
+pub const MAX_INTERVAL: f64 = 86_400.0;
+// timestamps past the year 9999 are as broken, no date nettui writes gets there
+const LATEST: f64 = 253_402_300_800.0;
+
+// one interface in one sample, as recorded
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recorded {
+    pub interface: String,
+    pub rx_bps: f64,
+    pub tx_bps: f64,
+    pub packets_in: f64,
+    pub packets_out: f64,
+    pub errors_in: f64,
+    pub errors_out: f64,
+}
+
+// every interface recorded at one time
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    // unix seconds
+    pub unix: f64,
+    // what the rates were measured over
+    pub interval: f64,
+    pub rows: Vec<Recorded>,
+}
+
+impl Frame {
+    // the interval as a Duration, 1s if it can't be one
+    pub fn duration(&self) -> Duration {
+        Duration::try_from_secs_f64(self.interval).unwrap_or(Duration::from_secs(1))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    pub frames: Vec<Frame>,
+    // lines that were neither a record nor the CSV header
+    pub skipped: usize,
+}
+
+// CSV and JSON lines may be mixed, each line is taken for what it looks like; rows with the
+// same timestamp in a row make up one frame
+pub fn parse(text: &str) -> Recording {
+    let mut recording = Recording::default();
+    // frames without an interval_secs get theirs from the timestamps afterwards
+    let mut measured = Vec::new();
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && *l != csvlog::HEADER) {
+        let rows = match line.chars().next() {
+            Some('{') => Json::parse(line).and_then(|j| json_row(&j)).map(|row| vec![row]),
+            Some('[') => Json::parse(line).and_then(|j| j.as_array()?.iter().map(json_row).collect()),
+            _ => csv_row(line).map(|(unix, row)| vec![(unix, None, row)]),
+        };
+        let Some(rows) = rows else {
+            recording.skipped += 1;
+            continue;
+        };
+        for (unix, interval, row) in rows {
+            match recording.frames.last_mut() {
+                Some(frame) if frame.unix == unix => frame.rows.push(row),
+                _ => {
+                    recording.frames.push(Frame { unix, interval: 0.0, rows: vec![row] });
+                    measured.push(interval);
+                }
+            }
+        }
+    }
+    // the gap since the previous frame, or to the next one for the first; 1s when neither helps
+    let times: Vec<f64> = recording.frames.iter().map(|f| f.unix).collect();
+    for (i, frame) in recording.frames.iter_mut().enumerate() {
+        let before = i.checked_sub(1).map(|j| times[i] - times[j]);
+        let after = times.get(i + 1).map(|next| next - times[i]);
+        frame.interval = measured[i]
+            .or(before.filter(|gap| *gap > 0.0))
+            .or(after.filter(|gap| *gap > 0.0))
+            .unwrap_or(1.0);
+    }
+    recording
+}
+
+// a --json array as one frame, what `nettui serve` sends; None unless every element is a row
+pub fn frame(j: &Json) -> Option<Frame> {
+    let rows: Vec<_> = j.as_array()?.iter().map(json_row).collect::<Option<_>>()?;
+    let (unix, interval) = rows.first().map_or((0.0, None), |(unix, interval, _)| (*unix, *interval));
+    Some(Frame { unix, interval: interval.unwrap_or(1.0), rows: rows.into_iter().map(|(_, _, row)| row).collect() })
+}
+
+// a --stream jsonl line or an element of a --json array
+fn json_row(j: &Json) -> Option<(f64, Option<f64>, Recorded)> {
+    let number = |key| j.get(key)?.as_f64();
+    let row = Recorded {
+        interface: j.get("interface")?.as_str()?.to_string(),
+        rx_bps: number("rx_bps")?,
+        tx_bps: number("tx_bps")?,
+        // null where the rate wasn't finite
+        packets_in: number("rx_pps").unwrap_or(0.0),
+        packets_out: number("tx_pps").unwrap_or(0.0),
+        errors_in: number("rx_errors_ps").unwrap_or(0.0),
+        errors_out: number("tx_errors_ps").unwrap_or(0.0),
+    };
+    let unix = number("timestamp").filter(|t| (0.0..LATEST).contains(t))?;
+    // missing or zero is left to the timestamps, not a number or too long skips the line
+    let interval = match number("interval_secs") {
+        Some(i) if !i.is_finite() || i > MAX_INTERVAL => return None,
+        i => i.filter(|i| *i > 0.0),
+    };
+    Some((unix, interval, row))
+}
+
+// a --log-csv row, in csvlog::HEADER's order
+fn csv_row(line: &str) -> Option<(f64, Recorded)> {
+    let fields = split_fields(line)?;
+    let [ts, interface, rest @ ..] = fields.as_slice() else {
+        return None;
+    };
+    let numbers: Vec<f64> = rest.iter().map(|f| f.parse().ok()).collect::<Option<_>>()?;
+    let [rx_bps, tx_bps, packets_in, packets_out, errors_in, errors_out] = numbers[..] else {
+        return None;
+    };
+    let row = Recorded { interface: interface.clone(), rx_bps, tx_bps, packets_in, packets_out, errors_in, errors_out };
+    Some((parse_iso8601(ts).filter(|t| (0.0..LATEST).contains(t))?, row))
+}
+
+// RFC 4180 fields, undoing csvlog's quoting; None for an unterminated quote
+fn split_fields(line: &str) -> Option<Vec<String>> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut()?;
+        match c {
+            '"' if quoted && chars.next_if_eq(&'"').is_some() => field.push('"'),
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+    (!quoted).then_some(fields)
+}
+
+// running sums of recorded rates times their intervals: the cumulative counters a live
+// source would have read, which is what sample() diffs
+#[derive(Clone, Debug, Default)]
+pub struct Integrator {
+    sums: HashMap<String, [f64; 6]>,
+}
+
+impl Integrator {
+    // the counters after `frame`, for the interfaces it has
+    pub fn add(&mut self, frame: &Frame) -> Vec<Counters> {
+        let read = frame.rows.iter().map(|r| {
+            let sum = self.sums.entry(r.interface.clone()).or_default();
+            let rates = [r.rx_bps, r.tx_bps, r.packets_in, r.packets_out, r.errors_in, r.errors_out];
+            for (total, rate) in sum.iter_mut().zip(rates) {
+                *total += rate.max(0.0) * frame.interval;
+            }
+            let [rx_bytes, tx_bytes, rx_packets, tx_packets, rx_errors, tx_errors] = sum.map(|v| v.round() as u64);
+            Counters { name: r.interface.clone(), rx_bytes, tx_bytes, rx_packets, tx_packets, rx_errors, tx_errors, ..Default::default() }
+        });
+        read.collect()
+    }
+
+    // what the counters were before `frame`, so a first sample of it has a baseline
+    pub fn before(&self, frame: &Frame) -> Vec<Counters> {
+        let counter = |r: &Recorded| {
+            let [rx_bytes, tx_bytes, rx_packets, tx_packets, rx_errors, tx_errors] =
+                self.sums.get(&r.interface).copied().unwrap_or_default().map(|v| v.round() as u64);
+            Counters { name: r.interface.clone(), rx_bytes, tx_bytes, rx_packets, tx_packets, rx_errors, tx_errors, ..Default::default() }
+        };
+        frame.rows.iter().map(counter).collect()
+    }
+}
+
+// the recording as a source plus where playback stands; snapshot() reports the counters
+// after frame `next - 1`, so priming with next at 0 gives the state before the first frame
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub recording: Recording,
+    // cumulative counters before the first frame and after each one
+    counters: Vec<Vec<Counters>>,
+    // when each frame is taken to have happened, spaced as recorded and ending about now
+    at: Vec<Instant>,
+    pub next: usize,
+    // the last frame sampled, None before the first
+    pub shown: Option<usize>,
+    pub playing: bool,
+    // 1x is the recorded pace
+    pub speed: u32,
+    // wall time played since the last frame, scaled by speed
+    owed: Duration,
+    ticked: Option<Instant>,
+}
+
+impl Replay {
+    pub fn new(recording: Recording, now: Instant) -> Self {
+        let mut integrator = Integrator::default();
+        let mut counters = Vec::new();
+        counters.extend(recording.frames.first().map(|f| integrator.before(f)));
+        counters.extend(recording.frames.iter().map(|f| integrator.add(f)));
+        let span: Duration = recording.frames.iter().map(Frame::duration).sum();
+        let start = now.checked_sub(span).unwrap_or(now);
+        let at = recording
+            .frames
+            .iter()
+            .scan(Duration::ZERO, |t, f| {
+                *t += f.duration();
+                Some(start.checked_add(*t).unwrap_or(now))
+            })
+            .collect();
+        Replay { recording, counters, at, next: 0, shown: None, playing: false, speed: 1, owed: Duration::ZERO, ticked: None }
+    }
+
+    pub fn len(&self) -> usize {
+        self.recording.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recording.frames.is_empty()
+    }
+
+    // the frame on screen
+    pub fn frame(&self) -> Option<&Frame> {
+        self.recording.frames.get(self.shown?)
+    }
+
+    // the time and interval sample() is given for frame `i`
+    pub fn timing(&self, i: usize) -> (Instant, Duration) {
+        (self.at[i], self.recording.frames[i].duration())
+    }
+
+    pub fn toggle_playing(&mut self, now: Instant) {
+        self.playing = !self.playing;
+        self.owed = Duration::ZERO;
+        self.ticked = self.playing.then_some(now);
+    }
+
+    // frames whose recorded time has passed at the playback speed since the last tick
+    pub fn due(&mut self, now: Instant) -> usize {
+        if !self.playing {
+            return 0;
+        }
+        let last = self.ticked.replace(now).unwrap_or(now);
+        self.owed += now.saturating_duration_since(last) * self.speed;
+        let mut due = 0;
+        let mut i = self.shown.map_or(0, |s| s + 1);
+        while let Some(frame) = self.recording.frames.get(i) {
+            let interval = frame.duration();
+            if self.owed < interval {
+                break;
+            }
+            self.owed -= interval;
+            due += 1;
+            i += 1;
+        }
+        due
+    }
+}
+
+impl NetworkSource for Replay {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.counters.get(self.next).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_and_json_lines() {
+        let text = "\
+timestamp,interface,rx_bps,tx_bps,packets_in_ps,packets_out_ps,errors_in_ps,errors_out_ps
+2026-10-15T18:17:10.000Z,eth0,1024.0,512.0,2.00,1.00,0.00,0.00
+2026-10-15T18:17:10.000Z,\"we,ird\",0.0,0.0,0.00,0.00,0.00,0.00
+2026-10-15T18:17:12.000Z,eth0,2048.0,0.0,4.00,0.00,0.00,0.00
+not a record
+{\"timestamp\":1792088233,\"interval_secs\":0.5,\"interface\":\"eth0\",\"rx_bps\":100,\"tx_bps\":0,\"rx_pps\":1,\"tx_pps\":0,\"rx_errors_ps\":0,\"tx_errors_ps\":null}
+[{\"timestamp\":1792088234,\"interface\":\"eth0\",\"rx_bps\":1,\"tx_bps\":1}]
+{\"timestamp\":1792088235,\"interface\":\"eth0\"}
+2026-10-15T18:17:16.000Z,eth0,1024.0
+";
+        let recording = parse(text);
+        assert_eq!(recording.skipped, 3);
+        let frames = &recording.frames;
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0].rows.len(), 2);
+        assert_eq!(frames[0].rows[1].interface, "we,ird");
+        assert_eq!(frames[0].interval, 2.0, "the gap to the next frame");
+        assert_eq!(frames[1].interval, 2.0, "the gap since the previous one");
+        assert_eq!(frames[1].rows[0].packets_in, 4.0);
+        assert_eq!(frames[2].interval, 0.5, "as recorded");
+        assert_eq!(frames[2].rows[0].errors_out, 0.0);
+        assert_eq!(frames[3].unix, 1_792_088_234.0);
+    }
+
+    #[test]
+    fn out_of_range_times_are_skipped() {
+        let text = r#"{"timestamp":1792088230,"interval_secs":1e999,"interface":"eth0","rx_bps":1,"tx_bps":1}
+{"timestamp":1792088230,"interval_secs":1e20,"interface":"eth0","rx_bps":1,"tx_bps":1}
+{"timestamp":1e999,"interface":"eth0","rx_bps":1,"tx_bps":1}
+[{"timestamp":-1,"interface":"eth0","rx_bps":1,"tx_bps":1}]
+99999-01-01T00:00:00.000Z,eth0,1.0,0.0,0.00,0.00,0.00,0.00
+{"timestamp":1792088231,"interval_secs":0,"interface":"eth0","rx_bps":1,"tx_bps":1}
+"#;
+        let recording = parse(text);
+        assert_eq!(recording.skipped, 5);
+        assert_eq!(recording.frames.len(), 1);
+        assert_eq!(recording.frames[0].interval, 1.0, "zero is left to the timestamps");
+        let replay = Replay::new(recording, Instant::now());
+        assert_eq!(replay.timing(0).1, Duration::from_secs(1));
+        let bad = Frame { unix: 0.0, interval: f64::INFINITY, rows: Vec::new() };
+        assert_eq!(bad.duration(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn quoted_fields() {
+        assert_eq!(split_fields("a,\"b,\"\"c\"\"\",d"), Some(vec!["a".to_string(), "b,\"c\"".to_string(), "d".to_string()]));
+        assert_eq!(split_fields("a,\"b"), None);
+    }
+
+    #[test]
+    fn counters_add_up_the_rates() {
+        let text = "\
+2026-10-15T18:17:10.000Z,eth0,1024.0,0.0,2.00,0.00,0.00,0.00
+2026-10-15T18:17:12.000Z,eth0,512.0,0.0,1.00,0.00,0.50,0.00
+2026-10-15T18:17:12.000Z,eth1,100.0,0.0,0.00,0.00,0.00,0.00
+";
+        let now = Instant::now();
+        let mut replay = Replay::new(parse(text), now);
+        assert_eq!(replay.len(), 2);
+        let read = |replay: &mut Replay, next| {
+            replay.next = next;
+            replay.snapshot().into_iter().map(|c| (c.name, c.rx_bytes, c.rx_packets, c.rx_errors)).collect::<Vec<_>>()
+        };
+        assert_eq!(read(&mut replay, 0), [("eth0".to_string(), 0, 0, 0)]);
+        assert_eq!(read(&mut replay, 1), [("eth0".to_string(), 2048, 4, 0)]);
+        assert_eq!(read(&mut replay, 2), [("eth0".to_string(), 3072, 6, 1), ("eth1".to_string(), 200, 0, 0)]);
+        assert_eq!(replay.timing(1), (now, Duration::from_secs(2)), "the last frame is now");
+        assert_eq!(replay.timing(0).0 + Duration::from_secs(2), now);
+    }
+
+    #[test]
+    fn playback_pace() {
+        let text = (0..10).map(|i| format!("2026-10-15T18:17:{:02}.000Z,eth0,1.0,1.0,0,0,0,0\n", 10 + i)).collect::<String>();
+        let start = Instant::now();
+        let mut replay = Replay::new(parse(&text), start);
+        assert_eq!(replay.due(start + Duration::from_secs(5)), 0, "paused");
+        replay.toggle_playing(start);
+        replay.shown = Some(0);
+        assert_eq!(replay.due(start + Duration::from_millis(1500)), 1);
+        replay.shown = Some(1);
+        replay.speed = 4;
+        assert_eq!(replay.due(start + Duration::from_millis(2000)), 2, "0.5s owed plus 2s at 4x");
+        replay.shown = Some(3);
+        assert_eq!(replay.due(start + Duration::from_secs(60)), 6, "no further than the last frame");
+    }
+}
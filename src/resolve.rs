@@ -0,0 +1,339 @@
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::sync::mpsc::{self, Sender, SyncSender};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::event::AppEvent;
+
+// addresses waiting for the worker; past this a request is refused and asked again later
+const QUEUE: usize = 64;
+
+// how long one nameserver gets to answer
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+// how long "no name" is believed: NXDOMAIN and empty answers, and shorter for a resolver
+// that didn't answer at all
+const NEGATIVE_TTL: Duration = Duration::from_secs(300);
+const FAILURE_TTL: Duration = Duration::from_secs(60);
+
+// the record's own TTL, within reason
+const MIN_TTL: Duration = Duration::from_secs(30);
+const MAX_TTL: Duration = Duration::from_secs(3600);
+
+pub const DEFAULT_CACHE: usize = 1024;
+
+// what a PTR query came back with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Name(String, Duration),
+    NoName,
+}
+
+// the name a PTR lookup asks for: 4.3.2.1.in-addr.arpa, or a v6 address's nibbles
+// backwards under ip6.arpa
+pub fn ptr_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, d] = v4.octets();
+            format!("{d}.{c}.{b}.{a}.in-addr.arpa")
+        }
+        IpAddr::V6(v6) => {
+            let mut name: String = v6.octets().iter().rev().map(|b| format!("{:x}.{:x}.", b & 0x0f, b >> 4)).collect();
+            name.push_str("ip6.arpa");
+            name
+        }
+    }
+}
+
+// a recursive PTR query for `ip`
+pub fn query(id: u16, ip: IpAddr) -> Vec<u8> {
+    let mut q = Vec::with_capacity(96);
+    q.extend(id.to_be_bytes());
+    // recursion desired, one question
+    q.extend([0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in ptr_name(ip).split('.') {
+        q.push(label.len() as u8);
+        q.extend(label.as_bytes());
+    }
+    q.push(0);
+    // PTR, IN
+    q.extend([0, 12, 0, 1]);
+    q
+}
+
+// the first PTR among the answers to query `id`; None for anything that isn't a usable
+// reply to it (another id, cut short, SERVFAIL), so the next nameserver gets a try
+pub fn parse_reply(id: u16, msg: &[u8]) -> Option<Answer> {
+    let word = |at: usize| msg.get(at..at + 2).map(|w| u16::from_be_bytes([w[0], w[1]]));
+    if msg.len() < 12 || word(0)? != id || msg[2] & 0x80 == 0 {
+        return None;
+    }
+    match msg[3] & 0x0f {
+        0 => {}
+        3 => return Some(Answer::NoName),
+        _ => return None,
+    }
+    let (questions, answers) = (word(4)?, word(6)?);
+    let mut at = 12;
+    for _ in 0..questions {
+        at = read_name(msg, at)?.1 + 4;
+    }
+    for _ in 0..answers {
+        at = read_name(msg, at)?.1;
+        let (kind, len) = (word(at)?, usize::from(word(at + 8)?));
+        let ttl = u32::from_be_bytes(msg.get(at + 4..at + 8)?.try_into().ok()?);
+        let data = at + 10;
+        if kind == 12 {
+            let name = read_name(msg, data)?.0;
+            let ttl = Duration::from_secs(ttl.into()).clamp(MIN_TTL, MAX_TTL);
+            return Some(if name.is_empty() { Answer::NoName } else { Answer::Name(name, ttl) });
+        }
+        at = data + len;
+    }
+    Some(Answer::NoName)
+}
+
+// a domain name at `at`, following compression pointers; the position after it in the
+// message (not after what a pointer led to)
+fn read_name(msg: &[u8], mut at: usize) -> Option<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut end = None;
+    // a pointer loop gives up here
+    for _ in 0..128 {
+        let len = *msg.get(at)?;
+        match len {
+            0 => return Some((labels.join("."), end.unwrap_or(at + 1))),
+            l if l & 0xc0 == 0xc0 => {
+                end.get_or_insert(at + 2);
+                at = usize::from(u16::from_be_bytes([l & 0x3f, *msg.get(at + 1)?]));
+            }
+            l => {
+                let label = msg.get(at + 1..at + 1 + usize::from(l))?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                at += 1 + usize::from(l);
+            }
+        }
+    }
+    None
+}
+
+// the `nameserver` lines of resolv.conf; scoped v6 addresses are skipped
+pub fn nameservers(resolv_conf: &str) -> Vec<IpAddr> {
+    resolv_conf
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("nameserver"))
+        .filter_map(|rest| rest.split_whitespace().next()?.parse().ok())
+        .collect()
+}
+
+// one lookup, asking each nameserver in turn; None when none of them answered
+fn lookup(ip: IpAddr, servers: &[IpAddr], id: u16) -> Option<Answer> {
+    servers.iter().find_map(|&server| {
+        let local: SocketAddr = if server.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+        let socket = UdpSocket::bind(local).ok()?;
+        socket.set_read_timeout(Some(TIMEOUT)).ok()?;
+        socket.connect((server, 53)).ok()?;
+        socket.send(&query(id, ip)).ok()?;
+        let mut buf = [0u8; 512];
+        let deadline = Instant::now() + TIMEOUT;
+        // stray replies to an earlier query don't count
+        while Instant::now() < deadline {
+            let n = socket.recv(&mut buf).ok()?;
+            if let Some(answer) = parse_reply(id, &buf[..n]) {
+                return Some(answer);
+            }
+        }
+        None
+    })
+}
+
+// PTR lookups on a worker thread, one at a time; each answer comes back as
+// AppEvent::Resolved so the UI never waits on a slow resolver. The thread ends when the
+// Resolver is dropped
+pub struct Resolver {
+    requests: SyncSender<IpAddr>,
+}
+
+impl Resolver {
+    pub fn start(tx: Sender<AppEvent>) -> Self {
+        let (requests, rx) = mpsc::sync_channel::<IpAddr>(QUEUE);
+        thread::spawn(move || {
+            let servers = nameservers(&std::fs::read_to_string("/etc/resolv.conf").unwrap_or_default());
+            let mut id = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.subsec_nanos() as u16);
+            while let Ok(ip) = rx.recv() {
+                id = id.wrapping_add(1);
+                let (name, ttl) = match lookup(ip, &servers, id) {
+                    Some(Answer::Name(name, ttl)) => (Some(name), ttl),
+                    Some(Answer::NoName) => (None, NEGATIVE_TTL),
+                    None => (None, FAILURE_TTL),
+                };
+                if tx.send(AppEvent::Resolved(ip, name, ttl)).is_err() {
+                    break;
+                }
+            }
+        });
+        Resolver { requests }
+    }
+
+    // false when the queue is full
+    pub fn request(&self, ip: IpAddr) -> bool {
+        self.requests.try_send(ip).is_ok()
+    }
+}
+
+struct Cached {
+    name: Option<String>,
+    expires: Instant,
+    // Names::clock when last shown, the least recent goes first
+    used: u64,
+}
+
+// address -> name, negative answers included, at most `capacity` of them
+pub struct Names {
+    entries: HashMap<IpAddr, Cached>,
+    // asked for and not answered yet
+    pending: HashSet<IpAddr>,
+    capacity: usize,
+    clock: u64,
+}
+
+impl Names {
+    pub fn new(capacity: usize) -> Self {
+        Names { entries: HashMap::new(), pending: HashSet::new(), capacity: capacity.max(1), clock: 0 }
+    }
+
+    // an expired name is still shown until the new answer is in
+    pub fn name(&self, ip: IpAddr) -> Option<&str> {
+        self.entries.get(&ip)?.name.as_deref()
+    }
+
+    // marks `ip` as shown; true when it has to be looked up (again)
+    pub fn wants(&mut self, ip: IpAddr, now: Instant) -> bool {
+        self.clock += 1;
+        match self.entries.get_mut(&ip) {
+            Some(cached) => {
+                cached.used = self.clock;
+                now >= cached.expires && !self.pending.contains(&ip)
+            }
+            None => !self.pending.contains(&ip),
+        }
+    }
+
+    pub fn asked(&mut self, ip: IpAddr) {
+        self.pending.insert(ip);
+    }
+
+    pub fn insert(&mut self, ip: IpAddr, name: Option<String>, ttl: Duration, now: Instant) {
+        self.pending.remove(&ip);
+        self.clock += 1;
+        self.entries.insert(ip, Cached { name, expires: now + ttl, used: self.clock });
+        while self.entries.len() > self.capacity {
+            let Some(oldest) = self.entries.iter().min_by_key(|(_, c)| c.used).map(|(&ip, _)| ip) else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ptr_names() {
+        assert_eq!(ptr_name("192.0.2.10".parse().unwrap()), "10.2.0.192.in-addr.arpa");
+        let v6 = ptr_name("2001:db8::567:89ab".parse().unwrap());
+        assert_eq!(v6, "b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa");
+
+        let q = query(0x1234, "1.1.1.1".parse().unwrap());
+        assert_eq!(&q[..4], &[0x12, 0x34, 0x01, 0x00]);
+        assert_eq!(&q[12..14], &[1, b'1']);
+        assert_eq!(&q[q.len() - 4..], &[0, 12, 0, 1]);
+    }
+
+    // the query echoed back with the given answer records
+    fn reply(id: u16, rcode: u8, answers: &[(u16, u32, Vec<u8>)]) -> Vec<u8> {
+        let mut msg = query(id, "1.1.1.1".parse().unwrap());
+        msg[2] = 0x81;
+        msg[3] = 0x80 | rcode;
+        msg[7] = answers.len() as u8;
+        for (kind, ttl, data) in answers {
+            // a pointer to the question's name
+            msg.extend([0xc0, 12]);
+            msg.extend(kind.to_be_bytes());
+            msg.extend([0, 1]);
+            msg.extend(ttl.to_be_bytes());
+            msg.extend((data.len() as u16).to_be_bytes());
+            msg.extend(data);
+        }
+        msg
+    }
+
+    #[test]
+    fn replies() {
+        let one = b"\x03one\x03one\x03one\x03one\x00".to_vec();
+        let ok = reply(7, 0, &[(12, 600, one.clone())]);
+        assert_eq!(parse_reply(7, &ok), Some(Answer::Name("one.one.one.one".to_string(), Duration::from_secs(600))));
+        assert_eq!(parse_reply(8, &ok), None, "another query's");
+        assert_eq!(parse_reply(7, &ok[..ok.len() - 3]), None, "cut short");
+
+        // a CNAME first, as classless delegation does; a tiny TTL is raised
+        let cname = reply(7, 0, &[(5, 60, b"\x01x\x00".to_vec()), (12, 1, one)]);
+        assert_eq!(parse_reply(7, &cname), Some(Answer::Name("one.one.one.one".to_string(), MIN_TTL)));
+
+        // a name that points into the question
+        let pointer = reply(7, 0, &[(12, 60, vec![0x03, b'd', b'n', b's', 0xc0, 12])]);
+        assert!(matches!(parse_reply(7, &pointer), Some(Answer::Name(name, _)) if name == "dns.1.1.1.1.in-addr.arpa"));
+
+        assert_eq!(parse_reply(7, &reply(7, 3, &[])), Some(Answer::NoName), "NXDOMAIN");
+        assert_eq!(parse_reply(7, &reply(7, 0, &[])), Some(Answer::NoName), "no records");
+        assert_eq!(parse_reply(7, &reply(7, 2, &[])), None, "SERVFAIL");
+
+        // a pointer to itself
+        let mut looped = reply(7, 0, &[(12, 60, vec![0xc0, 0])]);
+        let at = looped.len() - 2;
+        looped[at + 1] = at as u8;
+        assert_eq!(parse_reply(7, &looped), None);
+    }
+
+    #[test]
+    fn resolv_conf() {
+        let text = "# generated\nnameserver 127.0.0.53\noptions edns0\nnameserver 2001:db8::1\nnameserver fe80::1%eth0\n";
+        assert_eq!(nameservers(text), ["127.0.0.53".parse::<IpAddr>().unwrap(), "2001:db8::1".parse().unwrap()]);
+    }
+
+    #[test]
+    fn cache() {
+        let now = Instant::now();
+        let ip = |n: u8| IpAddr::from([10, 0, 0, n]);
+        let mut names = Names::new(2);
+        assert!(names.wants(ip(1), now));
+        names.asked(ip(1));
+        assert!(!names.wants(ip(1), now), "already asked");
+        names.insert(ip(1), Some("one".to_string()), Duration::from_secs(60), now);
+        names.insert(ip(2), None, NEGATIVE_TTL, now);
+        assert_eq!((names.name(ip(1)), names.name(ip(2))), (Some("one"), None));
+        assert!(!names.wants(ip(2), now), "negative answers are cached too");
+
+        // expired: asked again, the old name stays until then
+        let later = now + Duration::from_secs(61);
+        assert!(names.wants(ip(1), later));
+        assert_eq!(names.name(ip(1)), Some("one"));
+
+        // ip(1) was shown last, so ip(2) makes room
+        names.insert(ip(3), Some("three".to_string()), Duration::from_secs(60), later);
+        assert_eq!(names.len(), 2);
+        assert_eq!((names.name(ip(1)), names.name(ip(3))), (Some("one"), Some("three")));
+        assert!(names.wants(ip(2), later));
+    }
+}
@@ -0,0 +1,233 @@
+// the system routing table: /proc/net/route and /proc/net/ipv6_route on Linux, `netstat -rn`
+// elsewhere; the parsers are pure so fixtures can stand in for both
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    pub destination: IpAddr,
+    pub prefix: u8,
+    // None for directly connected networks
+    pub gateway: Option<IpAddr>,
+    pub interface: String,
+    // lower wins; netstat doesn't show metrics, there every route has 0 and the first one
+    // listed wins instead
+    pub metric: u32,
+}
+
+impl Route {
+    pub fn is_default(&self) -> bool {
+        self.prefix == 0
+    }
+
+    // `ip route` style: "default via 192.168.1.1 metric 100", "10.0.0.0/24 metric 100"
+    pub fn label(&self) -> String {
+        let mut label = if self.is_default() { "default".to_string() } else { format!("{}/{}", self.destination, self.prefix) };
+        if let Some(gw) = self.gateway {
+            label.push_str(&format!(" via {gw}"));
+        }
+        if self.metric > 0 {
+            label.push_str(&format!(" metric {}", self.metric));
+        }
+        label
+    }
+}
+
+// the interfaces that carry the default route: the lowest metric of each family, the
+// earlier route on a tie
+pub fn default_interfaces(routes: &[Route]) -> Vec<&str> {
+    let best = |v4: bool| {
+        routes.iter().filter(|r| r.is_default() && r.destination.is_ipv4() == v4).min_by_key(|r| r.metric).map(|r| r.interface.as_str())
+    };
+    let mut names: Vec<&str> = [best(true), best(false)].into_iter().flatten().collect();
+    names.dedup();
+    names
+}
+
+const RTF_UP: u32 = 0x0001;
+const RTF_REJECT: u32 = 0x0200;
+const RTF_LOCAL: u32 = 0x8000_0000;
+
+// /proc/net/route: a header, then hex fields in host byte order; routes that aren't up
+// are left out
+pub fn parse_v4(text: &str) -> Vec<Route> {
+    let hex = |s: &str| u32::from_str_radix(s, 16).ok();
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let f: Vec<&str> = line.split_whitespace().collect();
+            let (flags, metric, mask) = (hex(f.get(3)?)?, f.get(6)?.parse().ok()?, hex(f.get(7)?)?);
+            if flags & RTF_UP == 0 {
+                return None;
+            }
+            let addr = |s: &str| hex(s).map(|v| IpAddr::V4(Ipv4Addr::from(v.to_ne_bytes())));
+            let gateway = addr(f.get(2)?)?;
+            Some(Route {
+                destination: addr(f.get(1)?)?,
+                prefix: mask.count_ones() as u8,
+                gateway: Some(gateway).filter(|g| !g.is_unspecified()),
+                interface: f[0].to_string(),
+                metric,
+            })
+        })
+        .collect()
+}
+
+// /proc/net/ipv6_route: destination, prefix, source, prefix, next hop, metric, refcount,
+// use, flags, interface, all hex but the last. The kernel's own routes (local addresses,
+// multicast, the unreachable catch-alls on lo) aren't part of the table one configures
+pub fn parse_v6(text: &str) -> Vec<Route> {
+    let hex = |s: &str| u32::from_str_radix(s, 16).ok();
+    let addr = |s: &str| u128::from_str_radix(s, 16).ok().map(|v| IpAddr::V6(Ipv6Addr::from(v)));
+    text.lines()
+        .filter_map(|line| {
+            let f: Vec<&str> = line.split_whitespace().collect();
+            let flags = hex(f.get(8)?)?;
+            let destination = addr(f.first()?)?;
+            if flags & RTF_UP == 0 || flags & (RTF_REJECT | RTF_LOCAL) != 0 || destination.is_multicast() {
+                return None;
+            }
+            Some(Route {
+                destination,
+                prefix: u8::from_str_radix(f.get(1)?, 16).ok()?,
+                gateway: addr(f.get(4)?).filter(|g| !g.is_unspecified()),
+                interface: f.get(9)?.to_string(),
+                metric: hex(f.get(5)?)?,
+            })
+        })
+        .collect()
+}
+
+// `netstat -rn` on macOS and the BSDs: "Internet:" and "Internet6:" sections of
+// destination, gateway, flags, interface, multicast left out as on Linux. Destinations
+// come abbreviated ("127", "192.168.1") and scoped ("fe80::%lo0/64"); gateways that
+// aren't addresses ("link#6", a MAC) mean directly connected
+pub fn parse_netstat(text: &str) -> Vec<Route> {
+    let mut routes = Vec::new();
+    let mut v6 = None;
+    for line in text.lines() {
+        match line.trim() {
+            "Internet:" => v6 = Some(false),
+            "Internet6:" => v6 = Some(true),
+            _ => {}
+        }
+        let f: Vec<&str> = line.split_whitespace().collect();
+        let (Some(v6), [destination, gateway, _flags, interface, ..]) = (v6, f.as_slice()) else {
+            continue;
+        };
+        let Some((destination, prefix)) = netstat_destination(destination, v6).filter(|(d, _)| !d.is_multicast()) else {
+            continue;
+        };
+        let gateway = unscoped(gateway).parse().ok().filter(|g: &IpAddr| g.is_ipv6() == v6);
+        routes.push(Route { destination, prefix, gateway, interface: interface.to_string(), metric: 0 });
+    }
+    routes
+}
+
+fn unscoped(addr: &str) -> &str {
+    addr.split('%').next().unwrap_or(addr)
+}
+
+fn netstat_destination(s: &str, v6: bool) -> Option<(IpAddr, u8)> {
+    if s == "default" {
+        return Some((if v6 { Ipv6Addr::UNSPECIFIED.into() } else { Ipv4Addr::UNSPECIFIED.into() }, 0));
+    }
+    let (addr, prefix) = match s.split_once('/') {
+        Some((addr, prefix)) => (addr, Some(prefix.parse().ok()?)),
+        None => (s, None),
+    };
+    let addr = unscoped(addr);
+    if v6 {
+        return Some((addr.parse::<Ipv6Addr>().ok()?.into(), prefix.unwrap_or(128)));
+    }
+    // the octets given, the rest zero
+    let octets: Vec<u8> = addr.split('.').map(|o| o.parse().ok()).collect::<Option<_>>()?;
+    if octets.is_empty() || octets.len() > 4 {
+        return None;
+    }
+    let mut full = [0u8; 4];
+    full[..octets.len()].copy_from_slice(&octets);
+    Some((Ipv4Addr::from(full).into(), prefix.unwrap_or(octets.len() as u8 * 8)))
+}
+
+#[cfg(target_os = "linux")]
+pub fn read() -> Vec<Route> {
+    let read = |path: &str| std::fs::read_to_string(path).unwrap_or_default();
+    let mut routes = parse_v4(&read("/proc/net/route"));
+    routes.extend(parse_v6(&read("/proc/net/ipv6_route")));
+    routes
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read() -> Vec<Route> {
+    std::process::Command::new("netstat")
+        .arg("-rn")
+        .output()
+        .map(|out| parse_netstat(&String::from_utf8_lossy(&out.stdout)))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))).unwrap()
+    }
+
+    fn labels(routes: &[Route], interface: &str) -> Vec<String> {
+        routes.iter().filter(|r| r.interface == interface).map(Route::label).collect()
+    }
+
+    // the file holds addresses in host byte order
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn proc_net_route() {
+        let routes = parse_v4(&fixture("proc_net_route"));
+        assert_eq!(labels(&routes, "eth0"), ["default via 10.0.0.1 metric 100", "10.0.0.0/24 metric 100"]);
+        assert_eq!(labels(&routes, "wlan0"), ["default via 192.168.1.1 metric 600", "192.168.1.0/24 metric 600"]);
+        assert_eq!(labels(&routes, "docker0"), ["172.17.0.0/16"]);
+        assert!(labels(&routes, "wg0").is_empty(), "not up");
+        // two defaults, the lower metric carries the traffic
+        assert_eq!(default_interfaces(&routes), ["eth0"]);
+    }
+
+    #[test]
+    fn proc_net_ipv6_route() {
+        let routes = parse_v6(&fixture("proc_net_ipv6_route"));
+        assert_eq!(labels(&routes, "eth0"), ["2001:db8:85a3::/64 metric 256", "fe80::/64 metric 256", "default via fe80::1 metric 256"]);
+        assert_eq!(labels(&routes, "wlan0"), ["default via fe80::1 metric 1024"]);
+        assert!(labels(&routes, "lo").is_empty(), "local and unreachable routes");
+        assert_eq!(default_interfaces(&routes), ["eth0"]);
+    }
+
+    #[test]
+    fn netstat() {
+        let routes = parse_netstat(&fixture("netstat_rn_macos"));
+        assert_eq!(labels(&routes, "utun3"), ["default via 10.8.0.1", "10.8.0.0/16"]);
+        assert_eq!(labels(&routes, "en0"), [
+            "default via 192.168.1.1",
+            "192.168.1.0/24",
+            "192.168.1.1/32",
+            "192.168.1.1/32",
+            "default via fe80::a691:b1ff:fe2c:3d4e",
+            "2001:db8:1::/64",
+        ]);
+        assert_eq!(labels(&routes, "lo0"), ["127.0.0.0/8 via 127.0.0.1", "127.0.0.1/32 via 127.0.0.1", "::1/128 via ::1", "fe80::/64 via fe80::1"]);
+        // no metrics: the first default listed
+        assert_eq!(default_interfaces(&routes), ["en0"]);
+    }
+
+    #[test]
+    fn defaults_per_family() {
+        let route = |dest: &str, interface: &str, metric| Route {
+            destination: dest.parse().unwrap(),
+            prefix: 0,
+            gateway: None,
+            interface: interface.to_string(),
+            metric,
+        };
+        let routes = [route("0.0.0.0", "wlan0", 600), route("::", "eth0", 1024), route("0.0.0.0", "wwan0", 700)];
+        assert_eq!(default_interfaces(&routes), ["wlan0", "eth0"]);
+        assert!(default_interfaces(&[]).is_empty());
+    }
+}
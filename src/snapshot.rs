@@ -0,0 +1,220 @@
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::net::RowData;
+
+// one interface at one sample time, raw numbers only so scripts can do math;
+// the field names are the documented JSON schema (see --json in --help)
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotRow {
+    // seconds since the unix epoch
+    pub timestamp: f64,
+    // length of the interval the rates were measured over
+    pub interval_secs: f64,
+    pub interface: String,
+    pub rx_bps: f64,
+    pub tx_bps: f64,
+    pub rx_pps: f64,
+    pub tx_pps: f64,
+    pub rx_errors_ps: f64,
+    pub tx_errors_ps: f64,
+    // bytes since nettui started
+    pub rx_bytes_total: u64,
+    pub tx_bytes_total: u64,
+}
+
+impl SnapshotRow {
+    pub fn new(r: &RowData, timestamp: f64, interval_secs: f64) -> Self {
+        SnapshotRow {
+            timestamp,
+            interval_secs,
+            interface: r.interface.clone(),
+            rx_bps: r.rx_bps,
+            tx_bps: r.tx_bps,
+            rx_pps: r.packets_in,
+            tx_pps: r.packets_out,
+            rx_errors_ps: r.errors_in,
+            tx_errors_ps: r.errors_out,
+            rx_bytes_total: r.totals.rx_bytes,
+            tx_bytes_total: r.totals.tx_bytes,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{");
+        let _ = write!(out, "\"timestamp\":{}", number(self.timestamp));
+        let _ = write!(out, ",\"time\":{}", string(&iso8601(self.timestamp)));
+        let _ = write!(out, ",\"interval_secs\":{}", number(self.interval_secs));
+        let _ = write!(out, ",\"interface\":{}", string(&self.interface));
+        for (key, v) in [
+            ("rx_bps", self.rx_bps),
+            ("tx_bps", self.tx_bps),
+            ("rx_pps", self.rx_pps),
+            ("tx_pps", self.tx_pps),
+            ("rx_errors_ps", self.rx_errors_ps),
+            ("tx_errors_ps", self.tx_errors_ps),
+        ] {
+            let _ = write!(out, ",\"{key}\":{}", number(v));
+        }
+        let _ = write!(out, ",\"rx_bytes_total\":{}", self.rx_bytes_total);
+        let _ = write!(out, ",\"tx_bytes_total\":{}", self.tx_bytes_total);
+        out.push('}');
+        out
+    }
+}
+
+pub fn unix_now() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0)
+}
+
+// UTC, millisecond precision: 2026-10-15T18:14:00.123Z
+pub fn iso8601(unix: f64) -> String {
+    let millis = (unix * 1000.0).round() as i64;
+    let (secs, ms) = (millis.div_euclid(1000), millis.rem_euclid(1000));
+    let (days, sod) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // civil-from-days, Howard Hinnant's algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{ms:03}Z",
+        sod / 3600,
+        sod % 3600 / 60,
+        sod % 60
+    )
+}
+
+// the other way round, for reading recordings back; "Z" only, as iso8601 writes it
+pub fn parse_iso8601(s: &str) -> Option<f64> {
+    let s = s.strip_suffix('Z')?;
+    let (date, time) = s.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let (hms, frac) = time.split_once('.').unwrap_or((time, ""));
+    let mut hms = hms.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+    let (h, m, sec) = (hms.next()??, hms.next()??, hms.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || h > 23 || m > 59 || sec > 60 {
+        return None;
+    }
+    let frac: f64 = if frac.is_empty() { 0.0 } else { format!("0.{frac}").parse().ok()? };
+
+    // days-from-civil, the inverse of the algorithm above
+    let y = year - i64::from(month <= 2);
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some((days * 86_400 + h * 3600 + m * 60 + sec) as f64 + frac)
+}
+
+pub fn to_json_array(rows: &[SnapshotRow]) -> String {
+    let items: Vec<String> = rows.iter().map(SnapshotRow::to_json).collect();
+    format!("[{}]", items.join(","))
+}
+
+// JSON has no NaN/inf
+pub(crate) fn number(v: f64) -> String {
+    if v.is_finite() { format!("{v}") } else { "null".to_string() }
+}
+
+pub(crate) fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// a row for tests to start from and set what they look at: one interface's receive rate over
+// the second to 2026-10-15T18:17:10Z, everything else zero
+#[cfg(test)]
+impl SnapshotRow {
+    pub(crate) fn sample(interface: &str, rx_bps: f64) -> SnapshotRow {
+        SnapshotRow {
+            timestamp: 1_792_088_230.0,
+            interval_secs: 1.0,
+            interface: interface.to_string(),
+            rx_bps,
+            tx_bps: 0.0,
+            rx_pps: 0.0,
+            tx_pps: 0.0,
+            rx_errors_ps: 0.0,
+            tx_errors_ps: 0.0,
+            rx_bytes_total: 0,
+            tx_bytes_total: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_schema() {
+        let row = SnapshotRow {
+            timestamp: 1700000000.5,
+            interval_secs: 0.5,
+            interface: "eth0".to_string(),
+            rx_bps: 1024.0,
+            tx_bps: 0.0,
+            rx_pps: 2.5,
+            tx_pps: 0.0,
+            rx_errors_ps: 0.0,
+            tx_errors_ps: f64::NAN,
+            rx_bytes_total: 4096,
+            tx_bytes_total: 0,
+        };
+        assert_eq!(
+            row.to_json(),
+            "{\"timestamp\":1700000000.5,\"time\":\"2023-11-14T22:13:20.500Z\",\"interval_secs\":0.5,\
+             \"interface\":\"eth0\",\"rx_bps\":1024,\"tx_bps\":0,\
+             \"rx_pps\":2.5,\"tx_pps\":0,\"rx_errors_ps\":0,\"tx_errors_ps\":null,\
+             \"rx_bytes_total\":4096,\"tx_bytes_total\":0}"
+        );
+        assert_eq!(to_json_array(&[]), "[]");
+    }
+
+    #[test]
+    fn iso8601_dates() {
+        assert_eq!(iso8601(0.0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(iso8601(951_782_400.25), "2000-02-29T00:00:00.250Z");
+        assert_eq!(iso8601(1_792_088_230.0), "2026-10-15T18:17:10.000Z");
+    }
+
+    #[test]
+    fn iso8601_round_trip() {
+        for unix in [0.0, 951_782_400.25, 1_792_088_230.0, 1_700_000_000.5] {
+            assert_eq!(parse_iso8601(&iso8601(unix)), Some(unix));
+        }
+        assert_eq!(parse_iso8601("2026-10-15T18:17:10Z"), Some(1_792_088_230.0));
+        assert_eq!(parse_iso8601("2026-10-15 18:17:10"), None);
+        assert_eq!(parse_iso8601("2026-13-15T18:17:10Z"), None);
+    }
+
+    #[test]
+    fn escapes_interface_names() {
+        assert_eq!(string("a\"b\\c\u{1}"), "\"a\\\"b\\\\c\\u0001\"");
+    }
+}
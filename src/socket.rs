@@ -0,0 +1,154 @@
+// --socket: the latest sample for scripts and status bars (waybar, polybar) over a Unix socket,
+// one query per line and one line back. `GET eth0` is that interface's --json object, `LIST`
+// the array of all of them; anything else gets {"error": ...}
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::net::RowData;
+use crate::snapshot::{self, SnapshotRow};
+
+// a client that has sent nothing for this long is hung up on, so idle ones don't pile up
+#[cfg_attr(not(unix), allow(dead_code))]
+const IDLE: Duration = Duration::from_secs(60);
+
+// $XDG_RUNTIME_DIR/nettui.sock, or a per-user name in the temp directory without one
+pub fn default_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("nettui.sock"),
+        None => {
+            let user = std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_default();
+            std::env::temp_dir().join(if user.is_empty() { "nettui.sock".to_string() } else { format!("nettui-{user}.sock") })
+        }
+    }
+}
+
+// the reply to one line, without the newline
+pub fn answer(latest: &[SnapshotRow], line: &str) -> String {
+    let mut words = line.split_whitespace();
+    let verb = words.next().unwrap_or_default().to_ascii_uppercase();
+    let error = |e: String| format!("{{\"error\":{}}}", snapshot::string(&e));
+    match (verb.as_str(), words.next(), words.next()) {
+        ("LIST", None, _) => snapshot::to_json_array(latest),
+        ("GET", Some(name), None) => match latest.iter().find(|r| r.interface == name) {
+            Some(row) => row.to_json(),
+            None if latest.is_empty() => error("no sample yet".to_string()),
+            None => error(format!("no interface {name}")),
+        },
+        ("GET", ..) => error("usage: GET <interface>".to_string()),
+        ("", ..) => error("empty query, try LIST or GET <interface>".to_string()),
+        _ => error(format!("unknown query {verb}, try LIST or GET <interface>")),
+    }
+}
+
+// the sink sample() feeds; clients are served on their own threads from the last sample
+pub struct QuerySocket {
+    pub path: PathBuf,
+    latest: Arc<Mutex<Vec<SnapshotRow>>>,
+}
+
+impl QuerySocket {
+    #[cfg(unix)]
+    pub fn start(path: &Path) -> io::Result<QuerySocket> {
+        use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        // a socket nobody answers on is left over from a crash, one that answers is in use;
+        // anything else at the path is somebody's file and stays
+        if let Ok(meta) = std::fs::symlink_metadata(path) {
+            if !meta.file_type().is_socket() {
+                let message = format!("{} exists and isn't a socket", path.display());
+                return Err(io::Error::new(io::ErrorKind::AlreadyExists, message));
+            }
+            if UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(io::ErrorKind::AddrInUse, "another nettui is answering on it"));
+            }
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        // the rates aren't secret, but they're nobody else's business either
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        let latest: Arc<Mutex<Vec<SnapshotRow>>> = Arc::new(Mutex::new(Vec::new()));
+        let shared = Arc::clone(&latest);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let latest = Arc::clone(&shared);
+                std::thread::spawn(move || {
+                    let _ = stream.set_read_timeout(Some(IDLE));
+                    let mut out = &stream;
+                    for line in BufReader::new(&stream).lines() {
+                        let Ok(line) = line else { break };
+                        let reply = answer(&latest.lock().unwrap_or_else(|e| e.into_inner()), &line);
+                        if writeln!(out, "{reply}").is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        Ok(QuerySocket { path: path.to_path_buf(), latest })
+    }
+
+    #[cfg(not(unix))]
+    pub fn start(_path: &Path) -> io::Result<QuerySocket> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "--socket needs Unix domain sockets"))
+    }
+
+    pub fn update(&self, rows: &[RowData], unix: f64, interval_secs: f64) {
+        let rows = rows.iter().map(|r| SnapshotRow::new(r, unix, interval_secs)).collect();
+        *self.latest.lock().unwrap_or_else(|e| e.into_inner()) = rows;
+    }
+}
+
+// the file goes with the process, a later start would otherwise find it
+impl Drop for QuerySocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// `nettui query`: one line there, one line back
+#[cfg(unix)]
+pub fn query(path: &Path, line: &str) -> io::Result<String> {
+    let stream = std::os::unix::net::UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    writeln!(&stream, "{line}")?;
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply)?;
+    Ok(reply.trim_end().to_string())
+}
+
+#[cfg(not(unix))]
+pub fn query(_path: &Path, _line: &str) -> io::Result<String> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "queries need Unix domain sockets"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers() {
+        assert_eq!(answer(&[], "GET eth0"), "{\"error\":\"no sample yet\"}");
+        let latest = [SnapshotRow::sample("eth0", 2048.0), SnapshotRow::sample("wlan0", 0.0)];
+        assert!(answer(&latest, "GET eth0").starts_with("{\"timestamp\":1792088230,"));
+        assert!(answer(&latest, "get  eth0 ").contains("\"rx_bps\":2048"), "case and spacing aside");
+        assert_eq!(answer(&latest, "GET eth9"), "{\"error\":\"no interface eth9\"}");
+        assert_eq!(answer(&latest, "GET"), "{\"error\":\"usage: GET <interface>\"}");
+        assert!(answer(&latest, "LIST").starts_with("[{") && answer(&latest, "LIST").contains("wlan0"));
+        assert!(answer(&latest, "STATS").contains("unknown query STATS"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn leaves_other_files_alone() {
+        let path = std::env::temp_dir().join(format!("nettui-notes-{}.txt", std::process::id()));
+        std::fs::write(&path, "not a socket").unwrap();
+        let err = QuerySocket::start(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(err.to_string().contains("isn't a socket"), "{err}");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a socket");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
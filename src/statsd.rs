@@ -0,0 +1,116 @@
+// --statsd: each sample as statsd gauges over UDP, `nettui.eth0.rx_bps:2048|g`, for a statsd
+// or Telegraf listener already on the network. Sends never wait: a datagram the socket can't
+// take right now is dropped and counted
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+
+use crate::net::RowData;
+use crate::snapshot::SnapshotRow;
+
+// the payload that fits an Ethernet frame after IP and UDP headers, what statsd clients
+// usually keep to so nothing gets fragmented
+pub const MAX_PAYLOAD: usize = 1432;
+
+// a metric name's dots separate its parts and a colon ends it, so an interface name loses
+// both (eth0.100 becomes eth0_100), along with the rest of statsd's syntax and whitespace
+pub fn sanitize(name: &str) -> String {
+    name.chars().map(|c| if matches!(c, '.' | ':' | '|' | '@' | '#' | ',') || c.is_whitespace() || c.is_control() { '_' } else { c }).collect()
+}
+
+// one gauge per interface and metric; rates that aren't finite are left out
+pub fn lines(prefix: &str, rows: &[SnapshotRow]) -> Vec<String> {
+    let prefix = if prefix.is_empty() { String::new() } else { format!("{prefix}.") };
+    let mut out = Vec::new();
+    for r in rows {
+        let iface = sanitize(&r.interface);
+        let gauges = [
+            ("rx_bps", r.rx_bps),
+            ("tx_bps", r.tx_bps),
+            ("rx_pps", r.rx_pps),
+            ("tx_pps", r.tx_pps),
+            ("rx_errors_ps", r.rx_errors_ps),
+            ("tx_errors_ps", r.tx_errors_ps),
+            ("rx_bytes_total", r.rx_bytes_total as f64),
+            ("tx_bytes_total", r.tx_bytes_total as f64),
+        ];
+        out.extend(gauges.iter().filter(|(_, v)| v.is_finite()).map(|(k, v)| format!("{prefix}{iface}.{k}:{v}|g")));
+    }
+    out
+}
+
+// lines packed newline separated into datagrams of at most `max` bytes; a line longer than
+// that still goes, alone
+pub fn datagrams(lines: &[String], max: usize) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for line in lines {
+        match out.last_mut() {
+            Some(last) if last.len() + 1 + line.len() <= max => {
+                last.push('\n');
+                last.push_str(line);
+            }
+            _ => out.push(line.clone()),
+        }
+    }
+    out
+}
+
+// the sink sample() feeds
+pub struct Statsd {
+    pub prefix: String,
+    socket: UdpSocket,
+    // datagrams the socket wouldn't take or the network refused
+    pub dropped: u64,
+}
+
+impl Statsd {
+    pub fn start(addr: &str, prefix: &str) -> io::Result<Statsd> {
+        let to = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("cannot resolve {addr}")))?;
+        let socket = UdpSocket::bind(if to.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" })?;
+        socket.connect(to)?;
+        socket.set_nonblocking(true)?;
+        Ok(Statsd { prefix: prefix.trim_end_matches('.').to_string(), socket, dropped: 0 })
+    }
+
+    pub fn update(&mut self, rows: &[RowData], unix: f64, interval_secs: f64) {
+        let rows: Vec<SnapshotRow> = rows.iter().map(|r| SnapshotRow::new(r, unix, interval_secs)).collect();
+        for datagram in datagrams(&lines(&self.prefix, &rows), MAX_PAYLOAD) {
+            // WouldBlock when the buffer is full, ConnectionRefused after an ICMP port
+            // unreachable from a listener that isn't up yet; neither is worth waiting on
+            if self.socket.send(datagram.as_bytes()).is_err() {
+                self.dropped += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(name: &str, rx: f64) -> SnapshotRow {
+        SnapshotRow { tx_bps: 0.5, rx_bytes_total: 4096, ..SnapshotRow::sample(name, rx) }
+    }
+
+    #[test]
+    fn gauges() {
+        assert_eq!(sanitize("eth0.100"), "eth0_100");
+        assert_eq!(sanitize("wg:home net"), "wg_home_net");
+        let lines = lines("net", &[row("eth0.100", 2048.0), row("lo", f64::NAN)]);
+        assert_eq!(lines[0], "net.eth0_100.rx_bps:2048|g");
+        assert_eq!(lines[1], "net.eth0_100.tx_bps:0.5|g");
+        assert!(lines.contains(&"net.eth0_100.rx_bytes_total:4096|g".to_string()));
+        assert_eq!(lines.len(), 15, "lo's rx_bps isn't a number");
+        assert_eq!(super::lines("", &[row("lo", 1.0)])[0], "lo.rx_bps:1|g");
+    }
+
+    #[test]
+    fn splits_into_datagrams() {
+        let lines: Vec<String> = ["aaaa", "bbbb", "cccc", "dddddddddddd"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(datagrams(&lines, 9), ["aaaa\nbbbb", "cccc", "dddddddddddd"]);
+        assert_eq!(datagrams(&lines, 100), ["aaaa\nbbbb\ncccc\ndddddddddddd"]);
+        assert!(datagrams(&[], 9).is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::alert::{AlertEvent, Transition};
+use crate::app::AppState;
+use crate::net::Totals;
+use crate::snapshot::{iso8601, number, string};
+
+// one interface over the whole session, raw numbers like the --json snapshots
+#[derive(Clone, Debug, PartialEq)]
+pub struct IfaceSummary {
+    pub interface: String,
+    pub totals: Totals,
+    pub peak_rx_bps: f64,
+    pub peak_tx_bps: f64,
+}
+
+// what happened between start and quit; covers the interfaces the filter shows at exit and
+// 'x' hasn't hidden, including ones that were seen earlier and have since gone away
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    // unix seconds
+    pub started: f64,
+    pub duration: Duration,
+    // busiest first
+    pub interfaces: Vec<IfaceSummary>,
+    // the alert log as it was at exit, oldest first
+    pub alerts: Vec<AlertEvent>,
+}
+
+impl Summary {
+    pub fn new(app: &AppState, started: f64, duration: Duration) -> Self {
+        let mut interfaces: Vec<IfaceSummary> = app
+            .totals
+            .iter()
+            .filter(|(name, _)| app.filter.keep(name) && !app.hidden.contains(name))
+            .map(|(name, totals)| {
+                let (peak_rx_bps, peak_tx_bps) = app.session_peaks.get(name).copied().unwrap_or_default();
+                IfaceSummary { interface: name.clone(), totals: *totals, peak_rx_bps, peak_tx_bps }
+            })
+            .collect();
+        interfaces.sort_by(|a, b| {
+            let bytes = |s: &IfaceSummary| s.totals.rx_bytes + s.totals.tx_bytes;
+            bytes(b).cmp(&bytes(a)).then_with(|| a.interface.cmp(&b.interface))
+        });
+        Summary { started, duration, interfaces, alerts: app.alerts.log.clone() }
+    }
+
+    // a padded table in the units the TUI was showing at exit, then the alert log
+    pub fn to_text(&self, app: &AppState) -> String {
+        let mut out = format!("nettui session: {}\n", human_duration(self.duration));
+        if self.interfaces.is_empty() {
+            out.push_str("no interfaces\n");
+        } else {
+            self.push_table(&mut out, app);
+        }
+        if !self.alerts.is_empty() {
+            out.push_str("alerts:\n");
+            for e in &self.alerts {
+                out.push_str(&format!("  {}\n", app.alert_line(e)));
+            }
+        }
+        out
+    }
+
+    fn push_table(&self, out: &mut String, app: &AppState) {
+        let header = ["INTERFACE", "RX", "TX", "PKTS In", "PKTS Out", "Peak RX", "Peak TX", "Errors"];
+        let mut lines: Vec<Vec<String>> = vec![header.iter().map(|h| h.to_string()).collect()];
+        for s in &self.interfaces {
+            let t = &s.totals;
+            lines.push(vec![
+                s.interface.clone(),
+                app.size(t.rx_bytes),
+                app.size(t.tx_bytes),
+                app.count(t.packets_in),
+                app.count(t.packets_out),
+                app.rate(s.peak_rx_bps).trim().to_string(),
+                app.rate(s.peak_tx_bps).trim().to_string(),
+                app.count(t.errors_in + t.errors_out),
+            ]);
+        }
+
+        let widths: Vec<usize> = (0..header.len())
+            .map(|c| lines.iter().map(|l| l[c].chars().count()).max().unwrap_or(0))
+            .collect();
+        for line in &lines {
+            let cells: Vec<String> = line
+                .iter()
+                .enumerate()
+                .map(|(c, v)| if c == 0 { format!("{v:<w$}", w = widths[c]) } else { format!("{v:>w$}", w = widths[c]) })
+                .collect();
+            out.push_str(cells.join("  ").trim_end());
+            out.push('\n');
+        }
+    }
+
+    // --summary-json: one object, numbers raw
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{");
+        let _ = write!(out, "\"started\":{}", string(&iso8601(self.started)));
+        let _ = write!(out, ",\"duration_secs\":{}", number(self.duration.as_secs_f64()));
+        out.push_str(",\"interfaces\":[");
+        for (i, s) in self.interfaces.iter().enumerate() {
+            let t = &s.totals;
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "{{\"interface\":{}", string(&s.interface));
+            for (key, v) in [
+                ("rx_bytes", t.rx_bytes),
+                ("tx_bytes", t.tx_bytes),
+                ("rx_packets", t.packets_in),
+                ("tx_packets", t.packets_out),
+                ("rx_errors", t.errors_in),
+                ("tx_errors", t.errors_out),
+            ] {
+                let _ = write!(out, ",\"{key}\":{v}");
+            }
+            let _ = write!(out, ",\"peak_rx_bps\":{}", number(s.peak_rx_bps));
+            let _ = write!(out, ",\"peak_tx_bps\":{}}}", number(s.peak_tx_bps));
+        }
+        out.push_str("],\"alerts\":[");
+        for (i, e) in self.alerts.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let state = match e.transition {
+                Transition::Fired => "fired",
+                Transition::Cleared => "cleared",
+            };
+            let _ = write!(out, "{{\"time\":{},\"interface\":{}", string(&iso8601(e.at)), string(&e.interface));
+            let _ = write!(out, ",\"metric\":{},\"op\":{}", string(e.rule.metric.label()), string(e.rule.op.symbol()));
+            let _ = write!(out, ",\"threshold\":{},\"value\":{}", number(e.rule.threshold), number(e.value));
+            let _ = write!(out, ",\"state\":{}}}", string(state));
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+// 42s, 5m 07s, 2h 03m 09s
+pub fn human_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h > 0 {
+        format!("{h}h {m:02}m {s:02}s")
+    } else if m > 0 {
+        format!("{m}m {s:02}s")
+    } else {
+        format!("{s}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::parse_rule;
+
+    fn app() -> AppState {
+        let mut app = AppState::new();
+        app.filter.show_virtual = true;
+        app.filter.exclude = vec!["docker*".to_string()];
+        let totals = |rx, tx| Totals { rx_bytes: rx, tx_bytes: tx, packets_in: 3, errors_out: 1, ..Default::default() };
+        app.totals.insert("eth0".to_string(), totals(1024, 2048));
+        app.totals.insert("wlan0".to_string(), totals(1 << 20, 0));
+        app.totals.insert("docker0".to_string(), totals(1 << 30, 0));
+        app.totals.insert("wwan0".to_string(), totals(1 << 25, 0));
+        app.hidden = vec!["wwan0".to_string()];
+        app.session_peaks.insert("wlan0".to_string(), (4096.0, 0.0));
+        app
+    }
+
+    #[test]
+    fn only_filtered_interfaces_busiest_first() {
+        let summary = Summary::new(&app(), 0.0, Duration::from_secs(65));
+        let names: Vec<&str> = summary.interfaces.iter().map(|s| s.interface.as_str()).collect();
+        assert_eq!(names, ["wlan0", "eth0"]);
+
+        let text = summary.to_text(&app());
+        assert!(text.starts_with("nettui session: 1m 05s\n"));
+        assert!(text.contains("1 MiB"));
+        assert!(text.contains("4.0 KiB/s"));
+        assert!(!text.contains("docker0") && !text.contains("wwan0"), "{text}");
+        assert!(!summary.to_json().contains("wwan0"), "hidden with 'x'");
+    }
+
+    #[test]
+    fn json_schema() {
+        let mut summary = Summary::new(&app(), 1_700_000_000.0, Duration::from_millis(1500));
+        summary.interfaces.truncate(1);
+        assert_eq!(
+            summary.to_json(),
+            "{\"started\":\"2023-11-14T22:13:20.000Z\",\"duration_secs\":1.5,\"interfaces\":[\
+             {\"interface\":\"wlan0\",\"rx_bytes\":1048576,\"tx_bytes\":0,\"rx_packets\":3,\"tx_packets\":0,\
+             \"rx_errors\":0,\"tx_errors\":1,\"peak_rx_bps\":4096,\"peak_tx_bps\":0}],\"alerts\":[]}"
+        );
+    }
+
+    #[test]
+    fn alert_log() {
+        let mut app = app();
+        app.format.standard = crate::format::UnitStandard::Si;
+        let rule = parse_rule("eth0:rx:>:80MB/s").unwrap();
+        let event = |at, value, transition| AlertEvent { at, interface: "eth0".to_string(), rule: rule.clone(), value, transition };
+        app.alerts.log = vec![event(50531.0, 94.1e6, Transition::Fired), event(50620.0, 1e6, Transition::Cleared)];
+
+        let summary = Summary::new(&app, 0.0, Duration::from_secs(5));
+        let text = summary.to_text(&app);
+        assert!(text.ends_with("alerts:\n  14:02:11 eth0 rx 94.1 MB/s exceeded 80.0 MB/s\n  14:03:40 eth0 rx cleared at 1.0 MB/s\n"), "{text}");
+        assert!(summary.to_json().ends_with(
+            ",\"alerts\":[{\"time\":\"1970-01-01T14:02:11.000Z\",\"interface\":\"eth0\",\"metric\":\"rx\",\"op\":\">\",\
+             \"threshold\":80000000,\"value\":94100000,\"state\":\"fired\"},{\"time\":\"1970-01-01T14:03:40.000Z\",\
+             \"interface\":\"eth0\",\"metric\":\"rx\",\"op\":\">\",\"threshold\":80000000,\"value\":1000000,\"state\":\"cleared\"}]}"
+        ));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(human_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(human_duration(Duration::from_secs(307)), "5m 07s");
+        assert_eq!(human_duration(Duration::from_secs(7389)), "2h 03m 09s");
+    }
+}
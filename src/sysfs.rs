@@ -0,0 +1,242 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::iftype::Probe;
+use crate::net::LinkState;
+
+// per-interface files under /sys/class/net that sysinfo doesn't expose; an interface can be
+// deleted between listing it and reading here, so a missing or unreadable file is just None
+pub struct SysFs {
+    root: PathBuf,
+}
+
+impl Default for SysFs {
+    fn default() -> Self {
+        SysFs::at("/sys/class/net")
+    }
+}
+
+impl SysFs {
+    // a directory laid out like /sys/class/net, tests point this at a fixture
+    pub fn at(root: impl AsRef<Path>) -> Self {
+        SysFs { root: root.as_ref().to_path_buf() }
+    }
+
+    // names come from the kernel, but don't let one walk out of the directory
+    fn path(&self, iface: &str, file: &str) -> Option<PathBuf> {
+        if iface.is_empty() || iface.contains('/') || iface == "." || iface == ".." {
+            return None;
+        }
+        Some(self.root.join(iface).join(file))
+    }
+
+    fn read(&self, iface: &str, file: &str) -> Option<String> {
+        let text = fs::read_to_string(self.path(iface, file)?).ok()?;
+        Some(text.trim().to_string())
+    }
+
+    fn exists(&self, iface: &str, file: &str) -> bool {
+        self.path(iface, file).is_some_and(|p| p.exists())
+    }
+
+    pub fn operstate(&self, iface: &str) -> LinkState {
+        match self.read(iface, "operstate").as_deref() {
+            Some("up") => LinkState::Up,
+            Some("down" | "lowerlayerdown" | "notpresent") => LinkState::Down,
+            // loopback and most tunnels say "unknown", dormant/testing aren't a clear yes or no
+            _ => LinkState::Unknown,
+        }
+    }
+
+    // negotiated speed in Mb/s; virtual and down interfaces report -1 (or fail the read
+    // with EINVAL), wireless has no file at all
+    pub fn speed_mbps(&self, iface: &str) -> Option<u64> {
+        let speed: i64 = self.read(iface, "speed")?.parse().ok()?;
+        u64::try_from(speed).ok().filter(|&s| s > 0)
+    }
+
+    // what iftype::classify needs, a handful of small reads and stats
+    pub fn probe(&self, iface: &str) -> Probe {
+        let devtype = self.read(iface, "uevent").and_then(|text| {
+            text.lines().find_map(|l| l.strip_prefix("DEVTYPE=")).map(str::to_string)
+        });
+        Probe {
+            present: self.exists(iface, ""),
+            arphrd: self.read(iface, "type").and_then(|t| t.parse().ok()),
+            devtype,
+            device: self.exists(iface, "device"),
+            bridge: self.exists(iface, "bridge"),
+            bonding: self.exists(iface, "bonding"),
+            wireless: self.exists(iface, "wireless") || self.exists(iface, "phy80211"),
+            tun: self.exists(iface, "tun_flags"),
+        }
+    }
+
+    // the ports of a bond (bonding/slaves, in the bond's order) or a bridge (brif/, sorted);
+    // empty for everything else
+    pub fn members(&self, iface: &str) -> Vec<String> {
+        if let Some(slaves) = self.read(iface, "bonding/slaves") {
+            return slaves.split_whitespace().map(str::to_string).collect();
+        }
+        let Some(Ok(dir)) = self.path(iface, "brif").map(fs::read_dir) else {
+            return Vec::new();
+        };
+        let mut ports: Vec<String> = dir.flatten().map(|e| e.file_name().to_string_lossy().into_owned()).collect();
+        ports.sort();
+        ports
+    }
+
+    // "aa:bb:cc:dd:ee:ff"; tunnels have no address file or a shorter one
+    pub fn mac(&self, iface: &str) -> Option<[u8; 6]> {
+        let text = self.read(iface, "address")?;
+        let bytes: Vec<u8> = text.split(':').map(|b| u8::from_str_radix(b, 16)).collect::<Result<_, _>>().ok()?;
+        bytes.try_into().ok()
+    }
+
+    pub fn mtu(&self, iface: &str) -> Option<u64> {
+        self.read(iface, "mtu")?.parse().ok()
+    }
+
+    // one of the cumulative counters under statistics/, e.g. rx_dropped
+    pub fn statistic(&self, iface: &str, name: &str) -> Option<u64> {
+        self.read(iface, &format!("statistics/{name}"))?.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a throwaway /sys/class/net lookalike, removed on drop
+    struct Fixture(PathBuf);
+
+    impl Fixture {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("nettui-sysfs-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Fixture(dir)
+        }
+
+        fn file(&self, iface: &str, file: &str, contents: &str) {
+            let path = self.0.join(iface).join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn operstate() {
+        let fx = Fixture::new("operstate");
+        fx.file("eth0", "operstate", "up\n");
+        fx.file("eth1", "operstate", "down\n");
+        fx.file("lo", "operstate", "unknown\n");
+        fx.file("veth0", "operstate", "lowerlayerdown\n");
+        let sys = SysFs::at(&fx.0);
+        assert_eq!(sys.operstate("eth0"), LinkState::Up);
+        assert_eq!(sys.operstate("eth1"), LinkState::Down);
+        assert_eq!(sys.operstate("lo"), LinkState::Unknown);
+        assert_eq!(sys.operstate("veth0"), LinkState::Down);
+        // deleted between listing and reading
+        assert_eq!(sys.operstate("wlan0"), LinkState::Unknown);
+        assert_eq!(sys.operstate("../eth0"), LinkState::Unknown);
+    }
+
+    #[test]
+    fn speed() {
+        let fx = Fixture::new("speed");
+        fx.file("eth0", "speed", "1000\n");
+        fx.file("veth0", "speed", "-1\n");
+        fx.file("tun0", "speed", "");
+        let sys = SysFs::at(&fx.0);
+        assert_eq!(sys.speed_mbps("eth0"), Some(1000));
+        assert_eq!(sys.speed_mbps("veth0"), None);
+        assert_eq!(sys.speed_mbps("tun0"), None);
+        assert_eq!(sys.speed_mbps("wlan0"), None);
+
+        // renegotiated, the next read sees it
+        fx.file("eth0", "speed", "100\n");
+        assert_eq!(sys.speed_mbps("eth0"), Some(100));
+    }
+
+    #[test]
+    fn probe() {
+        let fx = Fixture::new("probe");
+        fx.file("wlan0", "type", "1\n");
+        fx.file("wlan0", "uevent", "DEVTYPE=wlan\nINTERFACE=wlan0\nIFINDEX=3\n");
+        fx.file("wlan0", "device/vendor", "0x8086\n");
+        fx.file("wlan0", "phy80211/index", "0\n");
+        fx.file("br0", "bridge/stp_state", "0\n");
+        fx.file("tap0", "tun_flags", "0x1002\n");
+        let sys = SysFs::at(&fx.0);
+
+        let wlan = sys.probe("wlan0");
+        assert_eq!(
+            wlan,
+            Probe {
+                present: true,
+                arphrd: Some(1),
+                devtype: Some("wlan".to_string()),
+                device: true,
+                wireless: true,
+                ..Default::default()
+            }
+        );
+        assert!(sys.probe("br0").bridge);
+        assert!(sys.probe("tap0").tun);
+        assert_eq!(sys.probe("gone0"), Probe::default());
+        assert_eq!(sys.probe(".."), Probe::default());
+    }
+
+    #[test]
+    fn statistics() {
+        let fx = Fixture::new("statistics");
+        fx.file("eth0", "statistics/rx_dropped", "17\n");
+        fx.file("eth0", "statistics/tx_dropped", "garbage\n");
+        let sys = SysFs::at(&fx.0);
+        assert_eq!(sys.statistic("eth0", "rx_dropped"), Some(17));
+        assert_eq!(sys.statistic("eth0", "tx_dropped"), None);
+        assert_eq!(sys.statistic("eth0", "rx_fifo_errors"), None);
+
+        // interface deleted after it was listed
+        fs::remove_dir_all(fx.0.join("eth0")).unwrap();
+        assert_eq!(sys.statistic("eth0", "rx_dropped"), None);
+    }
+
+    #[test]
+    fn mac_and_mtu() {
+        let fx = Fixture::new("mac");
+        fx.file("eth0", "address", "52:54:00:12:34:56\n");
+        fx.file("eth0", "mtu", "9000\n");
+        fx.file("ip6tnl0", "address", "00:00:00:00:00:00:00:00:00:00:00:00:00:00:00:00\n");
+        let sys = SysFs::at(&fx.0);
+        assert_eq!(sys.mac("eth0"), Some([0x52, 0x54, 0x00, 0x12, 0x34, 0x56]));
+        assert_eq!(sys.mtu("eth0"), Some(9000));
+        assert_eq!(sys.mac("ip6tnl0"), None);
+        assert_eq!((sys.mac("gone0"), sys.mtu("gone0")), (None, None));
+    }
+
+    #[test]
+    fn members() {
+        let fx = Fixture::new("members");
+        fx.file("bond0", "bonding/slaves", "eth1 eth0\n");
+        fx.file("br0", "brif/veth1/port_no", "0x2\n");
+        fx.file("br0", "brif/eth2/port_no", "0x1\n");
+        fx.file("eth0", "operstate", "up\n");
+        let sys = SysFs::at(&fx.0);
+        assert_eq!(sys.members("bond0"), ["eth1", "eth0"]);
+        assert_eq!(sys.members("br0"), ["eth2", "veth1"]);
+        assert!(sys.members("eth0").is_empty());
+        assert!(sys.members("gone0").is_empty());
+
+        // a port taken out at runtime is gone on the next read
+        fx.file("bond0", "bonding/slaves", "eth0\n");
+        assert_eq!(sys.members("bond0"), ["eth0"]);
+    }
+}
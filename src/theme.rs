@@ -0,0 +1,303 @@
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use ratatui::style::{Color, Modifier, Style};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    // no colors at all, bold and reverse still mark what matters
+    Monochrome,
+}
+
+impl ThemeName {
+    pub fn next(self) -> Self {
+        match self {
+            ThemeName::Dark => ThemeName::Light,
+            ThemeName::Light => ThemeName::Monochrome,
+            ThemeName::Monochrome => ThemeName::Dark,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeName::Dark => "dark",
+            ThemeName::Light => "light",
+            ThemeName::Monochrome => "monochrome",
+        }
+    }
+}
+
+// every style the UI draws with; nothing outside this module builds one, so switching
+// themes recolors the whole screen at once
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub text: Style,
+    // the status line at the top
+    pub title: Style,
+    pub border: Style,
+    // column headers and the TOTAL footer
+    pub table_header: Style,
+    pub selected: Style,
+    // group headers in the grouped table, section headings in the detail view
+    pub heading: Style,
+    // rates against --warn/--crit, Wi-Fi signal the same way
+    pub ok: Style,
+    pub warn: Style,
+    pub crit: Style,
+    pub error: Style,
+    // cells of a firing --alert rule
+    pub alert: Style,
+    // the background of a row that just had errors, for ERROR_FLASH
+    pub error_flash: Style,
+    pub link_up: Style,
+    pub link_down: Style,
+    // every cell of a row whose link went down
+    pub down_row: Style,
+    pub sparkline: Style,
+    // chart lines
+    pub rx: Style,
+    pub tx: Style,
+    // keys in the status bar and the '/' of the search prompt
+    pub key: Style,
+    // the TYPE column
+    pub physical: Style,
+    pub wireless: Style,
+    pub aggregate: Style,
+    pub tunnel: Style,
+    pub plumbing: Style,
+}
+
+// names the config file uses for `color_<slot> = "..."`
+pub const SLOTS: &[&str] = &[
+    "text",
+    "title",
+    "border",
+    "table_header",
+    "selected",
+    "heading",
+    "ok",
+    "warn",
+    "crit",
+    "error",
+    "alert",
+    "error_flash",
+    "link_up",
+    "link_down",
+    "down_row",
+    "sparkline",
+    "rx",
+    "tx",
+    "key",
+    "physical",
+    "wireless",
+    "aggregate",
+    "tunnel",
+    "plumbing",
+];
+
+impl Theme {
+    pub fn builtin(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Theme::dark(),
+            ThemeName::Light => Theme::light(),
+            ThemeName::Monochrome => Theme::monochrome(),
+        }
+    }
+
+    fn monochrome() -> Self {
+        let plain = Style::default();
+        let bold = plain.add_modifier(Modifier::BOLD);
+        Theme {
+            text: plain,
+            title: plain,
+            border: plain,
+            table_header: bold,
+            selected: plain.add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            heading: bold,
+            ok: plain,
+            warn: plain,
+            crit: bold,
+            error: bold,
+            alert: plain.add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            error_flash: plain.add_modifier(Modifier::ITALIC),
+            link_up: plain,
+            link_down: bold,
+            down_row: plain,
+            sparkline: plain,
+            rx: plain,
+            tx: plain,
+            key: bold,
+            physical: plain,
+            wireless: plain,
+            aggregate: plain,
+            tunnel: plain,
+            plumbing: plain,
+        }
+    }
+
+    // the terminal's own palette, which dark backgrounds are usually tuned for
+    fn dark() -> Self {
+        let mono = Theme::monochrome();
+        let fg = |c| Style::default().fg(c);
+        Theme {
+            ok: fg(Color::Green),
+            warn: fg(Color::Yellow),
+            crit: fg(Color::Red),
+            error: fg(Color::Red).add_modifier(Modifier::BOLD),
+            // reversed, so the color is the cell's background
+            alert: fg(Color::Red).add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            error_flash: Style::default().bg(Color::Indexed(52)),
+            link_up: fg(Color::Green),
+            link_down: fg(Color::Red).add_modifier(Modifier::BOLD),
+            down_row: fg(Color::DarkGray),
+            rx: fg(Color::Green),
+            tx: fg(Color::Cyan),
+            key: fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            physical: fg(Color::Green),
+            wireless: fg(Color::Cyan),
+            aggregate: fg(Color::Blue),
+            tunnel: fg(Color::Magenta),
+            plumbing: fg(Color::DarkGray),
+            ..mono
+        }
+    }
+
+    // yellow and cyan wash out on white, so fixed darker shades instead of the palette
+    fn light() -> Self {
+        let mono = Theme::monochrome();
+        let fg = |c| Style::default().fg(c);
+        let green = Color::Rgb(0x00, 0x80, 0x00);
+        let amber = Color::Rgb(0xaf, 0x5f, 0x00);
+        let red = Color::Rgb(0xaf, 0x00, 0x00);
+        let blue = Color::Rgb(0x00, 0x5f, 0xaf);
+        let grey = Color::Rgb(0x80, 0x80, 0x80);
+        Theme {
+            border: fg(grey),
+            ok: fg(green),
+            warn: fg(amber),
+            crit: fg(red),
+            error: fg(red).add_modifier(Modifier::BOLD),
+            alert: fg(red).add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            error_flash: Style::default().bg(Color::Rgb(0xff, 0xd7, 0xd7)),
+            link_up: fg(green),
+            link_down: fg(red).add_modifier(Modifier::BOLD),
+            down_row: fg(grey),
+            rx: fg(green),
+            tx: fg(blue),
+            key: fg(blue).add_modifier(Modifier::BOLD),
+            physical: fg(green),
+            wireless: fg(blue),
+            aggregate: fg(Color::Rgb(0x5f, 0x00, 0xaf)),
+            tunnel: fg(Color::Rgb(0x87, 0x00, 0x87)),
+            plumbing: fg(grey),
+            ..mono
+        }
+    }
+
+    fn slot(&mut self, name: &str) -> Option<&mut Style> {
+        Some(match name {
+            "text" => &mut self.text,
+            "title" => &mut self.title,
+            "border" => &mut self.border,
+            "table_header" => &mut self.table_header,
+            "selected" => &mut self.selected,
+            "heading" => &mut self.heading,
+            "ok" => &mut self.ok,
+            "warn" => &mut self.warn,
+            "crit" => &mut self.crit,
+            "error" => &mut self.error,
+            "alert" => &mut self.alert,
+            "error_flash" => &mut self.error_flash,
+            "link_up" => &mut self.link_up,
+            "link_down" => &mut self.link_down,
+            "down_row" => &mut self.down_row,
+            "sparkline" => &mut self.sparkline,
+            "rx" => &mut self.rx,
+            "tx" => &mut self.tx,
+            "key" => &mut self.key,
+            "physical" => &mut self.physical,
+            "wireless" => &mut self.wireless,
+            "aggregate" => &mut self.aggregate,
+            "tunnel" => &mut self.tunnel,
+            "plumbing" => &mut self.plumbing,
+            _ => return None,
+        })
+    }
+
+    // an override replaces the foreground only, bold and reverse stay; a slot that is
+    // nothing but a background gets a new background
+    pub fn set(&mut self, slot: &str, color: Color) -> Result<(), String> {
+        let style = self.slot(slot).ok_or_else(|| format!("unknown color '{slot}', one of: {}", SLOTS.join(", ")))?;
+        *style = if style.bg.is_some() && style.fg.is_none() { style.bg(color) } else { style.fg(color) };
+        Ok(())
+    }
+
+    pub fn with_overrides(mut self, overrides: &[(String, Color)]) -> Self {
+        for (slot, color) in overrides {
+            // slots were checked when the config was read
+            let _ = self.set(slot, *color);
+        }
+        self
+    }
+
+    // ok/warn/crit for a value against two thresholds
+    pub fn level(&self, value: f64, warn: f64, crit: f64) -> Style {
+        if value >= crit {
+            self.crit
+        } else if value >= warn {
+            self.warn
+        } else {
+            self.ok
+        }
+    }
+}
+
+// NO_COLOR (https://no-color.org) set to anything but the empty string, or a terminal
+// that can't do colors
+pub fn no_color_env() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) || std::env::var("TERM").is_ok_and(|t| t == "dumb")
+}
+
+// "red", "light-blue", "#ff8800" or a palette index like "208"
+pub fn parse_color(s: &str) -> Result<Color, String> {
+    Color::from_str(s.trim()).map_err(|_| format!("invalid color '{s}', expected a name like \"cyan\" or \"#rrggbb\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_slot_can_be_set() {
+        let mut theme = Theme::builtin(ThemeName::Dark);
+        for slot in SLOTS {
+            theme.set(slot, Color::Indexed(208)).unwrap();
+        }
+        assert_eq!(theme.text.fg, Some(Color::Indexed(208)));
+        assert_eq!(theme.plumbing.fg, Some(Color::Indexed(208)));
+        assert_eq!((theme.error_flash.fg, theme.error_flash.bg), (None, Some(Color::Indexed(208))));
+        // modifiers survive an override
+        assert!(theme.selected.add_modifier.contains(Modifier::REVERSED));
+        assert!(theme.set("background", Color::Red).unwrap_err().starts_with("unknown color 'background'"));
+    }
+
+    #[test]
+    fn monochrome_has_no_colors() {
+        let theme = Theme::builtin(ThemeName::Monochrome);
+        let all = [theme.ok, theme.warn, theme.crit, theme.error, theme.error_flash, theme.link_down, theme.rx, theme.tx, theme.key];
+        assert!(all.iter().all(|s| s.fg.is_none() && s.bg.is_none()));
+        assert_eq!(ThemeName::Dark.next().next().next(), ThemeName::Dark);
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(parse_color("cyan"), Ok(Color::Cyan));
+        assert_eq!(parse_color("light-red"), Ok(Color::LightRed));
+        assert_eq!(parse_color("#ff8800"), Ok(Color::Rgb(0xff, 0x88, 0x00)));
+        assert_eq!(parse_color("208"), Ok(Color::Indexed(208)));
+        assert!(parse_color("chartreuse-ish").is_err());
+    }
+}
@@ -0,0 +1,1568 @@
+use std::borrow::Cow;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::symbols::Marker;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Axis, Block, Borders, Chart, Clear, Dataset, GraphType, LegendPosition, Paragraph, Row, Table,
+    TableState,
+};
+use ratatui::Frame;
+
+use crate::alert::{Metric, Transition};
+use crate::app::{AppState, Entry, View, SPARK_WIDTH};
+use crate::columns::{self, Column};
+use crate::conns::ConnSort;
+use crate::format::{group_digits, human_count_rate, human_pps, number_width};
+use crate::history::{Average, Sample, AVERAGE_WINDOWS};
+use crate::iftype::{Group, IfaceKind};
+use crate::keymap::key_label;
+use crate::neigh::{self, NeighState, Neighbor};
+use crate::net::{LinkState, RowData, Totals, HAS_DROPS};
+use crate::ping::Method;
+use crate::remote::{Link, Remote};
+use crate::route::Route;
+use crate::snapshot::{iso8601, unix_now};
+use crate::summary::human_duration;
+use crate::theme::ThemeName;
+use crate::wg::{Peers, Tracker};
+use crate::wifi::WifiInfo;
+
+// recent samples listed in the detail view
+const DETAIL_SAMPLES: usize = 20;
+
+// routes listed in the detail view, the rest are counted
+const DETAIL_ROUTES: usize = 6;
+
+// neighbors listed there, likewise
+const DETAIL_NEIGHBORS: usize = 8;
+
+// "bridge", the longest IfaceKind label
+const TYPE_WIDTH: usize = 6;
+
+// fits any IPv4 address, longer IPv6 ones are cut short in the table
+const ADDR_WIDTH: usize = 15;
+
+// "█████ 100%"
+const UTIL_WIDTH: usize = 10;
+
+const NBRS_WIDTH: usize = 5;
+
+// the label and span of a rolling average column
+fn average_window(column: Column) -> Option<(&'static str, Duration)> {
+    let i = columns::AVERAGES.iter().position(|&c| c == column)?;
+    Some(AVERAGE_WINDOWS[i])
+}
+
+// longer hostnames are cut in the header, the clock and the table title need the room
+const HOSTNAME_WIDTH: usize = 24;
+
+// below this the name column is useless, narrower columns get dropped instead
+const MIN_NAME_WIDTH: u16 = 8;
+
+// time span covered by the history chart
+const CHART_WINDOW_SECS: f64 = 120.0;
+
+// every string the header and table show, formatted when a sample lands or a setting
+// changes so redraws and cursor movement only borrow them
+#[derive(Clone, Debug, Default)]
+pub struct TableText {
+    title: String,
+    // the shown columns when the text was built, what every row below has a cell for
+    columns: Vec<Column>,
+    header: Vec<String>,
+    // one entry per shown row, in display order
+    rows: Vec<Vec<(String, Style)>>,
+    // the style under each row's cells: error_flash after recent errors, otherwise none
+    row_styles: Vec<Style>,
+    footer: Vec<String>,
+    session: String,
+    // the --data-cap gauge after the title, colored as the cap gets close
+    budget: Option<(String, Style)>,
+    // longest interface name shown, or the header if that's wider
+    name_width: u16,
+}
+
+impl TableText {
+    pub fn new(app: &AppState) -> Self {
+        let columns = app.columns.shown();
+        let entries = app.entries();
+        let row_styles = entries
+            .iter()
+            .map(|e| match e {
+                Entry::Row(r) if app.error_flash(&r.interface) => app.theme.error_flash,
+                _ => Style::default(),
+            })
+            .collect();
+        let rows: Vec<Vec<(String, Style)>> = entries.into_iter().map(|e| entry_cells(app, e, &columns)).collect();
+        let name = columns.iter().position(|&c| c == Column::Iface);
+        TableText {
+            row_styles,
+            title: title(app),
+            header: header_cells(app, &columns),
+            name_width: rows.iter().filter_map(|cells| Some(cells[name?].0.chars().count())).max().unwrap_or(0).max(9) as u16,
+            rows,
+            footer: footer_cells(app, &columns),
+            columns,
+            session: session(app),
+            budget: budget(app),
+        }
+    }
+}
+
+fn title(app: &AppState) -> String {
+    let state = match &app.replay {
+        Some(r) => format!(
+            "replay {}/{} {} {}x",
+            r.shown.map_or(0, |i| i + 1),
+            r.len(),
+            if r.playing { "playing" } else { "paused" },
+            r.speed
+        ),
+        None if app.paused => "PAUSED".to_string(),
+        None => match app.remote.as_ref().map(|r| &r.link) {
+            Some(Link::Connected(_)) => "remote".to_string(),
+            Some(Link::Connecting) => "connecting".to_string(),
+            Some(Link::Lost(..)) => "DISCONNECTED".to_string(),
+            None => "live".to_string(),
+        },
+    };
+    let mut title = format!(
+        " {}   virtual: {}   view: {}   units: {} {}   sort: {} {}   ifaces: {} ",
+        state,
+        if app.filter.show_virtual { "on" } else { "off" },
+        if app.cumulative { "totals" } else { "rates" },
+        if app.format.bits { "bits" } else { "bytes" },
+        app.format.standard.label(),
+        app.sort_key.label(),
+        if app.sort_desc { app.glyphs.sort_desc } else { app.glyphs.sort_asc },
+        app.shown().len()
+    );
+    if !app.search.is_empty() {
+        title.push_str(&format!("  search: {} ", app.search));
+    }
+    if !app.hidden.is_empty() {
+        title.push_str(&format!("  {} hidden ('X' to list) ", app.hidden.len()));
+    }
+    let unread = app.unread_alerts();
+    if unread > 0 {
+        title.push_str(&format!("  alerts: {unread} new ('a' to list) "));
+    }
+    if app.hide_idle {
+        title.push_str(&format!("  idle hidden: {} ", app.hidden_idle()));
+    }
+    if app.freeze_order {
+        title.push_str("  order frozen ('f') ");
+    }
+    if app.smooth {
+        title.push_str(&format!("  smoothed: {:.1}s ", app.smooth_tau.as_secs_f64()));
+    }
+    if app.theme_name != ThemeName::Dark {
+        title.push_str(&format!("  theme: {} ", app.theme_name.label()));
+    }
+    if app.filter.is_active() {
+        title.push_str(&format!("  filter: {} ", app.filter.describe()));
+    }
+    if let Some(err) = app.csv.as_ref().and_then(|c| c.error()) {
+        title.push_str(&format!("  csv log stopped: {err} "));
+    }
+    if let Some(skipped) = app.replay.as_ref().map(|r| r.recording.skipped).filter(|&n| n > 0) {
+        title.push_str(&format!("  {skipped} malformed line{} skipped ", if skipped == 1 { "" } else { "s" }));
+    }
+    title
+}
+
+fn header_cells(app: &AppState, columns: &[Column]) -> Vec<String> {
+    let arrow = if app.sort_desc { app.glyphs.sort_desc } else { app.glyphs.sort_asc };
+    columns
+        .iter()
+        .map(|&c| {
+            let (total, rate) = match c {
+                Column::Iface => ("INTERFACE", "INTERFACE"),
+                Column::Link => ("LINK", "LINK"),
+                Column::Type => ("TYPE", "TYPE"),
+                Column::Addr => ("ADDRESS", "ADDRESS"),
+                Column::Rx => ("RX", "RX/s"),
+                Column::Tx => ("TX", "TX/s"),
+                Column::Util => ("UTIL", "UTIL"),
+                Column::PktIn => ("PKTS In", "PPS In"),
+                Column::PktOut => ("PKTS Out", "PPS Out"),
+                Column::ErrIn => ("Err In", "Err In/s"),
+                Column::ErrOut => ("Err Out", "Err Out/s"),
+                Column::DropIn => ("Drop In", "Drop In/s"),
+                Column::DropOut => ("Drop Out", "Drop Out/s"),
+                Column::Nbrs => ("Nbrs", "Nbrs"),
+                Column::McastIn => ("Mcast In", "Mcast In/s"),
+                Column::PeakRx => ("Peak RX", "Peak RX"),
+                Column::PeakTx => ("Peak TX", "Peak TX"),
+                Column::AvgRx => ("Avg RX", "Avg RX"),
+                Column::AvgTx => ("Avg TX", "Avg TX"),
+                Column::Avg1m | Column::Avg5m | Column::Avg15m => {
+                    let (label, _) = average_window(c).unwrap_or_default();
+                    return format!("Avg {label}");
+                }
+                Column::Trend => ("TREND", "TREND"),
+            };
+            let h = if app.cumulative { total } else { rate };
+            if app.sort_key.columns().contains(&c) { format!("{h} {arrow}") } else { h.to_string() }
+        })
+        .collect()
+}
+
+// "--" without an address, IPv6 cut to the column width
+fn short_addr(app: &AppState, r: &RowData) -> String {
+    let Some(ip) = r.info.primary_addr() else {
+        return "--".to_string();
+    };
+    let ip = ip.to_string();
+    if ip.chars().count() <= ADDR_WIDTH {
+        ip
+    } else {
+        let cut: String = ip.chars().take(ADDR_WIDTH - 1).collect();
+        format!("{cut}{}", app.glyphs.ellipsis)
+    }
+}
+
+// neighbors the interface sees, "--" where no neighbor table could be read at all
+fn nbrs_cell(app: &AppState, r: &RowData) -> String {
+    if app.neighbors.is_empty() { "--".to_string() } else { neigh::count(&app.neighbors, &r.interface).to_string() }
+}
+
+// multicast packets in, "--" where the interface has no such counter
+fn multicast_cell(app: &AppState, r: &RowData) -> String {
+    match r.multicast_in {
+        None => "--".to_string(),
+        Some(_) if app.cumulative => app.count(r.totals.multicast_in),
+        Some(pps) => app.packet_rate(pps),
+    }
+}
+
+// percent of line rate, or a bar with the percent next to it; "--" without a known speed
+fn util_cell(app: &AppState, r: &RowData) -> String {
+    let Some(pct) = r.utilization() else {
+        return "--".to_string();
+    };
+    if !app.show_gauge {
+        return format!("{pct:.1}%");
+    }
+    format!("{} {pct:>3.0}%", gauge(app, pct, UTIL_WIDTH - 5))
+}
+
+// a bar `width` cells wide, padded; eighths of a block per step, clamped since a sample
+// can overshoot line rate slightly
+fn gauge(app: &AppState, pct: f64, width: usize) -> String {
+    let steps = (pct.clamp(0.0, 100.0) / 100.0 * (width * 8) as f64).round() as usize;
+    let mut bar = app.glyphs.gauge_full.repeat(steps / 8);
+    bar.push_str(app.glyphs.gauge_eighths[steps % 8]);
+    let pad = width - bar.chars().count();
+    format!("{bar}{}", " ".repeat(pad))
+}
+
+// yellow from 75% of the cap, red from 90%
+fn budget(app: &AppState) -> Option<(String, Style)> {
+    let b = app.budget.as_ref()?;
+    let pct = b.fraction() * 100.0;
+    let mut text = format!(
+        " cap {} [{}] {pct:.0}% of {}, {} ",
+        b.cap.interface,
+        gauge(app, pct, 10),
+        app.size(b.cap.bytes),
+        b.outlook(unix_now())
+    );
+    if b.error.is_some() {
+        text.push_str("(not saved) ");
+    }
+    Some((text, app.theme.level(pct, 75.0, 90.0)))
+}
+
+// physical links stand out, plumbing recedes
+fn kind_style(app: &AppState, kind: IfaceKind) -> Style {
+    let theme = &app.theme;
+    match kind {
+        IfaceKind::Ethernet => theme.physical,
+        IfaceKind::Wifi => theme.wireless,
+        IfaceKind::Bond | IfaceKind::Vlan => theme.aggregate,
+        IfaceKind::WireGuard | IfaceKind::Tun | IfaceKind::Vpn => theme.tunnel,
+        IfaceKind::Loopback | IfaceKind::Bridge | IfaceKind::Container | IfaceKind::Auxiliary | IfaceKind::Unknown => {
+            theme.plumbing
+        }
+    }
+}
+
+// pinned rows are marked so it's clear why they sit above busier ones, and the default
+// route's interface so it's clear which one the traffic takes
+fn name_cell(app: &AppState, r: &RowData) -> String {
+    let mut name = match app.container_names.get(&r.interface) {
+        Some(container) => format!("{} ({container})", r.interface),
+        None => app.display_name(&r.interface),
+    };
+    if app.is_default_route(&r.interface) {
+        name = format!("{} {name}", app.glyphs.default_route);
+    }
+    if app.is_pinned(&r.interface) {
+        name = format!("{} {name}", app.glyphs.pinned);
+    }
+    match app.nest_depth(&r.interface) {
+        0 => name,
+        depth => format!("{}{} {name}", "  ".repeat(depth - 1), app.glyphs.member),
+    }
+}
+
+fn entry_cells(app: &AppState, entry: Entry, columns: &[Column]) -> Vec<(String, Style)> {
+    match entry {
+        Entry::Row(r) => row_cells(app, r, columns),
+        Entry::Group(group, members) => group_cells(app, group, &members, columns),
+    }
+}
+
+// "▾ Physical (2)"; folded sections sum their members' RX/TX instead
+fn group_cells(app: &AppState, group: Group, members: &[&RowData], columns: &[Column]) -> Vec<(String, Style)> {
+    let folded = app.collapsed.contains(&group);
+    let (rx, tx) = if !folded {
+        (String::new(), String::new())
+    } else if app.cumulative {
+        let sum = |f: fn(&Totals) -> u64| members.iter().map(|r| f(&r.totals)).sum::<u64>();
+        (app.size(sum(|t| t.rx_bytes)), app.size(sum(|t| t.tx_bytes)))
+    } else {
+        let (rx, tx) = members.iter().map(|r| app.shown_rates(r)).fold((0.0, 0.0), |a, b| (a.0 + b.0, a.1 + b.1));
+        (app.rate(rx), app.rate(tx))
+    };
+    let cell = |c: Column| match c {
+        Column::Iface => format!("{} {} ({})", if folded { app.glyphs.folded } else { app.glyphs.unfolded }, group.label(), members.len()),
+        Column::Rx => rx.clone(),
+        Column::Tx => tx.clone(),
+        _ => String::new(),
+    };
+    columns.iter().map(|&c| (cell(c), app.theme.heading)).collect()
+}
+
+fn row_cells(app: &AppState, r: &RowData, columns: &[Column]) -> Vec<(String, Style)> {
+    let plain = app.theme.text;
+    let t = &r.totals;
+    let totals = app.cumulative;
+    let (rx, tx) = app.shown_rates(r);
+    let cell = |c: Column| match c {
+        Column::Iface => (name_cell(app, r), plain),
+        Column::Link => (r.info.state.label().to_string(), plain),
+        Column::Type => (r.info.kind.label().to_string(), plain),
+        Column::Addr => (short_addr(app, r), plain),
+        Column::Rx if totals => (app.size(t.rx_bytes), plain),
+        Column::Rx => (app.rate(rx), app.rate_style(rx)),
+        Column::Tx if totals => (app.size(t.tx_bytes), plain),
+        Column::Tx => (app.rate(tx), app.rate_style(tx)),
+        // a share of line rate only makes sense for the current sample
+        Column::Util if totals => ("--".to_string(), plain),
+        Column::Util => (util_cell(app, r), plain),
+        Column::PktIn if totals => (app.count(t.packets_in), plain),
+        Column::PktIn => (app.packet_rate(r.packets_in), plain),
+        Column::PktOut if totals => (app.count(t.packets_out), plain),
+        Column::PktOut => (app.packet_rate(r.packets_out), plain),
+        Column::ErrIn if totals => (app.count(t.errors_in), plain),
+        Column::ErrIn => (app.pps(r.errors_in), app.error_style(r.errors_in)),
+        Column::ErrOut if totals => (app.count(t.errors_out), plain),
+        Column::ErrOut => (app.pps(r.errors_out), app.error_style(r.errors_out)),
+        Column::DropIn if totals => (app.count(t.drops_in), plain),
+        Column::DropIn => (app.pps(r.drops_in), app.error_style(r.drops_in)),
+        Column::DropOut if totals => (app.count(t.drops_out), plain),
+        Column::DropOut => (app.pps(r.drops_out), app.error_style(r.drops_out)),
+        Column::Nbrs => (nbrs_cell(app, r), plain),
+        Column::McastIn => (multicast_cell(app, r), plain),
+        Column::PeakRx => (app.rate(app.peak(r).0), plain),
+        Column::PeakTx => (app.rate(app.peak(r).1), plain),
+        Column::AvgRx => (app.rate(app.average(r).0), plain),
+        Column::AvgTx => (app.rate(app.average(r).1), plain),
+        Column::Avg1m | Column::Avg5m | Column::Avg15m => {
+            let (_, window) = average_window(c).unwrap_or_default();
+            (app.average_cell(&r.interface, window), plain)
+        }
+        Column::Trend => (app.sparkline(&r.interface), app.theme.sparkline),
+    };
+    let firing = app.alerts.firing_metrics(&r.interface);
+    columns
+        .iter()
+        .map(|&c| {
+            let (text, mut style) = cell(c);
+            // a link that went down keeps its row, greyed out so the event is noticed
+            if r.info.state == LinkState::Down {
+                style = app.theme.down_row;
+            }
+            match (c, r.info.state) {
+                (Column::Link, LinkState::Up) => style = app.theme.link_up,
+                (Column::Link, LinkState::Down) => style = app.theme.link_down,
+                (Column::Type, state) if state != LinkState::Down => style = kind_style(app, r.info.kind),
+                _ => {}
+            }
+            // a firing --alert marks the name and the cells it is about, down or not
+            if firing.iter().any(|&m| c == Column::Iface || alert_columns(m).contains(&c)) {
+                style = app.theme.alert;
+            }
+            (text, style)
+        })
+        .collect()
+}
+
+fn alert_columns(metric: Metric) -> &'static [Column] {
+    match metric {
+        Metric::Rx => &[Column::Rx],
+        Metric::Tx => &[Column::Tx],
+        Metric::Total => &[Column::Rx, Column::Tx],
+        Metric::Packets => &[Column::PktIn, Column::PktOut],
+        Metric::Errors => &[Column::ErrIn, Column::ErrOut],
+        Metric::Drops => &[Column::DropIn, Column::DropOut],
+    }
+}
+
+// sums over exactly the rows on screen, kept out of the rows so it never gets sorted
+fn footer_cells(app: &AppState, columns: &[Column]) -> Vec<String> {
+    let shown = app.shown();
+    let total = |f: fn(&Totals) -> u64| shown.iter().map(|r| f(&r.totals)).sum::<u64>();
+    let rate = |f: fn(&RowData) -> f64| shown.iter().map(|r| f(r)).sum::<f64>();
+    let totals = app.cumulative;
+    let now = app.last_sample.unwrap_or_else(Instant::now);
+    let cell = |c: Column| match c {
+        Column::Iface => "TOTAL".to_string(),
+        Column::Rx if totals => app.size(total(|t| t.rx_bytes)),
+        Column::Rx => app.rate(shown.iter().map(|r| app.shown_rates(r).0).sum()),
+        Column::Tx if totals => app.size(total(|t| t.tx_bytes)),
+        Column::Tx => app.rate(shown.iter().map(|r| app.shown_rates(r).1).sum()),
+        Column::PktIn if totals => app.count(total(|t| t.packets_in)),
+        Column::PktIn => app.packet_rate(rate(|r| r.packets_in)),
+        Column::PktOut if totals => app.count(total(|t| t.packets_out)),
+        Column::PktOut => app.packet_rate(rate(|r| r.packets_out)),
+        Column::ErrIn if totals => app.count(total(|t| t.errors_in)),
+        Column::ErrIn => app.pps(rate(|r| r.errors_in)),
+        Column::ErrOut if totals => app.count(total(|t| t.errors_out)),
+        Column::ErrOut => app.pps(rate(|r| r.errors_out)),
+        Column::DropIn if totals => app.count(total(|t| t.drops_in)),
+        Column::DropIn => app.pps(rate(|r| r.drops_in)),
+        Column::DropOut if totals => app.count(total(|t| t.drops_out)),
+        Column::DropOut => app.pps(rate(|r| r.drops_out)),
+        Column::McastIn => {
+            let counted = shown.iter().filter(|r| r.multicast_in.is_some());
+            if totals {
+                app.count(counted.map(|r| r.totals.multicast_in).sum())
+            } else {
+                app.packet_rate(counted.filter_map(|r| r.multicast_in).sum())
+            }
+        }
+        // peaks of different interfaces don't add up, averages do
+        Column::AvgRx => app.rate(shown.iter().map(|r| app.average(r).0).sum()),
+        Column::AvgTx => app.rate(shown.iter().map(|r| app.average(r).1).sum()),
+        Column::Avg1m | Column::Avg5m | Column::Avg15m => {
+            let (_, window) = average_window(c).unwrap_or_default();
+            let averages: Vec<Average> = shown.iter().filter_map(|r| app.history.average(&r.interface, now, window)).collect();
+            let rate = app.rate(averages.iter().map(|a| a.rate).sum());
+            if averages.iter().any(|a| a.partial) { format!("~{}", rate.trim()) } else { rate }
+        }
+        _ => String::new(),
+    };
+    columns.iter().map(|&c| cell(c)).collect()
+}
+
+fn session(app: &AppState) -> String {
+    let shown = app.shown();
+    let session_rx: u64 = shown.iter().map(|r| r.totals.rx_bytes).sum();
+    let session_tx: u64 = shown.iter().map(|r| r.totals.tx_bytes).sum();
+    format!(" session: rx {}  tx {} ", app.size(session_rx), app.size(session_tx))
+}
+
+// which machine this is on the header's top border, how long nettui has been running and
+// the clock on the right; formatted per frame since the clock moves between samples
+fn draw_header(f: &mut Frame, area: Rect, app: &AppState) {
+    let mut spans = vec![Span::styled(app.text.title.as_str(), app.theme.title)];
+    if let Some((text, style)) = &app.text.budget {
+        spans.push(Span::styled(text.as_str(), *style));
+    }
+    let (left, right) = identity(app, area.width as usize);
+    let block = bordered(app).title(Span::styled(left, app.theme.title)).title(Line::from(right).right_aligned());
+    let header = Paragraph::new(Line::from(spans)).block(block);
+    f.render_widget(header, area);
+}
+
+// " nettui on web-01 — Debian GNU/Linux 12, kernel 6.1.0 " and " up 3m 05s  14:02:11 UTC ",
+// or when the frame on screen was recorded under --replay; where the border is short the
+// kernel goes first, then the OS, then the hostname is cut
+fn identity(app: &AppState, width: usize) -> (String, String) {
+    let right = match app.replay.as_ref().and_then(|r| r.frame()) {
+        Some(frame) => {
+            let clock = iso8601(frame.unix);
+            format!(" recorded {} {} UTC ", &clock[..10], &clock[11..19])
+        }
+        None => {
+            let clock = iso8601(unix_now());
+            format!(" up {}  {} UTC ", human_duration(app.started.elapsed()), &clock[11..19])
+        }
+    };
+    let host = &app.host;
+    let name = ellipsize(&host.name, HOSTNAME_WIDTH, app.glyphs.ellipsis);
+    let mut parts = Vec::new();
+    match &app.remote {
+        // the server's name once it has said it, the address until then
+        Some(_) if !host.name.is_empty() => parts.push(format!(" remote {name}")),
+        Some(remote) => parts.push(format!(" remote {}", ellipsize(&remote.addr, HOSTNAME_WIDTH, app.glyphs.ellipsis))),
+        None if !host.name.is_empty() => parts.push(format!(" on {name}")),
+        None => {}
+    }
+    if !host.os.is_empty() {
+        parts.push(format!(" {} {}", app.glyphs.dash, host.os));
+    }
+    if !host.kernel.is_empty() {
+        parts.push(format!(", kernel {}", host.kernel));
+    }
+    // both corners and a space between the two
+    let room = width.saturating_sub(right.chars().count() + 3);
+    let fits = |parts: &[String]| format!(" nettui{} ", parts.concat());
+    while parts.len() > 1 && fits(&parts).chars().count() > room {
+        parts.pop();
+    }
+    let left = ellipsize(&fits(&parts), room, app.glyphs.ellipsis).into_owned();
+    (left, right)
+}
+
+// every box on screen, drawn in the theme's border style with rounded or ASCII corners
+fn bordered(app: &AppState) -> Block<'static> {
+    Block::default().borders(Borders::ALL).border_set(app.glyphs.border).border_style(app.theme.border)
+}
+
+// name, link, type and address on the left, every numeric column right-aligned; cells
+// come with their column since narrow terminals leave some columns out
+fn aligned_row<'a, I, T>(cells: I) -> Row<'a>
+where
+    I: IntoIterator<Item = (Column, T)>,
+    T: Into<Span<'a>>,
+{
+    Row::new(cells.into_iter().map(|(column, c)| {
+        let line = Line::from(c.into());
+        if column.left_aligned() { line } else { line.right_aligned() }
+    }))
+}
+
+fn draw_table(f: &mut Frame, area: Rect, app: &AppState) {
+    // explain an empty table instead of leaving it blank
+    let empty = app.text.rows.is_empty() && app.sampled;
+    let reason = if empty && !app.search.is_empty() {
+        Some(format!("no interface name contains \"{}\" ('/' then esc clears the search)", app.search))
+    } else if empty && app.hidden_idle() > 0 {
+        Some(format!("all {} interfaces are idle ('z' to show them)", app.hidden_idle()))
+    } else if empty && !app.hidden.is_empty() {
+        Some(format!("every interface is hidden ('X' to show them again, {} hidden)", app.hidden.len()))
+    } else if empty && app.filter.is_active() {
+        Some(format!(
+            "no interface matches {} (virtual interfaces {}, 'i' to toggle)",
+            app.filter.describe(),
+            if app.filter.show_virtual { "shown" } else { "hidden" }
+        ))
+    } else {
+        None
+    };
+    if let Some(reason) = reason {
+        let msg = Paragraph::new(reason).style(app.theme.text).block(bordered(app).title(Span::from("Interfaces")));
+        f.render_widget(msg, area);
+        return;
+    }
+
+    let Some((columns, widths, name_width)) = layout_columns(app, area.width) else {
+        let msg = Paragraph::new("terminal too small, make it wider").style(app.theme.text).block(bordered(app));
+        f.render_widget(msg, area);
+        return;
+    };
+
+    // cells borrow the cached text, nothing is formatted per frame
+    // except names too long for a squeezed name column
+    let text = &app.text;
+    let room = |i: usize| if text.columns[i] == Column::Iface { name_width as usize } else { usize::MAX };
+    let ellipsize = |s, width| ellipsize(s, width, app.glyphs.ellipsis);
+    // the header cursor ('<' and '>' move its column) shows as a highlighted heading
+    let heading = |i: usize| {
+        let style = if app.header_cursor == Some(text.columns[i]) { app.theme.selected } else { Style::default() };
+        Span::styled(ellipsize(&text.header[i], room(i)), style)
+    };
+    let header_row = aligned_row(columns.iter().map(|&i| (text.columns[i], heading(i)))).style(app.theme.table_header);
+    // the selection's highlight goes on top of a flashing row's style
+    let table_rows = text.rows.iter().zip(&text.row_styles).map(|(cells, style)| {
+        aligned_row(columns.iter().map(|&i| (text.columns[i], Span::styled(ellipsize(&cells[i].0, room(i)), cells[i].1)))).style(*style)
+    });
+    let footer_row = aligned_row(columns.iter().map(|&i| (text.columns[i], ellipsize(&text.footer[i], room(i)))))
+        .top_margin(1)
+        .style(app.theme.table_header);
+
+    let table = Table::new(table_rows, &widths)
+        .header(header_row)
+        .footer(footer_row)
+        .column_spacing(1)
+        .style(app.theme.text)
+        .row_highlight_style(app.theme.selected);
+
+    let block = bordered(app).title_bottom(text.session.as_str());
+    let inner = block.inner(area);
+
+    // the header and the footer with its margin take three lines; ratatui moves the
+    // offset only when the selection would leave the screen
+    let total = text.rows.len();
+    let visible = (inner.height as usize).saturating_sub(3);
+    let offset = app.table_offset.get().min(total.saturating_sub(visible));
+    let mut table_state = TableState::default().with_offset(offset).with_selected(app.selected_index());
+    f.render_stateful_widget(table, inner, &mut table_state);
+    app.table_offset.set(table_state.offset());
+    app.page_rows.set(visible);
+
+    // what a mouse click hit-tests against: the columns sit side by side one space apart
+    app.table_area.set(inner);
+    let mut x = inner.x;
+    *app.table_columns.borrow_mut() = columns
+        .iter()
+        .zip(&widths)
+        .filter_map(|(&i, w)| {
+            let Constraint::Length(w) = *w else { return None };
+            x += w + 1;
+            Some((text.columns[i], x - w - 1, w))
+        })
+        .collect();
+
+    // drawn after the table because the title needs the offset the table settled on
+    let mut title = if total > visible {
+        let first = table_state.offset() + 1;
+        format!("Interfaces (showing {first}{}{} of {total})", app.glyphs.range, (first + visible - 1).min(total))
+    } else {
+        "Interfaces".to_string()
+    };
+    // columns squeezed out by the width, the detail view still has all of them
+    let squeezed = text.columns.len() - columns.len();
+    if squeezed > 0 {
+        title.push_str(&format!(" +{squeezed} cols"));
+    }
+    f.render_widget(block.title(Span::from(title)), area);
+}
+
+// the sockets from conns_scroll down, as many as fit; addresses get the width of the longest
+// one on screen, the process whatever is left
+fn draw_connections(f: &mut Frame, area: Rect, app: &AppState) {
+    let shown = app.shown_connections();
+    let on = app.connections_interface().map(|r| format!(" on {}", r.interface)).unwrap_or_default();
+    let mut block = bordered(app);
+    if app.conns.unreadable > 0 {
+        block = block.title_bottom(format!(" owners in {} other processes need root ", app.conns.unreadable));
+    }
+    let reason = if app.conns_at.is_none() {
+        Some("reading sockets...".to_string())
+    } else if let Some(e) = &app.conns.error {
+        Some(e.clone())
+    } else if shown.is_empty() && !on.is_empty() {
+        Some(format!("no sockets{on}'s addresses ('F' lists all of them)"))
+    } else if shown.is_empty() {
+        Some("no sockets".to_string())
+    } else {
+        None
+    };
+    if let Some(reason) = reason {
+        let block = block.title(Span::from(format!("Connections{on}")));
+        f.render_widget(Paragraph::new(reason).style(app.theme.text).block(block), area);
+        return;
+    }
+
+    let inner = block.inner(area);
+    let visible = (inner.height as usize).saturating_sub(1);
+    app.page_rows.set(visible);
+    let first = app.conns_scroll.min(shown.len().saturating_sub(visible.max(1)));
+    let rows: Vec<[String; 5]> = shown
+        .iter()
+        .skip(first)
+        .take(visible)
+        .map(|s| [s.proto.label().to_string(), s.local.to_string(), remote_label(app, s.remote), s.state_label().to_string(), s.process_label()])
+        .collect();
+
+    let arrow = if app.conns_desc { app.glyphs.sort_desc } else { app.glyphs.sort_asc };
+    let sorted = match app.conns_sort {
+        ConnSort::Remote => 2,
+        ConnSort::State => 3,
+        ConnSort::Process => 4,
+    };
+    let header: Vec<String> = ["PROTO", "LOCAL", "REMOTE", "STATE", "PROCESS"]
+        .iter()
+        .enumerate()
+        .map(|(i, h)| if i == sorted { format!("{h} {arrow}") } else { h.to_string() })
+        .collect();
+    let width = |c: usize| rows.iter().map(|r| r[c].chars().count()).chain([header[c].chars().count()]).max().unwrap_or(0) as u16;
+    let widths = [
+        Constraint::Length(width(0)),
+        Constraint::Length(width(1)),
+        Constraint::Length(width(2)),
+        Constraint::Length(width(3)),
+        Constraint::Min(0),
+    ];
+    let table = Table::new(rows.into_iter().map(Row::new), widths)
+        .header(Row::new(header).style(app.theme.table_header))
+        .column_spacing(2)
+        .style(app.theme.text);
+    f.render_widget(table, inner);
+
+    let title = if shown.len() > visible && visible > 0 {
+        let last = (first + visible).min(shown.len());
+        format!("Connections (showing {}{}{last} of {}){on}", first + 1, app.glyphs.range, shown.len())
+    } else {
+        format!("Connections ({}){on}", shown.len())
+    };
+    f.render_widget(block.title(Span::from(title)), area);
+}
+
+// busiest processes first; the numbers come from per-socket TCP byte counters, so they are
+// estimates and the block says so
+fn draw_processes(f: &mut Frame, area: Rect, app: &AppState) {
+    let on = app.selected_name().map(|name| format!(" on {name}")).unwrap_or_default();
+    let block = bordered(app)
+        .title(Span::from(format!("Processes{on} (estimated)")))
+        .title_bottom(" TCP payload only: UDP and packet headers aren't counted ");
+    let rates = app.process_rates();
+    let reason = if app.procs_at.is_none() {
+        Some("measuring...".to_string())
+    } else if rates.is_empty() {
+        Some(format!("no TCP traffic{on}"))
+    } else {
+        None
+    };
+    if let Some(reason) = reason {
+        f.render_widget(Paragraph::new(reason).style(app.theme.text).block(block), area);
+        return;
+    }
+
+    let rows = rates.iter().map(|r| {
+        let pid = r.pid.map_or("--".to_string(), |pid| pid.to_string());
+        Row::new([
+            Line::from(pid).right_aligned(),
+            Line::from(r.name.as_str()),
+            Line::from(Span::styled(app.rate(r.rx_bps), app.rate_style(r.rx_bps))).right_aligned(),
+            Line::from(Span::styled(app.rate(r.tx_bps), app.rate_style(r.tx_bps))).right_aligned(),
+        ])
+    });
+    let rate_width = (number_width(app.format.precision) + 6).max(12) as u16;
+    let header = Row::new([
+        Line::from("PID").right_aligned(),
+        Line::from("PROCESS"),
+        Line::from("RX/s").right_aligned(),
+        Line::from("TX/s").right_aligned(),
+    ])
+    .style(app.theme.table_header);
+    let widths = [Constraint::Length(7), Constraint::Min(12), Constraint::Length(rate_width), Constraint::Length(rate_width)];
+    let table = Table::new(rows, widths).header(header).column_spacing(2).style(app.theme.text).block(block);
+    f.render_widget(table, area);
+}
+
+// "name:port" once 'n' has a name for the address, the address and port otherwise
+fn remote_label(app: &AppState, addr: SocketAddr) -> String {
+    match app.names.name(addr.ip()).filter(|_| app.resolve) {
+        Some(name) => format!("{name}:{}", addr.port()),
+        None => addr.to_string(),
+    }
+}
+
+// the remote ends moving the most bytes on the selected interface over the last interval
+fn draw_talkers(f: &mut Frame, area: Rect, app: &AppState) {
+    let on = app.capture_on.as_deref().map(|name| format!(" on {name}")).unwrap_or_default();
+    let mut block = bordered(app).title(Span::from(format!("Top talkers{on}")));
+    if let Some(dropped) = app.capture.as_ref().map(|c| c.dropped()).filter(|&n| n > 0) {
+        block = block.title_bottom(format!(" {} packets not counted: the display fell behind ", group_digits(dropped, app.format.separator)));
+    }
+    let reason = if let Some(e) = &app.capture_error {
+        Some(e.clone())
+    } else if app.capture_on.is_none() {
+        Some("no interface selected".to_string())
+    } else if app.talkers_at.is_none() {
+        Some("capturing...".to_string())
+    } else if app.talkers.is_empty() {
+        Some(format!("no IP traffic{on}"))
+    } else {
+        None
+    };
+    if let Some(reason) = reason {
+        f.render_widget(Paragraph::new(reason).style(app.theme.text).block(block), area);
+        return;
+    }
+
+    let rows = app.talkers.iter().map(|t| {
+        let (remote, port, transport) = match t.peer {
+            Some(p) => (app.remote_label(p.remote), p.port.map_or("--".to_string(), |port| port.to_string()), p.transport.label()),
+            None => ("(others)".to_string(), String::new(), String::new()),
+        };
+        Row::new([
+            Line::from(remote),
+            Line::from(port).right_aligned(),
+            Line::from(transport),
+            Line::from(Span::styled(app.rate(t.rx_bps), app.rate_style(t.rx_bps))).right_aligned(),
+            Line::from(Span::styled(app.rate(t.tx_bps), app.rate_style(t.tx_bps))).right_aligned(),
+        ])
+    });
+    let rate_width = (number_width(app.format.precision) + 6).max(12) as u16;
+    let header = Row::new([
+        Line::from("REMOTE"),
+        Line::from("PORT").right_aligned(),
+        Line::from("PROTO"),
+        Line::from("RX/s").right_aligned(),
+        Line::from("TX/s").right_aligned(),
+    ])
+    .style(app.theme.table_header);
+    let widths = [
+        Constraint::Min(15),
+        Constraint::Length(5),
+        Constraint::Length(6),
+        Constraint::Length(rate_width),
+        Constraint::Length(rate_width),
+    ];
+    let table = Table::new(rows, widths).header(header).column_spacing(2).style(app.theme.text).block(block);
+    f.render_widget(table, area);
+}
+
+// `s` cut to `width` chars, ending in `ellipsis` (one char) when something was cut
+fn ellipsize<'a>(s: &'a str, width: usize, ellipsis: &str) -> Cow<'a, str> {
+    if s.chars().count() <= width {
+        return Cow::Borrowed(s);
+    }
+    let kept: String = s.chars().take(width.saturating_sub(1)).collect();
+    Cow::Owned(format!("{kept}{ellipsis}"))
+}
+
+// which cached columns fit into `width` and how wide each one is, plus the name column's
+// width; columns go by Column::priority, lowest first, and None means not even name, RX
+// and TX fit
+fn layout_columns(app: &AppState, width: u16) -> Option<(Vec<usize>, Vec<Constraint>, u16)> {
+    // "1023.9 KiB/s" at the default precision
+    let rate_width = (number_width(app.format.precision) + 6).max(12) as u16;
+    let width_of = |c: Column| match c {
+        Column::Iface => 0,
+        Column::Link => 4,
+        Column::Type => TYPE_WIDTH as u16,
+        Column::Addr => ADDR_WIDTH as u16,
+        Column::Rx | Column::Tx | Column::PeakRx | Column::PeakTx | Column::AvgRx | Column::AvgTx => rate_width,
+        // room for the "~"
+        Column::Avg1m | Column::Avg5m | Column::Avg15m => rate_width + 1,
+        Column::Util => UTIL_WIDTH as u16,
+        Column::PktIn | Column::PktOut | Column::McastIn => 11,
+        Column::ErrIn | Column::ErrOut | Column::DropIn | Column::DropOut => 10,
+        Column::Nbrs => NBRS_WIDTH as u16,
+        Column::Trend => SPARK_WIDTH as u16,
+    };
+    let cached = &app.text.columns;
+    let mut ranks: Vec<u8> = cached.iter().filter_map(|c| c.priority()).collect();
+    ranks.sort_unstable();
+    ranks.dedup();
+
+    // borders on both sides, one space between columns
+    let inner = width.saturating_sub(2);
+    // everything at first, then each rank and the ones below it are left out
+    for floor in std::iter::once(0).chain(ranks.into_iter().map(|r| r + 1)) {
+        let columns: Vec<usize> = (0..cached.len()).filter(|&i| cached[i].priority().is_none_or(|p| p >= floor)).collect();
+        let fixed: u16 = columns.iter().map(|&i| width_of(cached[i])).sum::<u16>() + (columns.len() as u16).saturating_sub(1);
+        if inner >= fixed + MIN_NAME_WIDTH {
+            let name = app.text.name_width.min(inner - fixed);
+            let constraints = columns
+                .iter()
+                .map(|&i| Constraint::Length(if cached[i] == Column::Iface { name } else { width_of(cached[i]) }))
+                .collect();
+            return Some((columns, constraints, name));
+        }
+    }
+    None
+}
+
+fn draw_detail(f: &mut Frame, area: Rect, app: &AppState) {
+    let name = app.selected_name().unwrap_or_default();
+    let block = bordered(app).title(Span::from(format!("Interface {}", app.display_name(name))));
+
+    // the interface can vanish while the view is open (cable pulled, container removed)
+    let Some(r) = app.shown().into_iter().find(|r| r.interface == name) else {
+        let gone = Paragraph::new(format!("{name} is gone (enter/esc to go back)")).style(app.theme.text).block(block);
+        f.render_widget(gone, area);
+        return;
+    };
+
+    let t = &r.totals;
+    let mut lines = Vec::new();
+    // the title has the friendly name, filters and --interface go by this one
+    if app.adapters.contains_key(&r.interface) {
+        lines.push(Line::from(format!("Name         {}", r.interface)));
+    }
+    lines.extend([
+        Line::from(format!("RX rate      {}", app.rate(app.shown_rates(r).0))),
+        Line::from(format!("TX rate      {}", app.rate(app.shown_rates(r).1))),
+        Line::from(format!("RX session   {}", app.size(t.rx_bytes))),
+        Line::from(format!("TX session   {}", app.size(t.tx_bytes))),
+        Line::from(format!("Packets      in {} ({})   out {} ({})",
+            app.count(t.packets_in), human_count_rate(r.packets_in, "pps", false),
+            app.count(t.packets_out), human_count_rate(r.packets_out, "pps", false))),
+        Line::from(format!("Errors       in {} ({})   out {} ({})",
+            app.count(t.errors_in), human_pps(r.errors_in, false), app.count(t.errors_out), human_pps(r.errors_out, false))),
+    ]);
+    if HAS_DROPS {
+        lines.push(Line::from(format!("Drops        in {} ({})   out {} ({})",
+            app.count(t.drops_in), human_pps(r.drops_in, false), app.count(t.drops_out), human_pps(r.drops_out, false))));
+    }
+    if let Some(pps) = r.multicast_in {
+        lines.push(Line::from(format!("Multicast    in {} ({})", app.count(t.multicast_in), human_count_rate(pps, "pps", false))));
+    }
+    // --backend procfs only, sysinfo doesn't read these
+    if let Some(e) = &t.extra {
+        lines.extend([
+            Line::from(format!("Frame        {}", app.count(e.rx_frame))),
+            Line::from(format!("FIFO         rx {}   tx {}", app.count(e.rx_fifo), app.count(e.tx_fifo))),
+            Line::from(format!("Collisions   {}   carrier {}", app.count(e.collisions), app.count(e.carrier))),
+            Line::from(format!("Compressed   rx {}   tx {}", app.count(e.rx_compressed), app.count(e.tx_compressed))),
+        ]);
+    }
+    let last = app.last_errors.get(&r.interface).copied().unwrap_or_default();
+    let ago = |at: Option<Instant>| at.map_or("never".to_string(), |at| format!("{} ago", human_duration(at.elapsed())));
+    lines.push(Line::from(format!("Last error   rx {}   tx {}", ago(last.0), ago(last.1))));
+    // the peak/avg columns may be off or squeezed out of the table
+    let (peak_rx, peak_tx) = app.peaks.get(&r.interface).copied().unwrap_or_default();
+    let (avg_rx, avg_tx) = app.average(r);
+    lines.extend([
+        Line::from(format!("Peak         rx {}   tx {}", app.rate(peak_rx).trim(), app.rate(peak_tx).trim())),
+        Line::from(format!("Average      rx {}   tx {}", app.rate(avg_rx).trim(), app.rate(avg_tx).trim())),
+        Line::from(format!("Link         {}   type {}", r.info.state.label(), r.info.kind.label())),
+        Line::from(match (r.info.speed_mbps, r.utilization()) {
+            (Some(speed), Some(pct)) => format!("Speed        {speed} Mb/s   utilization {pct:.1}%"),
+            _ => "Speed        --".to_string(),
+        }),
+        Line::from(format!("MAC          {}", r.info.mac_string().as_deref().unwrap_or("-- (none)"))),
+        Line::from(if r.info.mtu == 0 { "MTU          --".to_string() } else { format!("MTU          {}", r.info.mtu) }),
+        Line::from(if r.info.addrs.is_empty() { "Addresses    --" } else { "Addresses" }),
+    ]);
+    // own lines so a full IPv6 address with its prefix still fits the pane
+    lines.extend(r.info.addrs.iter().map(|a| Line::from(format!("  {a}"))));
+    if !r.info.members.is_empty() || matches!(r.info.kind, IfaceKind::Bond | IfaceKind::Bridge) {
+        lines.extend(member_lines(app, r));
+    }
+    if let Some(vlan) = &r.info.vlan {
+        lines.push(Line::from(format!("VLAN         {} on {}", vlan.id, vlan.parent)));
+    }
+    if let Some(ns) = &r.info.netns {
+        lines.push(Line::from(format!("Namespace    {ns}")));
+    }
+    lines.extend(vlan_lines(app, r));
+    let routes: Vec<&Route> = app.routes.iter().filter(|rt| rt.interface == r.interface).collect();
+    lines.push(Line::from(if routes.is_empty() { "Routes       --" } else { "Routes" }));
+    lines.extend(routes.iter().take(DETAIL_ROUTES).map(|rt| Line::from(format!("  {}", rt.label()))));
+    if routes.len() > DETAIL_ROUTES {
+        lines.push(Line::from(format!("  and {} more", routes.len() - DETAIL_ROUTES)));
+    }
+    lines.extend(neighbor_lines(app, &r.interface));
+    if let Some(l) = app.latency.get(&r.interface) {
+        let rtt = l.last().map_or("--".to_string(), |rtt| format!("{:.1} ms", rtt.as_secs_f64() * 1000.0));
+        let loss = l.loss_pct();
+        let style = if loss >= 20.0 { app.theme.crit } else if loss > 0.0 { app.theme.warn } else { app.theme.text };
+        lines.extend([
+            Line::from(format!("Gateway      {} by {}", l.gateway, l.method.label())),
+            Line::from(vec![
+                Span::from(format!("  rtt {rtt}   ")),
+                Span::styled(format!("loss {loss:.0}%"), style),
+                Span::from(format!(" of {}", l.samples.len())),
+            ]),
+            Line::from(format!("  {}", l.sparkline(SPARK_WIDTH * 2, app.glyphs.bars))),
+        ]);
+        if l.method == Method::Udp {
+            lines.push(Line::from("  no permission for ICMP sockets"));
+        }
+    }
+    if let Some(wifi) = &r.info.wifi {
+        lines.extend(wifi_lines(app, wifi));
+    }
+    if let Some(tracker) = app.wireguard.as_ref().filter(|t| t.interface == r.interface) {
+        lines.extend(wireguard_lines(app, tracker));
+    }
+    // over the session, or its last hour
+    if let Some(p) = app.percentiles(name) {
+        let list = |values: &[f64]| values.iter().map(|v| app.rate(*v).trim().to_string()).collect::<Vec<_>>().join(" / ");
+        lines.extend([
+            Line::from(format!("p50/95/99    over {}", human_duration(p.span))),
+            Line::from(format!("  rx {}", list(&p.rx))),
+            Line::from(format!("  tx {}", list(&p.tx))),
+        ]);
+    }
+    if let Some(b) = app.budget.as_ref().filter(|b| b.cap.interface == r.interface) {
+        let style = app.theme.level(b.fraction() * 100.0, 75.0, 90.0);
+        lines.extend([
+            Line::from(Span::styled(
+                format!("Data cap     {} of {} ({:.0}%)", app.size(b.usage.used), app.size(b.cap.bytes), b.fraction() * 100.0),
+                style,
+            )),
+            Line::from(format!("             {}, {} budget", b.outlook(unix_now()), b.cap.period.label())),
+        ]);
+    }
+    lines.extend([
+        Line::from(""),
+        Line::from(Span::styled("Recent samples (newest first)", app.theme.heading)),
+    ]);
+    if let Some(ring) = app.history.get(name) {
+        lines.extend(ring.iter().rev().take(DETAIL_SAMPLES).map(|s| {
+            Line::from(format!("  rx {:>12}   tx {:>12}", app.rate(s.rx), app.rate(s.tx)))
+        }));
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(48), Constraint::Min(20)])
+        .split(area);
+    f.render_widget(Paragraph::new(lines).style(app.theme.text).block(block), chunks[0]);
+    draw_chart(f, chunks[1], app, name);
+}
+
+// a bond's or bridge's ports with their rates, to see how evenly a bond spreads the load;
+// a port shown in the table has the rates shown there
+fn member_lines(app: &AppState, r: &RowData) -> Vec<Line<'static>> {
+    if r.info.members.is_empty() {
+        return vec![Line::from("Members      none")];
+    }
+    let width = r.info.members.iter().map(|m| m.chars().count()).max().unwrap_or(0);
+    let mut lines = vec![Line::from("Members")];
+    lines.extend(r.info.members.iter().map(|m| {
+        let rates = match app.rows.iter().find(|row| row.interface == *m) {
+            Some(row) => Some(app.shown_rates(row)),
+            None => app.member_rates.get(m).copied(),
+        };
+        Line::from(match rates {
+            Some((rx, tx)) => format!("  {m:<width$}  rx {}   tx {}", app.rate(rx).trim(), app.rate(tx).trim()),
+            None => format!("  {m:<width$}  rx --   tx --"),
+        })
+    }));
+    lines
+}
+
+// the VLANs riding on this interface with their rates. The kernel already counts tagged
+// frames on the parent too, so its own rates are the aggregate; what's left over after the
+// VLANs is the untagged traffic
+fn vlan_lines(app: &AppState, r: &RowData) -> Vec<Line<'static>> {
+    let vlans: Vec<&RowData> =
+        app.rows.iter().filter(|v| v.interface != r.interface && v.info.vlan.as_ref().is_some_and(|v| v.parent == r.interface)).collect();
+    if vlans.is_empty() {
+        return Vec::new();
+    }
+    let width = vlans.iter().map(|v| v.interface.chars().count()).max().unwrap_or(0).max("untagged".len());
+    let line = |name: &str, (rx, tx): (f64, f64)| {
+        Line::from(format!("  {name:<width$}  rx {}   tx {}", app.rate(rx).trim(), app.rate(tx).trim()))
+    };
+    let mut lines = vec![Line::from("VLANs")];
+    lines.extend(vlans.iter().map(|v| line(&v.interface, app.shown_rates(v))));
+    let (rx, tx) = app.shown_rates(r);
+    let (vlan_rx, vlan_tx) = vlans.iter().map(|v| app.shown_rates(v)).fold((0.0, 0.0), |a, b| (a.0 + b.0, a.1 + b.1));
+    // smoothing and sampling skew can put the VLANs' sum a little over the parent
+    lines.push(line("untagged", ((rx - vlan_rx).max(0.0), (tx - vlan_tx).max(0.0))));
+    lines
+}
+
+// the neighbor count as in the table, then one line each: address, state, MAC. Entries on
+// their way out or never answered are set apart, and the MAC cut off for a long IPv6
+fn neighbor_lines(app: &AppState, name: &str) -> Vec<Line<'static>> {
+    let neighbors: Vec<&Neighbor> = app.neighbors.iter().filter(|n| n.interface == name).collect();
+    if neighbors.is_empty() {
+        return vec![Line::from("Neighbors    --")];
+    }
+    let count = neigh::count(&app.neighbors, name);
+    let mut lines = vec![Line::from(match neighbors.len() - count {
+        0 => format!("Neighbors    {count}"),
+        failed => format!("Neighbors    {count}   failed {failed}"),
+    })];
+    lines.extend(neighbors.iter().take(DETAIL_NEIGHBORS).map(|n| {
+        let style = match n.state {
+            NeighState::Stale | NeighState::Delay | NeighState::Probe => app.theme.warn,
+            NeighState::Incomplete => app.theme.down_row,
+            NeighState::Failed => app.theme.crit,
+            _ => app.theme.text,
+        };
+        Line::from(Span::styled(format!("  {:<15} {:<10} {}", n.ip, n.state.label(), n.mac.as_deref().unwrap_or("--")), style))
+    }));
+    if neighbors.len() > DETAIL_NEIGHBORS {
+        lines.push(Line::from(format!("  and {} more", neighbors.len() - DETAIL_NEIGHBORS)));
+    }
+    lines
+}
+
+// the detail view's WireGuard section: each peer with where it is, what it routes, how
+// long ago it last shook hands and its traffic; without root one line says why it's empty
+fn wireguard_lines(app: &AppState, tracker: &Tracker) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(""), Line::from(Span::styled("WireGuard", app.theme.heading))];
+    let peers = match &tracker.peers {
+        Peers::Read(peers) => peers,
+        Peers::Denied => {
+            lines.push(Line::from(Span::styled("Peers        need root (wg show was denied)", app.theme.warn)));
+            return lines;
+        }
+        Peers::Failed(e) => {
+            lines.push(Line::from(format!("Peers        n/a: {e}")));
+            return lines;
+        }
+    };
+    lines.push(Line::from(format!("Peers        {}", peers.len())));
+    for p in peers {
+        let handshake = match p.latest_handshake {
+            Some(at) => format!("{} ago", human_duration(Duration::from_secs((unix_now() as u64).saturating_sub(at)))),
+            None => "never".to_string(),
+        };
+        let rates = match tracker.rates(&p.public_key) {
+            Some((rx, tx)) => format!("rx {}   tx {}", app.rate(rx).trim(), app.rate(tx).trim()),
+            None => "rx --   tx --".to_string(),
+        };
+        let allowed = if p.allowed_ips.is_empty() { "--".to_string() } else { p.allowed_ips.join(", ") };
+        lines.extend([
+            Line::from(format!("  {}   handshake {handshake}", p.short_key())),
+            Line::from(format!("    endpoint {}", p.endpoint.as_deref().unwrap_or("--"))),
+            Line::from(format!("    allowed  {allowed}")),
+            Line::from(format!("    rate     {rates}")),
+            Line::from(format!("    total    rx {}   tx {}", app.size(p.rx_bytes).trim(), app.size(p.tx_bytes).trim())),
+        ]);
+    }
+    lines
+}
+
+// the detail view's wireless section; what couldn't be read says so instead of hiding
+fn wifi_lines(app: &AppState, wifi: &WifiInfo) -> Vec<Line<'static>> {
+    let na = || "n/a".to_string();
+    let signal = match (wifi.signal_dbm, wifi.signal_percent()) {
+        (Some(dbm), Some(pct)) => {
+            let style = if dbm >= -60 { app.theme.ok } else if dbm >= -70 { app.theme.warn } else { app.theme.crit };
+            Span::styled(format!("{dbm} dBm ({pct}%)"), style)
+        }
+        _ => Span::from(na()),
+    };
+    let freq = match (wifi.freq_mhz, wifi.channel()) {
+        (Some(f), Some(ch)) => format!("{f} MHz (channel {ch})"),
+        (Some(f), None) => format!("{f} MHz"),
+        _ => na(),
+    };
+    vec![
+        Line::from(""),
+        Line::from(Span::styled("Wi-Fi", app.theme.heading)),
+        Line::from(format!("SSID         {}", wifi.ssid.clone().unwrap_or_else(na))),
+        Line::from(vec![Span::from("Signal       "), signal]),
+        Line::from(format!("Frequency    {freq}")),
+        Line::from(format!("Bitrate      {}", wifi.bitrate_mbps.map_or_else(na, |b| format!("{b:.1} Mb/s")))),
+    ]
+}
+
+// RX/TX of one interface over the last CHART_WINDOW_SECS, x in seconds relative to now
+fn draw_chart(f: &mut Frame, area: Rect, app: &AppState, name: &str) {
+    let now = Instant::now();
+    let points = |pick: fn(&Sample) -> f64| -> Vec<(f64, f64)> {
+        app.history
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|s| (-now.duration_since(s.at).as_secs_f64(), pick(s)))
+            .filter(|(x, _)| *x >= -CHART_WINDOW_SECS)
+            .collect()
+    };
+    let rx = points(|s| s.rx);
+    let tx = points(|s| s.tx);
+    // floor keeps the axis labels readable on idle interfaces
+    let max = rx.iter().chain(&tx).map(|(_, y)| *y).fold(0.0, f64::max).max(1024.0);
+
+    let marker = if app.glyphs.ascii { Marker::Dot } else { Marker::Braille };
+    let datasets = vec![
+        Dataset::default()
+            .name("RX")
+            .marker(marker)
+            .graph_type(GraphType::Line)
+            .style(app.theme.rx)
+            .data(&rx),
+        Dataset::default()
+            .name("TX")
+            .marker(marker)
+            .graph_type(GraphType::Line)
+            .style(app.theme.tx)
+            .data(&tx),
+    ];
+
+    let window = CHART_WINDOW_SECS as u64;
+    let x_axis = Axis::default()
+        .bounds([-CHART_WINDOW_SECS, 0.0])
+        .labels([format!("-{window}s"), format!("-{}s", window / 2), "now".to_string()]);
+    let y_axis = Axis::default()
+        .bounds([0.0, max])
+        .labels(["0".to_string(), app.rate(max / 2.0).trim().to_string(), app.rate(max).trim().to_string()]);
+
+    let chart = Chart::new(datasets)
+        .style(app.theme.text)
+        .block(bordered(app).title(Span::from(if name.is_empty() {
+            "history (select an interface)".to_string()
+        } else {
+            format!("{name} history")
+        })))
+        .x_axis(x_axis)
+        .y_axis(y_axis)
+        .legend_position(Some(LegendPosition::TopLeft))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    f.render_widget(chart, area);
+    asciify(f, area, app);
+}
+
+// the chart draws its axes, legend box and dots itself, with no way to pick the symbols
+fn asciify(f: &mut Frame, area: Rect, app: &AppState) {
+    let buffer = f.buffer_mut();
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = &mut buffer[(x, y)];
+            if let Some(symbol) = app.glyphs.fallback(cell.symbol()) {
+                cell.set_symbol(symbol);
+            }
+        }
+    }
+}
+
+// centered popup listing the keymap, clipped with a marker when the terminal is too small
+fn draw_help(f: &mut Frame, area: Rect, app: &AppState) {
+    let mut lines: Vec<Line> = app
+        .keymap
+        .bindings()
+        .filter(|(keys, _)| !keys.is_empty())
+        .map(|(keys, action)| {
+            let keys: Vec<String> = keys.iter().map(|k| key_label(*k, app.glyphs)).collect();
+            Line::from(format!(" {:<10} {}", keys.join(" / "), action.description()))
+        })
+        .collect();
+    lines.push(Line::from(""));
+    // full duplex: each direction has the whole line rate to itself
+    lines.push(Line::from(" UTIL is the busier of RX/TX over link speed"));
+    lines.push(Line::from(" press any key to close"));
+
+    let width = 50.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let visible = height.saturating_sub(2) as usize;
+    if visible < lines.len() && visible > 0 {
+        lines.truncate(visible - 1);
+        lines.push(Line::from(format!(" {}", app.glyphs.ellipsis)));
+    }
+
+    let help = Paragraph::new(lines).style(app.theme.text).block(bordered(app).title(Span::from(" Keys ")));
+    f.render_widget(Clear, popup);
+    f.render_widget(help, popup);
+}
+
+// centered list of the interfaces hidden with 'x', the cursor entry highlighted
+fn draw_hidden(f: &mut Frame, area: Rect, app: &AppState) {
+    let mut lines: Vec<Line> = if app.hidden.is_empty() {
+        vec![Line::from(" nothing hidden, 'x' hides the selected row")]
+    } else {
+        app.hidden
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let line = Line::from(format!(" {name}"));
+                if i == app.hidden_cursor { line.style(app.theme.selected) } else { line }
+            })
+            .collect()
+    };
+    lines.push(Line::from(""));
+    lines.push(Line::from(" enter: show again   esc: close"));
+
+    let width = 44.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    // keep the cursor entry visible in a short terminal
+    let visible = height.saturating_sub(2) as usize;
+    let scroll = (app.hidden_cursor + 3).saturating_sub(visible) as u16;
+
+    let list = Paragraph::new(lines).scroll((scroll, 0)).style(app.theme.text).block(bordered(app).title(Span::from(" Hidden ")));
+    f.render_widget(Clear, popup);
+    f.render_widget(list, popup);
+}
+
+// every column this platform has in drawing order, checked when shown
+fn draw_columns(f: &mut Frame, area: Rect, app: &AppState) {
+    let mut lines: Vec<Line> = app
+        .columns
+        .entries()
+        .iter()
+        .enumerate()
+        .map(|(i, &(c, on))| {
+            let check = if on { "x" } else { " " };
+            let line = Line::from(format!(" [{check}] {}", c.label()));
+            if i == app.columns_cursor { line.style(app.theme.selected) } else { line }
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(" space: on/off   < >: move"));
+    lines.push(Line::from(" esc: close"));
+
+    let width = 32.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let visible = height.saturating_sub(2) as usize;
+    let scroll = (app.columns_cursor + 3).saturating_sub(visible) as u16;
+
+    let list = Paragraph::new(lines).scroll((scroll, 0)).style(app.theme.text).block(bordered(app).title(Span::from(" Columns ")));
+    f.render_widget(Clear, popup);
+    f.render_widget(list, popup);
+}
+
+// the alert log newest first, as much as fits below alerts_scroll
+fn draw_alerts(f: &mut Frame, area: Rect, app: &AppState) {
+    let log = &app.alerts.log;
+    let mut lines: Vec<Line> = if log.is_empty() && app.alerts.rules.is_empty() {
+        vec![Line::from(" no alert rules, see --alert")]
+    } else if log.is_empty() {
+        vec![Line::from(" nothing has fired yet")]
+    } else {
+        log.iter()
+            .rev()
+            .skip(app.alerts_scroll)
+            .map(|e| {
+                let line = Line::from(format!(" {}", app.alert_line(e)));
+                if e.transition == Transition::Fired { line.style(app.theme.error) } else { line }
+            })
+            .collect()
+    };
+    lines.push(Line::from(""));
+    lines.push(Line::from(" c: clear all   esc: close"));
+
+    let width = 72.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2).max(3)).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    // the key line stays at the bottom however long the log is
+    let visible = height.saturating_sub(2) as usize;
+    if lines.len() > visible {
+        let keys = lines.split_off(lines.len() - 2);
+        lines.truncate(visible.saturating_sub(2));
+        lines.extend(keys);
+    }
+
+    let title = format!(" Alerts ({}) ", log.len());
+    let list = Paragraph::new(lines).style(app.theme.text).block(bordered(app).title(Span::from(title)));
+    f.render_widget(Clear, popup);
+    f.render_widget(list, popup);
+}
+
+// key hints on the left, sampling on the right; hints that don't fit end in "…"
+// instead of wrapping. Not cached in TableText since "ago" changes between samples
+fn draw_status(f: &mut Frame, area: Rect, app: &AppState) {
+    let ago = match app.last_sample {
+        Some(at) => format!("sampled {:.1}s ago", at.elapsed().as_secs_f64()),
+        None => "waiting for a sample".to_string(),
+    };
+    // a replayed sample happened whenever it was recorded, how long ago means nothing
+    let right = match app.replay.as_ref().and_then(|r| r.frame()) {
+        Some(frame) => format!(" sampled over {:.1}s ", frame.interval),
+        None => format!(" every {} ms  {ago} ", app.refresh_ms),
+    };
+    // a sink that can't keep up says so next to it, without taking over the hints
+    let warning = app.influx.as_ref().and_then(|i| i.status().warning()).map(|w| format!(" {w} "));
+    let right_width = (right.chars().count() + warning.as_ref().map_or(0, |w| w.chars().count())).min(area.width as usize) as u16;
+    let room = area.width.saturating_sub(right_width) as usize;
+
+    let key_style = app.theme.key;
+    let mut spans = Vec::new();
+    let mut used = 0;
+    let hints = if let Some(message) = app.message() {
+        spans.push(Span::from(format!(" {message}")));
+        Vec::new()
+    } else {
+        app.hints()
+    };
+    for (i, (keys, hint)) in hints.iter().enumerate() {
+        let width = 2 + keys.chars().count() + 1 + hint.chars().count();
+        // the last hint may use the space the ellipsis would have needed
+        let reserve = if i + 1 == hints.len() { 0 } else { 2 };
+        if used + width + reserve > room {
+            spans.push(Span::from(format!(" {}", app.glyphs.ellipsis)));
+            break;
+        }
+        spans.push(Span::from(if i == 0 { " " } else { "  " }));
+        spans.push(Span::styled(keys.clone(), key_style));
+        spans.push(Span::from(format!(" {hint}")));
+        used += width;
+    }
+
+    let halves = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(right_width)])
+        .split(area);
+    f.render_widget(Paragraph::new(Line::from(spans)).style(app.theme.text), halves[0]);
+    let right = match warning {
+        Some(w) => Line::from(vec![Span::styled(w, app.theme.warn), Span::from(right)]),
+        None => Line::from(right),
+    };
+    f.render_widget(Paragraph::new(right.right_aligned()).style(app.theme.text), halves[1]);
+}
+
+// one line under everything while '/' is open, the terminal cursor sits at the edit point
+fn draw_search(f: &mut Frame, area: Rect, app: &AppState) {
+    let prompt = Line::from(vec![Span::styled("/", app.theme.key), Span::from(app.search.as_str())]);
+    f.render_widget(Paragraph::new(prompt).style(app.theme.text), area);
+    let before = Span::from(app.search.chars().take(app.search_cursor).collect::<String>());
+    let x = area.x + 1 + before.width() as u16;
+    f.set_cursor_position((x.min(area.right().saturating_sub(1)), area.y));
+}
+
+// the 'E' prompt in the same place, with the format it will write unless the path says otherwise,
+// or the question whether to replace a file already at that path
+fn draw_export(f: &mut Frame, area: Rect, app: &AppState) {
+    if app.export_overwrite {
+        let question = format!("{} exists, overwrite? y/n", app.export_path.trim());
+        f.render_widget(Paragraph::new(Span::styled(question, app.theme.warn)), area);
+        return;
+    }
+    let label = format!("export {}: ", app.export_format.extension());
+    let prompt = Line::from(vec![Span::styled(label.as_str(), app.theme.key), Span::from(app.export_path.as_str())]);
+    f.render_widget(Paragraph::new(prompt).style(app.theme.text), area);
+    let before = Span::from(app.export_path.chars().take(app.export_cursor).collect::<String>());
+    let x = area.x + label.chars().count() as u16 + before.width() as u16;
+    f.set_cursor_position((x.min(area.right().saturating_sub(1)), area.y));
+}
+
+pub fn draw(f: &mut Frame, app: &AppState) {
+    // draw_table records them again if a table ends up on screen
+    app.table_area.set(Rect::default());
+    app.table_columns.borrow_mut().clear();
+    let prompt = if app.searching || app.exporting { 1 } else { 0 };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(prompt), Constraint::Length(1)].as_ref())
+        .split(f.area());
+    if app.searching {
+        draw_search(f, chunks[2], app);
+    }
+    if app.exporting {
+        draw_export(f, chunks[2], app);
+    }
+    draw_status(f, chunks[3], app);
+
+    draw_header(f, chunks[0], app);
+
+    // render into the second chunk (chunks[0] is header)
+    match app.view {
+        View::Table if app.show_chart => {
+            let body = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .split(chunks[1]);
+            draw_table(f, body[0], app);
+            draw_chart(f, body[1], app, app.selected_name().unwrap_or_default());
+        }
+        View::Table => draw_table(f, chunks[1], app),
+        View::Detail => draw_detail(f, chunks[1], app),
+        View::Connections => draw_connections(f, chunks[1], app),
+        View::Processes => draw_processes(f, chunks[1], app),
+        View::Talkers => draw_talkers(f, chunks[1], app),
+    }
+
+    if app.show_hidden {
+        draw_hidden(f, f.area(), app);
+    }
+    if app.show_columns {
+        draw_columns(f, f.area(), app);
+    }
+    if app.show_alerts {
+        draw_alerts(f, f.area(), app);
+    }
+    if app.show_help {
+        draw_help(f, f.area(), app);
+    }
+    if let Some(remote) = app.remote.as_ref().filter(|r| !r.is_connected()) {
+        draw_disconnected(f, f.area(), app, remote);
+    }
+}
+
+// over everything while `nettui connect` has no server, the last sample stays underneath
+fn draw_disconnected(f: &mut Frame, area: Rect, app: &AppState, remote: &Remote) {
+    let lines = match &remote.link {
+        Link::Lost(reason, retry) => {
+            let wait = retry.saturating_duration_since(Instant::now()).as_secs_f64().ceil();
+            let mut lines = vec![
+                Line::from(format!(" {reason}")),
+                Line::from(format!(" retrying in {wait:.0}s, attempt {}", remote.failures + 1)),
+            ];
+            if app.sampled {
+                lines.push(Line::from(" the table is the last sample received"));
+            }
+            lines
+        }
+        _ => vec![Line::from(format!(" connecting to {}{}", remote.addr, app.glyphs.ellipsis))],
+    };
+    let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 3).clamp(40, 70).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect { x: area.x + (area.width - width) / 2, y: area.y + (area.height - height) / 2, width, height };
+    let title = if matches!(remote.link, Link::Lost(..)) { " Disconnected " } else { " Connecting " };
+    let block = bordered(app).title(Span::styled(title, app.theme.alert));
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).style(app.theme.text).block(block), popup);
+}
+
+// the table as it is on screen for 'e', header to footer with the shown columns, padded with
+// spaces instead of drawn
+pub fn view_text(app: &AppState) -> String {
+    let text = &app.text;
+    let cells = |line: &[String]| line.iter().map(|c| c.trim().to_string()).collect::<Vec<_>>();
+    let mut lines = vec![cells(&text.header)];
+    lines.extend(text.rows.iter().map(|row| row.iter().map(|(c, _)| c.trim().to_string()).collect()));
+    if !text.footer.is_empty() {
+        lines.push(cells(&text.footer));
+    }
+    let widths: Vec<usize> = (0..text.columns.len())
+        .map(|c| lines.iter().filter_map(|l| l.get(c)).map(|v| v.chars().count()).max().unwrap_or(0))
+        .collect();
+    let mut out = String::new();
+    for line in &lines {
+        let padded: Vec<String> = line
+            .iter()
+            .zip(&text.columns)
+            .zip(&widths)
+            .map(|((v, column), &w)| if column.left_aligned() { format!("{v:<w$}") } else { format!("{v:>w$}") })
+            .collect();
+        out.push_str(padded.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+// same columns as the TUI rate view, padded with spaces
+pub fn plain_table(app: &AppState) -> String {
+    let header = ["INTERFACE", "RX/s", "TX/s", "PPS In", "PPS Out", "Err In/s", "Err Out/s"];
+    let mut lines: Vec<Vec<String>> = vec![header.iter().map(|h| h.to_string()).collect()];
+    for r in &app.rows {
+        lines.push(vec![
+            r.interface.clone(),
+            app.rate(r.rx_bps).trim_end().to_string(),
+            app.rate(r.tx_bps).trim_end().to_string(),
+            app.packet_rate(r.packets_in).trim().to_string(),
+            app.packet_rate(r.packets_out).trim().to_string(),
+            app.pps(r.errors_in).trim_end().to_string(),
+            app.pps(r.errors_out).trim_end().to_string(),
+        ]);
+    }
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|c| lines.iter().map(|l| l[c].chars().count()).max().unwrap_or(0))
+        .collect();
+    let mut out = String::new();
+    for line in &lines {
+        let cells: Vec<String> = line
+            .iter()
+            .enumerate()
+            .map(|(c, v)| if c == 0 { format!("{v:<w$}", w = widths[c]) } else { format!("{v:>w$}", w = widths[c]) })
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,85 @@
+// which interface a VLAN rides on and its tag: /proc/net/vlan/config where the 8021q module
+// keeps it, the lower_<parent> link in sysfs, and last the "eth0.100" naming convention
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vlan {
+    pub id: u16,
+    pub parent: String,
+}
+
+impl Vlan {
+    // "vlan 100 on eth0"
+    pub fn label(&self) -> String {
+        format!("vlan {} on {}", self.id, self.parent)
+    }
+}
+
+// two header lines, then "eth0.100       | 100  | eth0" per VLAN
+pub fn parse_proc_config(text: &str) -> HashMap<String, Vlan> {
+    text.lines()
+        .skip(2)
+        .filter_map(|line| {
+            let mut f = line.split('|').map(str::trim);
+            let (name, id, parent) = (f.next()?, f.next()?.parse().ok()?, f.next()?);
+            (!name.is_empty() && !parent.is_empty()).then(|| (name.to_string(), Vlan { id, parent: parent.to_string() }))
+        })
+        .collect()
+}
+
+// "eth0.100" is VLAN 100 on eth0; names like "vlan100" don't say what they ride on
+pub fn from_name(name: &str) -> Option<Vlan> {
+    let (parent, id) = name.rsplit_once('.')?;
+    Some(Vlan { id: id.parse().ok()?, parent: parent.to_string() }).filter(|_| !parent.is_empty())
+}
+
+#[cfg(target_os = "linux")]
+pub fn lookup(name: &str) -> Option<Vlan> {
+    if let Some(vlan) = std::fs::read_to_string("/proc/net/vlan/config").ok().and_then(|t| parse_proc_config(&t).remove(name)) {
+        return Some(vlan);
+    }
+    // without the proc file (another namespace, a kernel without it) the parent is still
+    // linked from sysfs, only the tag has to come from the name
+    let lower = std::fs::read_dir(format!("/sys/class/net/{name}")).ok().and_then(|dir| {
+        dir.flatten().find_map(|e| e.file_name().to_str()?.strip_prefix("lower_").map(str::to_string))
+    });
+    let by_name = from_name(name);
+    match (lower, by_name) {
+        (Some(parent), Some(vlan)) => Some(Vlan { parent, ..vlan }),
+        (_, by_name) => by_name,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn lookup(name: &str) -> Option<Vlan> {
+    from_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proc_config() {
+        let text = "VLAN Dev name\t | VLAN ID\n\
+                    Name-Type: VLAN_NAME_TYPE_RAW_PLUS_VID_NO_PAD\n\
+                    eth0.100       | 100  | eth0\n\
+                    vlan200        | 200  | bond0\n\
+                    broken         | x    | eth0\n";
+        let vlans = parse_proc_config(text);
+        assert_eq!(vlans.get("eth0.100"), Some(&Vlan { id: 100, parent: "eth0".to_string() }));
+        assert_eq!(vlans.get("vlan200").map(Vlan::label).as_deref(), Some("vlan 200 on bond0"));
+        assert_eq!(vlans.len(), 2);
+        assert!(parse_proc_config("").is_empty());
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(from_name("eth0.100"), Some(Vlan { id: 100, parent: "eth0".to_string() }));
+        // QinQ: the outer VLAN is the parent
+        assert_eq!(from_name("eth0.100.20"), Some(Vlan { id: 20, parent: "eth0.100".to_string() }));
+        assert_eq!(from_name("vlan100"), None);
+        assert_eq!(from_name(".100"), None);
+        assert_eq!(from_name("eth0.99999"), None);
+    }
+}
@@ -0,0 +1,197 @@
+// WireGuard peers of a wg interface from `wg show <iface> dump`, read only while that
+// interface's detail view is open; the parser is pure so tests don't need the tool
+use std::collections::HashMap;
+
+use crate::net::Counters;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Peer {
+    pub public_key: String,
+    // None until the peer has been heard from, or when it has no fixed address
+    pub endpoint: Option<String>,
+    pub allowed_ips: Vec<String>,
+    // unix seconds, None before the first handshake
+    pub latest_handshake: Option<u64>,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+impl Peer {
+    // keys are 44 characters of base64, the start is enough to tell peers apart
+    pub fn short_key(&self) -> String {
+        let start: String = self.public_key.chars().take(10).collect();
+        if start.len() < self.public_key.len() { format!("{start}…") } else { start }
+    }
+}
+
+// what one `wg show` gave
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Peers {
+    Read(Vec<Peer>),
+    // the kernel only hands peers to root (CAP_NET_ADMIN)
+    Denied,
+    Failed(String),
+}
+
+// tab separated: first the interface (private key, public key, listen port, fwmark), then
+// one line per peer: public key, preshared key, endpoint, allowed IPs, latest handshake,
+// transfer rx, transfer tx, persistent keepalive. "(none)" and 0 mean not set
+pub fn parse_dump(text: &str) -> Vec<Peer> {
+    let none = |s: &str| Some(s.to_string()).filter(|s| s != "(none)");
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let f: Vec<&str> = line.split('\t').collect();
+            let [public_key, _, endpoint, allowed_ips, handshake, rx, tx, ..] = f.as_slice() else {
+                return None;
+            };
+            Some(Peer {
+                public_key: public_key.to_string(),
+                endpoint: none(endpoint),
+                allowed_ips: none(allowed_ips).map_or_else(Vec::new, |ips| ips.split(',').map(str::to_string).collect()),
+                latest_handshake: handshake.parse().ok().filter(|&t| t > 0),
+                rx_bytes: rx.parse().ok()?,
+                tx_bytes: tx.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+// stderr of a failed `wg show`: "Unable to access interface: Operation not permitted"
+pub fn parse_failure(stderr: &str) -> Peers {
+    if stderr.contains("Operation not permitted") || stderr.contains("Permission denied") {
+        Peers::Denied
+    } else {
+        let reason = stderr.lines().next().unwrap_or("").trim();
+        Peers::Failed(if reason.is_empty() { "wg show failed".to_string() } else { reason.to_string() })
+    }
+}
+
+// the peers of `interface` and their rates since the previous read, worked out from the
+// transfer counters the way interface rates are, so a re-added peer starts from zero
+#[derive(Debug)]
+pub struct Tracker {
+    pub interface: String,
+    pub peers: Peers,
+    baseline: HashMap<String, Counters>,
+    rates: HashMap<String, (f64, f64)>,
+}
+
+impl Tracker {
+    pub fn new(interface: &str) -> Tracker {
+        Tracker { interface: interface.to_string(), peers: Peers::Read(Vec::new()), baseline: HashMap::new(), rates: HashMap::new() }
+    }
+
+    pub fn update(&mut self, peers: Peers, interval_secs: f64) {
+        let interval_secs = if interval_secs > 0.0 { interval_secs } else { 1.0 };
+        if let Peers::Read(list) = &peers {
+            let counters = |p: &Peer| Counters { name: p.public_key.clone(), rx_bytes: p.rx_bytes, tx_bytes: p.tx_bytes, ..Default::default() };
+            self.rates = list
+                .iter()
+                .filter_map(|p| {
+                    let d = counters(p).since(self.baseline.get(&p.public_key)?);
+                    Some((p.public_key.clone(), (d.rx_bytes as f64 / interval_secs, d.tx_bytes as f64 / interval_secs)))
+                })
+                .collect();
+            self.baseline = list.iter().map(|p| (p.public_key.clone(), counters(p))).collect();
+        }
+        self.peers = peers;
+    }
+
+    // rx and tx in bytes per second; None for a peer seen once so far
+    pub fn rates(&self, public_key: &str) -> Option<(f64, f64)> {
+        self.rates.get(public_key).copied()
+    }
+}
+
+#[cfg(feature = "wireguard")]
+pub fn show(interface: &str) -> Option<Peers> {
+    let out = match std::process::Command::new("wg").args(["show", interface, "dump"]).output() {
+        Ok(out) => out,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Some(Peers::Failed("wg not found".to_string())),
+        Err(e) => return Some(Peers::Failed(e.to_string())),
+    };
+    Some(if out.status.success() {
+        Peers::Read(parse_dump(&String::from_utf8_lossy(&out.stdout)))
+    } else {
+        parse_failure(&String::from_utf8_lossy(&out.stderr))
+    })
+}
+
+// nothing to show without the `wireguard` feature
+#[cfg(not(feature = "wireguard"))]
+pub fn show(_interface: &str) -> Option<Peers> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMP: &str = "\
+        cHJpdmF0ZQ==\tc2VydmVyLXB1YmxpYy1rZXk=\t51820\toff\n\
+        cGVlci1vbmUtcHVibGljLWtleQ==\t(none)\t203.0.113.5:51820\t10.0.0.2/32,fd00::2/128\t1700000000\t4096\t2048\t25\n\
+        cGVlci10d28tcHVibGljLWtleQ==\t(none)\t(none)\t(none)\t0\t0\t0\toff\n";
+
+    #[test]
+    fn dump() {
+        let peers = parse_dump(DUMP);
+        assert_eq!(peers, [
+            Peer {
+                public_key: "cGVlci1vbmUtcHVibGljLWtleQ==".to_string(),
+                endpoint: Some("203.0.113.5:51820".to_string()),
+                allowed_ips: vec!["10.0.0.2/32".to_string(), "fd00::2/128".to_string()],
+                latest_handshake: Some(1_700_000_000),
+                rx_bytes: 4096,
+                tx_bytes: 2048,
+            },
+            Peer {
+                public_key: "cGVlci10d28tcHVibGljLWtleQ==".to_string(),
+                endpoint: None,
+                allowed_ips: Vec::new(),
+                latest_handshake: None,
+                rx_bytes: 0,
+                tx_bytes: 0,
+            },
+        ]);
+        assert_eq!(peers[0].short_key(), "cGVlci1vbm…");
+        assert!(parse_dump("").is_empty());
+    }
+
+    #[test]
+    fn failures() {
+        assert_eq!(parse_failure("Unable to access interface: Operation not permitted\n"), Peers::Denied);
+        assert_eq!(parse_failure("Unable to access interface: Protocol not supported\n"), Peers::Failed("Unable to access interface: Protocol not supported".to_string()));
+        assert_eq!(parse_failure(""), Peers::Failed("wg show failed".to_string()));
+    }
+
+    #[test]
+    fn rates_from_deltas() {
+        let peer = |key: &str, rx, tx| Peer {
+            public_key: key.to_string(),
+            endpoint: None,
+            allowed_ips: Vec::new(),
+            latest_handshake: None,
+            rx_bytes: rx,
+            tx_bytes: tx,
+        };
+        let mut tracker = Tracker::new("wg0");
+        tracker.update(Peers::Read(vec![peer("a", 1000, 500)]), 1.0);
+        assert_eq!(tracker.rates("a"), None, "one read is no rate yet");
+
+        tracker.update(Peers::Read(vec![peer("a", 3000, 1500), peer("b", 100, 100)]), 2.0);
+        assert_eq!(tracker.rates("a"), Some((1000.0, 500.0)));
+        assert_eq!(tracker.rates("b"), None);
+
+        // the peer was removed and re-added: counters start over, not a negative rate
+        tracker.update(Peers::Read(vec![peer("a", 200, 100), peer("b", 300, 100)]), 1.0);
+        assert_eq!(tracker.rates("a"), Some((0.0, 0.0)));
+        assert_eq!(tracker.rates("b"), Some((200.0, 0.0)));
+
+        // a denied read keeps the last baseline
+        tracker.update(Peers::Denied, 1.0);
+        assert_eq!(tracker.peers, Peers::Denied);
+        tracker.update(Peers::Read(vec![peer("b", 400, 100)]), 1.0);
+        assert_eq!(tracker.rates("b"), Some((100.0, 0.0)));
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+// what a wireless interface is associated with; every field is None when it couldn't be
+// read (no permission, rfkill, `iw` missing, not associated), shown as "n/a"
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WifiInfo {
+    pub ssid: Option<String>,
+    pub signal_dbm: Option<i32>,
+    pub freq_mhz: Option<u32>,
+    // transmit bitrate, what the link is actually negotiated at
+    pub bitrate_mbps: Option<f64>,
+}
+
+impl WifiInfo {
+    // the usual rough mapping, -100 dBm is 0% and -50 dBm or better is 100%
+    pub fn signal_percent(&self) -> Option<u8> {
+        self.signal_dbm.map(|dbm| ((dbm + 100) * 2).clamp(0, 100) as u8)
+    }
+
+    pub fn channel(&self) -> Option<u32> {
+        channel(self.freq_mhz?)
+    }
+}
+
+// IEEE channel numbers for the 2.4, 5 and 6 GHz bands
+pub fn channel(freq_mhz: u32) -> Option<u32> {
+    match freq_mhz {
+        2484 => Some(14),
+        2412..=2472 => Some((freq_mhz - 2407) / 5),
+        5955..=7115 => Some((freq_mhz - 5950) / 5),
+        5160..=5885 => Some((freq_mhz - 5000) / 5),
+        _ => None,
+    }
+}
+
+// wlan0, wlp3s0; interfaces listed in /proc/net/wireless count too
+pub fn looks_wireless(name: &str) -> bool {
+    name.starts_with("wlan") || name.starts_with("wlp")
+}
+
+// /proc/net/wireless: two header lines, then "wlan0: 0000   54.  -56.  -256 ..." where the
+// fourth column is the signal level in dBm (the trailing dot marks an updated value)
+pub fn parse_proc_wireless(text: &str) -> HashMap<String, i32> {
+    text.lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (name, rest) = line.split_once(':')?;
+            let level = rest.split_whitespace().nth(2)?.trim_end_matches('.');
+            Some((name.trim().to_string(), level.parse::<f64>().ok()?.round() as i32))
+        })
+        .collect()
+}
+
+// `iw dev <iface> link`; "Not connected." and anything unparseable leave fields empty
+pub fn parse_iw_link(text: &str) -> WifiInfo {
+    let mut info = WifiInfo::default();
+    for line in text.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        let number = || value.split_whitespace().next();
+        match key {
+            "SSID" => info.ssid = Some(value.to_string()).filter(|s| !s.is_empty()),
+            "freq" => info.freq_mhz = number().and_then(|v| v.parse::<f64>().ok()).map(|f| f.round() as u32),
+            "signal" => info.signal_dbm = number().and_then(|v| v.parse().ok()),
+            "tx bitrate" => info.bitrate_mbps = number().and_then(|v| v.parse().ok()),
+            _ => {}
+        }
+    }
+    info
+}
+
+#[cfg(all(feature = "wifi", target_os = "linux"))]
+mod probe {
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::process::Command;
+    use std::sync::{Mutex, OnceLock};
+    use std::time::{Duration, Instant};
+
+    use super::{looks_wireless, parse_iw_link, parse_proc_wireless, WifiInfo};
+
+    // SSID, frequency and bitrate rarely change and cost a process each, so `iw` runs at
+    // most this often per interface; the signal comes from /proc every sample
+    const IW_EVERY: Duration = Duration::from_secs(5);
+
+    static IW_CACHE: OnceLock<Mutex<HashMap<String, (Instant, WifiInfo)>>> = OnceLock::new();
+
+    fn iw_link(name: &str) -> WifiInfo {
+        let cache = IW_CACHE.get_or_init(Default::default);
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((at, info)) = cache.get(name)
+            && at.elapsed() < IW_EVERY
+        {
+            return info.clone();
+        }
+        let info = Command::new("iw")
+            .args(["dev", name, "link"])
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| parse_iw_link(&String::from_utf8_lossy(&out.stdout)))
+            .unwrap_or_default();
+        cache.insert(name.to_string(), (Instant::now(), info.clone()));
+        info
+    }
+
+    pub fn wireless(name: &str) -> Option<WifiInfo> {
+        let levels = std::fs::read_to_string("/proc/net/wireless").map(|t| parse_proc_wireless(&t)).unwrap_or_default();
+        let sysfs = Path::new("/sys/class/net").join(name).join("wireless").exists();
+        if !sysfs && !levels.contains_key(name) && !looks_wireless(name) {
+            return None;
+        }
+        let mut info = iw_link(name);
+        // fresher than the cached iw answer
+        if let Some(&dbm) = levels.get(name) {
+            info.signal_dbm = Some(dbm);
+        }
+        Some(info)
+    }
+}
+
+// None for wired interfaces, and everywhere without the `wifi` feature or off Linux
+#[cfg(all(feature = "wifi", target_os = "linux"))]
+pub fn wireless(name: &str) -> Option<WifiInfo> {
+    probe::wireless(name)
+}
+
+#[cfg(not(all(feature = "wifi", target_os = "linux")))]
+pub fn wireless(_name: &str) -> Option<WifiInfo> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proc_wireless() {
+        let text = "Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE\n \
+                    face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22\n \
+                    wlan0: 0000   54.  -56.  -256        0      0      0      0      0        0\n\
+                    wlp2s0: 0000   0   -71   -256        0      0      0      0      0        0\n";
+        let levels = parse_proc_wireless(text);
+        assert_eq!(levels.get("wlan0"), Some(&-56));
+        assert_eq!(levels.get("wlp2s0"), Some(&-71));
+        assert!(parse_proc_wireless("").is_empty());
+    }
+
+    #[test]
+    fn iw_link() {
+        let text = "Connected to aa:bb:cc:dd:ee:ff (on wlan0)\n\
+                    \tSSID: home net\n\
+                    \tfreq: 5180.0\n\
+                    \tRX: 123456 bytes (789 packets)\n\
+                    \tsignal: -61 dBm\n\
+                    \trx bitrate: 433.3 MBit/s VHT-MCS 9 80MHz short GI VHT-NSS 1\n\
+                    \ttx bitrate: 390.0 MBit/s VHT-MCS 8 80MHz VHT-NSS 1\n";
+        let info = parse_iw_link(text);
+        assert_eq!(
+            info,
+            WifiInfo { ssid: Some("home net".to_string()), signal_dbm: Some(-61), freq_mhz: Some(5180), bitrate_mbps: Some(390.0) }
+        );
+        assert_eq!(info.channel(), Some(36));
+        assert_eq!(info.signal_percent(), Some(78));
+
+        assert_eq!(parse_iw_link("Not connected.\n"), WifiInfo::default());
+    }
+
+    #[test]
+    fn channels() {
+        assert_eq!(channel(2412), Some(1));
+        assert_eq!(channel(2484), Some(14));
+        assert_eq!(channel(5745), Some(149));
+        assert_eq!(channel(5955), Some(1));
+        assert_eq!(channel(900), None);
+    }
+}
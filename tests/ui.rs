@@ -0,0 +1,1857 @@
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use nettui::adapters::{self, Adapters};
+use nettui::app::{Action, AppState, SortKey, View, CONTAINERS_EVERY, LOOKUPS_PER_SAMPLE};
+use nettui::capture::{self, Peer, Talker, Transport};
+use nettui::cli::Args;
+use nettui::conns::{Listing, Proto, Socket};
+use nettui::containers::Containers;
+use nettui::ping::{Method, Pinged, Target};
+use nettui::procfs::ProcNetDev;
+use nettui::procs::{self, Flow, Reading};
+use nettui::remote::{self, Hello, Remote, Server};
+use nettui::replay::{self, Replay};
+use nettui::resolve::Resolver;
+use nettui::route::Route;
+use nettui::datacap::{parse_cap, Budget};
+use nettui::event::AppEvent;
+use nettui::export;
+use nettui::host::Host;
+use nettui::columns::{Column, Columns};
+use nettui::iftype::IfaceKind;
+use nettui::json::Json;
+use nettui::influx::{self, Influx};
+use nettui::keymap::{parse_key, Keymap};
+use nettui::neigh::{NeighState, Neighbor};
+use nettui::net::{Counters, IfaceAddr, IfaceInfo, LinkState, NetworkSource, HAS_DROPS};
+use nettui::netns;
+use nettui::snapshot::unix_now;
+use nettui::socket::{self, QuerySocket};
+use nettui::statsd::{self, Statsd};
+use nettui::sysfs::SysFs;
+use nettui::theme::{Theme, ThemeName};
+use nettui::wg::{Peer as WgPeer, Peers};
+use nettui::ui::{draw, plain_table};
+use nettui::wifi::WifiInfo;
+use nettui::vlan;
+use ratatui::backend::TestBackend;
+use ratatui::style::{Color, Style};
+use ratatui::Terminal;
+
+// two interfaces with fixed traffic between reads
+struct Fixed {
+    reads: u64,
+}
+
+impl NetworkSource for Fixed {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.reads += 1;
+        vec![
+            Counters { name: "eth0".to_string(), rx_bytes: 2048 * self.reads, tx_bytes: 1024 * self.reads, ..Default::default() },
+            Counters { name: "wlan0".to_string(), rx_bytes: 512 * self.reads, ..Default::default() },
+        ]
+    }
+
+    fn info(&self, name: &str) -> IfaceInfo {
+        match name {
+            "eth0" => IfaceInfo {
+                addrs: vec![
+                    IfaceAddr { ip: "2001:db8:85a3::8a2e:370:7334".parse().unwrap(), prefix: 64 },
+                    IfaceAddr { ip: "192.168.1.10".parse().unwrap(), prefix: 24 },
+                ],
+                mac: [0x52, 0x54, 0x00, 0x12, 0x34, 0x56],
+                mtu: 9000,
+                state: LinkState::Up,
+                speed_mbps: Some(1),
+                kind: IfaceKind::Ethernet,
+                wifi: None,
+                members: Vec::new(),
+                vlan: None,
+                netns: None,
+            },
+            "wlan0" => IfaceInfo {
+                state: LinkState::Down,
+                kind: IfaceKind::Wifi,
+                wifi: Some(WifiInfo { ssid: Some("home".to_string()), signal_dbm: Some(-67), ..Default::default() }),
+                ..Default::default()
+            },
+            // a link that went down keeps its row
+            _ => IfaceInfo { state: LinkState::Down, ..Default::default() },
+        }
+    }
+}
+
+// n interfaces, tap000 the busiest
+struct Many {
+    n: u64,
+    reads: u64,
+}
+
+impl NetworkSource for Many {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.reads += 1;
+        (0..self.n)
+            .map(|i| Counters { name: format!("tap{i:03}"), rx_bytes: (self.n - i) * 100 * self.reads, ..Default::default() })
+            .collect()
+    }
+}
+
+fn sampled_app() -> AppState {
+    let mut app = AppState::new();
+    // sysfs differs between machines, keep every interface
+    app.filter.show_virtual = true;
+    app.set_theme(ThemeName::Monochrome);
+    let mut source = Fixed { reads: 0 };
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    app
+}
+
+fn render(app: &AppState) -> String {
+    render_sized(app, 240, 30)
+}
+
+fn render_sized(app: &AppState, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| draw(f, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let mut out = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            out.push_str(buffer[(x, y)].symbol());
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[test]
+fn table_lists_interfaces_busiest_first() {
+    let screen = render(&sampled_app());
+    let eth0 = screen.find("eth0").expect("eth0 row");
+    let wlan0 = screen.find("wlan0").expect("wlan0 row");
+    assert!(eth0 < wlan0, "sorted by total rate");
+    assert!(screen.contains("2.0 KiB/s"));
+    assert!(screen.contains("TOTAL"));
+    assert!(screen.contains("ifaces: 2"));
+    assert!(screen.contains("192.168.1.10 "));
+    assert!(screen.contains("--"));
+    assert!(screen.contains("eth0      up   ether"));
+    assert!(screen.contains("wlan0     down"), "down links stay listed");
+    assert!(screen.contains("1.6%"), "2 KiB/s on a 1 Mb/s link");
+
+    let mut app = sampled_app();
+    app.selected = Some("wlan0".to_string());
+    app.apply(Action::TogglePin);
+    let screen = render(&app);
+    assert!(screen.find("★ wlan0").unwrap() < screen.find("eth0").unwrap(), "pinned above busier rows");
+}
+
+#[test]
+fn hidden_interfaces_overlay() {
+    let mut app = sampled_app();
+    app.selected = Some("wlan0".to_string());
+    app.apply(Action::Hide);
+    let screen = render(&app);
+    assert!(!screen.contains("wlan0"));
+    assert!(screen.contains("1 hidden"));
+
+    app.apply(Action::HiddenList);
+    let screen = render(&app);
+    assert!(screen.contains("Hidden"));
+    assert!(screen.contains(" wlan0"));
+}
+
+#[test]
+fn column_chooser() {
+    let mut app = sampled_app();
+    let path = std::env::temp_dir().join(format!("nettui-columns-{}.toml", std::process::id()));
+    std::fs::write(&path, "bits = true\n").unwrap();
+    app.config_path = Some(path.clone());
+    let press = |app: &mut AppState, code| app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    press(&mut app, KeyCode::Char('C'));
+    let screen = render_sized(&app, 200, 30);
+    assert!(screen.contains("[x] address"), "{screen}");
+    assert!(screen.contains("[ ] peak RX"));
+
+    // address off, peak RX on
+    let find = |app: &AppState, column| app.columns.entries().iter().position(|&(c, _)| c == column).unwrap();
+    while app.columns_cursor < find(&app, Column::Addr) {
+        press(&mut app, KeyCode::Down);
+    }
+    press(&mut app, KeyCode::Char(' '));
+    while app.columns_cursor < find(&app, Column::PeakRx) {
+        press(&mut app, KeyCode::Down);
+    }
+    press(&mut app, KeyCode::Char(' '));
+    press(&mut app, KeyCode::Esc);
+    assert!(!app.show_columns);
+    let screen = render_sized(&app, 260, 30);
+    assert!(!screen.contains("ADDRESS"), "{screen}");
+    assert!(screen.contains("Peak RX") && !screen.contains("Peak TX"));
+    assert!(!screen.contains("cols"), "left out on purpose, not squeezed\n{screen}");
+
+    let saved = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert!(saved.starts_with("bits = true\ncolumns = [\"iface\", \"link\", \"type\", \"rx\", \"tx\""), "{saved}");
+    assert!(saved.contains("\"peak_rx\", \"trend\"]"), "{saved}");
+
+    // still a table with more on than there's room for
+    app.apply(Action::ToggleAverages);
+    app.apply(Action::ToggleMulticast);
+    let screen = render_sized(&app, 60, 20);
+    assert!(screen.contains("RX/s"), "{screen}");
+    assert!(screen.contains("cols"));
+}
+
+#[test]
+fn columns_flag() {
+    let args = Args::try_parse_from(["nettui", "--columns", "tx,rx,stats", "--multicast"]).unwrap();
+    let app = AppState::from_args(args);
+    let expected: Vec<Column> = [Column::Iface, Column::Tx, Column::Rx, Column::McastIn, Column::PeakRx, Column::PeakTx, Column::AvgRx, Column::AvgTx]
+        .into_iter()
+        .filter(|c| c.available())
+        .collect();
+    assert_eq!(app.columns.shown(), expected);
+    assert!(Args::try_parse_from(["nettui", "--columns", "rx,speed"]).is_err());
+}
+
+#[test]
+fn reordering_columns() {
+    let mut app = sampled_app();
+    let path = std::env::temp_dir().join(format!("nettui-order-{}.toml", std::process::id()));
+    app.config_path = Some(path.clone());
+    let press = |app: &mut AppState, code| app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    press(&mut app, KeyCode::Char('>'));
+    assert!(app.message.as_ref().is_some_and(|(m, _)| m.contains("picks a column")));
+
+    // the header cursor onto LINK, which then moves right past TYPE
+    press(&mut app, KeyCode::Right);
+    press(&mut app, KeyCode::Right);
+    assert_eq!(app.header_cursor, Some(Column::Link));
+    press(&mut app, KeyCode::Char('>'));
+    let screen = render_sized(&app, 260, 30);
+    assert!(screen.find("TYPE").unwrap() < screen.find("LINK").unwrap(), "{screen}");
+
+    // and TX ahead of RX from the chooser
+    press(&mut app, KeyCode::Char('C'));
+    let tx = app.columns.entries().iter().position(|&(c, _)| c == Column::Tx).unwrap();
+    while app.columns_cursor < tx {
+        press(&mut app, KeyCode::Down);
+    }
+    press(&mut app, KeyCode::Char('<'));
+    assert_eq!(app.columns.entries()[app.columns_cursor].0, Column::Tx, "the cursor follows the column");
+    press(&mut app, KeyCode::Esc);
+    let screen = render_sized(&app, 260, 30);
+    assert!(screen.find("TX/s").unwrap() < screen.find("RX/s").unwrap(), "{screen}");
+
+    let saved = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert!(saved.starts_with("columns = [\"iface\", \"type\", \"link\", \"addr\", \"tx\", \"rx\""), "{saved}");
+}
+
+#[test]
+fn search_prompt() {
+    let mut app = sampled_app();
+    app.apply(Action::Search);
+    for c in "wl".chars() {
+        app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+    }
+    let screen = render(&app);
+    assert!(screen.contains("search: wl"));
+    let lines: Vec<&str> = screen.lines().collect();
+    assert!(lines[lines.len() - 2].starts_with("/wl"), "prompt above the status bar");
+    assert!(lines[lines.len() - 1].contains("enter keep  esc clear"));
+    assert!(!screen.contains("eth0"));
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('x')));
+    let screen = render(&app);
+    assert!(screen.contains("no interface name contains \"wlx\""));
+}
+
+#[test]
+fn header_names_the_host() {
+    let mut app = sampled_app();
+    app.host = Host { name: "build-server-07.eu-west.example.internal".to_string(), os: "Debian GNU/Linux 12 (bookworm)".to_string(), kernel: "6.1.0-18-amd64".to_string() };
+    let top = |app: &AppState, width| render_sized(app, width, 30).lines().next().unwrap().to_string();
+    let wide = top(&app, 240);
+    assert!(wide.contains(" nettui on build-server-07.eu-west… — Debian GNU/Linux 12 (bookworm), kernel 6.1.0-18-amd64 "), "{wide}");
+    assert!(wide.contains(" up 0s  ") && wide.contains(" UTC "), "{wide}");
+
+    // the kernel and then the OS give way before the hostname is cut any further
+    let narrow = top(&app, 100);
+    assert!(narrow.contains(" nettui on build-server-07.eu-west… — Debian GNU/Linux 12 (bookworm) "), "{narrow}");
+    let narrower = top(&app, 80);
+    assert!(narrower.contains(" nettui on build-server-07.eu-west… ") && narrower.contains(" UTC "), "{narrower}");
+    let tiny = top(&app, 40);
+    assert!(tiny.contains(" nettui on bu") && tiny.contains(" UTC "), "{tiny}");
+}
+
+#[test]
+fn status_bar_hints_follow_the_view() {
+    let mut app = sampled_app();
+    let status = |app: &AppState, width| render_sized(app, width, 30).lines().last().unwrap().to_string();
+    let wide = status(&app, 240);
+    assert!(wide.starts_with(" q quit  ? help  / search"));
+    assert!(wide.contains("  every 500 ms  sampled "));
+    assert!(!wide.contains('…'));
+
+    let narrow = status(&app, 80);
+    assert!(narrow.contains(" …"), "cut short, not wrapped: {narrow}");
+    assert!(narrow.contains("every 500 ms"));
+
+    app.apply(Action::Down);
+    app.apply(Action::Open);
+    assert!(status(&app, 240).starts_with(" esc back  ↑ up"));
+
+    // a copy's message takes the hints' place for a few seconds
+    app.message = Some(("copied eth0".to_string(), Instant::now()));
+    assert!(status(&app, 240).starts_with(" copied eth0 "));
+    app.message = Some(("copied eth0".to_string(), Instant::now() - Duration::from_secs(10)));
+    assert!(status(&app, 240).starts_with(" esc back"));
+}
+
+#[test]
+fn remapped_keys_show_up_in_hints_and_help() {
+    let mut app = sampled_app();
+    app.keymap = Keymap::new(&[
+        (Action::Quit, vec![parse_key("ctrl+q").unwrap()]),
+        (Action::CycleSort, vec![parse_key("o").unwrap()]),
+    ])
+    .unwrap();
+    app.refresh_text();
+    let status = render_sized(&app, 240, 30).lines().last().unwrap().to_string();
+    assert!(status.starts_with(" ctrl+q quit  ? help"), "{status}");
+    assert!(status.contains(" o sort"), "{status}");
+
+    assert!(app.handle_key(KeyEvent::from(KeyCode::Char('q'))), "q is no longer bound");
+    app.apply(Action::Help);
+    let help = render_sized(&app, 120, 50);
+    assert!(help.contains("ctrl+q"));
+    assert!(help.contains(" o          "), "{help}");
+}
+
+#[test]
+fn wifi_section() {
+    let mut app = sampled_app();
+    app.selected = Some("wlan0".to_string());
+    app.apply(Action::Open);
+    let screen = render(&app);
+    assert!(screen.contains("SSID         home"));
+    assert!(screen.contains("Signal       -67 dBm (66%)"));
+    assert!(screen.contains("Frequency    n/a") && screen.contains("Bitrate      n/a"));
+}
+
+#[test]
+fn grouped_table() {
+    let mut app = sampled_app();
+    app.apply(Action::ToggleGroups);
+    let screen = render(&app);
+    assert!(screen.find("▾ Physical (1)").unwrap() < screen.find("▾ Wi-Fi (1)").unwrap());
+
+    // fold the Wi-Fi section: header, eth0, header
+    for _ in 0..3 {
+        app.apply(Action::Down);
+    }
+    app.apply(Action::Open);
+    let screen = render(&app);
+    let header = screen.lines().find(|l| l.contains("▸ Wi-Fi (1)")).expect("folded header");
+    assert!(header.contains("512 B/s"), "sums its members: {header}");
+    assert!(!screen.contains("wlan0"));
+}
+
+// fg of the first cell showing `needle`
+fn style_of(app: &AppState, needle: &str) -> Option<Style> {
+    let mut terminal = Terminal::new(TestBackend::new(240, 30)).unwrap();
+    terminal.draw(|f| draw(f, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let screen = render(app);
+    let (y, line) = screen.lines().enumerate().find(|(_, l)| l.contains(needle))?;
+    let x = line[..line.find(needle)?].chars().count() as u16;
+    Some(buffer[(x, y as u16)].style())
+}
+
+fn color_of(app: &AppState, needle: &str) -> Option<Color> {
+    style_of(app, needle)?.fg
+}
+
+#[test]
+fn alerts_highlight_their_cells_and_ring_once() {
+    let args = Args::try_parse_from(["nettui", "--alert", "eth0:rx:>:1k", "--alert", "*:tx:>:10M", "--alert-clear", "1"]).unwrap();
+    let mut app = AppState::from_args(args);
+    app.filter.show_virtual = true;
+    let mut source = Fixed { reads: 0 };
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert!(std::mem::take(&mut app.bell));
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert!(!app.bell, "still the same alert");
+    assert_eq!(app.alerts.log.len(), 1);
+
+    let alert = app.theme.alert;
+    assert_eq!(style_of(&app, "2.0 KiB/s").unwrap().add_modifier, alert.add_modifier);
+    assert_eq!(style_of(&app, "eth0").unwrap().add_modifier, alert.add_modifier, "and the name");
+    assert_ne!(style_of(&app, "1.0 KiB/s").unwrap().add_modifier, alert.add_modifier, "TX is fine");
+    assert_ne!(style_of(&app, "wlan0").unwrap().add_modifier, alert.add_modifier);
+}
+
+#[test]
+fn alert_pane_scrolls_and_clears() {
+    let args = Args::try_parse_from(["nettui", "--alert", "eth0:rx:>:1k"]).unwrap();
+    let mut app = AppState::from_args(args);
+    app.filter.show_virtual = true;
+    let mut source = Fixed { reads: 0 };
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert!(render(&app).contains("alerts: 1 new"));
+
+    let press = |app: &mut AppState, c| app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    press(&mut app, 'a');
+    let screen = render_sized(&app, 120, 30);
+    assert!(screen.contains("Alerts (1)"));
+    assert!(screen.contains(" eth0 rx 2.0 KiB/s exceeded 1000 B/s"), "{screen}");
+    assert!(!screen.contains("alerts: 1 new"), "read now");
+    assert!(screen.contains("clear all"));
+
+    // a later event goes on top, scrolling down skips past it
+    let mut cleared = app.alerts.log[0].clone();
+    cleared.transition = nettui::alert::Transition::Cleared;
+    app.alerts.log.push(cleared);
+    let screen = render_sized(&app, 120, 30);
+    assert!(screen.find("rx cleared at").unwrap() < screen.find("exceeded").unwrap());
+    press(&mut app, 'j');
+    assert!(!render_sized(&app, 120, 30).contains("rx cleared at"));
+
+    press(&mut app, 'c');
+    assert!(render(&app).contains("nothing has fired yet"));
+    press(&mut app, 'a');
+    assert!(!app.show_alerts);
+    assert_eq!(app.unread_alerts(), 0);
+}
+
+#[test]
+fn data_cap_gauge_in_the_header_and_detail() {
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    app.budget = Some(Budget::load(parse_cap("eth0:4KiB/30d").unwrap(), None, unix_now()));
+    let mut source = Fixed { reads: 0 };
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    let screen = render(&app);
+    assert!(screen.contains("cap eth0 ["), "{screen}");
+    assert!(screen.contains("] 75% of 4 KiB, resets "));
+
+    app.selected = Some("eth0".to_string());
+    app.apply(Action::Open);
+    let screen = render_sized(&app, 120, 40);
+    assert!(screen.contains("Data cap     3 KiB of 4 KiB (75%)"), "{screen}");
+    assert!(screen.contains("30-day budget"));
+}
+
+// eth0 has rx errors on the third read and is gone on the fourth
+struct Flaky {
+    reads: u64,
+}
+
+impl NetworkSource for Flaky {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.reads += 1;
+        let wlan0 = Counters { name: "wlan0".to_string(), rx_bytes: 512 * self.reads, ..Default::default() };
+        if self.reads == 4 {
+            return vec![wlan0];
+        }
+        let rx_errors = if self.reads >= 3 { 4 } else { 0 };
+        vec![Counters { name: "eth0".to_string(), rx_bytes: 2048 * self.reads, rx_errors, ..Default::default() }, wlan0]
+    }
+}
+
+#[test]
+fn rows_flash_after_errors() {
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    app.set_theme(ThemeName::Monochrome);
+    let mut source = Flaky { reads: 0 };
+    let start = Instant::now();
+    app.prime(&mut source);
+    app.sample(&mut source, start + Duration::from_secs(1), Duration::from_secs(1));
+    assert!(!app.error_flash("eth0"));
+    let flash = app.theme.error_flash.add_modifier;
+    assert!(!style_of(&app, "wlan0").unwrap().add_modifier.contains(flash));
+
+    // eth0 isn't selected, so only the flash is on its row
+    app.selected = Some("wlan0".to_string());
+    app.sample(&mut source, start + Duration::from_secs(2), Duration::from_secs(1));
+    assert!(app.error_flash("eth0"));
+    assert!(style_of(&app, "eth0").unwrap().add_modifier.contains(flash));
+    assert!(!style_of(&app, "wlan0").unwrap().add_modifier.contains(flash));
+
+    // gone for a read and back: the count and the time of the error are still there
+    app.sample(&mut source, start + Duration::from_secs(3), Duration::from_secs(1));
+    app.sample(&mut source, start + Duration::from_secs(4), Duration::from_secs(1));
+    assert_eq!(app.totals["eth0"].errors_in, 4);
+    assert!(app.error_flash("eth0"));
+    app.sample(&mut source, start + Duration::from_secs(8), Duration::from_secs(4));
+    assert!(!app.error_flash("eth0"), "faded");
+    assert!(!style_of(&app, "eth0").unwrap().add_modifier.contains(flash));
+
+    app.selected = Some("eth0".to_string());
+    app.apply(Action::Open);
+    let screen = render_sized(&app, 120, 40);
+    assert!(screen.contains("Errors       in 4 "), "{screen}");
+    assert!(screen.contains("Last error   rx "));
+    assert!(screen.contains(" ago   tx never"));
+}
+
+// interfaces come, go and change speed between samples as the test sets `rates`, bytes per read
+struct Churn {
+    rates: Vec<(String, u64)>,
+    totals: HashMap<String, u64>,
+}
+
+impl Churn {
+    fn new(rates: &[(&str, u64)]) -> Self {
+        let mut churn = Churn { rates: Vec::new(), totals: HashMap::new() };
+        churn.set(rates);
+        churn
+    }
+
+    fn set(&mut self, rates: &[(&str, u64)]) {
+        self.rates = rates.iter().map(|&(name, rate)| (name.to_string(), rate)).collect();
+    }
+}
+
+impl NetworkSource for Churn {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.rates
+            .iter()
+            .map(|(name, rate)| {
+                let total = self.totals.entry(name.clone()).or_default();
+                *total += rate;
+                Counters { name: name.clone(), rx_bytes: *total, ..Default::default() }
+            })
+            .collect()
+    }
+}
+
+fn churned(source: &mut Churn) -> AppState {
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    app.set_theme(ThemeName::Monochrome);
+    app.prime(source);
+    app.sample(source, Instant::now(), Duration::from_secs(1));
+    app
+}
+
+fn order(app: &AppState) -> Vec<&str> {
+    app.shown().iter().map(|r| r.interface.as_str()).collect()
+}
+
+#[test]
+fn selection_sticks_to_the_interface() {
+    let mut source = Churn::new(&[("eth0", 3000), ("eth1", 2000), ("eth2", 1000)]);
+    let mut app = churned(&mut source);
+    app.selected = Some("eth2".to_string());
+    assert_eq!(app.selected_index(), Some(2));
+
+    // resorted to the top, still the one selected
+    source.set(&[("eth0", 3000), ("eth1", 2000), ("eth2", 9000)]);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(order(&app), ["eth2", "eth0", "eth1"]);
+    assert_eq!(app.selected_name(), Some("eth2"));
+    assert_eq!(app.selected_index(), Some(0));
+    assert!(app.message().is_none());
+
+    // gone: the row that's now at its height takes the cursor, with a word about it
+    source.set(&[("eth0", 3000), ("eth1", 2000)]);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(app.selected_name(), Some("eth0"));
+    assert_eq!(app.message(), Some("eth2 went away, selected eth0"));
+    let screen = render(&app);
+    assert!(screen.contains("eth2 went away, selected eth0"), "{screen}");
+
+    // the last row going away leaves the cursor on the new last row
+    app.selected = Some("eth1".to_string());
+    source.set(&[("eth0", 3000)]);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(app.selected_name(), Some("eth0"));
+
+    // and nothing left leaves nothing selected
+    source.set(&[]);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(app.selected_name(), None);
+    assert_eq!(app.message(), Some("eth0 went away"));
+}
+
+#[test]
+fn resorting_keeps_the_selection_on_screen() {
+    let names: Vec<String> = (0..20).map(|i| format!("tap{i:02}")).collect();
+    let rates = |busiest: usize| -> Vec<(&str, u64)> {
+        names.iter().enumerate().map(|(i, n)| (n.as_str(), if i == busiest { 100_000 } else { 5000 - 100 * i as u64 })).collect()
+    };
+    let mut source = Churn::new(&rates(0));
+    let mut app = churned(&mut source);
+    app.selected = Some("tap00".to_string());
+    assert!(render_sized(&app, 120, 14).contains("tap00"));
+
+    // tap00 drops to the bottom, far below the first screenful
+    source.set(&rates(19).into_iter().map(|(n, r)| (n, if n == "tap00" { 0 } else { r })).collect::<Vec<_>>());
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(order(&app).last(), Some(&"tap00"));
+    assert_eq!(app.selected_name(), Some("tap00"));
+    let screen = render_sized(&app, 120, 14);
+    assert!(screen.contains("tap00"), "scrolled along\n{screen}");
+    assert!(!screen.contains("tap19"), "the top scrolled off\n{screen}");
+}
+
+#[test]
+fn frozen_order_keeps_rows_in_place() {
+    let mut source = Churn::new(&[("eth0", 3000), ("eth1", 2000), ("eth2", 1000)]);
+    let mut app = churned(&mut source);
+    app.selected = Some("eth1".to_string());
+    app.handle_key(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE));
+    assert!(app.freeze_order);
+    assert!(render(&app).contains("order frozen ('f')"));
+
+    // the numbers move, the rows don't; a newcomer goes last
+    source.set(&[("eth0", 1000), ("eth1", 2000), ("eth2", 8192), ("eth3", 9000)]);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(order(&app), ["eth0", "eth1", "eth2", "eth3"]);
+    assert!(render(&app).contains("8.0 KiB/s"), "values still update");
+    assert_eq!(app.selected_index(), Some(1));
+
+    // an interface leaving doesn't shuffle the others either
+    source.set(&[("eth0", 1000), ("eth2", 8192), ("eth3", 9000)]);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(order(&app), ["eth0", "eth2", "eth3"]);
+    assert_eq!(app.selected_name(), Some("eth2"), "the nearest row");
+
+    // picking a sort still sorts, and thawing goes back to sorting every sample
+    app.apply(Action::ReverseSort);
+    assert_eq!(order(&app), ["eth0", "eth2", "eth3"]);
+    app.apply(Action::ReverseSort);
+    assert_eq!(order(&app), ["eth3", "eth2", "eth0"]);
+    app.apply(Action::FreezeOrder);
+    source.set(&[("eth0", 9999), ("eth2", 8192), ("eth3", 9000)]);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(order(&app), ["eth0", "eth3", "eth2"]);
+    assert_eq!(app.selected_name(), Some("eth2"));
+}
+
+#[test]
+fn rolling_average_columns() {
+    let args = Args::try_parse_from(["nettui", "--averages", "--stats"]).unwrap();
+    let mut app = AppState::from_args(args);
+    app.filter.show_virtual = true;
+    assert!(app.history.capacity() >= 15 * 60 * 2, "15 minutes at 500ms");
+    let mut source = Fixed { reads: 0 };
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    let screen = render_sized(&app, 320, 30);
+    let header = screen.lines().find(|l| l.contains("INTERFACE")).unwrap();
+    assert!(header.find("Avg TX").unwrap() < header.find("Avg 1m").unwrap());
+    assert!(header.find("Avg 15m").unwrap() < header.find("TREND").unwrap());
+    // a single sample is less than any window
+    assert!(screen.contains("~3.0 KiB/s"), "{screen}");
+
+    app.apply(Action::ToggleAverages);
+    assert!(!render_sized(&app, 320, 30).contains("Avg 1m"));
+}
+
+fn socket(proto: Proto, local: &str, remote: &str, state: u8, process: Option<(u32, &str)>) -> Socket {
+    Socket {
+        proto,
+        local: local.parse().unwrap(),
+        remote: remote.parse().unwrap(),
+        state,
+        inode: 0,
+        process: process.map(|(pid, name)| (pid, name.to_string())),
+    }
+}
+
+#[test]
+fn connections_tab() {
+    let mut app = sampled_app();
+    app.selected = Some("eth0".to_string());
+    let press = |app: &mut AppState, code| app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.view, View::Connections);
+    assert!(render(&app).contains("reading sockets..."));
+
+    let listing = Listing {
+        sockets: vec![
+            socket(Proto::Tcp, "192.168.1.10:54000", "10.17.0.34:443", 1, Some((40, "curl"))),
+            socket(Proto::Tcp, "0.0.0.0:22", "0.0.0.0:0", 10, Some((812, "sshd"))),
+            socket(Proto::Udp, "127.0.0.53:53", "0.0.0.0:0", 7, None),
+        ],
+        unreadable: 3,
+        error: None,
+    };
+    app.set_connections(listing, Instant::now());
+    let screen = render(&app);
+    assert!(screen.contains("Connections (2) on eth0"), "{screen}");
+    assert!(screen.contains("tcp    192.168.1.10:54000  10.17.0.34:443  ESTAB    curl[40]"), "{screen}");
+    assert!(screen.contains("0.0.0.0:22"), "listening everywhere");
+    assert!(!screen.contains("127.0.0.53"));
+    assert!(screen.contains("owners in 3 other processes need root"));
+    assert!(screen.contains("STATE ▲"));
+
+    // every socket, by process with the unknown owner last, then the other way round
+    press(&mut app, KeyCode::Char('F'));
+    press(&mut app, KeyCode::Char('s'));
+    press(&mut app, KeyCode::Char('s'));
+    let screen = render(&app);
+    assert!(screen.contains("Connections (3)") && screen.contains("PROCESS ▲"), "{screen}");
+    assert!(screen.find("curl[40]").unwrap() < screen.find("sshd[812]").unwrap());
+    assert!(screen.find("sshd[812]").unwrap() < screen.find("127.0.0.53").unwrap());
+    press(&mut app, KeyCode::Char('S'));
+    assert!(render(&app).find("127.0.0.53").unwrap() < render(&app).find("curl[40]").unwrap());
+
+    // scrolls within what fits, esc goes back to the interfaces
+    let screen = render_sized(&app, 120, 9);
+    assert!(screen.contains("showing 1–2 of 3"), "{screen}");
+    press(&mut app, KeyCode::Char('G'));
+    assert!(render_sized(&app, 120, 9).contains("showing 2–3 of 3"));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.view, View::Table);
+}
+
+#[test]
+fn reverse_dns_names() {
+    let mut app = sampled_app();
+    app.selected = Some("eth0".to_string());
+    let (tx, _rx) = std::sync::mpsc::channel();
+    app.resolver = Some(Resolver::start(tx));
+    app.view = View::Connections;
+    let remote = |i: usize| format!("10.17.{}.{}:443", i / 200, i % 200 + 1);
+    let sockets = (0..40).map(|i| socket(Proto::Tcp, "192.168.1.10:54000", &remote(i), 1, None)).collect();
+    app.set_connections(Listing { sockets, unreadable: 0, error: None }, Instant::now());
+
+    // nothing is looked up until 'n', then only so many per sample
+    let ip = |i: usize| remote(i).parse::<std::net::SocketAddr>().unwrap().ip();
+    let now = Instant::now();
+    assert!(app.names.wants(ip(0), now));
+    app.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+    assert!(app.resolve);
+    let asked = (0..40).filter(|&i| !app.names.wants(ip(i), now)).count();
+    assert_eq!(asked, LOOKUPS_PER_SAMPLE);
+
+    app.set_name(ip(0), Some("edge.example.net".to_string()), Duration::from_secs(300), now);
+    app.set_name(ip(1), None, Duration::from_secs(300), now);
+    let screen = render(&app);
+    assert!(screen.contains("edge.example.net:443"), "{screen}");
+    assert!(screen.contains(&remote(1)), "no name, the number stays");
+    assert!(screen.contains("names"), "the hint");
+
+    app.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+    let screen = render(&app);
+    assert!(!screen.contains("edge.example.net") && screen.contains(&remote(0)));
+}
+
+// Fixed with a routing table: wlan0 has the default route, eth0 only its own network
+struct Routed(Fixed);
+
+impl NetworkSource for Routed {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.0.snapshot()
+    }
+
+    fn info(&self, name: &str) -> IfaceInfo {
+        self.0.info(name)
+    }
+
+    fn routes(&self) -> Vec<Route> {
+        let route = |dest: &str, prefix, gateway: Option<&str>, interface: &str, metric| Route {
+            destination: dest.parse().unwrap(),
+            prefix,
+            gateway: gateway.map(|g| g.parse().unwrap()),
+            interface: interface.to_string(),
+            metric,
+        };
+        vec![
+            route("0.0.0.0", 0, Some("192.168.0.1"), "wlan0", 600),
+            route("0.0.0.0", 0, Some("192.168.1.1"), "eth0", 900),
+            route("192.168.1.0", 24, None, "eth0", 100),
+        ]
+    }
+}
+
+#[test]
+fn default_route_is_marked() {
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    let mut source = Routed(Fixed { reads: 0 });
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    let screen = render(&app);
+    assert!(screen.contains("▸ wlan0"), "{screen}");
+    assert!(!screen.contains("▸ eth0"));
+
+    app.selected = Some("eth0".to_string());
+    app.apply(Action::Open);
+    let screen = render(&app);
+    assert!(screen.contains("default via 192.168.1.1 metric 900"), "{screen}");
+    assert!(screen.contains("192.168.1.0/24 metric 100"));
+}
+
+// Fixed with neighbor tables: eth0 sees two hosts and one that never answered
+struct Neighbored(Fixed);
+
+impl NetworkSource for Neighbored {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.0.snapshot()
+    }
+
+    fn neighbors(&self) -> Vec<Neighbor> {
+        let neighbor = |ip: &str, mac: Option<&str>, interface: &str, state| Neighbor {
+            ip: ip.parse().unwrap(),
+            mac: mac.map(str::to_string),
+            interface: interface.to_string(),
+            state,
+        };
+        vec![
+            neighbor("192.168.1.1", Some("aa:bb:cc:dd:ee:01"), "eth0", NeighState::Reachable),
+            neighbor("192.168.1.9", Some("aa:bb:cc:dd:ee:09"), "eth0", NeighState::Stale),
+            neighbor("192.168.1.77", None, "eth0", NeighState::Failed),
+            neighbor("192.168.0.1", None, "wlan0", NeighState::Incomplete),
+        ]
+    }
+}
+
+#[test]
+fn neighbor_counts() {
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    let mut source = Neighbored(Fixed { reads: 0 });
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    let screen = render_sized(&app, 175, 20);
+    let (x, _) = position(&screen, "INTERFACE", "Nbrs");
+    let count = |name: &str| {
+        let line = screen.lines().find(|l| l.contains(name)).unwrap();
+        line.chars().skip(x as usize).take(5).collect::<String>().trim().to_string()
+    };
+    // the failed entry isn't a neighbor, the incomplete one is
+    assert_eq!(count("eth0"), "2", "{screen}");
+    assert_eq!(count("wlan0"), "1");
+
+    app.selected = Some("eth0".to_string());
+    app.apply(Action::Open);
+    let screen = render_sized(&app, 120, 50);
+    assert!(screen.contains("Neighbors    2   failed 1"), "{screen}");
+    assert!(screen.contains("192.168.1.9     stale      aa:bb:cc:dd:ee:09"));
+    assert!(screen.contains("192.168.1.77    failed     --"));
+
+    // the table was read once, with the first sample
+    let mut empty = Fixed { reads: 0 };
+    app.sample(&mut empty, Instant::now(), Duration::from_secs(1));
+    assert_eq!(app.neighbors.len(), 4);
+}
+
+// one WireGuard interface with a peer sending 1 KiB a read; `denied` stands in for not
+// running as root
+struct Tunnel {
+    reads: u64,
+    denied: bool,
+}
+
+impl NetworkSource for Tunnel {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.reads += 1;
+        vec![Counters { name: "wg0".to_string(), rx_bytes: 1024 * self.reads, ..Default::default() }]
+    }
+
+    fn info(&self, _name: &str) -> IfaceInfo {
+        IfaceInfo { state: LinkState::Up, kind: IfaceKind::WireGuard, ..Default::default() }
+    }
+
+    fn wg_peers(&self, _name: &str) -> Option<Peers> {
+        if self.denied {
+            return Some(Peers::Denied);
+        }
+        Some(Peers::Read(vec![WgPeer {
+            public_key: "cGVlci1vbmUtcHVibGljLWtleQ==".to_string(),
+            endpoint: Some("203.0.113.5:51820".to_string()),
+            allowed_ips: vec!["10.0.0.2/32".to_string()],
+            latest_handshake: Some(unix_now() as u64 - 65),
+            rx_bytes: 1024 * self.reads,
+            tx_bytes: 0,
+        }]))
+    }
+}
+
+#[test]
+fn wireguard_peers_in_the_detail_view() {
+    let mut app = AppState::new();
+    let mut source = Tunnel { reads: 0, denied: false };
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert!(app.wireguard.is_none(), "only read while the detail view is open");
+
+    app.selected = Some("wg0".to_string());
+    app.apply(Action::Open);
+    for _ in 0..2 {
+        app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    }
+    let screen = render_sized(&app, 120, 50);
+    assert!(screen.contains("Peers        1"), "{screen}");
+    assert!(screen.contains("cGVlci1vbm…   handshake 1m 0"), "65s, give or take a second");
+    assert!(screen.contains("endpoint 203.0.113.5:51820"));
+    assert!(screen.contains("allowed  10.0.0.2/32"));
+    assert!(screen.contains("rate     rx 1.0 KiB/s"));
+
+    source.denied = true;
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert!(render_sized(&app, 120, 50).contains("Peers        need root (wg show was denied)"));
+
+    app.apply(Action::Open);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert!(app.wireguard.is_none());
+}
+
+// a container host: eth0 plus a veth into nginx-1; counts how often it's asked
+struct Docker {
+    asked: std::cell::Cell<u32>,
+}
+
+impl NetworkSource for Docker {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        ["eth0", "veth1a2b3c"].map(|name| Counters { name: name.to_string(), ..Default::default() }).to_vec()
+    }
+
+    fn containers(&self) -> Containers {
+        self.asked.set(self.asked.get() + 1);
+        Containers::from([("veth1a2b3c".to_string(), "nginx-1".to_string())])
+    }
+}
+
+#[test]
+fn veths_named_by_container() {
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    let mut source = Docker { asked: Default::default() };
+    let start = Instant::now();
+    app.prime(&mut source);
+    app.sample(&mut source, start, Duration::from_secs(1));
+    assert!(!render(&app).contains("nginx-1"), "only with --containers");
+    assert_eq!(source.asked.get(), 0);
+
+    app.containers = true;
+    app.sample(&mut source, start + Duration::from_secs(1), Duration::from_secs(1));
+    let screen = render(&app);
+    assert!(screen.contains("veth1a2b3c (nginx-1)"), "{screen}");
+    assert!(!screen.contains("eth0 ("));
+
+    app.sample(&mut source, start + Duration::from_secs(5), Duration::from_secs(1));
+    assert_eq!(source.asked.get(), 1);
+    app.sample(&mut source, start + CONTAINERS_EVERY + Duration::from_secs(1), Duration::from_secs(1));
+    assert_eq!(source.asked.get(), 2);
+}
+
+// bond0 over eth0 and eth1, eth0 carrying three times as much; `ports` is what the bond
+// lists right now
+struct Bonded {
+    reads: u64,
+    ports: Vec<String>,
+}
+
+impl NetworkSource for Bonded {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.reads += 1;
+        let counters = |name: &str, rx| Counters { name: name.to_string(), rx_bytes: rx * self.reads, ..Default::default() };
+        vec![counters("bond0", 4096), counters("eth0", 3072), counters("eth1", 1024)]
+    }
+
+    fn info(&self, name: &str) -> IfaceInfo {
+        match name {
+            "bond0" => IfaceInfo { kind: IfaceKind::Bond, members: self.ports.clone(), ..Default::default() },
+            _ => IfaceInfo { kind: IfaceKind::Ethernet, ..Default::default() },
+        }
+    }
+}
+
+#[test]
+fn bond_members() {
+    let mut app = AppState::new();
+    let mut source = Bonded { reads: 0, ports: vec!["eth0".to_string(), "eth1".to_string()] };
+    // a port hidden from the table still has its rate
+    app.hidden = vec!["eth1".to_string()];
+    app.prime(&mut source);
+    for _ in 0..2 {
+        app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    }
+    app.apply(Action::ToggleGroups);
+    app.apply(Action::NestMembers);
+    let screen = render(&app);
+    assert!(screen.contains("└ eth0"), "{screen}");
+    assert!(position(&screen, "bond0", "bond0").1 < position(&screen, "└ eth0", "eth0").1);
+
+    app.selected = Some("bond0".to_string());
+    app.apply(Action::Open);
+    let screen = render_sized(&app, 120, 50);
+    assert!(screen.contains("eth0  rx 3.0 KiB/s   tx --"), "{screen}");
+    assert!(screen.contains("eth1  rx 1.0 KiB/s   tx --"));
+
+    // taken out of the bond: gone with the next sample
+    source.ports.pop();
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    let screen = render_sized(&app, 120, 50);
+    assert!(!screen.contains("eth1  rx"), "{screen}");
+    assert!(screen.contains("eth0  rx 3.0 KiB/s"));
+}
+
+// eth0 with two VLANs on it; the parent's counters include the tagged traffic
+struct Tagged {
+    reads: u64,
+}
+
+impl NetworkSource for Tagged {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        self.reads += 1;
+        let counters = |name: &str, rx| Counters { name: name.to_string(), rx_bytes: rx * self.reads, ..Default::default() };
+        vec![counters("eth0", 4096), counters("eth0.100", 1024), counters("eth0.200", 2048)]
+    }
+
+    fn info(&self, name: &str) -> IfaceInfo {
+        match vlan::from_name(name) {
+            Some(vlan) => IfaceInfo { kind: IfaceKind::Vlan, vlan: Some(vlan), ..Default::default() },
+            None => IfaceInfo { kind: IfaceKind::Ethernet, ..Default::default() },
+        }
+    }
+}
+
+#[test]
+fn vlans_under_their_parent() {
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    let mut source = Tagged { reads: 0 };
+    app.prime(&mut source);
+    for _ in 0..2 {
+        app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    }
+    assert!(!render(&app).contains("└ eth0.100"), "flat until asked");
+    app.apply(Action::NestMembers);
+    let screen = render(&app);
+    assert!(screen.contains("└ eth0.200"), "{screen}");
+    assert!(position(&screen, "└ eth0.200", "eth0.200").1 < position(&screen, "└ eth0.100", "eth0.100").1, "by rate like the rest");
+
+    app.selected = Some("eth0".to_string());
+    app.apply(Action::Open);
+    let screen = render_sized(&app, 120, 50);
+    assert!(screen.contains("eth0.100  rx 1.0 KiB/s   tx --"), "{screen}");
+    assert!(screen.contains("eth0.200  rx 2.0 KiB/s   tx --"));
+    assert!(screen.contains("untagged  rx 1.0 KiB/s   tx --"));
+
+    app.apply(Action::Back);
+    app.selected = Some("eth0.100".to_string());
+    app.apply(Action::Open);
+    let screen = render_sized(&app, 120, 50);
+    assert!(screen.contains("VLAN         100 on eth0"), "{screen}");
+    assert!(!screen.contains("untagged"));
+}
+
+// the host's eth0 and blue's, with `failing` the namespaces that couldn't be read
+struct Spaces {
+    failing: Vec<(String, String)>,
+}
+
+impl NetworkSource for Spaces {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        ["eth0", "blue:eth0"].iter().map(|name| Counters { name: name.to_string(), ..Default::default() }).collect()
+    }
+
+    fn info(&self, name: &str) -> IfaceInfo {
+        let netns = netns::split(name).map(|(ns, _)| ns.to_string());
+        IfaceInfo { kind: IfaceKind::Ethernet, netns, ..Default::default() }
+    }
+
+    fn netns_errors(&self) -> Vec<(String, String)> {
+        self.failing.clone()
+    }
+}
+
+#[test]
+fn interfaces_of_other_namespaces() {
+    let mut app = AppState::new();
+    let denied = ("red".to_string(), "permission denied (needs root)".to_string());
+    let mut source = Spaces { failing: vec![denied.clone()] };
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    let screen = render(&app);
+    assert!(screen.contains("blue:eth0"), "{screen}");
+    assert!(screen.contains("netns red: permission denied (needs root)"), "{screen}");
+
+    // said once, not with every sample
+    app.message = None;
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(app.message, None);
+    source.failing.push(("green".to_string(), "gone".to_string()));
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(app.message.as_ref().map(|m| m.0.as_str()), Some("netns green: gone"));
+
+    app.selected = Some("blue:eth0".to_string());
+    app.apply(Action::Open);
+    assert!(render_sized(&app, 120, 50).contains("Namespace    blue"));
+}
+
+#[test]
+fn procfs_backend() {
+    let dir = std::env::temp_dir().join(format!("nettui-procfs-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/");
+    for (from, to) in [("proc_net_route", "route"), ("proc_net_fib_trie", "fib_trie")] {
+        std::fs::copy(format!("{fixtures}{from}"), dir.join(to)).unwrap();
+    }
+    let dev = |rx: u64, multicast: u64| {
+        let header = "Inter-|   Receive                                                |  Transmit\n face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed\n";
+        format!("{header}  eth0: {rx} 10 0 0 0 1 0 {multicast} 500 5 0 0 0 3 0 0\n")
+    };
+    std::fs::write(dir.join("dev"), dev(10_000, 40)).unwrap();
+    let mut app = AppState::new();
+    let mut source = ProcNetDev::at(&dir, SysFs::at(dir.join("no-sysfs")));
+    app.prime(&mut source);
+    std::fs::write(dir.join("dev"), dev(12_048, 140)).unwrap();
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    let screen = render(&app);
+    assert!(screen.contains("10.0.0.5"), "{screen}");
+    assert!(!screen.contains("Mcast"), "off by default");
+    app.apply(Action::ToggleMulticast);
+    let screen = render_sized(&app, 220, 20);
+    assert!(screen.contains("Mcast In/s"), "{screen}");
+    assert!(screen.contains("100.0 pps"), "{screen}");
+
+    app.selected = Some("eth0".to_string());
+    app.apply(Action::Open);
+    let screen = render_sized(&app, 120, 60);
+    assert!(screen.contains("2.0 KiB/s"), "{screen}");
+    assert!(screen.contains("Multicast    in 100 (100 pps)"), "{screen}");
+    assert!(screen.contains("Frame        0"));
+    assert!(screen.contains("Collisions   0   carrier 0"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// a Windows host where the counters come under the adapter's GUID; `extra` adds an
+// interface, `asked` counts lookups
+struct Adapted {
+    extra: bool,
+    asked: std::cell::Cell<u32>,
+}
+
+const GUID: &str = "{4D36E972-E325-11CE-BFC1-08002BE10318}";
+
+impl NetworkSource for Adapted {
+    fn snapshot(&mut self) -> Vec<Counters> {
+        let names = if self.extra { &[GUID, "Loopback Pseudo-Interface 1"][..] } else { &[GUID][..] };
+        names.iter().map(|name| Counters { name: name.to_string(), ..Default::default() }).collect()
+    }
+
+    fn adapters(&self) -> Adapters {
+        self.asked.set(self.asked.get() + 1);
+        adapters::index(vec![(GUID.to_string(), "Ethernet 2".to_string(), "Intel I219-V".to_string())])
+    }
+}
+
+#[test]
+fn windows_adapter_names() {
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    let mut source = Adapted { extra: false, asked: Default::default() };
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    let screen = render(&app);
+    assert!(screen.contains("Ethernet 2 — Intel I219-V"), "{screen}");
+    assert!(!screen.contains(GUID));
+
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(source.asked.get(), 1, "only when the interface list changes");
+    source.extra = true;
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(source.asked.get(), 2);
+
+    app.selected = Some(GUID.to_string());
+    app.apply(Action::Open);
+    let screen = render_sized(&app, 120, 50);
+    assert!(screen.contains("Interface Ethernet 2 — Intel I219-V"), "{screen}");
+    assert!(screen.contains("Name         {4D36E972-E325"), "{screen}");
+}
+
+#[test]
+fn gateway_latency_in_the_detail_view() {
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    let mut source = Routed(Fixed { reads: 0 });
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    app.selected = Some("eth0".to_string());
+    app.apply(Action::Open);
+    assert!(!render(&app).contains("Gateway"), "nothing probed yet");
+
+    let target = Target { interface: "eth0".to_string(), gateway: "192.168.1.1".parse().unwrap() };
+    let ping = |rtt: Option<u64>, method| Pinged { target: target.clone(), method, rtt: rtt.map(Duration::from_millis) };
+    for rtt in [Some(2), Some(4), None, Some(3)] {
+        app.set_ping(ping(rtt, Method::Icmp));
+    }
+    let screen = render_sized(&app, 120, 50);
+    assert!(screen.contains("Gateway      192.168.1.1 by icmp"), "{screen}");
+    assert!(screen.contains("rtt 3.0 ms   loss 25% of 4"));
+
+    // without ICMP sockets it says so; probes of a gateway no route uses are dropped
+    app.set_ping(ping(None, Method::Udp));
+    let other = Target { interface: "eth0".to_string(), gateway: "10.9.9.9".parse().unwrap() };
+    app.set_ping(Pinged { target: other, method: Method::Icmp, rtt: None });
+    let screen = render_sized(&app, 120, 50);
+    assert!(screen.contains("192.168.1.1 by udp") && screen.contains("no permission for ICMP sockets"), "{screen}");
+    assert!(screen.contains("rtt --   loss 40% of 5"));
+}
+
+#[test]
+fn processes_tab() {
+    let mut app = sampled_app();
+    app.selected = Some("eth0".to_string());
+    app.apply(Action::Processes);
+    if let Some(why) = procs::unavailable() {
+        assert_eq!(app.view, View::Table);
+        assert!(render(&app).contains(why));
+        app.view = View::Processes;
+    }
+    assert!(render(&app).contains("measuring..."));
+
+    let flow = |process: Option<(u32, &str)>, local: &str, rx_bps, tx_bps| Flow {
+        process: process.map(|(pid, name)| (pid, name.to_string())),
+        local: local.parse().unwrap(),
+        rx_bps,
+        tx_bps,
+    };
+    let flows = vec![
+        flow(Some((40, "curl")), "192.168.1.10", 2048.0, 0.0),
+        flow(Some((812, "sshd")), "192.168.1.10", 0.0, 4096.0),
+        flow(Some((40, "curl")), "192.168.1.10", 1024.0, 0.0),
+        flow(None, "192.168.1.10", 512.0, 0.0),
+        flow(Some((99, "dnsmasq")), "127.0.0.1", 9999.0, 0.0),
+    ];
+    app.set_processes(Reading { flows, error: None }, Instant::now());
+    let screen = render(&app);
+    assert!(screen.contains("Processes on eth0 (estimated)"), "{screen}");
+    assert!(screen.contains("UDP and packet headers aren't counted"));
+    let sshd = screen.find("sshd").unwrap();
+    assert!(sshd < screen.find("curl").unwrap() && screen.find("curl").unwrap() < screen.find("other users'").unwrap());
+    assert!(screen.contains("3.0 KiB/s"), "curl's two sockets: {screen}");
+    assert!(!screen.contains("dnsmasq"), "not on eth0");
+
+    // a kernel without sock_diag closes the tab
+    app.set_processes(Reading { flows: Vec::new(), error: Some("sock_diag: Protocol not supported".to_string()) }, Instant::now());
+    assert_eq!(app.view, View::Table);
+    assert!(render(&app).contains("no processes tab: sock_diag: Protocol not supported"));
+}
+
+#[test]
+fn top_talkers_tab() {
+    let mut app = sampled_app();
+    app.selected = Some("eth0".to_string());
+    app.apply(Action::TopTalkers);
+    match capture::unavailable() {
+        Some(why) => {
+            assert_eq!(app.view, View::Table);
+            assert!(render(&app).contains(why));
+            app.view = View::Talkers;
+            app.capture_on = Some("eth0".to_string());
+        }
+        // a real capture, or the reason there is none (no CAP_NET_RAW, no eth0 here)
+        None => {
+            assert_eq!(app.capture_on.as_deref(), Some("eth0"));
+            assert!(app.capture.is_some() != app.capture_error.is_some());
+            app.capture_error = None;
+        }
+    }
+    assert!(render(&app).contains("capturing..."));
+
+    let peer = |remote: &str, port, transport| Some(Peer { remote: remote.parse().unwrap(), port, transport });
+    app.talkers = vec![
+        Talker { peer: peer("140.82.112.3", Some(443), Transport::Tcp), rx_bps: 8192.0, tx_bps: 1024.0 },
+        Talker { peer: peer("2606:4700::1111", Some(53), Transport::Udp), rx_bps: 512.0, tx_bps: 256.0 },
+        Talker { peer: peer("192.168.1.1", None, Transport::Icmp), rx_bps: 64.0, tx_bps: 64.0 },
+        Talker { peer: None, rx_bps: 10.0, tx_bps: 0.0 },
+    ];
+    app.talkers_at = Some(Instant::now());
+    let screen = render(&app);
+    assert!(screen.contains("Top talkers on eth0"), "{screen}");
+    assert!(screen.contains("REMOTE") && screen.contains("8.0 KiB/s"));
+    let github = screen.find("140.82.112.3").unwrap();
+    assert!(github < screen.find("2606:4700::1111").unwrap() && screen.find("icmp").unwrap() < screen.find("(others)").unwrap());
+
+    app.capture_error = Some("capturing needs CAP_NET_RAW".to_string());
+    assert!(render(&app).contains("capturing needs CAP_NET_RAW"));
+
+    // another interface starts over, leaving the tab stops the capture
+    if capture::unavailable().is_none() {
+        app.apply(Action::Down);
+        assert_ne!(app.capture_on.as_deref(), Some("eth0"));
+        assert!(app.talkers.is_empty() && app.talkers_at.is_none());
+        app.apply(Action::TopTalkers);
+        assert_eq!(app.view, View::Table);
+        assert!(app.capture.is_none() && app.capture_on.is_none());
+    }
+}
+
+#[test]
+fn themes_recolor_everything_at_once() {
+    let mut app = sampled_app();
+    assert_eq!(color_of(&app, "up   ether"), Some(Color::Reset), "monochrome");
+    // NO_COLOR may be set where the tests run
+    app.no_color = false;
+
+    app.set_theme(ThemeName::Dark);
+    app.refresh_text();
+    assert_eq!(color_of(&app, "up   ether"), Some(Color::Green));
+    assert_eq!(color_of(&app, "ether"), Some(Color::Green));
+
+    // 'T' goes on to the light theme, the config's override rides along
+    app.colors = vec![("link_up".to_string(), Color::Indexed(208))];
+    app.apply(Action::CycleTheme);
+    assert_eq!(app.theme_name, ThemeName::Light);
+    assert_eq!(color_of(&app, "up   ether"), Some(Color::Indexed(208)));
+    assert_eq!(color_of(&app, "ether"), Some(Color::Rgb(0x00, 0x80, 0x00)));
+    assert!(render(&app).contains("theme: light"));
+}
+
+#[test]
+fn no_color_wins_over_themes_and_overrides() {
+    let args = Args::try_parse_from(["nettui", "--no-color", "--theme", "light"]).unwrap();
+    let mut app = AppState::from_args(args);
+    app.filter.show_virtual = true;
+    app.colors = vec![("link_up".to_string(), Color::Indexed(208))];
+    let mut source = Fixed { reads: 0 };
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    assert_eq!(app.theme_name, ThemeName::Monochrome);
+
+    app.apply(Action::CycleTheme);
+    assert_eq!(app.theme, Theme::builtin(ThemeName::Monochrome));
+    assert_eq!(color_of(&app, "up   ether"), Some(Color::Reset));
+    // --once output never had styling to strip
+    assert!(!plain_table(&app).contains('\x1b'));
+}
+
+#[test]
+fn ascii_mode_emits_nothing_above_0x7f() {
+    let args = Args::try_parse_from(["nettui", "--ascii", "--show-virtual", "--stats", "--pin", "eth0"]).unwrap();
+    let mut app = AppState::from_args(args);
+    let mut source = Fixed { reads: 0 };
+    app.prime(&mut source);
+    for _ in 0..3 {
+        app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    }
+    app.apply(Action::ToggleGauge);
+    app.apply(Action::ToggleChart);
+    app.apply(Action::ToggleGroups);
+
+    let mut screens = vec![render(&app), render_sized(&app, 60, 8)];
+    app.apply(Action::ToggleGroups);
+    app.apply(Action::Search);
+    screens.push(render(&app));
+    app.handle_key(KeyEvent::from(KeyCode::Esc));
+    app.apply(Action::Open);
+    screens.push(render(&app));
+    app.apply(Action::Help);
+    screens.push(render_sized(&app, 120, 12));
+    app.apply(Action::Help);
+    app.apply(Action::HiddenList);
+    screens.push(render(&app));
+
+    for screen in &screens {
+        let odd: String = screen.chars().filter(|c| !c.is_ascii()).collect();
+        assert!(odd.is_empty(), "non-ASCII {odd:?} in\n{screen}");
+    }
+    assert!(screens[0].contains("+---"), "ASCII borders");
+    assert!(screens[0].contains("* eth0"), "pinned");
+    assert!(screens[0].contains("RX/s v"), "sort arrow");
+}
+
+#[test]
+fn utilization_gauge() {
+    let mut app = sampled_app();
+    app.apply(Action::ToggleGauge);
+    let screen = render(&app);
+    assert!(screen.contains("▏       2%"));
+    assert!(screen.contains("--"), "no speed, no gauge");
+}
+
+#[test]
+fn detail_view_and_help_overlay() {
+    let mut app = sampled_app();
+    app.apply(Action::Down);
+    app.apply(Action::Open);
+    let screen = render(&app);
+    assert!(screen.contains("Interface eth0"));
+    assert!(screen.contains("  192.168.1.10/24"));
+    assert!(screen.contains("  2001:db8:85a3::8a2e:370:7334/64"), "IPv6 in full");
+    assert!(screen.contains("MAC          52:54:00:12:34:56"));
+    assert!(screen.contains("MTU          9000"));
+    assert!(screen.contains("Peak         rx ") && screen.contains("Average      rx "));
+    assert!(screen.contains("Speed        1 Mb/s   utilization 1.6%"));
+    assert!(screen.contains("eth0 history"));
+    assert!(screen.contains("p50/95/99    over 0s"));
+    assert!(screen.contains("  rx 2.0 KiB/s / 2.0 KiB/s / 2.0 KiB/s"), "{screen}");
+
+    assert!(!screen.contains("Wi-Fi"), "wired");
+
+    app.apply(Action::Help);
+    assert!(render(&app).contains(" Keys "));
+}
+
+#[test]
+fn narrow_terminals_drop_columns() {
+    let app = sampled_app();
+    let wide = render_sized(&app, 175, 20);
+    assert!(wide.contains("Err In/s") && wide.contains("PPS In") && wide.contains("Nbrs"));
+    assert_eq!(wide.contains("Drop In/s"), HAS_DROPS, "only where sysfs has the counters");
+
+    let medium = render_sized(&app, 120, 20);
+    assert!(!medium.contains("Err In/s"), "errors go first");
+    assert!(medium.contains("PPS In"));
+
+    let narrow = render_sized(&app, 40, 20);
+    assert!(!narrow.contains("PPS In"));
+    assert!(narrow.contains("wlan0") && narrow.contains("RX/s") && narrow.contains("TX/s"));
+    // link, type, address, util, packets, errors, drops where there are any, neighbors and
+    // the trend
+    assert!(narrow.contains(if HAS_DROPS { "+12 cols" } else { "+10 cols" }));
+    assert!(!wide.contains(" cols"));
+
+    // the name column gives way before RX/TX do
+    let squeezed = render_sized(&app, 36, 20);
+    assert!(squeezed.contains("INTERFA…") && squeezed.contains("wlan0"));
+
+    assert!(render_sized(&app, 30, 20).contains("terminal too small"));
+}
+
+// screen position of the first `needle` on the line containing `line`
+fn position(screen: &str, line: &str, needle: &str) -> (u16, u16) {
+    let (y, text) = screen.lines().enumerate().find(|(_, l)| l.contains(line)).unwrap();
+    let x = text.chars().collect::<String>().find(needle).unwrap();
+    (text[..x].chars().count() as u16, y as u16)
+}
+
+fn mouse(kind: MouseEventKind, (column, row): (u16, u16)) -> MouseEvent {
+    MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE }
+}
+
+#[test]
+fn mouse_selects_sorts_and_scrolls() {
+    let mut app = sampled_app();
+    let click = MouseEventKind::Down(MouseButton::Left);
+    let now = Instant::now();
+    let screen = render(&app);
+
+    // headers sort, the same one again reverses
+    let tx = position(&screen, "INTERFACE", "TX/s");
+    app.handle_mouse(mouse(click, tx), now);
+    assert_eq!((app.sort_key, app.sort_desc), (SortKey::Tx, true));
+    app.handle_mouse(mouse(click, tx), now);
+    assert_eq!((app.sort_key, app.sort_desc), (SortKey::Tx, false));
+    app.handle_mouse(mouse(click, position(&screen, "INTERFACE", "INTERFACE")), now);
+    assert_eq!((app.sort_key, app.sort_desc), (SortKey::Name, false), "names A-Z first");
+    app.handle_mouse(mouse(click, position(&screen, "INTERFACE", "LINK")), now);
+    assert_eq!(app.sort_key, SortKey::Name, "nothing to sort LINK by");
+
+    // a click selects, a second one on the same row opens it
+    let screen = render(&app);
+    let wlan0 = position(&screen, "wlan0", "wlan0");
+    app.handle_mouse(mouse(click, wlan0), now);
+    assert_eq!(app.selected_name(), Some("wlan0"));
+    assert_eq!(app.view, View::Table);
+    app.handle_mouse(mouse(click, wlan0), now + Duration::from_secs(2));
+    assert_eq!(app.view, View::Table, "too slow for a double-click");
+    app.handle_mouse(mouse(click, wlan0), now + Duration::from_millis(2100));
+    assert_eq!(app.view, View::Detail);
+
+    // the detail view has no table to hit
+    render(&app);
+    app.handle_mouse(mouse(click, wlan0), now + Duration::from_secs(5));
+    assert_eq!(app.view, View::Detail);
+    app.apply(Action::Back);
+
+    // the footer and the border aren't rows
+    let screen = render(&app);
+    app.handle_mouse(mouse(click, position(&screen, "TOTAL", "TOTAL")), now);
+    app.handle_mouse(mouse(click, (0, wlan0.1)), now);
+    assert_eq!(app.selected_name(), Some("wlan0"));
+
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    let mut source = Many { n: 100, reads: 0 };
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+    let screen = render_sized(&app, 120, 21);
+    let row = position(&screen, "tap000", "tap000");
+    // the first notch only puts the cursor on the top row, like the arrow keys
+    app.handle_mouse(mouse(MouseEventKind::ScrollDown, row), now);
+    app.handle_mouse(mouse(MouseEventKind::ScrollDown, row), now);
+    app.handle_mouse(mouse(MouseEventKind::ScrollDown, row), now);
+    assert_eq!(app.selected_name(), Some("tap006"));
+    app.handle_mouse(mouse(MouseEventKind::ScrollUp, row), now);
+    assert_eq!(app.selected_name(), Some("tap003"));
+    // outside the table the wheel does nothing
+    app.handle_mouse(mouse(MouseEventKind::ScrollDown, (0, 0)), now);
+    assert_eq!(app.selected_name(), Some("tap003"));
+}
+
+#[test]
+fn long_tables_scroll() {
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    let mut source = Many { n: 100, reads: 0 };
+    app.prime(&mut source);
+    app.sample(&mut source, Instant::now(), Duration::from_secs(1));
+
+    // 21 lines: 3 header, 2 table borders, 3 table header/footer and the status bar leave 12 rows
+    assert!(render_sized(&app, 120, 21).contains("showing 1–12 of 100"));
+    app.apply(Action::Down);
+    app.apply(Action::PageDown);
+    app.apply(Action::PageDown);
+    let screen = render_sized(&app, 120, 21);
+    assert!(screen.contains("showing 14–25 of 100"), "selection stays on the last row");
+    assert!(screen.contains("tap024") && !screen.contains("tap012"));
+
+    // re-sorting by totals keeps the order here, so the view doesn't move
+    app.apply(Action::Up);
+    app.apply(Action::ToggleTotals);
+    assert!(render_sized(&app, 120, 21).contains("showing 14–25 of 100"));
+    // reversed, tap023 lands on row 77 and the view follows it
+    app.apply(Action::ReverseSort);
+    assert!(render_sized(&app, 120, 21).contains("showing 66–77 of 100"));
+    app.apply(Action::ReverseSort);
+
+    app.apply(Action::PageUp);
+    app.apply(Action::PageUp);
+    app.apply(Action::PageUp);
+    assert!(render_sized(&app, 120, 21).contains("showing 1–12 of 100"));
+}
+
+#[test]
+fn replay_steps_and_plays() {
+    let text = "\
+timestamp,interface,rx_bps,tx_bps,packets_in_ps,packets_out_ps,errors_in_ps,errors_out_ps
+2026-10-15T18:17:10.000Z,eth0,1024.0,512.0,2.00,1.00,0.00,0.00
+garbage
+2026-10-15T18:17:11.000Z,eth0,2048.0,512.0,4.00,1.00,0.00,0.00
+{\"timestamp\":1792088232,\"interval_secs\":1,\"interface\":\"eth0\",\"rx_bps\":4096,\"tx_bps\":0,\"rx_pps\":8,\"tx_pps\":0}
+";
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    let start = Instant::now();
+    app.replay = Some(Replay::new(replay::parse(text), start));
+    app.seek(0);
+    let rx = |app: &AppState| app.rows.iter().find(|r| r.interface == "eth0").map(|r| r.rx_bps);
+    assert_eq!(rx(&app), Some(1024.0));
+    let screen = render(&app);
+    assert!(screen.contains("replay 1/3 paused 1x"), "{screen}");
+    assert!(screen.contains("1 malformed line skipped"), "{screen}");
+    assert!(screen.contains("recorded 2026-10-15 18:17:10 UTC"), "{screen}");
+
+    let press = |app: &mut AppState, code| app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    press(&mut app, KeyCode::Right);
+    press(&mut app, KeyCode::Right);
+    assert_eq!(rx(&app), Some(4096.0));
+    assert!(render(&app).contains("recorded 2026-10-15 18:17:12 UTC"));
+    press(&mut app, KeyCode::Right);
+    assert_eq!(rx(&app), Some(4096.0), "nothing after the last frame");
+    // back starts over and samples up to the frame again
+    press(&mut app, KeyCode::Left);
+    assert_eq!(rx(&app), Some(2048.0));
+    assert_eq!(app.history.get("eth0").map(|ring| ring.len()), Some(2));
+
+    press(&mut app, KeyCode::Left);
+    press(&mut app, KeyCode::Char('+'));
+    press(&mut app, KeyCode::Char(' '));
+    assert!(render(&app).contains("replay 1/3 playing 2x"));
+    // space took its own time, the frames come due relative to it
+    app.play(Instant::now() + Duration::from_millis(1100));
+    assert_eq!(app.replay.as_ref().and_then(|r| r.shown), Some(2));
+    let screen = render(&app);
+    assert!(screen.contains("replay 3/3 paused 2x") && screen.contains("end of the recording"), "{screen}");
+}
+
+#[test]
+fn export_as_raw_numbers() {
+    let mut app = sampled_app();
+    app.search = "eth".to_string();
+    // drops and neighbors depend on the platform
+    let names = ["iface", "link", "type", "address", "rx", "tx", "util", "packets", "errors", "trend"];
+    app.columns = Columns::from_names(&names.map(String::from));
+    app.refresh_text();
+    let dir = std::env::temp_dir().join(format!("nettui-export-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let press = |app: &mut AppState, code| app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+
+    press(&mut app, KeyCode::Char('E'));
+    assert!(app.exporting);
+    assert!(app.export_path.starts_with("nettui-") && app.export_path.ends_with(".txt"), "{}", app.export_path);
+    let screen = render(&app);
+    assert!(screen.contains("export txt: nettui-") && screen.contains("tab format"), "{screen}");
+    // a directory in front of the suggested name, then csv
+    press(&mut app, KeyCode::Home);
+    for c in format!("{}/", dir.display()).chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Tab);
+    assert!(app.export_path.ends_with(".csv"));
+    assert!(render(&app).contains("export csv: "));
+    press(&mut app, KeyCode::Enter);
+    assert!(!app.exporting);
+    let path = dir.join(app.export_path.rsplit('/').next().unwrap());
+    let csv = std::fs::read_to_string(&path).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("interface,link,type,address,rx_bps,tx_bps,util_pct,rx_pps,tx_pps,rx_errors_ps,tx_errors_ps"));
+    assert_eq!(lines.next(), Some("eth0,up,ether,192.168.1.10,2048,1024,1.6383999999999999,0,0,0,0"), "{csv}");
+    assert_eq!(lines.next(), None, "the search still applies");
+    assert!(render(&app).contains(&format!("exported 1 interface to {}", path.canonicalize().unwrap().display())));
+
+    // JSON by the extension typed, totals in the totals view
+    app.apply(Action::ToggleTotals);
+    app.apply(Action::ExportAs);
+    for _ in 0..app.export_path.chars().count() {
+        press(&mut app, KeyCode::Backspace);
+    }
+    for c in format!("{}/view.json", dir.display()).chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Enter);
+    let json = std::fs::read_to_string(dir.join("view.json")).unwrap();
+    assert!(json.starts_with("[{\"interface\":\"eth0\",\"link\":\"up\""), "{json}");
+    assert!(json.contains("\"rx_bytes\":2048,\"tx_bytes\":1024,\"util_pct\":null"), "{json}");
+
+    // the text export is the table on screen
+    app.export_path = format!("{}/view.txt", dir.display());
+    app.exporting = true;
+    press(&mut app, KeyCode::Enter);
+    let text = std::fs::read_to_string(dir.join("view.txt")).unwrap();
+    assert!(text.lines().next().is_some_and(|l| l.starts_with("INTERFACE")), "{text}");
+    assert!(text.contains("eth0") && text.contains("2 KiB") && text.contains("TOTAL") && !text.contains("wlan0"), "{text}");
+
+    // the same path again asks first; n goes back to the path with the file untouched
+    std::fs::write(dir.join("view.txt"), "mine").unwrap();
+    app.exporting = true;
+    press(&mut app, KeyCode::Enter);
+    assert!(app.exporting && app.export_overwrite);
+    let screen = render(&app);
+    assert!(screen.contains("view.txt exists, overwrite? y/n") && screen.contains("y overwrite"), "{screen}");
+    press(&mut app, KeyCode::Char('n'));
+    assert!(app.exporting && !app.export_overwrite);
+    assert_eq!(std::fs::read_to_string(dir.join("view.txt")).unwrap(), "mine");
+    assert!(render(&app).contains("export csv: "));
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Char('y'));
+    assert!(!app.exporting && !app.export_overwrite);
+    assert!(std::fs::read_to_string(dir.join("view.txt")).unwrap().starts_with("INTERFACE"));
+
+    // a default name that's taken gets a number instead
+    let default = dir.join("nettui-2026-10-15T18-17-10.txt");
+    assert_eq!(export::write_numbered(&app, &default, export::Format::Txt).unwrap().0, default);
+    let (second, _) = export::write_numbered(&app, &default, export::Format::Txt).unwrap();
+    assert_eq!(second, dir.join("nettui-2026-10-15T18-17-10-1.txt"));
+    assert_eq!(export::unused(&default), dir.join("nettui-2026-10-15T18-17-10-2.txt"));
+
+    // a write that fails says so
+    app.apply(Action::ExportAs);
+    press(&mut app, KeyCode::Home);
+    for c in format!("{}/missing/", dir.display()).chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Enter);
+    assert!(render(&app).contains("export failed: "));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// the Remote updates that arrive within `within`
+fn remote_updates(rx: &mpsc::Receiver<AppEvent>, within: Duration) -> Vec<remote::Update> {
+    let until = Instant::now() + within;
+    let mut updates = Vec::new();
+    while let Ok(ev) = rx.recv_timeout(until.saturating_duration_since(Instant::now())) {
+        if let AppEvent::Remote(update) = ev {
+            let lost = matches!(update, remote::Update::Lost(..));
+            updates.push(update);
+            if lost {
+                break;
+            }
+        }
+    }
+    updates
+}
+
+#[test]
+fn connect_draws_a_servers_samples() {
+    let hello = Hello { host: Host { name: "web-01".to_string(), os: "Debian GNU/Linux 12".to_string(), kernel: String::new() }, interval_ms: 1000 };
+    let server = Server::start("127.0.0.1:0", Some("s3cret".to_string()), hello).unwrap();
+    let addr = server.addr.to_string();
+
+    // a wrong token is refused before any sample
+    let (tx, rx) = mpsc::channel();
+    remote::connect(tx, addr.clone(), Some("guess".to_string()));
+    match remote_updates(&rx, Duration::from_secs(5)).as_slice() {
+        [remote::Update::Lost(reason, wait)] => {
+            assert_eq!(reason, "refused: bad token");
+            assert_eq!(*wait, remote::BACKOFF.0);
+        }
+        other => panic!("{other:?}"),
+    }
+    drop(rx);
+
+    let mut app = AppState::new();
+    app.filter.show_virtual = true;
+    app.remote = Some(Remote::new(addr.clone(), Some("s3cret".to_string())));
+    let screen = render(&app);
+    assert!(screen.contains(&format!("remote {addr}")) && screen.contains("connecting to"), "{screen}");
+
+    let (tx, rx) = mpsc::channel();
+    remote::connect(tx, addr.clone(), Some("s3cret".to_string()));
+    let Ok(AppEvent::Remote(connected)) = rx.recv_timeout(Duration::from_secs(5)) else {
+        panic!("no hello");
+    };
+    app.remote_update(connected, Instant::now());
+    assert_eq!(app.refresh_ms, 1000, "the server's interval");
+    // the server's own sampling, two rows as sample() leaves them
+    let local = sampled_app();
+    while server.clients() == 0 {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    for _ in 0..2 {
+        server.update(&local.rows, unix_now(), 1.0);
+        let Ok(AppEvent::Remote(sample)) = rx.recv_timeout(Duration::from_secs(5)) else {
+            panic!("no sample");
+        };
+        app.remote_update(sample, Instant::now());
+    }
+    let rx_rate = |app: &AppState, name: &str| app.rows.iter().find(|r| r.interface == name).map(|r| r.rx_bps);
+    assert_eq!(rx_rate(&app, "eth0"), Some(2048.0));
+    assert_eq!(rx_rate(&app, "wlan0"), Some(512.0));
+    let screen = render(&app);
+    assert!(screen.contains(" nettui remote web-01 — Debian GNU/Linux 12 "), "{screen}");
+    assert!(screen.contains(" remote ") && screen.contains("2.0 KiB/s") && !screen.contains("Disconnected"), "{screen}");
+
+    // a lost connection puts the overlay over the last sample
+    app.remote_update(remote::Update::Lost("connection reset by peer".to_string(), Duration::from_secs(4)), Instant::now());
+    let screen = render(&app);
+    assert!(screen.contains("Disconnected") && screen.contains("connection reset by peer"), "{screen}");
+    assert!(screen.contains("retrying in 4s, attempt 2") && screen.contains("DISCONNECTED"), "{screen}");
+    assert!(screen.contains("2.0 KiB/s"), "{screen}");
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_answers_queries() {
+    let path = std::env::temp_dir().join(format!("nettui-test-{}.sock", std::process::id()));
+    let sock = QuerySocket::start(&path).unwrap();
+    assert_eq!(socket::query(&path, "GET eth0").unwrap(), "{\"error\":\"no sample yet\"}");
+    // a second nettui on the same path is turned away rather than stealing it
+    assert_eq!(QuerySocket::start(&path).err().map(|e| e.kind()), Some(std::io::ErrorKind::AddrInUse));
+
+    let app = sampled_app();
+    sock.update(&app.rows, 1_792_088_230.0, 1.0);
+    let eth0 = socket::query(&path, "GET eth0").unwrap();
+    assert!(eth0.starts_with("{\"timestamp\":1792088230,") && eth0.contains("\"rx_bps\":2048"), "{eth0}");
+    let list = socket::query(&path, "LIST").unwrap();
+    assert!(list.starts_with("[{") && list.contains("\"interface\":\"wlan0\""), "{list}");
+    assert_eq!(socket::query(&path, "GET eth9").unwrap(), "{\"error\":\"no interface eth9\"}");
+
+    drop(sock);
+    assert!(!path.exists(), "the socket file goes with it");
+}
+
+// one request off a fake InfluxDB: the request line and headers, then the body
+fn influx_request(listener: &std::net::TcpListener, reply: &str) -> (String, String) {
+    use std::io::{BufRead, BufReader, Read, Write};
+    let (mut stream, _) = listener.accept().unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let (mut head, mut line) = (String::new(), String::new());
+    while reader.read_line(&mut line).unwrap() > 2 {
+        head.push_str(&line);
+        line.clear();
+    }
+    let length: usize = head.lines().find_map(|l| l.strip_prefix("Content-Length: ")).unwrap().parse().unwrap();
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+    stream.write_all(reply.as_bytes()).unwrap();
+    (head, String::from_utf8(body).unwrap())
+}
+
+#[test]
+fn influx_writes_line_protocol_and_warns_on_failure() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let target = influx::target(&url, Some("home"), "net").unwrap();
+    let mut app = sampled_app();
+    app.influx = Some(Influx::start(target, Some("t0ken".to_string())));
+
+    app.influx.as_ref().unwrap().update(&app.rows, 1_792_088_230.0, 1.0);
+    let (head, body) = influx_request(&listener, "HTTP/1.1 204 No Content\r\n\r\n");
+    assert!(head.starts_with("POST /api/v2/write?bucket=net&precision=ns&org=home HTTP/1.1\r\n"), "{head}");
+    assert!(head.contains("Authorization: Token t0ken\r\n"), "{head}");
+    assert!(body.starts_with("net,iface=eth0 rx_bps=2048,tx_bps=1024,"), "{body}");
+    assert!(body.contains("\nnet,iface=wlan0 rx_bps=512,") && body.ends_with(" 1792088230000000000\n"), "{body}");
+
+    // a rejected write isn't retried, it's dropped and shows in the status bar
+    app.influx.as_ref().unwrap().update(&app.rows, 1_792_088_231.0, 1.0);
+    let reply = "HTTP/1.1 401 Unauthorized\r\nContent-Type: application/json\r\n\r\n{\"code\":\"unauthorized\",\"message\":\"unauthorized access\"}";
+    influx_request(&listener, reply);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app.influx.as_ref().unwrap().status().dropped == 0 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let status = app.influx.as_ref().unwrap().status();
+    assert_eq!(status.error.as_deref(), Some("HTTP 401: unauthorized access"));
+    assert_eq!(status.dropped, 1);
+    let screen = render_sized(&app, 160, 24);
+    assert!(screen.contains("influx: HTTP 401: unauthorized access, 1 batch dropped"), "{screen}");
+}
+
+#[test]
+fn statsd_sends_gauges_over_udp() {
+    let listener = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    listener.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let mut statsd = Statsd::start(&listener.local_addr().unwrap().to_string(), "lab").unwrap();
+    let mut app = sampled_app();
+    // a VLAN and an alias, names that would otherwise break up the metric name
+    app.rows[0].interface = "eth0.100".to_string();
+    app.rows[1].interface = "wlan0:1".to_string();
+    statsd.update(&app.rows, 1_792_088_230.0, 1.0);
+
+    let mut lines = Vec::new();
+    let mut buf = [0; 2048];
+    while lines.len() < 16 {
+        let n = listener.recv(&mut buf).unwrap();
+        assert!(n <= statsd::MAX_PAYLOAD);
+        lines.extend(String::from_utf8_lossy(&buf[..n]).lines().map(str::to_string));
+    }
+    assert_eq!(lines.len(), 16, "8 gauges for each of 2 interfaces: {lines:?}");
+    for expected in ["lab.eth0_100.rx_bps:2048|g", "lab.eth0_100.tx_bps:1024|g", "lab.wlan0_1.rx_bps:512|g", "lab.wlan0_1.tx_bps:0|g"] {
+        assert!(lines.iter().any(|l| l == expected), "{expected} in {lines:?}");
+    }
+    assert!(lines.iter().all(|l| l.starts_with("lab.") && l.ends_with("|g")), "{lines:?}");
+    assert_eq!(statsd.dropped, 0);
+}
+
+#[test]
+fn remote_drops_samples_with_a_broken_interval() {
+    let mut app = AppState::new();
+    app.remote = Some(Remote::new("127.0.0.1:1".to_string(), None));
+    let sample = |interval: &str| format!(r#"[{{"timestamp":1792088230,"interval_secs":{interval},"interface":"eth0","rx_bps":2048,"tx_bps":0}}]"#);
+    // what the reader thread would make of it: not a sample at all
+    assert!(replay::frame(&Json::parse(&sample("1e999")).unwrap()).is_none());
+    assert!(replay::frame(&Json::parse(&sample("1e20")).unwrap()).is_none());
+
+    // and one that got through anyway is dropped rather than sampled
+    let mut frame = replay::frame(&Json::parse(&sample("1")).unwrap()).unwrap();
+    for interval in [f64::INFINITY, f64::NAN, 1e20] {
+        frame.interval = interval;
+        app.remote_update(remote::Update::Sample(frame.clone()), Instant::now());
+        assert!(!app.sampled && app.rows.is_empty(), "{interval}");
+    }
+    frame.interval = 1.0;
+    app.remote_update(remote::Update::Sample(frame), Instant::now());
+    assert!(app.sampled);
+}